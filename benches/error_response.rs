@@ -0,0 +1,57 @@
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use error_pile::ReqwestPileResExt;
+
+/// decodes the error of a response built in memory, no socket involved
+fn decode(rt: &tokio::runtime::Runtime, status: u16, content_type: &str, body: &'static str) {
+    let res = http::Response::builder()
+        .status(status)
+        .header("content-type", content_type)
+        .body(body)
+        .unwrap();
+    let err = rt
+        .block_on(reqwest::Response::from(res).error_for_pile())
+        .unwrap_err();
+    black_box(err);
+}
+
+/// the shapes a throttling storm is made of: Graph answering 503 with a
+/// throttling code, Document Intelligence errors and plain failures
+fn error_response(c: &mut Criterion) {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let mut group = c.benchmark_group("error_response");
+
+    group.bench_function("graph_throttled", |b| {
+        b.iter(|| {
+            decode(
+                &rt,
+                503,
+                "application/json",
+                r#"{"error":{"code":"activityLimitReached","message":"The request has been throttled","innerError":{"date":"2025-06-02T10:00:00","request-id":"3f1c2d4e-0000-4000-8000-000000000000"}}}"#,
+            )
+        })
+    });
+
+    group.bench_function("document_intelligence", |b| {
+        b.iter(|| {
+            decode(
+                &rt,
+                400,
+                "application/json",
+                r#"{"error":{"code":"InvalidRequest","message":"Invalid request.","innererror":{"code":"InvalidContent","message":"The file is corrupted or format is unsupported."}}}"#,
+            )
+        })
+    });
+
+    group.bench_function("plain_text", |b| {
+        b.iter(|| decode(&rt, 502, "text/plain", "upstream connect error"))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, error_response);
+criterion_main!(benches);
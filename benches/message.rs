@@ -0,0 +1,55 @@
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use error_pile::ErrPile;
+
+/// what the request logging calls on every failed request
+fn source_str(c: &mut Criterion) {
+    let mut group = c.benchmark_group("source_str");
+
+    let fixed = ErrPile::NotReady;
+    group.bench_function("fixed", |b| b.iter(|| black_box(&fixed).source_str().len()));
+
+    let custom = ErrPile::custom("room type not mapped");
+    group.bench_function("custom", |b| {
+        b.iter(|| black_box(&custom).source_str().len())
+    });
+
+    let io: ErrPile = std::io::Error::other("disk full").into();
+    group.bench_function("source", |b| b.iter(|| black_box(&io).source_str().len()));
+
+    group.finish();
+}
+
+/// the fixed messages raised in the rate loops
+fn construct(c: &mut Criterion) {
+    let mut group = c.benchmark_group("construct");
+
+    group.bench_function("custom", |b| {
+        b.iter(|| black_box(ErrPile::custom(black_box("room type not mapped"))))
+    });
+    group.bench_function("custom_static", |b| {
+        b.iter(|| black_box(ErrPile::custom_static(black_box("room type not mapped"))))
+    });
+
+    group.finish();
+}
+
+fn display(c: &mut Criterion) {
+    let mut group = c.benchmark_group("display");
+
+    let conflict = ErrPile::conflict_with_version("reservation", "7");
+    group.bench_function("optional_part", |b| {
+        b.iter(|| black_box(&conflict).to_string().len())
+    });
+
+    let cancelled = ErrPile::cancelled();
+    group.bench_function("optional_part_missing", |b| {
+        b.iter(|| black_box(&cancelled).to_string().len())
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, source_str, construct, display);
+criterion_main!(benches);
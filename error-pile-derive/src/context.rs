@@ -0,0 +1,64 @@
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote};
+use syn::{
+    Ident, ItemFn, Result, ReturnType, Token, parse::Parser, punctuated::Punctuated,
+    spanned::Spanned,
+};
+
+/// The function made a plain `fn` returning the future, so
+/// `#[track_caller]` sees the caller (an `async fn` only sees the executor
+/// polling it). The frame is built before the body runs, the arguments
+/// are moved into the body after. Only the raw `Debug` text is kept up
+/// front, `with_frame` redacts it when the call fails
+pub fn expand(args: TokenStream, item: TokenStream) -> Result<TokenStream> {
+    let args = Punctuated::<Ident, Token![,]>::parse_terminated.parse2(args)?;
+    let ItemFn {
+        attrs,
+        vis,
+        mut sig,
+        block,
+    } = syn::parse2(item)?;
+
+    let Some(asyncness) = sig.asyncness.take() else {
+        return Err(syn::Error::new(
+            sig.fn_token.span(),
+            "pile_context goes on async functions",
+        ));
+    };
+
+    let output = match &sig.output {
+        ReturnType::Type(_, ty) => ty.to_token_stream(),
+        ReturnType::Default => {
+            return Err(syn::Error::new(
+                sig.paren_token.span.join(),
+                "pile_context needs the function to return a PileResult",
+            ));
+        }
+    };
+    sig.output = syn::parse_quote! {
+        -> impl ::core::future::Future<Output = #output>
+    };
+
+    let function = format!("::{}", sig.ident);
+    let frame_args = args.iter().map(|arg| {
+        let name = arg.to_string();
+        quote! { .arg(#name, &#arg) }
+    });
+
+    Ok(quote! {
+        #(#attrs)*
+        #[track_caller]
+        #vis #sig {
+            let __pile_frame = ::error_pile::ContextFrame::new(
+                ::core::concat!(::core::module_path!(), #function),
+                ::core::panic::Location::caller(),
+            )
+            #(#frame_args)*;
+
+            #asyncness move {
+                let result: #output = async move #block.await;
+                result.map_err(|err| err.with_frame(__pile_frame))
+            }
+        }
+    })
+}
@@ -0,0 +1,203 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Attribute, Data, DeriveInput, Ident, LitInt, LitStr, Result, parse_quote};
+
+/// snake case names of `PileKind`, with the variant they stand for
+const KINDS: &[(&str, &str)] = &[
+    ("auth", "Auth"),
+    ("permission", "Permission"),
+    ("in_use", "InUse"),
+    ("not_ready", "NotReady"),
+    ("not_found", "NotFound"),
+    ("conflict", "Conflict"),
+    ("timeout", "Timeout"),
+    ("cancelled", "Cancelled"),
+    ("unsupported", "Unsupported"),
+    ("config", "Config"),
+    ("rate_limited", "RateLimited"),
+    ("validation", "Validation"),
+    ("payment_declined", "PaymentDeclined"),
+    ("upstream", "Upstream"),
+    ("network", "Network"),
+    ("database", "Database"),
+    ("ssh", "Ssh"),
+    ("io", "Io"),
+    ("parse", "Parse"),
+    ("document", "Document"),
+    ("internal", "Internal"),
+];
+
+/// what `#[pile(...)]` says about the enum or a variant
+#[derive(Default, Clone)]
+struct Mapping {
+    kind: Option<Ident>,
+    code: Option<LitStr>,
+    status: Option<u16>,
+    transient: bool,
+    transparent: bool,
+}
+
+impl Mapping {
+    /// the mapping of the attributes, on top of `self`
+    fn parse(&self, attrs: &[Attribute]) -> Result<Self> {
+        let mut mapping = self.clone();
+
+        for attr in attrs.iter().filter(|a| a.path().is_ident("pile")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("kind") {
+                    let kind: LitStr = meta.value()?.parse()?;
+                    let Some((_, variant)) = KINDS.iter().find(|(name, _)| kind.value() == *name)
+                    else {
+                        let names: Vec<_> = KINDS.iter().map(|(name, _)| *name).collect();
+                        return Err(meta.error(format!(
+                            "unknown kind `{}`, expected one of {}",
+                            kind.value(),
+                            names.join(", ")
+                        )));
+                    };
+                    mapping.kind = Some(Ident::new(variant, kind.span()));
+                } else if meta.path.is_ident("code") {
+                    mapping.code = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("status") {
+                    let status: LitInt = meta.value()?.parse()?;
+                    let value = status.base10_parse::<u16>()?;
+                    if !(100..=599).contains(&value) {
+                        return Err(syn::Error::new(status.span(), "not a HTTP status"));
+                    }
+                    mapping.status = Some(value);
+                } else if meta.path.is_ident("transient") {
+                    mapping.transient = true;
+                } else if meta.path.is_ident("transparent") {
+                    mapping.transparent = true;
+                } else {
+                    return Err(meta
+                        .error("expected `kind`, `code`, `status`, `transient` or `transparent`"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(mapping)
+    }
+
+    /// the `AppError` of the variant bound to `err`
+    fn app_error(&self) -> TokenStream {
+        let kind = self
+            .kind
+            .clone()
+            .unwrap_or_else(|| Ident::new("Internal", Span::call_site()));
+        let mut app = quote! {
+            ::error_pile::AppError::new(err, ::error_pile::PileKind::#kind)
+        };
+        if let Some(code) = &self.code {
+            app = quote! { #app.with_code(#code) };
+        }
+        if let Some(status) = self.status {
+            app = quote! { #app.with_status(#status) };
+        }
+        if self.transient {
+            app = quote! { #app.transient() };
+        }
+        app
+    }
+}
+
+pub fn expand(input: &DeriveInput) -> Result<TokenStream> {
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "IntoPile can only be derived for enums",
+        ));
+    };
+
+    let defaults = Mapping::default().parse(&input.attrs)?;
+    if defaults.transparent {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "`transparent` goes on the variants",
+        ));
+    }
+
+    let name = &input.ident;
+    let mut arms = Vec::new();
+    let mut predicates = Vec::new();
+
+    for variant in &data.variants {
+        let ident = &variant.ident;
+        let mapping = defaults.parse(&variant.attrs)?;
+
+        if mapping.transparent {
+            let mut fields = variant.fields.iter();
+            let (Some(field), None) = (fields.next(), fields.next()) else {
+                return Err(syn::Error::new_spanned(
+                    ident,
+                    "a transparent variant needs exactly one field",
+                ));
+            };
+            let pattern = match &field.ident {
+                Some(field) => quote! { #name::#ident { #field: inner } },
+                None => quote! { #name::#ident(inner) },
+            };
+            arms.push(quote! {
+                #pattern => ::core::convert::Into::into(inner),
+            });
+        } else {
+            let app = mapping.app_error();
+            arms.push(quote! {
+                err @ #name::#ident { .. } => ::error_pile::ErrPile::from(#app),
+            });
+        }
+
+        let predicate = format_ident!("is_{}", snake_case(&ident.to_string()));
+        let doc = format!("checks if this is `{ident}`");
+        predicates.push(quote! {
+            #[doc = #doc]
+            pub fn #predicate(&self) -> bool {
+                ::core::matches!(self, Self::#ident { .. })
+            }
+        });
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut from_where = where_clause.cloned().unwrap_or_else(|| parse_quote!(where));
+    from_where.predicates.push(parse_quote! {
+        #name #ty_generics: ::std::error::Error + ::core::marker::Send + ::core::marker::Sync + 'static
+    });
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::core::convert::From<#name #ty_generics> for ::error_pile::ErrPile
+        #from_where
+        {
+            fn from(err: #name #ty_generics) -> Self {
+                match err {
+                    #(#arms)*
+                }
+            }
+        }
+
+        #[automatically_derived]
+        #[allow(dead_code)]
+        impl #impl_generics #name #ty_generics #where_clause {
+            #(#predicates)*
+        }
+    })
+}
+
+/// `RoomMissing` to `room_missing`, `HTTPTimeout` to `http_timeout`
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::with_capacity(name.len() + 4);
+
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_lower) {
+                snake.push('_');
+            }
+        }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
@@ -0,0 +1,56 @@
+//! Procedural macros of `error-pile`, used through its `derive` feature
+
+use proc_macro::TokenStream;
+use syn::{DeriveInput, parse_macro_input};
+
+mod context;
+mod into_pile;
+
+/// Converts the error enum of a service into `ErrPile`
+///
+/// Each variant is mapped with `#[pile(...)]` to a kind (the snake case
+/// name of `PileKind`), a code, a status and the transient flag, the
+/// attribute on the enum gives the defaults. `#[pile(transparent)]` hands
+/// the single field of the variant over to its own `Into<ErrPile>`.
+/// The variants also get a `is_<variant>()` predicate
+///
+/// ```ignore
+/// #[derive(Debug, thiserror::Error, IntoPile)]
+/// #[pile(kind = "internal")]
+/// enum BookingError {
+///     #[error("room {0} does not exist")]
+///     #[pile(kind = "not_found", code = "room_missing")]
+///     RoomMissing(String),
+///     #[error("the room is already booked")]
+///     #[pile(kind = "conflict", code = "overbooked", status = 409)]
+///     Overbooked,
+///     #[error(transparent)]
+///     #[pile(transparent)]
+///     Pms(ErrPile),
+/// }
+/// ```
+#[proc_macro_derive(IntoPile, attributes(pile))]
+pub fn derive_into_pile(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    into_pile::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Records the async function, the arguments named in the attribute and
+/// where it was called from on the errors leaving it, see `ContextFrame`.
+/// The function has to return a `PileResult`, the arguments to be `Debug`
+///
+/// ```ignore
+/// #[pile_context(room)]
+/// async fn reserve_room(room: &str, guest: &Guest) -> PileResult<Reservation> {
+///     let availability = pms.availability(room).await?;
+///     …
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn pile_context(args: TokenStream, item: TokenStream) -> TokenStream {
+    context::expand(args.into(), item.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS errpile_errors (
+    id BIGSERIAL PRIMARY KEY,
+    kind TEXT NOT NULL,
+    severity TEXT NOT NULL,
+    code TEXT,
+    message TEXT NOT NULL,
+    user_message TEXT NOT NULL,
+    chain JSONB NOT NULL,
+    metadata JSONB NOT NULL,
+    fingerprint TEXT NOT NULL,
+    transient BOOLEAN NOT NULL,
+    occurrences BIGINT NOT NULL,
+    occurred_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS errpile_errors_occurred_at ON errpile_errors (occurred_at);
+CREATE INDEX IF NOT EXISTS errpile_errors_fingerprint ON errpile_errors (fingerprint, occurred_at);
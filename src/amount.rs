@@ -0,0 +1,215 @@
+use core::fmt;
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::ErrPile;
+
+/// Why an amount didn't parse
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AmountFailure {
+    /// `89,50` or `1.234,50`, written with a comma as decimal separator
+    CommaDecimalSeparator,
+    /// `1,234`, a thousands separator or a decimal comma, the line has
+    /// to be checked
+    AmbiguousSeparator,
+    /// not a number at all
+    InvalidNumber,
+    /// the amount is in another currency than the expected one
+    CurrencyMismatch,
+    /// the text around the number isn't a currency
+    UnknownCurrency,
+}
+
+impl AmountFailure {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::CommaDecimalSeparator => "comma_decimal_separator",
+            Self::AmbiguousSeparator => "ambiguous_separator",
+            Self::InvalidNumber => "invalid_number",
+            Self::CurrencyMismatch => "currency_mismatch",
+            Self::UnknownCurrency => "unknown_currency",
+        }
+    }
+}
+
+impl fmt::Display for AmountFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An amount of a rate import or a folio that doesn't parse, with the
+/// raw input so the line can be found and fixed, see `parse_amount`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AmountError {
+    pub failure: AmountFailure,
+    /// the text as it was received
+    pub input: String,
+    /// ISO 4217 code the amount had to be in
+    pub expected_currency: Option<String>,
+    /// ISO 4217 code written along with the amount
+    pub currency: Option<String>,
+    pub source: Option<rust_decimal::Error>,
+}
+
+impl AmountError {
+    pub fn new<I>(failure: AmountFailure, input: I) -> Self
+    where
+        I: Into<String>,
+    {
+        Self {
+            failure,
+            input: input.into(),
+            expected_currency: None,
+            currency: None,
+            source: None,
+        }
+    }
+
+    /// The amount read with a comma as decimal separator and dots as
+    /// thousands separators, what `89,50` most likely meant
+    pub fn comma_decimal(&self) -> Option<Decimal> {
+        if self.failure != AmountFailure::CommaDecimalSeparator {
+            return None;
+        }
+        let (_, number) = split_currency(&self.input).ok()?;
+        Decimal::from_str(&number.replace('.', "").replace(',', ".")).ok()
+    }
+}
+
+impl fmt::Display for AmountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid amount `{}`", self.input)?;
+        match (self.failure, &self.expected_currency, &self.currency) {
+            (AmountFailure::CurrencyMismatch, Some(expected), Some(found)) => {
+                write!(f, ": in {found}, expected {expected}")
+            }
+            (AmountFailure::CommaDecimalSeparator, ..) => {
+                f.write_str(": a comma is used as decimal separator")
+            }
+            (AmountFailure::AmbiguousSeparator, ..) => {
+                f.write_str(": the comma may separate the thousands or the decimals")
+            }
+            (AmountFailure::UnknownCurrency, ..) => f.write_str(": unknown currency"),
+            _ => f.write_str(": not a number"),
+        }
+    }
+}
+
+impl std::error::Error for AmountError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.as_ref().map(|e| e as _)
+    }
+}
+
+impl From<AmountError> for ErrPile {
+    fn from(value: AmountError) -> Self {
+        ErrPile::Amount(Box::new(value))
+    }
+}
+
+impl ErrPile {
+    /// the amount that didn't parse, see `parse_amount`
+    pub fn amount_error(&self) -> Option<&AmountError> {
+        match self.peeled() {
+            Self::Amount(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Parses an amount of a rate import or a folio, `.` being the decimal
+/// separator. The ISO 4217 code or the symbol (`€`, `£`) may come before
+/// or after the number and has to be `currency` when one is expected:
+///
+/// ```ignore
+/// let rate = parse_amount("EUR 89.50", Some("EUR"))?;
+/// ```
+///
+/// `89,50` is a `CommaDecimalSeparator` failure rather than a generic
+/// parse error, `AmountError::comma_decimal` gives what was meant.
+/// `1,234` may be either, an `AmbiguousSeparator`
+pub fn parse_amount(input: &str, currency: Option<&str>) -> Result<Decimal, AmountError> {
+    let error = |failure: AmountFailure| AmountError {
+        expected_currency: currency.map(|c| c.to_ascii_uppercase()),
+        ..AmountError::new(failure, input)
+    };
+
+    let (found, number) =
+        split_currency(input).map_err(|_| error(AmountFailure::UnknownCurrency))?;
+    if let (Some(found), Some(expected)) = (&found, currency)
+        && !found.eq_ignore_ascii_case(expected)
+    {
+        return Err(AmountError {
+            currency: Some(found.clone()),
+            ..error(AmountFailure::CurrencyMismatch)
+        });
+    }
+
+    Decimal::from_str(number).map_err(|source| {
+        let failure = separator_failure(number);
+        AmountError {
+            currency: found,
+            source: Some(source),
+            ..error(failure)
+        }
+    })
+}
+
+/// the currency written before or after the number, and the number
+fn split_currency(input: &str) -> Result<(Option<String>, &str), ()> {
+    let input = input.trim();
+    // `n/a`, `-`: not a number rather than a currency
+    if !input.contains(|c: char| c.is_ascii_digit()) {
+        return Ok((None, input));
+    }
+    let is_number = |c: char| c.is_ascii_digit() || matches!(c, '.' | ',' | '-' | '+');
+
+    let start = input.find(is_number).unwrap_or(input.len());
+    let end = input.rfind(is_number).map_or(start, |end| end + 1);
+    let number = &input[start..end];
+    let currency = match (input[..start].trim(), input[end..].trim()) {
+        ("", "") => None,
+        (code, "") | ("", code) => Some(currency_code(code).ok_or(())?),
+        _ => return Err(()),
+    };
+
+    Ok((currency, number))
+}
+
+fn currency_code(text: &str) -> Option<String> {
+    match text {
+        "€" => Some("EUR".into()),
+        "£" => Some("GBP".into()),
+        code if code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic()) => {
+            Some(code.to_ascii_uppercase())
+        }
+        _ => None,
+    }
+}
+
+/// Whether the number uses a decimal comma: a single comma, after the
+/// dots and followed by digits (`89,50`, `1.234,50`). Alone with three
+/// digits (`1,234`) it may as well be a thousands separator, several
+/// (`1,234,567`) are thousands separators
+fn separator_failure(number: &str) -> AmountFailure {
+    let Some(comma) = number.rfind(',') else {
+        return AmountFailure::InvalidNumber;
+    };
+    let decimals = &number[comma + 1..];
+    let comma_decimal = number.matches(',').count() == 1
+        && number.rfind('.').is_none_or(|dot| dot < comma)
+        && !decimals.is_empty()
+        && decimals.chars().all(|c| c.is_ascii_digit());
+
+    if !comma_decimal {
+        AmountFailure::InvalidNumber
+    } else if !number.contains('.') && decimals.len() == 3 {
+        AmountFailure::AmbiguousSeparator
+    } else {
+        AmountFailure::CommaDecimalSeparator
+    }
+}
@@ -0,0 +1,70 @@
+use core::fmt;
+use std::error::Error;
+
+use crate::{ErrPile, PileKind};
+
+/// An error of a service's own enum, with the kind, code and status it
+/// maps to. What `#[derive(IntoPile)]` converts the variants into, the
+/// enum stays around as the error so its message and sources are kept
+#[derive(Debug)]
+pub struct AppError {
+    pub kind: PileKind,
+    pub code: Option<&'static str>,
+    /// status to answer with, the one of the kind when `None`
+    pub status: Option<u16>,
+    pub transient: bool,
+    pub error: Box<dyn Error + Send + Sync>,
+}
+
+impl AppError {
+    pub fn new<E>(error: E, kind: PileKind) -> Self
+    where
+        E: Error + Send + Sync + 'static,
+    {
+        Self {
+            kind,
+            code: None,
+            status: None,
+            transient: false,
+            error: Box::new(error),
+        }
+    }
+
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    pub fn with_status(mut self, status: u16) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn transient(mut self) -> Self {
+        self.transient = true;
+        self
+    }
+
+    /// the application error, if it is an `E`
+    pub fn downcast_ref<E: Error + 'static>(&self) -> Option<&E> {
+        self.error.downcast_ref()
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl Error for AppError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.error.source()
+    }
+}
+
+impl From<AppError> for ErrPile {
+    fn from(value: AppError) -> Self {
+        ErrPile::App(Box::new(value))
+    }
+}
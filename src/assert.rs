@@ -0,0 +1,86 @@
+use core::fmt::Debug;
+
+use crate::ErrPile;
+
+/// what `assert_pile!` and `assert_pile_code!` accept: an `ErrPile` or a
+/// `Result` holding one
+#[doc(hidden)]
+pub trait PileAssertable {
+    /// the error, or the `Ok` value formatted for the failure message
+    fn pile_err(&self) -> Result<&ErrPile, String>;
+}
+
+impl PileAssertable for ErrPile {
+    fn pile_err(&self) -> Result<&ErrPile, String> {
+        Ok(self)
+    }
+}
+
+impl<T: Debug> PileAssertable for Result<T, ErrPile> {
+    fn pile_err(&self) -> Result<&ErrPile, String> {
+        match self {
+            Ok(value) => Err(format!("{value:?}")),
+            Err(err) => Ok(err),
+        }
+    }
+}
+
+/// the error to match, panics with the `Ok` value when there is none
+#[doc(hidden)]
+#[track_caller]
+pub fn __pile_err<'a, R: PileAssertable + ?Sized>(result: &'a R, expected: &str) -> &'a ErrPile {
+    match result.pile_err() {
+        Ok(err) => err,
+        Err(value) => panic!("expected an error matching `{expected}`, got Ok({value})"),
+    }
+}
+
+/// the failure message, with the whole chain
+#[doc(hidden)]
+#[track_caller]
+pub fn __pile_mismatch(expected: &str, err: &ErrPile) -> ! {
+    panic!(
+        "expected an error matching `{expected}`, got\n{}\n{err:?}",
+        err.pretty()
+    )
+}
+
+/// Asserts a `Result` failed with, or an `ErrPile` is, an error matching
+/// the pattern. `Captured` and `Context` are looked through, the failure
+/// message holds the whole chain instead of `matches!`'s `false`
+///
+/// ```ignore
+/// assert_pile!(login(&creds).await, ErrPile::Auth);
+/// assert_pile!(res, ErrPile::NotFound(err) if err.resource == "room");
+/// assert_pile!(res, ErrPile::Http(http) if http.status.as_u16() == 409);
+/// ```
+#[macro_export]
+macro_rules! assert_pile {
+    ($result:expr, $pattern:pat $(if $guard:expr)? $(,)?) => {{
+        let result = &$result;
+        let expected = stringify!($pattern $(if $guard)?);
+        let err = $crate::__pile_err(result, expected);
+        if !matches!(err.peeled(), $pattern $(if $guard)?) {
+            $crate::__pile_mismatch(expected, err);
+        }
+    }};
+}
+
+/// Asserts the error carries the machine readable code, see
+/// `ErrPile::code`
+///
+/// ```ignore
+/// assert_pile_code!(res, "itemNotFound");
+/// ```
+#[macro_export]
+macro_rules! assert_pile_code {
+    ($result:expr, $code:expr $(,)?) => {{
+        let result = &$result;
+        let code: &str = $code;
+        let expected = format!("code {code:?}");
+        let err = $crate::__pile_err(result, &expected);
+        if err.code().as_deref() != Some(code) {
+            $crate::__pile_mismatch(&expected, err);
+        }
+    }};
+}
@@ -0,0 +1,88 @@
+use std::future::Future;
+
+use crate::{Deadline, ErrPile, PileResult, RetryPolicy};
+
+/// Outcome of `retry_batch`
+#[derive(Debug)]
+pub struct BatchSummary<K, T> {
+    pub succeeded: Vec<(K, T)>,
+    /// failed with an error retrying doesn't fix
+    pub failed: Vec<(K, ErrPile)>,
+    /// still failing with a transient error when the policy gave up, the
+    /// ones worth another run later
+    pub exhausted: Vec<(K, ErrPile)>,
+}
+
+impl<K, T> BatchSummary<K, T> {
+    /// every item succeeded
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty() && self.exhausted.is_empty()
+    }
+}
+
+/// Runs `op` for every key, then retries only the items the policy
+/// retries (those that failed with a transient error for a `Backoff`), in
+/// rounds, until they succeed or the policy gives up. The wait between
+/// two rounds is the longest one the policy asks for the retried items.
+///
+/// The night audit export re-running two items after a 503 instead of
+/// the whole batch:
+///
+/// ```ignore
+/// let summary = retry_batch(&Backoff::new(), folio_ids, |id| export_folio(id)).await;
+/// for (id, err) in &summary.failed {
+///     tracing::error!(%id, "{}", err.pretty());
+/// }
+/// ```
+pub async fn retry_batch<K, T, P, I, F, Fut>(policy: &P, keys: I, mut op: F) -> BatchSummary<K, T>
+where
+    K: Clone,
+    P: RetryPolicy + ?Sized,
+    I: IntoIterator<Item = K>,
+    F: FnMut(K) -> Fut,
+    Fut: Future<Output = PileResult<T>>,
+{
+    let started = tokio::time::Instant::now();
+    let mut summary = BatchSummary {
+        succeeded: Vec::new(),
+        failed: Vec::new(),
+        exhausted: Vec::new(),
+    };
+    let mut pending: Vec<K> = keys.into_iter().collect();
+    let mut attempt = 0;
+
+    while !pending.is_empty() {
+        let mut retried = Vec::new();
+        let mut wait = None;
+
+        for key in pending {
+            let err = match op(key.clone()).await {
+                Ok(value) => {
+                    summary.succeeded.push((key, value));
+                    continue;
+                }
+                Err(err) => err,
+            };
+
+            match policy.next_delay(attempt, started.elapsed(), &err) {
+                Some(delay) => {
+                    wait = wait.max(Some(delay));
+                    retried.push((key, err));
+                }
+                None if err.is_transient() => summary.exhausted.push((key, err)),
+                None => summary.failed.push((key, err)),
+            }
+        }
+
+        pending = Vec::with_capacity(retried.len());
+        match wait {
+            Some(wait) if Deadline::ensure(wait, "retry").is_ok() => {
+                tokio::time::sleep(wait).await;
+                pending.extend(retried.into_iter().map(|(key, _)| key));
+            }
+            _ => summary.exhausted.extend(retried),
+        }
+        attempt += 1;
+    }
+    summary
+}
@@ -0,0 +1,95 @@
+use std::io::Read;
+
+use bytes::Bytes;
+
+use crate::{
+    ErrPile, PileResult,
+    http::{ErrorBody, max_error_body, parse_json_body},
+};
+
+/// Same as `ReqwestPileResExt` but for `reqwest::blocking::Response`,
+/// used by the synchronous CLI tools and scripts
+pub trait ReqwestBlockingPileResExt: Sized {
+    /// converts the reponse into appropriate ErrPile
+    fn to_pile_result<T>(self) -> PileResult<T>
+    where
+        T: for<'de> serde::Deserialize<'de>;
+
+    /// returns the response untouched on success, otherwise converts
+    /// it into appropriate ErrPile
+    fn error_for_pile(self) -> PileResult<Self>;
+
+    /// raw body of a successful response
+    fn to_pile_bytes(self) -> PileResult<Bytes>;
+
+    /// text body of a successful response
+    fn to_pile_text(self) -> PileResult<String>;
+
+    /// ignores the body of a successful response
+    fn to_pile_empty(self) -> PileResult;
+
+    /// reads the `data` of a GraphQL response
+    fn to_pile_graphql<T>(self) -> PileResult<T>
+    where
+        T: for<'de> serde::Deserialize<'de>;
+}
+
+impl ReqwestBlockingPileResExt for reqwest::blocking::Response {
+    fn to_pile_result<T>(self) -> PileResult<T>
+    where
+        T: for<'de> serde::Deserialize<'de>,
+    {
+        let body = self.error_for_pile()?.bytes()?;
+        parse_json_body(&body)
+    }
+
+    fn error_for_pile(self) -> PileResult<Self> {
+        let status = self.status();
+        if status.is_success() {
+            return Ok(self);
+        }
+
+        let headers = self.headers().clone();
+        let url = self.url().clone();
+
+        if let Some(err) = ErrPile::throttled(status, &headers, &url) {
+            return Err(err);
+        }
+
+        let limit = max_error_body();
+        let mut body = Vec::new();
+        let body = match self
+            .take((limit as u64).saturating_add(1))
+            .read_to_end(&mut body)
+        {
+            Ok(_) => {
+                let truncated = body.len() > limit;
+                body.truncate(limit);
+                ErrorBody::Read { body, truncated }
+            }
+            Err(e) => ErrorBody::Failed(e.to_string()),
+        };
+
+        Err(ErrPile::from_error_parts(status, headers, url, body))
+    }
+
+    fn to_pile_bytes(self) -> PileResult<Bytes> {
+        Ok(self.error_for_pile()?.bytes()?)
+    }
+
+    fn to_pile_text(self) -> PileResult<String> {
+        Ok(self.error_for_pile()?.text()?)
+    }
+
+    fn to_pile_empty(self) -> PileResult {
+        self.error_for_pile().map(|_| ())
+    }
+
+    fn to_pile_graphql<T>(self) -> PileResult<T>
+    where
+        T: for<'de> serde::Deserialize<'de>,
+    {
+        let body = self.error_for_pile()?.bytes()?;
+        crate::graphql::graphql_result(&body)
+    }
+}
@@ -0,0 +1,33 @@
+use std::future::Future;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::{ErrPile, PileResult};
+
+impl From<&CancellationToken> for ErrPile {
+    fn from(_: &CancellationToken) -> Self {
+        ErrPile::cancelled()
+    }
+}
+
+/// Returns `ErrPile::Cancelled` if the token has already been cancelled,
+/// handy as a check point inside long running loops
+pub fn check_cancelled(token: &CancellationToken) -> PileResult {
+    if token.is_cancelled() {
+        return Err(token.into());
+    }
+
+    Ok(())
+}
+
+/// Runs the future until it completes or the token is cancelled,
+/// whichever happens first
+pub async fn until_cancelled<F, T>(token: &CancellationToken, fut: F) -> PileResult<T>
+where
+    F: Future<Output = PileResult<T>>,
+{
+    match token.run_until_cancelled(fut).await {
+        Some(res) => res,
+        None => Err(token.into()),
+    }
+}
@@ -0,0 +1,256 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    path::Path,
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ErrPile, PileResult};
+
+static CATALOG: RwLock<Option<Arc<MessageCatalog>>> = RwLock::new(None);
+
+/// bumped by every `set_message_catalog`, 0 while none was ever set
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// the catalog of the generation the thread last read, so
+    /// `user_message` doesn't take the lock on every call
+    static SNAPSHOT: RefCell<(u64, Option<Arc<MessageCatalog>>)> = const { RefCell::new((0, None)) };
+}
+
+/// Who reads the message: the guest, the staff at the front desk or the
+/// engineer looking at the logs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Audience {
+    Guest,
+    FrontDesk,
+    Engineer,
+}
+
+/// the texts of a code, a missing audience falls back to the built-in
+/// message
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guest: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub front_desk: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub engineer: Option<String>,
+}
+
+impl CatalogEntry {
+    pub fn text(&self, audience: Audience) -> Option<&str> {
+        match audience {
+            Audience::Guest => self.guest.as_deref(),
+            Audience::FrontDesk => self.front_desk.as_deref(),
+            Audience::Engineer => self.engineer.as_deref(),
+        }
+    }
+}
+
+/// Texts of the errors by code, kept by the hotel chain in a file so the
+/// wording changes without a release:
+///
+/// ```json
+/// {
+///   "room_unavailable": {
+///     "guest": "Sorry, there is no {room_type} room left for these dates",
+///     "front_desk": "No {room_type} left from {arrival} to {departure}, offer an upgrade"
+///   },
+///   "NotFound": { "guest": "We could not find what you were looking for" }
+/// }
+/// ```
+///
+/// An error is looked up by its `code`, then by its variant name. The
+/// `{placeholders}` are the arguments of `ErrPile::message_args`, unknown
+/// ones are left as they are
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MessageCatalog {
+    pub entries: HashMap<String, CatalogEntry>,
+}
+
+impl MessageCatalog {
+    pub fn from_json(json: &[u8]) -> serde_json::Result<Self> {
+        serde_json::from_slice(json)
+    }
+
+    /// same layout as the JSON, a table per code
+    #[cfg(feature = "toml")]
+    pub fn from_toml(toml: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(toml)
+    }
+
+    /// Reads the catalog, TOML for the `.toml` files (with the `toml`
+    /// feature), JSON otherwise. A file that doesn't parse is a `Config`
+    /// error naming the file
+    pub fn load<P>(path: P) -> PileResult<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let content = std::fs::read(path)?;
+        let invalid = |reason: String| ErrPile::config_in_file("message_catalog", path, reason);
+
+        #[cfg(feature = "toml")]
+        if path.extension().is_some_and(|ext| ext == "toml") {
+            let content = String::from_utf8(content).map_err(|e| invalid(e.to_string()))?;
+            return Self::from_toml(&content).map_err(|e| invalid(e.message().to_string()));
+        }
+
+        Self::from_json(&content).map_err(|e| invalid(e.to_string()))
+    }
+
+    /// the entry of the error, by code then variant name
+    pub fn entry(&self, err: &ErrPile) -> Option<&CatalogEntry> {
+        err.code()
+            .and_then(|code| self.entries.get(&code))
+            .or_else(|| self.entries.get(err.variant_name()))
+    }
+
+    /// the text of the error for the audience, with its arguments filled
+    pub fn render(&self, err: &ErrPile, audience: Audience) -> Option<String> {
+        let template = self.entry(err)?.text(audience)?;
+        Some(fill(template, &err.message_args()))
+    }
+}
+
+/// Makes `user_message` (the guest) and `message_for` use the catalog,
+/// `None` goes back to the built-in messages. Can be called again to
+/// reload the file
+pub fn set_message_catalog(catalog: Option<MessageCatalog>) {
+    *CATALOG.write().unwrap_or_else(|e| e.into_inner()) = catalog.map(Arc::new);
+    GENERATION.fetch_add(1, Ordering::Release);
+}
+
+/// The catalog set with `set_message_catalog`. Nothing to look up until
+/// one is set, then the lock is only taken again after it changes
+pub fn message_catalog() -> Option<Arc<MessageCatalog>> {
+    let generation = GENERATION.load(Ordering::Acquire);
+    if generation == 0 {
+        return None;
+    }
+
+    SNAPSHOT.with(|snapshot| {
+        let mut snapshot = snapshot.borrow_mut();
+        if snapshot.0 != generation {
+            let catalog = CATALOG.read().unwrap_or_else(|e| e.into_inner()).clone();
+            *snapshot = (generation, catalog);
+        }
+        snapshot.1.clone()
+    })
+}
+
+/// `MessageCatalog::load` then `set_message_catalog`, the catalog in use
+/// is kept when the file is invalid
+pub fn load_message_catalog<P>(path: P) -> PileResult
+where
+    P: AsRef<Path>,
+{
+    set_message_catalog(Some(MessageCatalog::load(path)?));
+    Ok(())
+}
+
+impl ErrPile {
+    /// Text of the error for the audience: the entry of the catalog or
+    /// the built-in message, `user_message` for the guest and the front
+    /// desk, the full message for the engineer
+    pub fn message_for(&self, audience: Audience) -> String {
+        if audience == Audience::Guest {
+            return self.user_message();
+        }
+
+        match catalog_message(self, audience) {
+            Some(text) => text,
+            None if audience == Audience::FrontDesk => self.user_message(),
+            None => self.to_string(),
+        }
+    }
+
+    /// Values the catalog templates can use: `code`, `kind`, `variant`,
+    /// `message`, `retry_after` (seconds) and the fields of the variant
+    pub fn message_args(&self) -> Vec<(&'static str, String)> {
+        let mut args = vec![
+            ("kind", self.kind().as_str().to_string()),
+            ("variant", self.variant_name().to_string()),
+            ("message", self.peeled().to_string()),
+        ];
+        if let Some(code) = self.code() {
+            args.push(("code", code));
+        }
+        if let Some(retry_after) = self.retry_after_secs() {
+            args.push(("retry_after", retry_after.to_string()));
+        }
+
+        match self.peeled() {
+            Self::NotFound(err) => {
+                args.push(("resource", err.resource.to_string()));
+                args.push(("id", err.id.clone()));
+            }
+            Self::RateLimited(err) => args.extend(err.scope.clone().map(|s| ("scope", s))),
+            Self::PaymentDeclined { category, .. } => {
+                args.push(("category", category.to_string()));
+            }
+            Self::Multi(multi) => args.push(("count", multi.len().to_string())),
+            Self::KeyEncoder(err) => args.extend(err.encoder.clone().map(|e| ("encoder", e))),
+            #[cfg(feature = "decimal")]
+            Self::Amount(amount) => {
+                args.push(("input", amount.input.clone()));
+                args.extend(amount.expected_currency.clone().map(|c| ("currency", c)));
+            }
+            #[cfg(feature = "booking")]
+            Self::Booking(booking) => args.extend(booking.message_args()),
+            _ => {}
+        }
+
+        args
+    }
+}
+
+/// the text of the catalog in use, if it has one for the error
+pub(crate) fn catalog_message(err: &ErrPile, audience: Audience) -> Option<String> {
+    message_catalog()?.render(err, audience)
+}
+
+/// replaces the `{name}` of the template, `{{` and `}}` are braces
+fn fill(template: &str, args: &[(&str, String)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find(['{', '}']) {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+
+        let value = tail.strip_prefix('{').and_then(|inner| {
+            let end = inner.find('}')?;
+            let value = args.iter().find(|(name, _)| *name == &inner[..end])?;
+            Some((&value.1, end + 2))
+        });
+        match value {
+            Some((value, len)) => {
+                out.push_str(value);
+                rest = &tail[len..];
+            }
+            None => {
+                out.push_str(&tail[..1]);
+                rest = &tail[1..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,401 @@
+use core::fmt;
+use std::{sync::LazyLock, time::Duration};
+
+use regex::Regex;
+use reqwest::{StatusCode, header::HeaderMap};
+use roxmltree::Document;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{ErrPile, PileKind, XmlFault, mask_card_data, parse_retry_after};
+
+/// Booking.com appends the id of the request as a comment to its XML
+/// answers, `<!-- RUID: [UmFuZG9tSVYk…] -->`
+static RUID: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"RUID:\s*\[([^\]\s]+)\]").expect("valid pattern"));
+
+/// the namespaces of the Expedia QuickConnect (EQC) messages
+const EQC_NAMESPACE: &str = "expediaconnect.com";
+
+/// Channel manager the rates, availability and reservations are synced with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+    BookingCom,
+    Expedia,
+}
+
+impl Channel {
+    /// channel of the API host, `None` for the other hosts
+    pub fn from_host(host: &str) -> Option<Self> {
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        let under = |domain: &str| host == domain || host.ends_with(&format!(".{domain}"));
+
+        if under("booking.com") {
+            Some(Self::BookingCom)
+        } else if [
+            "expedia.com",
+            "expediapartnercentral.com",
+            "expediaquickconnect.com",
+        ]
+        .iter()
+        .any(|domain| under(domain))
+        {
+            Some(Self::Expedia)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::BookingCom => "Booking.com",
+            Self::Expedia => "Expedia",
+        }
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Why the channel refused the request, what decides whether the sync
+/// is retried, the mapping is fixed or the rates are corrected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelRejection {
+    /// the credentials of the connection were refused
+    Auth,
+    /// too many requests, in the status or only in the message
+    RateLimited,
+    /// the hotel code is unknown to the channel or not active
+    PropertyNotMapped,
+    /// a room type or rate plan is not mapped on the channel
+    RoomOrRateNotMapped,
+    /// ARI for a date in the past or beyond the window of the channel
+    DateOutOfRange,
+    /// ARI with a price below the minimum or above the maximum
+    PriceOutOfRange,
+    /// the request was refused for another reason
+    InvalidRequest,
+    /// the channel failed on its side, the request can be sent again
+    Unavailable,
+}
+
+impl ChannelRejection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Auth => "auth",
+            Self::RateLimited => "rate_limited",
+            Self::PropertyNotMapped => "property_not_mapped",
+            Self::RoomOrRateNotMapped => "room_or_rate_not_mapped",
+            Self::DateOutOfRange => "date_out_of_range",
+            Self::PriceOutOfRange => "price_out_of_range",
+            Self::InvalidRequest => "invalid_request",
+            Self::Unavailable => "unavailable",
+        }
+    }
+
+    /// Reason of an OTA `Error/@Code` (the OTA `ERR` code list), as
+    /// Booking.com answers its OTA endpoints
+    fn from_ota_code(code: &str) -> Option<Self> {
+        Some(match code.trim() {
+            "497" => Self::Auth,
+            "375" | "392" | "400" => Self::PropertyNotMapped,
+            "249" | "402" | "783" => Self::RoomOrRateNotMapped,
+            "15" => Self::DateOutOfRange,
+            "187" | "448" | "450" => Self::Unavailable,
+            _ => return None,
+        })
+    }
+
+    /// Reason of an Expedia QuickConnect error code: 1xxx authentication,
+    /// 2xxx a malformed request, 3xxx a business rule (told apart by the
+    /// message) and 4xxx an internal error to retry
+    fn from_eqc_code(code: &str, message: &str) -> Option<Self> {
+        Some(match code.trim().parse::<u16>().ok()? {
+            1000..=1999 => Self::Auth,
+            2000..=2999 => Self::InvalidRequest,
+            3000..=3999 => Self::from_message(message).unwrap_or(Self::InvalidRequest),
+            4000..=4999 => Self::Unavailable,
+            _ => return None,
+        })
+    }
+
+    /// reason told by the message, for the codes without a meaning of
+    /// their own
+    fn from_message(message: &str) -> Option<Self> {
+        let message = message.to_ascii_lowercase();
+        let has = |words: &[&str]| words.iter().any(|w| message.contains(w));
+
+        if has(&[
+            "too many requests",
+            "rate limit",
+            "request limit",
+            "throttl",
+        ]) {
+            Some(Self::RateLimited)
+        } else if has(&["authentication", "authorization failed", "not authorized"]) {
+            Some(Self::Auth)
+        } else if has(&[
+            "room type",
+            "roomtype",
+            "rate plan",
+            "rateplan",
+            "rate code",
+        ]) && has(&[
+            "not found",
+            "not mapped",
+            "does not exist",
+            "invalid",
+            "unknown",
+        ]) {
+            Some(Self::RoomOrRateNotMapped)
+        } else if has(&["hotel", "property"])
+            && has(&[
+                "not found",
+                "not mapped",
+                "does not exist",
+                "invalid",
+                "not active",
+            ])
+        {
+            Some(Self::PropertyNotMapped)
+        } else if has(&["date"]) && has(&["past", "future", "too far", "out of range"]) {
+            Some(Self::DateOutOfRange)
+        } else if has(&["price", "amount", "rate"])
+            && has(&[
+                "too low", "too high", "below", "above", "minimum", "maximum", "exceed",
+            ])
+        {
+            Some(Self::PriceOutOfRange)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for ChannelRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error envelope of a channel manager: the JSON `errors` and the XML
+/// (OTA or B.XML) faults of Booking.com, the `<Error code="">` of the
+/// Expedia QuickConnect messages and the `errors` of their JSON APIs.
+/// Card data in the messages is masked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelError {
+    pub channel: Channel,
+    pub reason: ChannelRejection,
+    pub code: Option<String>,
+    pub message: String,
+    /// the RUID of Booking.com, what their connectivity support asks for
+    pub request_id: Option<String>,
+    pub retry_after: Option<Duration>,
+}
+
+impl ChannelError {
+    /// Parses the error of a response of the channel, `None` when the
+    /// body is not one of its envelopes. Channels answer most failures
+    /// with 200 (see `error_for_channel`), the status only decides when
+    /// the body doesn't
+    pub fn parse(
+        channel: Channel,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Option<Self> {
+        let mut err = match channel {
+            Channel::BookingCom => Self::parse_booking_com(body)?,
+            Channel::Expedia => Self::parse_expedia(body)?,
+        };
+
+        err.retry_after = parse_retry_after(headers);
+        if err.reason == ChannelRejection::InvalidRequest {
+            err.reason = match status.as_u16() {
+                429 => ChannelRejection::RateLimited,
+                401 | 403 => ChannelRejection::Auth,
+                500.. => ChannelRejection::Unavailable,
+                _ => err.reason,
+            };
+        }
+
+        Some(err)
+    }
+
+    /// Error of a body known to come from a channel without the host: the
+    /// Booking.com envelopes carrying a RUID and the Expedia QuickConnect
+    /// messages
+    pub(crate) fn recognize(body: &[u8]) -> Option<Self> {
+        let text = String::from_utf8_lossy(body);
+        if text.contains(EQC_NAMESPACE) {
+            return Self::parse_expedia(body);
+        }
+
+        Self::parse_booking_com(body).filter(|err| err.request_id.is_some())
+    }
+
+    fn parse_booking_com(body: &[u8]) -> Option<Self> {
+        if let Ok(json) = serde_json::from_slice::<Value>(body) {
+            // `{"data": …, "errors": [{"code", "message"}], "meta": {"ruid"}}`
+            let (code, message) = first_error(&json)?;
+            let request_id = json
+                .pointer("/meta/ruid")
+                .and_then(Value::as_str)
+                .map(String::from);
+            return Some(Self::new(Channel::BookingCom, code, message, request_id));
+        }
+
+        let text = String::from_utf8_lossy(body);
+        let request_id = RUID.captures(&text).map(|c| c[1].to_string());
+
+        // the OTA endpoints answer `<Errors><Error Code ShortText>`, the
+        // B.XML ones a bare `<error>` with the message as text
+        let (code, message) = match XmlFault::parse(&text) {
+            Some(fault) => (fault.code, fault.message.or(fault.detail)?),
+            None => {
+                let doc = Document::parse(&text).ok()?;
+                let root = doc.root_element();
+                if !root.tag_name().name().eq_ignore_ascii_case("error") {
+                    return None;
+                }
+                let message = root
+                    .descendants()
+                    .filter_map(|n| n.text())
+                    .flat_map(str::split_whitespace)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                (None, Some(message).filter(|m| !m.is_empty())?)
+            }
+        };
+
+        Some(Self::new(Channel::BookingCom, code, message, request_id))
+    }
+
+    fn parse_expedia(body: &[u8]) -> Option<Self> {
+        if let Ok(json) = serde_json::from_slice::<Value>(body) {
+            let (code, message) = first_error(&json)?;
+            return Some(Self::new(Channel::Expedia, code, message, None));
+        }
+
+        // `<AvailRateUpdateRS xmlns="…expediaconnect.com/EQC/…"><Error code="3202">…`
+        let text = String::from_utf8_lossy(body);
+        let doc = Document::parse(&text).ok()?;
+        let error = doc
+            .root_element()
+            .descendants()
+            .find(|n| n.is_element() && n.tag_name().name() == "Error")?;
+        let message = error
+            .descendants()
+            .filter_map(|n| n.text())
+            .flat_map(str::split_whitespace)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Some(Self::new(
+            Channel::Expedia,
+            error.attribute("code").map(String::from),
+            message,
+            None,
+        ))
+    }
+
+    fn new(
+        channel: Channel,
+        code: Option<String>,
+        message: String,
+        request_id: Option<String>,
+    ) -> Self {
+        // rate limits come with any code, the message tells
+        let reason = ChannelRejection::from_message(&message)
+            .filter(|reason| *reason == ChannelRejection::RateLimited)
+            .or_else(|| {
+                let code = code.as_deref()?;
+                match channel {
+                    Channel::BookingCom => ChannelRejection::from_ota_code(code),
+                    Channel::Expedia => ChannelRejection::from_eqc_code(code, &message),
+                }
+            })
+            .or_else(|| ChannelRejection::from_message(&message))
+            .unwrap_or(ChannelRejection::InvalidRequest);
+
+        Self {
+            channel,
+            reason,
+            code,
+            message: mask_card_data(&message).into_owned(),
+            request_id,
+            retry_after: None,
+        }
+    }
+
+    /// Whether the same request can be sent again: throttled or failed
+    /// on the side of the channel. Rejected ARI has to be corrected first
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self.reason,
+            ChannelRejection::RateLimited | ChannelRejection::Unavailable
+        )
+    }
+
+    /// category of the error, see `ErrPile::kind`. Refused credentials
+    /// and missing mappings are fixed in the configuration of the
+    /// connection, not by the caller
+    pub fn kind(&self) -> PileKind {
+        match self.reason {
+            ChannelRejection::Auth
+            | ChannelRejection::PropertyNotMapped
+            | ChannelRejection::RoomOrRateNotMapped => PileKind::Config,
+            ChannelRejection::RateLimited => PileKind::RateLimited,
+            ChannelRejection::DateOutOfRange
+            | ChannelRejection::PriceOutOfRange
+            | ChannelRejection::InvalidRequest => PileKind::Validation,
+            ChannelRejection::Unavailable => PileKind::Upstream,
+        }
+    }
+}
+
+impl fmt::Display for ChannelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} rejected the request", self.channel)?;
+        if let Some(code) = &self.code {
+            write!(f, " (code {code})")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for ChannelError {}
+
+impl From<ChannelError> for ErrPile {
+    fn from(value: ChannelError) -> Self {
+        ErrPile::Channel(Box::new(value))
+    }
+}
+
+impl ErrPile {
+    /// why a channel manager refused the request
+    pub fn channel_rejection(&self) -> Option<ChannelRejection> {
+        match self.peeled() {
+            Self::Channel(err) => Some(err.reason),
+            _ => None,
+        }
+    }
+}
+
+/// code and message of the first entry of a JSON `errors` array, the
+/// codes are numbers or strings
+fn first_error(json: &Value) -> Option<(Option<String>, String)> {
+    let error = json.get("errors")?.as_array()?.first()?;
+    let message = error.get("message")?.as_str()?.to_string();
+    let code = match error.get("code") {
+        Some(Value::String(code)) => Some(code.clone()),
+        Some(Value::Number(code)) => Some(code.to_string()),
+        _ => None,
+    };
+    Some((code, message))
+}
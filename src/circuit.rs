@@ -0,0 +1,158 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::{ErrPile, PileKind, PileResult};
+
+/// Where the circuit of a key is at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// calls go through
+    Closed,
+    /// calls fail right away with `ErrPile::NotReady`
+    Open,
+    /// the cooldown is over, the next call probes the endpoint
+    HalfOpen,
+}
+
+#[derive(Debug)]
+enum Circuit {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    HalfOpen { probing: bool },
+}
+
+/// Stops calling an endpoint that keeps failing: after `threshold`
+/// consecutive failures of a key (an endpoint, a tenant, …) the circuit
+/// opens and calls fail fast with `ErrPile::NotReady` for `cooldown`.
+/// Then a single call probes the endpoint, closing the circuit again on
+/// success.
+///
+/// Transient errors count as failures, `trips_on` picks the kinds
+/// instead. Other errors (a 404, bad input) say nothing about the health
+/// of the endpoint and reset the count like a success
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    kinds: Vec<PileKind>,
+    circuits: Mutex<HashMap<String, Circuit>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            cooldown,
+            kinds: Vec::new(),
+            circuits: Mutex::default(),
+        }
+    }
+
+    /// counts errors of the kind as failures, once called only the
+    /// listed kinds do
+    pub fn trips_on(mut self, kind: PileKind) -> Self {
+        self.kinds.push(kind);
+        self
+    }
+
+    pub fn state(&self, key: &str) -> CircuitState {
+        match self.lock().get(key) {
+            None | Some(Circuit::Closed { .. }) => CircuitState::Closed,
+            Some(Circuit::Open { until }) if *until > Instant::now() => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Runs the call unless the circuit of `key` is open
+    pub async fn call<T, F>(&self, key: &str, call: F) -> PileResult<T>
+    where
+        F: Future<Output = PileResult<T>>,
+    {
+        let mut probe = Probe {
+            breaker: self,
+            key,
+            armed: self.acquire(key)?,
+        };
+        let result = call.await;
+        probe.armed = false;
+        self.record(key, result.as_ref().err());
+        result
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Circuit>> {
+        self.circuits.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn counts(&self, err: &ErrPile) -> bool {
+        if self.kinds.is_empty() {
+            err.is_transient()
+        } else {
+            self.kinds.contains(&err.kind())
+        }
+    }
+
+    /// lets the call through, `Ok(true)` when it probes a half open circuit
+    fn acquire(&self, key: &str) -> PileResult<bool> {
+        let mut circuits = self.lock();
+        let Some(circuit) = circuits.get_mut(key) else {
+            return Ok(false);
+        };
+
+        match circuit {
+            Circuit::Closed { .. } => Ok(false),
+            Circuit::Open { until } if *until > Instant::now() => Err(ErrPile::NotReady),
+            Circuit::Open { .. } | Circuit::HalfOpen { probing: false } => {
+                *circuit = Circuit::HalfOpen { probing: true };
+                Ok(true)
+            }
+            Circuit::HalfOpen { probing: true } => Err(ErrPile::NotReady),
+        }
+    }
+
+    fn record(&self, key: &str, err: Option<&ErrPile>) {
+        let failed = err.is_some_and(|err| self.counts(err));
+        let mut circuits = self.lock();
+
+        if !failed {
+            circuits.remove(key);
+            return;
+        }
+
+        let circuit = circuits
+            .entry(key.to_string())
+            .or_insert(Circuit::Closed { failures: 0 });
+        let failures = match circuit {
+            Circuit::Closed { failures } => *failures + 1,
+            // the probe failed
+            _ => self.threshold,
+        };
+        *circuit = if failures >= self.threshold {
+            Circuit::Open {
+                until: Instant::now() + self.cooldown,
+            }
+        } else {
+            Circuit::Closed { failures }
+        };
+    }
+}
+
+/// lets another call probe when the probing one is dropped unfinished
+struct Probe<'a> {
+    breaker: &'a CircuitBreaker,
+    key: &'a str,
+    armed: bool,
+}
+
+impl Drop for Probe<'_> {
+    fn drop(&mut self) {
+        if self.armed
+            && let Some(circuit) = self.breaker.lock().get_mut(self.key)
+        {
+            *circuit = Circuit::HalfOpen { probing: false };
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use sqlx::error::{DatabaseError, ErrorKind};
+
+use crate::{ErrPile, PileKind, StorageError};
+
+static CONSTRAINT_KINDS: RwLock<Option<HashMap<String, PileKind>>> = RwLock::new(None);
+
+/// Kind a violation of the named constraint maps to, a unique index on
+/// the room nights of the reservations being a `Conflict` of the booking
+/// rather than a `Database` failure. The status, severity and user message
+/// follow the kind
+pub fn register_constraint_kind<N>(constraint: N, kind: PileKind)
+where
+    N: Into<String>,
+{
+    CONSTRAINT_KINDS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_or_insert_with(HashMap::new)
+        .insert(constraint.into(), kind);
+}
+
+/// the kind registered with `register_constraint_kind`
+pub fn constraint_kind(constraint: &str) -> Option<PileKind> {
+    CONSTRAINT_KINDS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()?
+        .get(constraint)
+        .copied()
+}
+
+/// Integrity constraint the statement broke
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConstraintViolation {
+    Unique,
+    ForeignKey,
+    NotNull,
+    Check,
+}
+
+impl ConstraintViolation {
+    fn of(db: &dyn DatabaseError) -> Option<Self> {
+        match db.kind() {
+            ErrorKind::UniqueViolation => Some(Self::Unique),
+            ErrorKind::ForeignKeyViolation => Some(Self::ForeignKey),
+            ErrorKind::NotNullViolation => Some(Self::NotNull),
+            ErrorKind::CheckViolation => Some(Self::Check),
+            _ => None,
+        }
+    }
+}
+
+impl ErrPile {
+    /// the constraint violation of a database error
+    pub fn constraint_violation(&self) -> Option<ConstraintViolation> {
+        ConstraintViolation::of(self.database_error()?)
+    }
+
+    /// Name of the violated constraint. SQLite doesn't report it, the
+    /// message has the name of a `CHECK` and the columns of the others
+    pub fn constraint(&self) -> Option<&str> {
+        let db = self.database_error()?;
+        ConstraintViolation::of(db)?;
+        constraint_name(db)
+    }
+
+    /// checks if a unique constraint or index was violated, the row
+    /// already exists
+    pub fn is_unique_violation(&self) -> bool {
+        self.constraint_violation() == Some(ConstraintViolation::Unique)
+    }
+
+    /// `db.system` of a database error, the driver that returned it. The
+    /// drivers are told apart with the `postgres`, `sqlite` and `mysql`
+    /// features, `None` for the others
+    pub fn db_system(&self) -> Option<&'static str> {
+        #[allow(unused_variables)]
+        let db = self.database_error()?;
+        #[cfg(feature = "postgres")]
+        if db
+            .try_downcast_ref::<sqlx::postgres::PgDatabaseError>()
+            .is_some()
+        {
+            return Some("postgresql");
+        }
+        #[cfg(feature = "sqlite")]
+        if db.try_downcast_ref::<sqlx::sqlite::SqliteError>().is_some() {
+            return Some("sqlite");
+        }
+        #[cfg(feature = "mysql")]
+        if db
+            .try_downcast_ref::<sqlx::mysql::MySqlDatabaseError>()
+            .is_some()
+        {
+            return Some("mysql");
+        }
+        None
+    }
+
+    fn database_error(&self) -> Option<&dyn DatabaseError> {
+        match self.peeled() {
+            Self::Storage(storage) => match &**storage {
+                StorageError::DB(db) => match db.as_ref() {
+                    sqlx::Error::Database(db) => Some(db.as_ref()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// kind registered for the constraint the error violated
+pub(crate) fn violated_constraint_kind(db: &sqlx::Error) -> Option<PileKind> {
+    let sqlx::Error::Database(db) = db else {
+        return None;
+    };
+    ConstraintViolation::of(db.as_ref())?;
+    constraint_kind(constraint_name(db.as_ref())?)
+}
+
+/// checks if the statement broke a constraint, running it again gives
+/// the same answer
+pub(crate) fn is_constraint_violation(db: &dyn DatabaseError) -> bool {
+    ConstraintViolation::of(db).is_some()
+}
+
+fn constraint_name(db: &dyn DatabaseError) -> Option<&str> {
+    db.constraint().or_else(|| {
+        db.message()
+            .split_once("constraint failed: ")
+            .map(|(_, name)| name.trim())
+    })
+}
@@ -0,0 +1,139 @@
+use core::fmt;
+use std::{borrow::Cow, panic::Location};
+
+use crate::{ErrPile, redact};
+
+/// A function the error went through, recorded by `#[pile_context]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextFrame {
+    /// path of the function, `booking::reserve_room`
+    pub function: &'static str,
+    /// the arguments named in the attribute, `Debug` formatted, redacted
+    /// once the frame is attached to an error
+    pub args: Vec<(&'static str, String)>,
+    /// where the function was called from
+    pub location: &'static Location<'static>,
+}
+
+impl ContextFrame {
+    pub fn new(function: &'static str, location: &'static Location<'static>) -> Self {
+        Self {
+            function,
+            args: Vec::new(),
+            location,
+        }
+    }
+
+    /// Records the argument as it is, the calls that succeed only pay for
+    /// the `Debug` formatting
+    pub fn arg<V: fmt::Debug + ?Sized>(mut self, name: &'static str, value: &V) -> Self {
+        self.args.push((name, format!("{value:?}")));
+        self
+    }
+
+    fn redacted(mut self) -> Self {
+        for (_, value) in &mut self.args {
+            if let Cow::Owned(scrubbed) = redact(value) {
+                *value = scrubbed;
+            }
+        }
+        self
+    }
+}
+
+/// `booking::reserve_room(room: "214") at src/front_desk.rs:42:9`
+impl fmt::Display for ContextFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}(", self.function)?;
+        for (i, (name, value)) in self.args.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{name}: {value}")?;
+        }
+        write!(f, ") at {}", self.location)
+    }
+}
+
+/// An error with the functions it propagated out of and the correlation
+/// ids attached on the way, displays and behaves as the inner error
+#[derive(Debug)]
+pub struct ContextError {
+    /// innermost first
+    pub frames: Vec<ContextFrame>,
+    /// see `ErrPile::with_correlation_id`
+    pub correlation_ids: Vec<(&'static str, String)>,
+    pub error: ErrPile,
+}
+
+impl fmt::Display for ContextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl std::error::Error for ContextError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        std::error::Error::source(&self.error)
+    }
+}
+
+impl ErrPile {
+    /// records that the error went through the function of `frame`, its
+    /// arguments are redacted here
+    pub fn with_frame(self, frame: ContextFrame) -> Self {
+        let frame = frame.redacted();
+        match self {
+            Self::Context(mut context) => {
+                context.frames.push(frame);
+                Self::Context(context)
+            }
+            error => Self::Context(Box::new(ContextError {
+                frames: vec![frame],
+                correlation_ids: Vec::new(),
+                error,
+            })),
+        }
+    }
+
+    /// Attaches the id of the request the error happened in, listed first
+    /// by `correlation_ids` so the logs, the reports and the response share
+    /// it
+    pub fn with_correlation_id<I: Into<String>>(self, name: &'static str, id: I) -> Self {
+        let id = id.into();
+        match self {
+            Self::Context(mut context) => {
+                context.correlation_ids.retain(|(n, _)| *n != name);
+                context.correlation_ids.insert(0, (name, id));
+                Self::Context(context)
+            }
+            error => Self::Context(Box::new(ContextError {
+                frames: Vec::new(),
+                correlation_ids: vec![(name, id)],
+                error,
+            })),
+        }
+    }
+
+    /// the ids of `with_correlation_id`, outermost first
+    pub(crate) fn attached_correlation_ids(&self) -> Vec<(&'static str, String)> {
+        match self {
+            Self::Context(context) => {
+                let mut ids = context.correlation_ids.clone();
+                ids.extend(context.error.attached_correlation_ids());
+                ids
+            }
+            Self::Captured(captured) => captured.error.attached_correlation_ids(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// the functions the error went through, innermost first
+    pub fn context_frames(&self) -> &[ContextFrame] {
+        match self {
+            Self::Context(context) => &context.frames,
+            Self::Captured(captured) => captured.error.context_frames(),
+            _ => &[],
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use reqwest::header::HeaderName;
+
+use crate::{REDACTED, redact, redact_url};
+
+static CAPTURE_CURL: AtomicBool = AtomicBool::new(false);
+
+/// When enabled, requests sent with `send_pile` keep a redacted `curl`
+/// reproduction of themselves on the error
+pub fn set_capture_curl(enabled: bool) {
+    CAPTURE_CURL.store(enabled, Ordering::Relaxed);
+}
+
+/// whether failed requests carry a `curl` reproduction
+pub fn capture_curl() -> bool {
+    CAPTURE_CURL.load(Ordering::Relaxed)
+}
+
+/// headers whose values never end up in the command
+const SECRET_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+    "api-key",
+    "ocp-apim-subscription-key",
+    "x-functions-key",
+];
+
+/// bodies larger than this are left out of the command
+const MAX_BODY: usize = 4 * 1024;
+
+/// header carries credentials and must not be logged
+pub(crate) fn is_secret_header(name: &HeaderName) -> bool {
+    SECRET_HEADERS.contains(&name.as_str())
+}
+
+/// single quotes the value for a POSIX shell
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// `curl` command equivalent to the request, credentials in headers,
+/// the url and the body (see `redact`) are replaced so it can be pasted
+/// into a ticket
+pub fn curl_command(request: &reqwest::Request) -> String {
+    let mut cmd = format!(
+        "curl -X {} {}",
+        request.method(),
+        quote(&redact_url(request.url()))
+    );
+
+    for (name, value) in request.headers() {
+        let value = if is_secret_header(name) {
+            REDACTED
+        } else {
+            value.to_str().unwrap_or("<binary>")
+        };
+        cmd.push_str(&format!(" -H {}", quote(&format!("{name}: {value}"))));
+    }
+
+    if let Some(body) = request.body() {
+        match body.as_bytes().map(std::str::from_utf8) {
+            Some(Ok(text)) if text.len() <= MAX_BODY => {
+                cmd.push_str(&format!(" --data-raw {}", quote(&redact(text))));
+            }
+            // streamed, binary or huge bodies can't be reproduced inline
+            Some(_) | None => cmd.push_str(" --data-binary @body"),
+        }
+    }
+
+    cmd
+}
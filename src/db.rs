@@ -0,0 +1,76 @@
+use sqlx::migrate::MigrateError;
+
+use crate::{ErrPile, StorageError};
+
+/// Rough category of a failed migration run, deploy tooling treats
+/// a dirty schema very differently from an unreachable database
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationFailure {
+    /// an applied migration was modified (checksum mismatch)
+    ChecksumMismatch,
+    /// a migration was partially applied, or an applied migration
+    /// is missing from the source. Needs manual intervention
+    Dirty,
+    /// migration versions are out of order
+    VersionConflict,
+    /// the database could not be reached
+    Connectivity,
+    /// the migration itself failed (bad SQL, constraint, ...)
+    Execution,
+    /// the migrations could not be resolved from the source
+    Source,
+    Other,
+}
+
+impl MigrationFailure {
+    fn from_migrate(err: &MigrateError) -> Self {
+        match err {
+            MigrateError::VersionMismatch(_) => Self::ChecksumMismatch,
+            MigrateError::Dirty(_) | MigrateError::VersionMissing(_) => Self::Dirty,
+            MigrateError::VersionNotPresent(_)
+            | MigrateError::VersionTooOld(..)
+            | MigrateError::VersionTooNew(..) => Self::VersionConflict,
+            MigrateError::Execute(db) | MigrateError::ExecuteMigration(db, _) => {
+                if Self::is_connectivity(db) {
+                    Self::Connectivity
+                } else {
+                    Self::Execution
+                }
+            }
+            MigrateError::Source(_) => Self::Source,
+            _ => Self::Other,
+        }
+    }
+
+    fn is_connectivity(db: &sqlx::Error) -> bool {
+        matches!(
+            db,
+            sqlx::Error::Io(_)
+                | sqlx::Error::Tls(_)
+                | sqlx::Error::PoolTimedOut
+                | sqlx::Error::PoolClosed
+        )
+    }
+}
+
+impl ErrPile {
+    /// category of the migration failure, if this is a migration error
+    pub fn migration_failure(&self) -> Option<MigrationFailure> {
+        match self {
+            Self::Storage(storage) => match &**storage {
+                StorageError::Migrate(err) => Some(MigrationFailure::from_migrate(err)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// the schema is dirty, a human has to look at it before
+    /// migrating again
+    pub fn is_dirty_migration(&self) -> bool {
+        matches!(
+            self.migration_failure(),
+            Some(MigrationFailure::Dirty | MigrationFailure::ChecksumMismatch)
+        )
+    }
+}
@@ -0,0 +1,105 @@
+use std::{borrow::Cow, cell::Cell, future::Future, time::Duration};
+
+use tokio::time::Instant;
+
+use crate::{ErrPile, PileResult};
+
+thread_local! {
+    /// deadline of the scope being polled on this thread
+    static CURRENT: Cell<Option<Deadline>> = const { Cell::new(None) };
+}
+
+/// Time budget of a request, shared by everything awaited in
+/// `Deadline::scope`: `retry`, `PileMiddleware`, `LroPoller` and
+/// `with_timeout` give up with `ErrPile::Timeout` instead of waiting past
+/// it, so a request with 30s left doesn't schedule a 60s backoff.
+/// `PileRetry` stops retrying and returns the last error
+///
+/// ```ignore
+/// Deadline::after(Duration::from_secs(30))
+///     .scope(async {
+///         let folio = retry(&Backoff::new(), || pms.folio(id)).await?;
+///         LroPoller::new().poll(&client, accepted).await
+///     })
+///     .await?;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deadline {
+    at: Instant,
+    budget: Duration,
+}
+
+impl Deadline {
+    /// `budget` from now on
+    pub fn after(budget: Duration) -> Self {
+        Self {
+            at: Instant::now() + budget,
+            budget,
+        }
+    }
+
+    /// the deadline of the scope the caller runs in, the earliest one
+    /// when scopes are nested
+    pub fn current() -> Option<Self> {
+        CURRENT.with(Cell::get)
+    }
+
+    pub fn at(&self) -> Instant {
+        self.at
+    }
+
+    /// time left, zero once expired
+    pub fn remaining(&self) -> Duration {
+        self.at.saturating_duration_since(Instant::now())
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    /// `ErrPile::Timeout` of the operation, after the whole budget
+    pub fn timeout<O>(&self, operation: O) -> ErrPile
+    where
+        O: Into<Cow<'static, str>>,
+    {
+        ErrPile::timeout(operation, self.budget)
+    }
+
+    /// Runs `fut` with this deadline as the current one, a deadline
+    /// already in place is kept when it's earlier
+    pub async fn scope<F: Future>(self, fut: F) -> F::Output {
+        let mut fut = std::pin::pin!(fut);
+        std::future::poll_fn(|cx| {
+            let previous = CURRENT.get();
+            let deadline = match previous {
+                Some(previous) if previous.at <= self.at => previous,
+                _ => self,
+            };
+            CURRENT.set(Some(deadline));
+            let _restore = Restore(previous);
+            fut.as_mut().poll(cx)
+        })
+        .await
+    }
+
+    /// fails with the `Timeout` of the current deadline when waiting
+    /// `wait` would run past it
+    pub(crate) fn ensure<O>(wait: Duration, operation: O) -> PileResult<()>
+    where
+        O: Into<Cow<'static, str>>,
+    {
+        match Self::current() {
+            Some(deadline) if wait > deadline.remaining() => Err(deadline.timeout(operation)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// puts the outer deadline back once the poll is over, panics included
+struct Restore(Option<Deadline>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        CURRENT.set(self.0);
+    }
+}
@@ -0,0 +1,94 @@
+use std::{borrow::Cow, path::PathBuf, time::Duration};
+
+use crate::{ErrPile, Opt};
+
+// The payloads of the variants with more than one field, boxed so a
+// `PileResult` stays four words. The constructors of `ErrPile` build them
+
+/// The requested resource does not exist, see `ErrPile::not_found`
+#[derive(Debug, thiserror::Error)]
+#[error("The {resource} `{id}` could not be found")]
+pub struct NotFoundError {
+    pub resource: &'static str,
+    pub id: String,
+}
+
+/// Optimistic concurrency failure, someone else updated the resource
+/// first
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "The {resource} was modified by someone else{}",
+    Opt(" (current version: ", current_version.as_ref(), ")")
+)]
+pub struct ConflictError {
+    pub resource: String,
+    pub current_version: Option<String>,
+}
+
+/// Operation level timeout, e.g. waiting on Graph or a SFTP transfer
+#[derive(Debug, thiserror::Error)]
+#[error("The operation `{operation}` timed out after {after:?}")]
+pub struct TimeoutError {
+    pub operation: Cow<'static, str>,
+    pub after: Duration,
+}
+
+/// Invalid or missing configuration value, mostly encountered during the
+/// startup
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "Invalid configuration for `{key}`{}: {reason}",
+    Opt(" in ", source_file.as_ref().map(|p| p.display()), "")
+)]
+pub struct ConfigError {
+    pub key: String,
+    pub source_file: Option<PathBuf>,
+    pub reason: String,
+}
+
+/// Upstream is throttling the requests
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "Too many requests{}, please try again later",
+    Opt(" to ", scope.as_ref(), "")
+)]
+pub struct RateLimitError {
+    pub retry_after: Option<Duration>,
+    pub scope: Option<String>,
+}
+
+/// Response was successful but the body did not match the expected shape
+#[derive(Debug, thiserror::Error)]
+#[error("Error parsing Json Data at `{path}`: {source} (near: {snippet})")]
+pub struct DeserializeError {
+    pub path: String,
+    pub snippet: String,
+    #[source]
+    pub source: serde_json::Error,
+}
+
+/// A regular expression that doesn't compile
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid pattern{}", Opt(" `", pattern.as_ref(), "`"))]
+pub struct RegexError {
+    pub pattern: Option<String>,
+    #[source]
+    pub source: regex::Error,
+}
+
+/// A task started with `spawn_pile` panicked or was aborted
+#[derive(Debug, thiserror::Error)]
+#[error("Task `{name}` did not complete")]
+pub struct TaskError {
+    pub name: Cow<'static, str>,
+    #[source]
+    pub source: ErrPile,
+}
+
+/// A panic caught by `pile_catch`, `location` is where it happened
+#[derive(Debug, thiserror::Error)]
+#[error("Panicked{}: {message}", Opt(" at ", location.as_ref(), ""))]
+pub struct PanicError {
+    pub message: String,
+    pub location: Option<String>,
+}
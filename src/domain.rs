@@ -0,0 +1,297 @@
+#[cfg(feature = "booking")]
+use std::borrow::Cow;
+#[cfg(feature = "ssh")]
+use std::path::PathBuf;
+
+#[cfg(feature = "booking")]
+use chrono::NaiveDate;
+
+#[cfg(feature = "ssh")]
+use crate::SshKeyIssue;
+use crate::{AZError, ErrPile, MSResponseError, Opt, graph_diagnostics};
+
+/// Errors talking to other systems: HTTP requests, URLs, SSH and SFTP
+#[derive(Debug, thiserror::Error)]
+pub enum TransportError {
+    /// The url is redacted, secrets in the query are masked
+    #[error(
+        "An error occurred while sending request{}{}",
+        Opt(" ", method.as_ref(), ""),
+        Opt(" ", url.as_ref(), "")
+    )]
+    Req {
+        method: Option<reqwest::Method>,
+        url: Option<String>,
+        /// redacted `curl` reproduction, see `set_capture_curl`
+        curl: Option<String>,
+        #[source]
+        source: reqwest::Error,
+    },
+
+    #[error("An error occurred while parsing the URL")]
+    Url(
+        #[source]
+        #[from]
+        url::ParseError,
+    ),
+
+    #[error("An error occurred while converting Http Header to string")]
+    ReqToStr(
+        #[source]
+        #[from]
+        reqwest::header::ToStrError,
+    ),
+
+    #[cfg(feature = "ssh")]
+    #[error("An error occurred with SSH")]
+    Ssh(
+        #[source]
+        #[from]
+        Box<russh::Error>,
+    ),
+
+    #[cfg(feature = "ssh")]
+    #[error(
+        "Unable to load the SSH key{}: {issue}",
+        Opt(" ", path.as_ref().map(|p| p.display()), "")
+    )]
+    SshKey {
+        path: Option<PathBuf>,
+        issue: SshKeyIssue,
+        #[source]
+        source: Box<russh::keys::Error>,
+    },
+
+    #[cfg(feature = "ssh")]
+    #[error("An error occurred with sftp connection")]
+    Sftp(
+        #[source]
+        #[from]
+        Box<russh_sftp::client::error::Error>,
+    ),
+}
+
+/// Errors reading or writing data: the database, files and zip archives
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    #[cfg(feature = "sqlx")]
+    #[error("Error connecting/ storing to DB")]
+    DB(
+        #[source]
+        #[from]
+        Box<sqlx::Error>,
+    ),
+
+    #[cfg(feature = "migrate")]
+    #[error("An error occurred while running the DB migrations")]
+    Migrate(
+        #[source]
+        #[from]
+        sqlx::migrate::MigrateError,
+    ),
+
+    #[error("IO Err: {0}")]
+    IO(
+        #[source]
+        #[from]
+        std::io::Error,
+    ),
+
+    #[cfg(feature = "zip")]
+    #[error("Error opening zip archive")]
+    Zip(
+        #[source]
+        #[from]
+        zip::result::ZipError,
+    ),
+}
+
+/// Errors processing documents: PDFs and images
+#[cfg(any(feature = "pdf", feature = "image"))]
+#[derive(Debug, thiserror::Error)]
+pub enum DocumentError {
+    #[cfg(feature = "pdf")]
+    #[error("An error occurred while parsing the PDF text (PDF_Extract)")]
+    ExtractPdf(
+        #[source]
+        #[from]
+        Box<pdfium_render::prelude::PdfiumError>,
+    ),
+
+    #[cfg(feature = "image")]
+    #[error("An error occurred while performing an operation on a Image")]
+    Image(
+        #[source]
+        #[from]
+        Box<image::ImageError>,
+    ),
+}
+
+/// Errors of the reservation flow, the vocabulary the booking services
+/// share. Unlike the other domains these describe the situation to the
+/// guest or the front desk, the message is the user message
+#[cfg(feature = "booking")]
+#[derive(Debug, thiserror::Error)]
+pub enum BookingError {
+    #[error("No {room_type} room is available from {arrival} to {departure}")]
+    RoomUnavailable {
+        room_type: String,
+        arrival: NaiveDate,
+        departure: NaiveDate,
+    },
+
+    #[error("The rate plan {rate_code} was not found{}", Opt(" for ", room_type.as_ref(), ""))]
+    RateNotFound {
+        rate_code: String,
+        room_type: Option<String>,
+    },
+
+    #[error("The stay from {arrival} to {departure} is not valid: {reason}")]
+    StayDatesInvalid {
+        arrival: NaiveDate,
+        departure: NaiveDate,
+        reason: Cow<'static, str>,
+    },
+
+    /// the booking would take the room type past its overbooking limit
+    #[error("Booking would overbook {room_type} on {date} by {rooms} room(s)")]
+    OverbookingRisk {
+        room_type: String,
+        date: NaiveDate,
+        rooms: u32,
+    },
+
+    #[error("The guest profile {profile_id} was not found")]
+    GuestProfileMissing { profile_id: String },
+}
+
+/// Errors returned by Microsoft services: Graph (through `MSResponse` or
+/// the Graph SDK) and Document Intelligence
+#[derive(Debug, thiserror::Error)]
+pub enum MicrosoftError {
+    #[cfg(feature = "graph")]
+    #[error("An error occurred while getting data using Microsoft Graph")]
+    Graph(
+        #[source]
+        #[from]
+        Box<graph_rs_sdk::GraphFailure>,
+    ),
+
+    #[cfg(feature = "graph")]
+    #[error("Graph Error Message")]
+    GraphErrMSg(
+        #[source]
+        #[from]
+        Box<graph_rs_sdk::error::ErrorMessage>,
+    ),
+
+    #[error("Request responded with an error{}", graph_diagnostics(&.0.error))]
+    MS(#[source] Box<MSResponseError>),
+
+    #[error("Document Intelligence Services returned with an error")]
+    AZ(
+        #[source]
+        #[from]
+        Box<AZError>,
+    ),
+}
+
+// the large errors are boxed to keep `ErrPile` small, these keep `?`
+// working on the unboxed ones
+#[cfg(feature = "sqlx")]
+impl From<sqlx::Error> for StorageError {
+    fn from(value: sqlx::Error) -> Self {
+        Self::DB(Box::new(value))
+    }
+}
+
+#[cfg(feature = "ssh")]
+impl From<russh::Error> for TransportError {
+    fn from(value: russh::Error) -> Self {
+        Self::Ssh(Box::new(value))
+    }
+}
+
+#[cfg(feature = "ssh")]
+impl From<russh_sftp::client::error::Error> for TransportError {
+    fn from(value: russh_sftp::client::error::Error) -> Self {
+        Self::Sftp(Box::new(value))
+    }
+}
+
+#[cfg(feature = "pdf")]
+impl From<pdfium_render::prelude::PdfiumError> for DocumentError {
+    fn from(value: pdfium_render::prelude::PdfiumError) -> Self {
+        Self::ExtractPdf(Box::new(value))
+    }
+}
+
+#[cfg(feature = "image")]
+impl From<image::ImageError> for DocumentError {
+    fn from(value: image::ImageError) -> Self {
+        Self::Image(Box::new(value))
+    }
+}
+
+// `?` on the domain enums, boxed in `ErrPile`
+impl From<TransportError> for ErrPile {
+    fn from(value: TransportError) -> Self {
+        ErrPile::Transport(Box::new(value))
+    }
+}
+
+impl From<StorageError> for ErrPile {
+    fn from(value: StorageError) -> Self {
+        ErrPile::Storage(Box::new(value))
+    }
+}
+
+#[cfg(feature = "booking")]
+impl From<BookingError> for ErrPile {
+    fn from(value: BookingError) -> Self {
+        ErrPile::Booking(Box::new(value))
+    }
+}
+
+/// `From` of the errors of a domain enum for `ErrPile`, through the
+/// domain enum, so `?` works on them as it did before the nesting
+macro_rules! through {
+    ($domain:ident: $($(#[$attr:meta])* $source:ty),* $(,)?) => {$(
+        $(#[$attr])*
+        impl From<$source> for ErrPile {
+            fn from(value: $source) -> Self {
+                ErrPile::from($domain::from(value))
+            }
+        }
+    )*};
+}
+
+through!(TransportError:
+    url::ParseError,
+    reqwest::header::ToStrError,
+    #[cfg(feature = "ssh")] Box<russh::Error>,
+    #[cfg(feature = "ssh")] russh::Error,
+    #[cfg(feature = "ssh")] Box<russh_sftp::client::error::Error>,
+    #[cfg(feature = "ssh")] russh_sftp::client::error::Error,
+);
+
+through!(StorageError:
+    std::io::Error,
+    #[cfg(feature = "sqlx")] Box<sqlx::Error>,
+    #[cfg(feature = "sqlx")] sqlx::Error,
+    #[cfg(feature = "migrate")] sqlx::migrate::MigrateError,
+    #[cfg(feature = "zip")] zip::result::ZipError,
+);
+
+through!(DocumentError:
+    #[cfg(feature = "pdf")] Box<pdfium_render::prelude::PdfiumError>,
+    #[cfg(feature = "pdf")] pdfium_render::prelude::PdfiumError,
+    #[cfg(feature = "image")] Box<image::ImageError>,
+    #[cfg(feature = "image")] image::ImageError,
+);
+
+through!(MicrosoftError:
+    Box<AZError>,
+    #[cfg(feature = "graph")] Box<graph_rs_sdk::GraphFailure>,
+    #[cfg(feature = "graph")] Box<graph_rs_sdk::error::ErrorMessage>,
+);
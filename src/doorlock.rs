@@ -0,0 +1,284 @@
+use core::fmt;
+use std::time::Duration;
+
+use reqwest::{StatusCode, header::HeaderMap};
+use serde_json::Value;
+
+use crate::{ErrPile, KeyEncoderFault, KeyEncoderReason, PileKind, parse_retry_after};
+
+/// The door lock system the error comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LockVendor {
+    /// ASSA ABLOY Visionline
+    Visionline,
+    /// Salto Space and Salto KS
+    Salto,
+}
+
+impl LockVendor {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Visionline => "visionline",
+            Self::Salto => "salto",
+        }
+    }
+}
+
+impl fmt::Display for LockVendor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What the lock system refused the request for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DoorLockFailure {
+    /// the lock or its gateway doesn't answer, the mobile key can be sent
+    /// again once it is back, a key card issued meanwhile
+    LockOffline,
+    /// the lock refused the credential: revoked, expired or not valid for
+    /// the door, a new one has to be issued
+    CredentialRejected,
+    /// the audit trail of the lock can't be read now, the key issuance
+    /// doesn't depend on it
+    AuditTrailUnavailable,
+    /// the credentials of the integration were refused
+    Auth,
+    /// the lock system server failed
+    Unavailable,
+    /// any other refusal
+    Rejected,
+}
+
+impl DoorLockFailure {
+    /// category of the error, see `ErrPile::kind`
+    pub fn kind(&self) -> PileKind {
+        match self {
+            Self::LockOffline => PileKind::Network,
+            Self::CredentialRejected => PileKind::Permission,
+            Self::AuditTrailUnavailable => PileKind::NotReady,
+            Self::Auth => PileKind::Config,
+            Self::Unavailable | Self::Rejected => PileKind::Upstream,
+        }
+    }
+
+    /// the same request can work later, without anyone fixing anything
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Self::LockOffline | Self::AuditTrailUnavailable | Self::Unavailable
+        )
+    }
+
+    /// Whether the key can't be issued, the failures of the audit trail
+    /// let the check-in go on
+    pub fn blocks_key_issuance(&self) -> bool {
+        !matches!(self, Self::AuditTrailUnavailable)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::LockOffline => "lock_offline",
+            Self::CredentialRejected => "credential_rejected",
+            Self::AuditTrailUnavailable => "audit_trail_unavailable",
+            Self::Auth => "auth",
+            Self::Unavailable => "unavailable",
+            Self::Rejected => "rejected",
+        }
+    }
+
+    fn from_response(status: u16, texts: &str) -> Self {
+        let texts = texts.to_lowercase();
+        let has = |words: &[&str]| words.iter().any(|w| texts.contains(w));
+
+        // the login of the integration, "invalid credentials" is not about
+        // the key of the guest
+        if matches!(status, 401 | 403) {
+            return Self::Auth;
+        }
+
+        // an offline lock blocks the key even when its audit trail is
+        // mentioned along
+        if has(&[
+            "offline",
+            "not online",
+            "unreachable",
+            "not reachable",
+            "no communication",
+            "not connected",
+        ]) {
+            Self::LockOffline
+        } else if has(&["audit", "event log", "opening log"]) {
+            Self::AuditTrailUnavailable
+        } else if has(&[
+            "credential",
+            "mobile key",
+            "key rejected",
+            "key revoked",
+            "key expired",
+            "invalid key",
+        ]) {
+            Self::CredentialRejected
+        } else if status >= 500 {
+            Self::Unavailable
+        } else {
+            Self::Rejected
+        }
+    }
+}
+
+impl fmt::Display for DoorLockFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error response of the door lock system, the `{code, message}` bodies of
+/// Visionline and Salto (`ErrorCode`/`Message` on Salto Space, under
+/// `error` on Salto KS)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DoorLockError {
+    pub vendor: LockVendor,
+    pub failure: DoorLockFailure,
+    pub code: Option<String>,
+    pub message: String,
+    /// the door or lock the error is about
+    pub lock: Option<String>,
+    pub retry_after: Option<Duration>,
+}
+
+impl DoorLockError {
+    pub fn new<M>(vendor: LockVendor, failure: DoorLockFailure, message: M) -> Self
+    where
+        M: Into<String>,
+    {
+        Self {
+            vendor,
+            failure,
+            code: None,
+            message: message.into(),
+            lock: None,
+            retry_after: None,
+        }
+    }
+
+    /// Parses the answer of the lock system, the texts decide the failure
+    /// and the status when they don't tell. A body that isn't JSON keeps
+    /// its text as the message
+    pub fn parse(vendor: LockVendor, status: StatusCode, headers: &HeaderMap, body: &[u8]) -> Self {
+        let (code, message, lock) = match serde_json::from_slice::<Value>(body) {
+            Ok(json) => {
+                let error = json.get("error").filter(|e| e.is_object()).unwrap_or(&json);
+                (
+                    text(error, &["code", "errorCode", "ErrorCode"]),
+                    text(
+                        error,
+                        &["message", "errorMessage", "Message", "ErrorMessage"],
+                    ),
+                    text(error, &["lockId", "doorId", "door", "lock", "DoorID"]),
+                )
+            }
+            Err(_) => (
+                None,
+                Some(String::from_utf8_lossy(body).trim().to_string()),
+                None,
+            ),
+        };
+        let message = message.unwrap_or_default();
+
+        let texts = format!("{} {message}", code.as_deref().unwrap_or_default());
+        Self {
+            code,
+            lock,
+            retry_after: parse_retry_after(headers),
+            ..Self::new(
+                vendor,
+                DoorLockFailure::from_response(status.as_u16(), &texts),
+                message,
+            )
+        }
+    }
+}
+
+impl fmt::Display for DoorLockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The door lock system ({}) refused the request ({})",
+            self.vendor, self.failure
+        )?;
+        if let Some(lock) = &self.lock {
+            write!(f, " for `{lock}`")?;
+        }
+        if let Some(code) = &self.code {
+            write!(f, " {code}")?;
+        }
+        if !self.message.is_empty() {
+            write!(f, ": {}", self.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DoorLockError {}
+
+impl From<DoorLockError> for ErrPile {
+    fn from(value: DoorLockError) -> Self {
+        ErrPile::DoorLock(Box::new(value))
+    }
+}
+
+/// The key cards are encoded through the lock system, its refusals tell
+/// the front desk what to do at the encoder: `ErrPile::key_encoder(err)`
+impl KeyEncoderFault for DoorLockError {
+    fn reason(&self) -> KeyEncoderReason {
+        let texts = format!(
+            "{} {}",
+            self.code.as_deref().unwrap_or_default(),
+            self.message
+        )
+        .to_lowercase();
+        let has = |words: &[&str]| words.iter().any(|w| texts.contains(w));
+
+        match self.failure {
+            DoorLockFailure::LockOffline | DoorLockFailure::Unavailable => {
+                KeyEncoderReason::EncoderOffline
+            }
+            _ if has(&[
+                "no card",
+                "card not",
+                "insert",
+                "read error",
+                "write error",
+                "card error",
+            ]) =>
+            {
+                KeyEncoderReason::CardReadFailure
+            }
+            DoorLockFailure::CredentialRejected => KeyEncoderReason::RoomNotAuthorized,
+            // the login of the integration is fixed by maintenance too
+            DoorLockFailure::Auth
+            | DoorLockFailure::AuditTrailUnavailable
+            | DoorLockFailure::Rejected => KeyEncoderReason::InvalidTrackData,
+        }
+    }
+}
+
+impl ErrPile {
+    /// what the door lock system refused the request for
+    pub fn door_lock_failure(&self) -> Option<DoorLockFailure> {
+        match self.peeled() {
+            Self::DoorLock(err) => Some(err.failure),
+            _ => None,
+        }
+    }
+}
+
+/// the first of the members that is a string or a number
+fn text(json: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter().find_map(|key| match json.get(key)? {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    })
+}
@@ -0,0 +1,128 @@
+use core::fmt;
+
+use futures_util::StreamExt;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::{ErrPile, PileResult, ReqwestPileResExt};
+
+/// Why a download failed part way through
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DownloadFailure {
+    /// the connection failed mid-stream (reset, timeout, ...)
+    Interrupted,
+    /// the body ended before `Content-Length` bytes were received
+    Truncated { expected: u64 },
+    /// the downloaded content does not match the expected SHA-256
+    ChecksumMismatch { expected: String, actual: String },
+    /// writing to the destination failed
+    Write,
+}
+
+impl DownloadFailure {
+    /// downloading again can fix it
+    pub fn is_transient(&self) -> bool {
+        !matches!(self, Self::Write)
+    }
+}
+
+impl fmt::Display for DownloadFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Interrupted => f.write_str("connection was interrupted"),
+            Self::Truncated { expected } => {
+                write!(f, "body was truncated, expected {expected} bytes")
+            }
+            Self::ChecksumMismatch { expected, actual } => {
+                write!(f, "checksum mismatch, expected {expected} got {actual}")
+            }
+            Self::Write => f.write_str("unable to write to the destination"),
+        }
+    }
+}
+
+/// A download that failed part way through, `received` bytes were
+/// written before
+#[derive(Debug, thiserror::Error)]
+#[error("Download from {url} failed after {received} bytes: {failure}")]
+pub struct DownloadError {
+    pub url: String,
+    pub received: u64,
+    pub failure: DownloadFailure,
+    #[source]
+    pub source: Option<Box<dyn std::error::Error + Send + Sync>>,
+}
+
+/// Streams the body of the response into the writer, returns the number
+/// of bytes written. Error statuses go through the usual error handling,
+/// failures mid-stream become `ErrPile::Download`.
+///
+/// When `expected_sha256` (hex) is given the content is verified as well.
+pub async fn download_to<W>(
+    response: reqwest::Response,
+    writer: &mut W,
+    expected_sha256: Option<&str>,
+) -> PileResult<u64>
+where
+    W: AsyncWrite + Unpin,
+{
+    let response = response.error_for_pile().await?;
+    let url = response.url().to_string();
+    let content_length = response.content_length();
+
+    let mut hasher = expected_sha256.map(|_| Sha256::new());
+    let mut received = 0u64;
+    let mut stream = response.bytes_stream();
+
+    let fail = |received, failure, source: Option<Box<dyn std::error::Error + Send + Sync>>| {
+        ErrPile::Download(Box::new(DownloadError {
+            url: url.clone(),
+            received,
+            failure,
+            source,
+        }))
+    };
+
+    while let Some(chunk) = stream.next().await {
+        let chunk =
+            chunk.map_err(|e| fail(received, DownloadFailure::Interrupted, Some(Box::new(e))))?;
+
+        writer
+            .write_all(&chunk)
+            .await
+            .map_err(|e| fail(received, DownloadFailure::Write, Some(Box::new(e))))?;
+
+        if let Some(hasher) = hasher.as_mut() {
+            hasher.update(&chunk);
+        }
+        received += chunk.len() as u64;
+    }
+
+    writer
+        .flush()
+        .await
+        .map_err(|e| fail(received, DownloadFailure::Write, Some(Box::new(e))))?;
+
+    if let Some(expected) = content_length
+        && received < expected
+    {
+        return Err(fail(
+            received,
+            DownloadFailure::Truncated { expected },
+            None,
+        ));
+    }
+
+    if let (Some(hasher), Some(expected)) = (hasher, expected_sha256) {
+        let actual = format!("{:x}", hasher.finalize());
+        if !actual.eq_ignore_ascii_case(expected.trim()) {
+            let failure = DownloadFailure::ChecksumMismatch {
+                expected: expected.trim().to_lowercase(),
+                actual,
+            };
+            return Err(fail(received, failure, None));
+        }
+    }
+
+    Ok(received)
+}
@@ -0,0 +1,137 @@
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use lettre::{AsyncTransport, Message, message::Mailbox};
+
+use crate::{ErrPile, ErrorReport, ErrorSink, PileResult, Severity};
+
+/// Emails errors to an ops distribution list, for the sites without
+/// a chat integration.
+///
+/// Only `Critical` errors are sent by default (see `min_severity`) and a
+/// fingerprint is mailed at most once per `throttle`
+pub struct EmailSink<T> {
+    transport: T,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+    min_severity: Severity,
+    throttle: Duration,
+    last_sent: Mutex<HashMap<String, Instant>>,
+}
+
+impl<T> EmailSink<T>
+where
+    T: AsyncTransport + Send + Sync,
+    T::Error: std::error::Error + Send + Sync + 'static,
+{
+    pub fn new<I>(transport: T, from: Mailbox, to: I) -> Self
+    where
+        I: IntoIterator<Item = Mailbox>,
+    {
+        Self {
+            transport,
+            from,
+            to: to.into_iter().collect(),
+            min_severity: Severity::Critical,
+            throttle: Duration::from_secs(15 * 60),
+            last_sent: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// errors below this severity are not mailed
+    pub fn min_severity(mut self, min_severity: Severity) -> Self {
+        self.min_severity = min_severity;
+        self
+    }
+
+    /// how long to wait before mailing the same fingerprint again
+    pub fn throttle(mut self, throttle: Duration) -> Self {
+        self.throttle = throttle;
+        self
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// whether the report is due, records it as sent if so
+    fn due(&self, report: &ErrorReport) -> bool {
+        if report.severity < self.min_severity {
+            return false;
+        }
+
+        let now = Instant::now();
+        let mut last_sent = self.last_sent.lock().unwrap_or_else(|e| e.into_inner());
+        last_sent.retain(|_, sent| now.duration_since(*sent) < self.throttle);
+        if last_sent.contains_key(&report.fingerprint) {
+            return false;
+        }
+        last_sent.insert(report.fingerprint.clone(), now);
+        true
+    }
+
+    fn message(&self, report: &ErrorReport) -> PileResult<Message> {
+        let mut builder = Message::builder().from(self.from.clone()).subject(format!(
+            "[{}] {}: {}",
+            report.severity, report.kind, report.message
+        ));
+        for to in &self.to {
+            builder = builder.to(to.clone());
+        }
+
+        builder
+            .body(render(report))
+            .map_err(|err| ErrPile::Mail(Box::new(err)))
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> ErrorSink for EmailSink<T>
+where
+    T: AsyncTransport + Send + Sync,
+    T::Error: std::error::Error + Send + Sync + 'static,
+{
+    async fn send(&self, report: &ErrorReport) -> PileResult {
+        if !self.due(report) {
+            return Ok(());
+        }
+
+        let message = self.message(report)?;
+        self.transport
+            .send(message)
+            .await
+            .map_err(|err| ErrPile::Mail(Box::new(err)))?;
+        Ok(())
+    }
+}
+
+/// plain text body: the message, the chain and the metadata
+fn render(report: &ErrorReport) -> String {
+    let mut body = format!("{report}\n");
+
+    if !report.chain.is_empty() {
+        body.push_str("\nCaused by:\n");
+        for (i, cause) in report.chain.iter().enumerate() {
+            let _ = writeln!(body, "    {i}: {cause}");
+        }
+    }
+
+    body.push('\n');
+    let _ = writeln!(body, "Kind:        {}", report.kind);
+    let _ = writeln!(body, "Severity:    {}", report.severity);
+    if let Some(code) = &report.code {
+        let _ = writeln!(body, "Code:        {code}");
+    }
+    let _ = writeln!(body, "Fingerprint: {}", report.fingerprint);
+    let _ = writeln!(body, "Transient:   {}", report.transient);
+    for (name, id) in &report.correlation_ids {
+        let _ = writeln!(body, "{:<12} {id}", format!("{name}:"));
+    }
+    let _ = writeln!(body, "First seen:  {}", report.first_seen.to_rfc3339());
+    let _ = writeln!(body, "Last seen:   {}", report.last_seen.to_rfc3339());
+    body
+}
@@ -0,0 +1,134 @@
+use core::fmt;
+use std::error::Error;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ErrPile, Opt, PileKind};
+
+/// Why the key card was not written, what decides the next step at the
+/// front desk: have the guest re-insert the card, call maintenance or
+/// check the room assignment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyEncoderReason {
+    /// the encoder doesn't answer: unplugged, powered off or off the
+    /// network
+    EncoderOffline,
+    /// the card couldn't be read or written, usually badly inserted
+    CardReadFailure,
+    /// the data for the card was refused by the encoder or the lock
+    /// system
+    InvalidTrackData,
+    /// the lock system doesn't let this encoder issue keys for the room
+    RoomNotAuthorized,
+}
+
+impl KeyEncoderReason {
+    /// category of the error, see `ErrPile::kind`
+    pub fn kind(&self) -> PileKind {
+        match self {
+            Self::EncoderOffline => PileKind::Network,
+            Self::CardReadFailure => PileKind::Io,
+            Self::InvalidTrackData => PileKind::Validation,
+            Self::RoomNotAuthorized => PileKind::Permission,
+        }
+    }
+
+    /// Whether encoding again can work without anyone fixing anything,
+    /// the card only has to go in again
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::CardReadFailure)
+    }
+
+    /// the encoder or the lock system has to be looked at, the front
+    /// desk can't fix it
+    pub fn needs_maintenance(&self) -> bool {
+        matches!(self, Self::EncoderOffline | Self::InvalidTrackData)
+    }
+
+    /// the next step, shown to the front desk
+    pub fn user_message(&self) -> &'static str {
+        match self {
+            Self::EncoderOffline => "The key encoder is not responding, please call maintenance",
+            Self::CardReadFailure => "The key card could not be read, please re-insert the card",
+            Self::InvalidTrackData => "The key card could not be written, please call maintenance",
+            Self::RoomNotAuthorized => {
+                "This encoder may not issue keys for the room, please check the room assignment"
+            }
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::EncoderOffline => "encoder_offline",
+            Self::CardReadFailure => "card_read_failure",
+            Self::InvalidTrackData => "invalid_track_data",
+            Self::RoomNotAuthorized => "room_not_authorized",
+        }
+    }
+}
+
+impl fmt::Display for KeyEncoderReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The key card encoder could not write the room key, the reason tells
+/// the front desk what to do next, see `ErrPile::key_encoder`
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "The key card could not be encoded{}: {reason}",
+    Opt(" on ", encoder.as_ref(), "")
+)]
+pub struct KeyEncoderError {
+    pub reason: KeyEncoderReason,
+    pub encoder: Option<String>,
+    #[source]
+    pub source: Option<Box<dyn Error + Send + Sync>>,
+}
+
+/// Implemented on the errors of the lock vendor SDKs the services wrap,
+/// tells the reason so `ErrPile::key_encoder` keeps the SDK error as the
+/// source. The `DoorLockError` of the Visionline and Salto APIs
+/// implements it with the `doorlock` feature
+pub trait KeyEncoderFault: Error + Send + Sync + 'static {
+    fn reason(&self) -> KeyEncoderReason;
+
+    /// the encoder at the desk, when the SDK tells
+    fn encoder(&self) -> Option<String> {
+        None
+    }
+}
+
+impl ErrPile {
+    /// Key card encoding failure of a vendor SDK, see `KeyEncoderFault`
+    pub fn key_encoder<E>(err: E) -> Self
+    where
+        E: KeyEncoderFault,
+    {
+        Self::KeyEncoder(Box::new(KeyEncoderError {
+            reason: err.reason(),
+            encoder: err.encoder(),
+            source: Some(Box::new(err)),
+        }))
+    }
+
+    /// reason of a key card encoding failure
+    pub fn key_encoder_reason(&self) -> Option<KeyEncoderReason> {
+        match self.peeled() {
+            Self::KeyEncoder(err) => Some(err.reason),
+            _ => None,
+        }
+    }
+}
+
+impl From<KeyEncoderReason> for ErrPile {
+    fn from(reason: KeyEncoderReason) -> Self {
+        Self::KeyEncoder(Box::new(KeyEncoderError {
+            reason,
+            encoder: None,
+            source: None,
+        }))
+    }
+}
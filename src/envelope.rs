@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{ErrPile, FieldErrors};
+
+/// The error body every service answers with, whatever the framework
+/// (axum, actix, warp, Rocket, `PileErrorLayer`). `code` is the kind,
+/// stable across the services, and the message is `user_message` so
+/// nothing internal leaks out. Missing values are `null` rather than left
+/// out, the documents all have the same members in the same order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorEnvelope {
+    pub status: u16,
+    pub code: String,
+    pub user_message: String,
+    #[serde(default)]
+    pub correlation_id: Option<String>,
+    /// seconds to wait before trying again
+    #[serde(default)]
+    pub retry_after: Option<u64>,
+    #[serde(default)]
+    pub field_errors: Option<FieldErrors>,
+}
+
+impl ErrorEnvelope {
+    /// the envelope of the error, with its first correlation id
+    pub fn new(err: &ErrPile) -> Self {
+        Self {
+            status: err.status_code(),
+            code: err.kind().as_str().into(),
+            user_message: err.user_message(),
+            correlation_id: err.correlation_ids().into_iter().next().map(|(_, id)| id),
+            retry_after: err.retry_after_secs(),
+            field_errors: err.field_errors().cloned(),
+        }
+    }
+
+    /// the id the request is known by, rather than the one of the error
+    pub fn with_correlation_id<I: Into<String>>(mut self, id: I) -> Self {
+        self.correlation_id = Some(id.into());
+        self
+    }
+
+    /// redacted JSON, the body of the response
+    pub fn to_json(&self) -> String {
+        let json = serde_json::to_string(self).unwrap_or_default();
+        crate::redact(&json).into_owned()
+    }
+}
+
+impl From<&ErrPile> for ErrorEnvelope {
+    fn from(err: &ErrPile) -> Self {
+        Self::new(err)
+    }
+}
+
+impl ErrPile {
+    /// see `ErrorEnvelope`
+    pub fn envelope(&self) -> ErrorEnvelope {
+        ErrorEnvelope::new(self)
+    }
+}
@@ -0,0 +1,62 @@
+use std::{borrow::Cow, sync::LazyLock};
+
+use regex::Regex;
+
+use crate::ErrPile;
+
+/// ids and numbers that differ between otherwise identical errors
+static VOLATILE: LazyLock<[(Regex, &str); 3]> = LazyLock::new(|| {
+    let pattern = |p| Regex::new(p).expect("valid pattern");
+    [
+        (
+            pattern(r"(?i)\b[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}\b"),
+            "<id>",
+        ),
+        // request ids, hashes
+        (pattern(r"(?i)\b[0-9a-f]{12,}\b"), "<id>"),
+        (pattern(r"\d+"), "<n>"),
+    ]
+});
+
+/// the message with uuids, hex ids and numbers replaced by placeholders,
+/// what `ErrPile::fingerprint` hashes
+pub fn normalize_message(message: &str) -> Cow<'_, str> {
+    let mut message = Cow::Borrowed(message);
+    for (pattern, placeholder) in VOLATILE.iter() {
+        if let Cow::Owned(replaced) = pattern.replace_all(&message, *placeholder) {
+            message = Cow::Owned(replaced);
+        }
+    }
+    message
+}
+
+/// FNV-1a, stable across builds and platforms unlike `DefaultHasher`
+fn fnv1a(parts: &[&str]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        // the separator keeps ("ab", "c") and ("a", "bc") apart
+        for byte in part.bytes().chain([0xff]) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+impl ErrPile {
+    /// Stable hash of the kind, the code and the root cause message with
+    /// ids and numbers stripped, so one Graph outage groups into one
+    /// fingerprint instead of thousands of near-identical ones
+    pub fn fingerprint(&self) -> String {
+        let root = self.chain().last().map(|err| err.to_string());
+        let message = normalize_message(root.as_deref().unwrap_or_default());
+        let code = self.code();
+
+        let hash = fnv1a(&[
+            self.kind().as_str(),
+            code.as_deref().unwrap_or_default(),
+            &message,
+        ]);
+        format!("{hash:016x}")
+    }
+}
@@ -0,0 +1,274 @@
+use core::fmt;
+use std::time::Duration;
+
+use reqwest::{StatusCode, header::HeaderMap};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{ErrPile, PileKind, mask_card_data, parse_retry_after};
+
+/// What the tax authority gateway refused the invoice for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FiscalFailure {
+    /// the document doesn't validate against the schema of the authority
+    SchemaValidation,
+    /// the invoice number is already registered, or out of sequence, as
+    /// told by one of the `SEQUENCE_CODES`
+    SequenceConflict,
+    /// the authority is closed for maintenance or outside its service
+    /// hours
+    ServiceWindow,
+    /// refused for a rule of the authority: tax ids, rates, totals
+    Rejected,
+    /// the gateway failed on its side
+    Unavailable,
+    /// none of the above, whether the number was registered is unknown
+    /// and someone has to look before the invoice is voided or sent again
+    Unknown,
+}
+
+/// the codes gateways answer a registered or out of sequence number with,
+/// compared ignoring case
+const SEQUENCE_CODES: &[&str] = &[
+    "DUP",
+    "DUPLICATE",
+    "DUPLICATE_INVOICE",
+    "INVOICE_EXISTS",
+    "ALREADY_REGISTERED",
+    "OUT_OF_SEQUENCE",
+    "SEQUENCE_GAP",
+];
+
+impl FiscalFailure {
+    /// Whether the invoice may be sent again under the same number. The
+    /// ones that can't have to be voided and issued again under the next
+    /// number of the sequence, except `Unknown` which needs a review
+    pub fn retry_allowed(&self) -> bool {
+        match self {
+            // never registered, the number is still free
+            Self::SchemaValidation | Self::ServiceWindow | Self::Unavailable => true,
+            Self::SequenceConflict | Self::Rejected | Self::Unknown => false,
+        }
+    }
+
+    /// the invoice has to be voided and issued again
+    pub fn must_void(&self) -> bool {
+        matches!(self, Self::SequenceConflict | Self::Rejected)
+    }
+
+    /// category of the error, see `ErrPile::kind`
+    pub fn kind(&self) -> PileKind {
+        match self {
+            Self::SchemaValidation => PileKind::Validation,
+            Self::SequenceConflict => PileKind::Conflict,
+            Self::ServiceWindow => PileKind::NotReady,
+            Self::Rejected | Self::Unavailable | Self::Unknown => PileKind::Upstream,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SchemaValidation => "schema_validation",
+            Self::SequenceConflict => "sequence_conflict",
+            Self::ServiceWindow => "service_window",
+            Self::Rejected => "rejected",
+            Self::Unavailable => "unavailable",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    fn from_response(status: u16, retry_after: bool, code: Option<&str>, texts: &str) -> Self {
+        let texts = texts.to_lowercase();
+        let has = |words: &[&str]| words.iter().any(|w| texts.contains(w));
+
+        // a schema error naming the sequence element is still a schema
+        // error, the markers of the destructive outcomes come last
+        if has(&[
+            "schema",
+            "xsd",
+            "malformed",
+            "not well-formed",
+            "validation",
+            "does not validate",
+        ]) {
+            Self::SchemaValidation
+        } else if has(&["maintenance", "service window", "outside service hours"])
+            || (status == 503 && retry_after)
+        {
+            Self::ServiceWindow
+        } else if status >= 500 {
+            Self::Unavailable
+        } else if code.is_some_and(|code| {
+            SEQUENCE_CODES
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(code))
+        }) {
+            Self::SequenceConflict
+        } else if status == 422 {
+            Self::Rejected
+        } else {
+            Self::Unknown
+        }
+    }
+}
+
+impl fmt::Display for FiscalFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error of a tax authority e-invoicing gateway, read from its JSON
+/// `{code, message}` / `errors` bodies or, with the `xml` feature, its
+/// XML and SOAP faults
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FiscalError {
+    pub failure: FiscalFailure,
+    /// the invoice may be sent again under the same number, otherwise it
+    /// has to be voided or reviewed, see `FiscalFailure::retry_allowed`
+    pub retry_allowed: bool,
+    pub code: Option<String>,
+    pub message: String,
+    pub retry_after: Option<Duration>,
+}
+
+impl FiscalError {
+    pub fn new<M>(failure: FiscalFailure, message: M) -> Self
+    where
+        M: Into<String>,
+    {
+        Self {
+            failure,
+            retry_allowed: failure.retry_allowed(),
+            code: None,
+            message: message.into(),
+            retry_after: None,
+        }
+    }
+
+    /// Parses the answer of the gateway, `None` when the body has neither
+    /// a code nor a message. A 503 with `Retry-After` is a service window,
+    /// a sequence conflict takes one of the `SEQUENCE_CODES` and an answer
+    /// that can't be told apart is `Unknown`
+    pub fn parse(status: StatusCode, headers: &HeaderMap, body: &[u8]) -> Option<Self> {
+        let (code, message) = match serde_json::from_slice::<Value>(body) {
+            Ok(json) => json_error(&json)?,
+            Err(_) => xml_error(body)?,
+        };
+
+        let retry_after = parse_retry_after(headers);
+        let texts = [code.as_deref(), Some(message.as_str())]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let failure = FiscalFailure::from_response(
+            status.as_u16(),
+            retry_after.is_some(),
+            code.as_deref(),
+            &texts,
+        );
+
+        Some(Self {
+            code,
+            message: mask_card_data(&message).into_owned(),
+            retry_after,
+            ..Self::new(failure, String::new())
+        })
+    }
+
+    /// the invoice can't be sent again, it has to be voided
+    pub fn must_void(&self) -> bool {
+        self.failure.must_void()
+    }
+
+    /// neither sent again nor voided until someone had a look
+    pub fn needs_review(&self) -> bool {
+        self.failure == FiscalFailure::Unknown
+    }
+
+    /// the same request can go out again as it is, later
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self.failure,
+            FiscalFailure::ServiceWindow | FiscalFailure::Unavailable
+        )
+    }
+}
+
+impl fmt::Display for FiscalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The tax authority refused the invoice ({})",
+            self.failure
+        )?;
+        if let Some(code) = &self.code {
+            write!(f, " {code}")?;
+        }
+        if !self.message.is_empty() {
+            write!(f, ": {}", self.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for FiscalError {}
+
+impl From<FiscalError> for ErrPile {
+    fn from(value: FiscalError) -> Self {
+        ErrPile::Fiscal(Box::new(value))
+    }
+}
+
+impl ErrPile {
+    /// Whether a refused invoice may be sent again under the same number,
+    /// `Some(false)` when it has to be voided or reviewed
+    pub fn fiscal_retry_allowed(&self) -> Option<bool> {
+        match self.peeled() {
+            Self::Fiscal(err) => Some(err.retry_allowed),
+            _ => None,
+        }
+    }
+}
+
+/// `{code, message}` at the root, under `error` or first of `errors`
+fn json_error(json: &Value) -> Option<(Option<String>, String)> {
+    let error = json
+        .get("errors")
+        .and_then(|errors| errors.as_array()?.first())
+        .or_else(|| json.get("error").filter(|e| e.is_object()))
+        .unwrap_or(json);
+
+    let text = |keys: &[&str]| {
+        keys.iter().find_map(|key| match error.get(key)? {
+            Value::String(text) => Some(text.trim().to_string()).filter(|t| !t.is_empty()),
+            Value::Number(number) => Some(number.to_string()),
+            _ => None,
+        })
+    };
+
+    let code = text(&["code", "errorCode", "error_code"]);
+    let message = text(&["message", "description", "detail", "errorMessage"]);
+    if code.is_none() && message.is_none() {
+        return None;
+    }
+    Some((code, message.unwrap_or_default()))
+}
+
+#[cfg(feature = "xml")]
+fn xml_error(body: &[u8]) -> Option<(Option<String>, String)> {
+    let fault = crate::XmlFault::parse(&String::from_utf8_lossy(body))?;
+    let message = match (fault.message, fault.detail) {
+        (Some(message), Some(detail)) => format!("{message} ({detail})"),
+        (Some(text), None) | (None, Some(text)) => text,
+        (None, None) => String::new(),
+    };
+    Some((fault.code, message))
+}
+
+#[cfg(not(feature = "xml"))]
+fn xml_error(_body: &[u8]) -> Option<(Option<String>, String)> {
+    None
+}
@@ -0,0 +1,142 @@
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{ErrPile, PileResult, mask_card_data};
+
+/// Single entry of a GraphQL `errors` array
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphQLError {
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<Vec<Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locations: Option<Vec<Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Map<String, Value>>,
+}
+
+impl GraphQLError {
+    /// `extensions.code`, if the server sent one
+    pub fn code(&self) -> Option<&str> {
+        self.extensions.as_ref()?.get("code")?.as_str()
+    }
+}
+
+impl fmt::Display for GraphQLError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(code) = self.code() {
+            write!(f, "{code} - ")?;
+        }
+
+        f.write_str(&mask_card_data(&self.message))?;
+
+        if let Some(path) = &self.path {
+            let path = path
+                .iter()
+                .map(|p| match p {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(".");
+            write!(f, " (at {path})")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// All the errors returned by a GraphQL response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct GraphQLErrors(pub Vec<GraphQLError>);
+
+impl GraphQLErrors {
+    /// reads the `errors` array of a GraphQL shaped body, `None`
+    /// if the body has no errors
+    pub fn from_body(body: &Value) -> Option<Self> {
+        let field = body.get("errors")?;
+        let errors = field.as_array()?;
+        // every entry of a GraphQL error has a message
+        if errors.is_empty() || !errors.iter().all(|e| e.get("message").is_some()) {
+            return None;
+        }
+
+        Vec::<GraphQLError>::deserialize(field).ok().map(Self)
+    }
+}
+
+impl fmt::Display for GraphQLErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, err) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            err.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for GraphQLErrors {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQLResponse<T> {
+    data: Option<T>,
+}
+
+/// the `errors` of a response, read without `data`
+#[derive(Debug, Deserialize)]
+struct GraphQLResponseErrors {
+    #[serde(default)]
+    errors: Vec<GraphQLError>,
+}
+
+/// converts a GraphQL response body into a result, any entry in
+/// `errors` makes it an error even if `data` is present. The errors are
+/// read first, the partial or null `data` next to them doesn't have to
+/// fit `T`
+pub(crate) fn graphql_result<T>(body: &[u8]) -> PileResult<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let errors = crate::http::parse_json_body::<GraphQLResponseErrors>(body)?.errors;
+    if !errors.is_empty() {
+        return Err(ErrPile::GraphQL(GraphQLErrors(errors)));
+    }
+
+    crate::http::parse_json_body::<GraphQLResponse<T>>(body)?
+        .data
+        .ok_or_else(|| ErrPile::custom_static("GraphQL response contained neither data nor errors"))
+}
+
+/// For resolvers: `user_message` as the message and `code` (the kind),
+/// `transient` and `retryAfter` (seconds) as extensions, the chain is
+/// logged and never reaches the gateway's clients. Plain `?` would use the
+/// `Display` conversion of async-graphql, so resolvers return
+/// `err.extend()`:
+///
+/// ```ignore
+/// async fn reservation(&self, id: i64) -> async_graphql::Result<Reservation> {
+///     self.pms.reservation(id).await.map_err(|err| err.extend())
+/// }
+/// ```
+#[cfg(feature = "async-graphql")]
+impl async_graphql::ErrorExtensions for ErrPile {
+    fn extend(&self) -> async_graphql::Error {
+        self.log_response();
+        async_graphql::Error::new(self.user_message()).extend_with(|_, extensions| {
+            extensions.set("code", self.kind().as_str());
+            extensions.set("transient", self.is_transient());
+            if let Some(retry_after) = self.retry_after_secs() {
+                extensions.set("retryAfter", retry_after);
+            }
+        })
+    }
+}
@@ -0,0 +1,75 @@
+use tonic::{Code, Status};
+
+use crate::{ErrPile, PileKind, RemoteError};
+
+/// gRPC code of a kind, transient errors of the other kinds are
+/// `Unavailable` so the clients retry them
+fn grpc_code(err: &ErrPile) -> Code {
+    match err.kind() {
+        PileKind::Auth => Code::Unauthenticated,
+        PileKind::Permission => Code::PermissionDenied,
+        PileKind::NotFound => Code::NotFound,
+        PileKind::InUse | PileKind::Conflict => Code::Aborted,
+        PileKind::Timeout => Code::DeadlineExceeded,
+        PileKind::Cancelled => Code::Cancelled,
+        PileKind::Unsupported => Code::Unimplemented,
+        PileKind::RateLimited => Code::ResourceExhausted,
+        PileKind::Validation => Code::InvalidArgument,
+        PileKind::PaymentDeclined => Code::FailedPrecondition,
+        PileKind::NotReady => Code::Unavailable,
+        _ if err.is_transient() => Code::Unavailable,
+        _ => Code::Internal,
+    }
+}
+
+/// kind of an error received as a bare status, without details
+fn code_kind(code: Code) -> PileKind {
+    match code {
+        Code::Unauthenticated => PileKind::Auth,
+        Code::PermissionDenied => PileKind::Permission,
+        Code::NotFound => PileKind::NotFound,
+        Code::Aborted | Code::AlreadyExists => PileKind::Conflict,
+        Code::DeadlineExceeded => PileKind::Timeout,
+        Code::Cancelled => PileKind::Cancelled,
+        Code::Unimplemented => PileKind::Unsupported,
+        Code::ResourceExhausted => PileKind::RateLimited,
+        Code::InvalidArgument | Code::OutOfRange | Code::FailedPrecondition => PileKind::Validation,
+        Code::Unavailable => PileKind::NotReady,
+        _ => PileKind::Upstream,
+    }
+}
+
+/// The status code of the kind with `user_message` as the message, the
+/// error itself (`RemoteError` as JSON) travels in the details
+impl From<ErrPile> for Status {
+    fn from(err: ErrPile) -> Self {
+        let details = serde_json::to_vec(&err).unwrap_or_default();
+        Status::with_details(grpc_code(&err), err.user_message(), details.into())
+    }
+}
+
+/// `ErrPile::Remote` from the details sent by a service using ErrPile,
+/// from the code and message otherwise
+impl From<Status> for ErrPile {
+    fn from(status: Status) -> Self {
+        if let Ok(remote) = serde_json::from_slice::<RemoteError>(status.details()) {
+            return remote.into();
+        }
+
+        RemoteError {
+            kind: code_kind(status.code()),
+            code: Some(format!("{:?}", status.code())),
+            message: status.message().to_string(),
+            user_message: String::new(),
+            transient: matches!(
+                status.code(),
+                Code::Unavailable | Code::ResourceExhausted | Code::DeadlineExceeded
+            ),
+            http_status: None,
+            chain: Vec::new(),
+            origin: None,
+            metadata: Default::default(),
+        }
+        .into()
+    }
+}
@@ -0,0 +1,122 @@
+use core::fmt;
+
+/// how many characters of the page text are kept
+const TEXT_LEN: usize = 300;
+
+/// Readable summary of a HTML error page (reverse proxies,
+/// sign-in pages, ...)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HtmlSummary {
+    /// `<title>` or the first heading of the page
+    pub title: Option<String>,
+    /// trimmed visible text of the page
+    pub text: String,
+}
+
+impl HtmlSummary {
+    /// checks if the body looks like a HTML document
+    pub fn looks_like_html(content_type: Option<&str>, body: &str) -> bool {
+        if content_type.is_some_and(|ct| ct.to_ascii_lowercase().contains("text/html")) {
+            return true;
+        }
+
+        let start = body.trim_start().get(..15).unwrap_or_default();
+        let start = start.to_ascii_lowercase();
+        start.starts_with("<!doctype html") || start.starts_with("<html")
+    }
+
+    pub fn parse(body: &str) -> Self {
+        let title = ["title", "h1", "h2", "h3"]
+            .iter()
+            .find_map(|tag| element_text(body, tag))
+            .filter(|t| !t.is_empty());
+
+        let body_start = find_ci(body, "<body").unwrap_or(0);
+        let mut text = collapse(&strip_tags(&body[body_start..]));
+        if let Some((idx, _)) = text.char_indices().nth(TEXT_LEN) {
+            text.truncate(idx);
+            text.push('…');
+        }
+
+        Self { title, text }
+    }
+}
+
+impl fmt::Display for HtmlSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.title {
+            Some(title) if self.text.is_empty() || self.text == *title => f.write_str(title),
+            Some(title) => write!(f, "{title} - {}", self.text),
+            None => f.write_str(&self.text),
+        }
+    }
+}
+
+/// case insensitive find, the needle must be ascii
+fn find_ci(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|w| w.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+fn element_text(body: &str, tag: &str) -> Option<String> {
+    let open = find_ci(body, &format!("<{tag}"))?;
+    // make sure we matched `<h1>` / `<h1 ...>` and not `<h1x>`
+    let after = body[open + tag.len() + 1..].chars().next()?;
+    if after != '>' && !after.is_whitespace() {
+        return None;
+    }
+
+    let content_start = open + body[open..].find('>')? + 1;
+    let content_end = content_start + find_ci(&body[content_start..], &format!("</{tag}"))?;
+    Some(collapse(&strip_tags(&body[content_start..content_end])))
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut out = String::with_capacity(html.len().min(4096));
+    let mut rest = html;
+
+    while let Some(lt) = rest.find('<') {
+        out.push_str(&rest[..lt]);
+        rest = &rest[lt..];
+
+        // drop script and style blocks entirely
+        let skip_to = ["script", "style"].iter().find_map(|tag| {
+            let is_tag = rest
+                .get(1..tag.len() + 1)
+                .is_some_and(|t| t.eq_ignore_ascii_case(tag));
+            is_tag.then(|| find_ci(rest, &format!("</{tag}")))
+        });
+
+        let end = match skip_to {
+            Some(Some(close)) => {
+                close
+                    + rest[close..]
+                        .find('>')
+                        .map_or(rest.len() - close, |i| i + 1)
+            }
+            Some(None) => rest.len(),
+            None => rest.find('>').map_or(rest.len(), |i| i + 1),
+        };
+
+        out.push(' ');
+        rest = &rest[end..];
+    }
+
+    out.push_str(rest);
+    decode_entities(&out)
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
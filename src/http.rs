@@ -0,0 +1,872 @@
+use core::fmt;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+use std::{
+    sync::{
+        RwLock,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use reqwest::{
+    Method, StatusCode,
+    header::{CONTENT_TYPE, HeaderMap, LOCATION},
+};
+use serde::Deserialize;
+use serde_json::Value;
+use url::Url;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use crate::{
+    AZError, DeserializeError, ErrPile, GraphErrorCode, GraphQLErrors, HtmlSummary,
+    MSResponseError, MicrosoftError, PileResult, ProblemDetails, REDACTED, ResponseSnapshot,
+    SerdeValue, TokenError, TransportError, capture_curl, capture_snapshots, curl_command,
+    inner_error_retry_after, redact, sharepoint::is_locked_response,
+};
+
+/// how much of the body is kept on the error
+const SNIPPET_LEN: usize = 1024;
+
+/// default limit on how much of an error response body is buffered
+pub const DEFAULT_MAX_ERROR_BODY: usize = 64 * 1024;
+
+static MAX_ERROR_BODY: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_ERROR_BODY);
+
+/// Sets how many bytes of an error response body are buffered, the
+/// rest of the body is dropped so a huge error page can't blow up memory
+pub fn set_max_error_body(limit: usize) {
+    MAX_ERROR_BODY.store(limit, Ordering::Relaxed);
+}
+
+/// how many bytes of an error response body are buffered
+pub fn max_error_body() -> usize {
+    MAX_ERROR_BODY.load(Ordering::Relaxed)
+}
+
+static MAP_AUTH_STATUS: AtomicBool = AtomicBool::new(false);
+
+/// When enabled, a 401 response becomes `ErrPile::Auth` and a 403
+/// `ErrPile::Permission`, unless the body carries a more specific error
+pub fn set_map_auth_status(enabled: bool) {
+    MAP_AUTH_STATUS.store(enabled, Ordering::Relaxed);
+}
+
+/// whether 401/403 responses are mapped to `Auth`/`Permission`
+pub fn map_auth_status() -> bool {
+    MAP_AUTH_STATUS.load(Ordering::Relaxed)
+}
+
+/// Custom decoder for vendor specific error bodies, returns `None`
+/// when the response is not in its format
+pub type ErrorDecoder = fn(&StatusCode, &HeaderMap, &[u8]) -> Option<ErrPile>;
+
+static DECODERS: RwLock<Vec<ErrorDecoder>> = RwLock::new(Vec::new());
+
+/// Registers a decoder that is consulted, in registration order,
+/// before the built-in problem/XML/GraphQL/AZ decoding
+pub fn register_error_decoder(decoder: ErrorDecoder) {
+    DECODERS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(decoder);
+}
+
+fn run_decoders(status: &StatusCode, headers: &HeaderMap, body: &[u8]) -> Option<ErrPile> {
+    let decoders = DECODERS.read().unwrap_or_else(|e| e.into_inner());
+    decoders
+        .iter()
+        .find_map(|decode| decode(status, headers, body))
+}
+
+/// Non success response returned by a HTTP endpoint, keeps the
+/// raw status and headers around for the retry and reporting layers
+#[derive(Debug)]
+pub struct HttpError {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    /// beginning of the response body
+    pub body_snippet: String,
+    /// body parsed as json, if it was valid json
+    pub body_json: Option<SerdeValue>,
+    /// title and text of the page, if the body was HTML
+    pub html: Option<HtmlSummary>,
+    pub url: Option<Url>,
+    pub method: Option<Method>,
+    /// wait time requested by the server (`Retry-After` and friends)
+    pub retry_after: Option<Duration>,
+    /// body was larger than `max_error_body()` and was cut
+    pub body_truncated: bool,
+    /// redacted `curl` reproduction, see `set_capture_curl`
+    pub curl: Option<String>,
+}
+
+impl HttpError {
+    pub fn new(status: StatusCode, headers: HeaderMap, body: &[u8]) -> Self {
+        let body_json = serde_json::from_slice::<Value>(body).ok();
+        Self::with_body(status, headers, body, body_json)
+    }
+
+    /// the error of a body that was already parsed
+    fn with_body(status: StatusCode, headers: HeaderMap, body: &[u8], json: Option<Value>) -> Self {
+        let body_json = json.map(|mut json| {
+            crate::redact::mask_card_json(&mut json);
+            SerdeValue(json)
+        });
+
+        let content_type = headers.get(CONTENT_TYPE).and_then(|v| v.to_str().ok());
+        let text = String::from_utf8_lossy(body);
+        let html = (body_json.is_none() && HtmlSummary::looks_like_html(content_type, &text))
+            .then(|| HtmlSummary::parse(&text));
+
+        Self {
+            status,
+            retry_after: parse_retry_after(&headers),
+            body_snippet: snippet(&text),
+            headers,
+            body_json,
+            html,
+            url: None,
+            method: None,
+            body_truncated: false,
+            curl: None,
+        }
+    }
+
+    pub fn with_url(mut self, url: Url) -> Self {
+        self.url = Some(url);
+        self
+    }
+
+    pub fn with_method(mut self, method: Method) -> Self {
+        self.method = Some(method);
+        self
+    }
+
+    /// Rough category of the status code
+    pub fn category(&self) -> &'static str {
+        match self.status.as_u16() {
+            // 1xx - Informational (shouldn't be errors, but handle just in case)
+            100..=199 => "Informational",
+            // 3xx - Redirection errors
+            300..=399 => "Redirection",
+            // 4xx - Client errors
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            403 => "Forbidden",
+            404 => "Not Found",
+            408 => "Request Timeout",
+            409 => "Conflict",
+            410 => "Gone",
+            413 => "Payload Too Large",
+            414 => "URI Too Long",
+            415 => "Unsupported Media Type",
+            422 => "Unprocessable Entity",
+            429 => "Too Many Requests",
+            430..=499 => "Client Error",
+            // 5xx - Server errors
+            500 => "Internal Server Error",
+            501 => "Not Implemented",
+            502 => "Bad Gateway",
+            503 => "Service Unavailable",
+            504 => "Gateway Timeout",
+            507 => "Insufficient Storage",
+            508..=599 => "Server Error",
+            _ => "Unknown Error",
+        }
+    }
+
+    /// `Location` header of a redirect response
+    pub fn location(&self) -> Option<&str> {
+        self.headers.get(LOCATION).and_then(|v| v.to_str().ok())
+    }
+
+    /// the response redirects to a sign-in page, the credentials
+    /// are missing or expired
+    pub fn is_auth_redirect(&self) -> bool {
+        redirects_to_sign_in(self.status, &self.headers, self.url.as_ref())
+    }
+
+    /// the error message sent by the server
+    pub fn message(&self) -> String {
+        if let Some(json) = &self.body_json {
+            return json.extract_error_from_json();
+        }
+
+        if let Some(html) = &self.html {
+            return html.to_string();
+        }
+
+        self.body_snippet.clone()
+    }
+
+    /// can the request be retried as is
+    pub fn is_transient(&self) -> bool {
+        matches!(self.status.as_u16(), 408 | 429 | 500 | 502 | 503 | 504)
+    }
+}
+
+/// see `HttpError::is_auth_redirect`
+fn redirects_to_sign_in(status: StatusCode, headers: &HeaderMap, url: Option<&Url>) -> bool {
+    if !status.is_redirection() {
+        return false;
+    }
+
+    let Some(location) = headers.get(LOCATION).and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+
+    // relative redirects are resolved against the request url
+    let target = match url {
+        Some(url) => url.join(location).ok(),
+        None => Url::parse(location).ok(),
+    };
+
+    let Some(target) = target else {
+        return false;
+    };
+
+    let host = target.host_str().unwrap_or_default().to_ascii_lowercase();
+    let path = target.path().to_ascii_lowercase();
+
+    LOGIN_HOSTS
+        .iter()
+        .any(|h| host == *h || host.ends_with(&format!(".{h}")))
+        || LOGIN_PATHS.iter().any(|p| path.contains(p))
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.category(), self.status.as_u16())?;
+
+        if let Some(method) = &self.method {
+            write!(f, " {method}")?;
+        }
+
+        if let Some(url) = &self.url {
+            write!(f, " {}", redact_url(url))?;
+        }
+
+        if self.status.is_redirection()
+            && let Some(location) = self.location()
+        {
+            match Url::parse(location) {
+                Ok(location) => write!(f, " -> {}", redact_url(&location))?,
+                Err(_) => write!(f, " -> {location}")?,
+            }
+        }
+
+        let message = self.message();
+        if !message.is_empty() {
+            write!(f, ": {message}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for HttpError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+impl From<HttpError> for ErrPile {
+    fn from(value: HttpError) -> Self {
+        ErrPile::Http(Box::new(value))
+    }
+}
+
+impl From<reqwest::Error> for ErrPile {
+    fn from(source: reqwest::Error) -> Self {
+        let url = source.url().map(redact_url);
+        // reqwest puts the full url in its message, only the redacted one is kept
+        TransportError::Req {
+            method: None,
+            url,
+            curl: None,
+            source: source.without_url(),
+        }
+        .into()
+    }
+}
+
+/// identity providers, a redirect there means we are not signed in
+const LOGIN_HOSTS: &[&str] = &[
+    "login.microsoftonline.com",
+    "login.live.com",
+    "login.windows.net",
+];
+
+/// sign-in pages of the other services
+const LOGIN_PATHS: &[&str] = &["/login", "/signin", "/sign-in", "/oauth2/authorize", "/sso"];
+
+/// query parameters whose values are always masked
+const SECRET_PARAMS: &[&str] = &["sig", "code", "key", "sas", "auth", "pwd"];
+
+/// query parameters containing these words are masked
+const SECRET_WORDS: &[&str] = &[
+    "token",
+    "secret",
+    "password",
+    "signature",
+    "apikey",
+    "api_key",
+    "api-key",
+];
+
+fn is_secret_param(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SECRET_PARAMS.contains(&name.as_str()) || SECRET_WORDS.iter().any(|w| name.contains(w))
+}
+
+/// Url safe to log, the password and secret looking query
+/// parameters (tokens, signatures, keys) are masked with `REDACTED`
+pub fn redact_url(url: &Url) -> String {
+    let mut url = url.clone();
+
+    if url.password().is_some() {
+        let _ = url.set_password(Some(REDACTED));
+    }
+
+    if url.query().is_some_and(|q| !q.is_empty()) {
+        let pairs = url
+            .query_pairs()
+            .map(|(k, v)| {
+                let v = if is_secret_param(&k) {
+                    REDACTED.into()
+                } else {
+                    v
+                };
+                (k.into_owned(), v.into_owned())
+            })
+            .collect::<Vec<_>>();
+
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+
+    // percent-encoded by the setters, shown as `redact` shows it
+    url.to_string().replace(ENCODED_REDACTED, REDACTED)
+}
+
+const ENCODED_REDACTED: &str = "%3Credacted%3E";
+
+/// Reads the wait time requested by the server from the headers,
+/// `x-ms-retry-after-ms` / `retry-after-ms` are preferred as they are
+/// more precise than `Retry-After`, which can be seconds or a HTTP date
+pub fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+    };
+
+    for name in ["x-ms-retry-after-ms", "retry-after-ms"] {
+        // negative, NaN or too large for a `Duration` falls through
+        if let Some(wait) = header(name)
+            .and_then(|v| v.parse::<f64>().ok())
+            .and_then(|ms| Duration::try_from_secs_f64(ms / 1000.0).ok())
+        {
+            return Some(wait);
+        }
+    }
+
+    let value = header("retry-after")?;
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    // a date in the past means we can retry right away
+    let wait = date.with_timezone(&Utc) - Utc::now();
+    Some(wait.to_std().unwrap_or_default())
+}
+
+/// reads the body up to `limit` bytes, the flag is set when
+/// the body was larger than the limit
+#[cfg(not(target_arch = "wasm32"))]
+async fn read_limited(
+    mut response: reqwest::Response,
+    limit: usize,
+) -> reqwest::Result<(Vec<u8>, bool)> {
+    let mut buf = Vec::new();
+
+    while let Some(chunk) = response.chunk().await? {
+        let room = limit - buf.len();
+        if chunk.len() > room {
+            buf.extend_from_slice(&chunk[..room]);
+            return Ok((buf, true));
+        }
+
+        buf.extend_from_slice(&chunk);
+    }
+
+    Ok((buf, false))
+}
+
+/// fetch hands the body over in one piece, it is cut to `limit` after
+#[cfg(target_arch = "wasm32")]
+async fn read_limited(
+    response: reqwest::Response,
+    limit: usize,
+) -> reqwest::Result<(Vec<u8>, bool)> {
+    let mut buf = response.bytes().await?.to_vec();
+    let truncated = buf.len() > limit;
+    buf.truncate(limit);
+    Ok((buf, truncated))
+}
+
+fn snippet(text: &str) -> String {
+    let text = redact(text);
+    match text.char_indices().nth(SNIPPET_LEN) {
+        Some((idx, _)) => format!("{}…", &text[..idx]),
+        None => text.into_owned(),
+    }
+}
+
+/// how many characters around the failing location are kept
+const PARSE_SNIPPET_LEN: usize = 120;
+
+/// deserializes the body, on failure the error keeps the json path
+/// and the part of the body where it failed
+pub(crate) fn parse_json_body<T>(body: &[u8]) -> PileResult<T>
+where
+    T: for<'de> serde::Deserialize<'de>,
+{
+    let de = &mut serde_json::Deserializer::from_slice(body);
+    serde_path_to_error::deserialize(de).map_err(|err| {
+        let path = err.path().to_string();
+        let source = err.into_inner();
+        let snippet = snippet_around(body, source.line(), source.column());
+
+        ErrPile::Deserialize(Box::new(DeserializeError {
+            path,
+            snippet,
+            source,
+        }))
+    })
+}
+
+fn snippet_around(body: &[u8], line: usize, column: usize) -> String {
+    let text = String::from_utf8_lossy(body);
+    let Some(line) = text.lines().nth(line.saturating_sub(1)) else {
+        return String::new();
+    };
+
+    let chars = line.chars().collect::<Vec<_>>();
+    let column = column.min(chars.len());
+    let start = column.saturating_sub(PARSE_SNIPPET_LEN / 2);
+    let end = (start + PARSE_SNIPPET_LEN).min(chars.len());
+
+    let mut out = String::new();
+    if start > 0 {
+        out.push('…');
+    }
+    out.extend(&chars[start..end]);
+    if end < chars.len() {
+        out.push('…');
+    }
+    redact(&out).into_owned()
+}
+
+/// Sends the request while keeping the method and url around, so
+/// both transport errors and error responses carry them
+pub trait RequestBuilderPileExt {
+    /// sends the request, error statuses are converted into ErrPile
+    #[allow(async_fn_in_trait)]
+    async fn send_pile(self) -> PileResult<reqwest::Response>;
+
+    /// sends the request and deserializes the successful response
+    #[allow(async_fn_in_trait)]
+    async fn pile_result<T>(self) -> PileResult<T>
+    where
+        T: for<'de> serde::Deserialize<'de>;
+}
+
+impl RequestBuilderPileExt for reqwest::RequestBuilder {
+    async fn send_pile(self) -> PileResult<reqwest::Response> {
+        let (client, request) = self.build_split();
+        let request = request?;
+        let method = request.method().clone();
+        let curl = capture_curl().then(|| curl_command(&request));
+        let started = Instant::now();
+
+        match client.execute(request).await {
+            Ok(res) => {
+                let elapsed = started.elapsed();
+                res.error_for_pile()
+                    .await
+                    .map_err(|e| e.with_request(method, curl, elapsed))
+            }
+            Err(e) => Err(ErrPile::from(e).with_request(method, curl, started.elapsed())),
+        }
+    }
+
+    async fn pile_result<T>(self) -> PileResult<T>
+    where
+        T: for<'de> serde::Deserialize<'de>,
+    {
+        let body = self.send_pile().await?.bytes().await?;
+        parse_json_body(&body)
+    }
+}
+
+/// what was read from the body of an error response
+pub(crate) enum ErrorBody {
+    Read { body: Vec<u8>, truncated: bool },
+    Failed(String),
+}
+
+impl ErrPile {
+    /// records the method and reproduction of the request that failed
+    fn with_request(mut self, method: Method, curl: Option<String>, elapsed: Duration) -> Self {
+        if let Some(snapshot) = self.snapshot_mut() {
+            snapshot.method = Some(method.to_string());
+            snapshot.elapsed_ms = Some(elapsed.as_millis() as u64);
+        }
+
+        match self.peeled_mut() {
+            Self::Http(http) => {
+                http.method = Some(method);
+                http.curl = curl;
+            }
+            Self::Transport(transport) => {
+                if let TransportError::Req {
+                    method: m, curl: c, ..
+                } = &mut **transport
+                {
+                    *m = Some(method);
+                    *c = curl;
+                }
+            }
+            _ => {}
+        }
+        self
+    }
+
+    /// redacted `curl` reproduction of the failed request, only kept
+    /// when `set_capture_curl` is enabled
+    pub fn curl(&self) -> Option<&str> {
+        match self.peeled() {
+            Self::Http(http) => http.curl.as_deref(),
+            Self::Transport(transport) => match &**transport {
+                TransportError::Req { curl, .. } => curl.as_deref(),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Handle all types of HTTP errors comprehensively
+    async fn handle_error_response(response: reqwest::Response) -> ErrPile {
+        let status = response.status();
+        let headers = response.headers().clone();
+        let url = response.url().clone();
+
+        if let Some(err) = Self::throttled(status, &headers, &url) {
+            return err;
+        }
+
+        let body = match read_limited(response, max_error_body()).await {
+            Ok((body, truncated)) => ErrorBody::Read { body, truncated },
+            Err(e) => ErrorBody::Failed(e.to_string()),
+        };
+
+        Self::from_error_parts(status, headers, url, body)
+    }
+
+    /// throttled responses don't need the body to be read
+    pub(crate) fn throttled(status: StatusCode, headers: &HeaderMap, url: &Url) -> Option<ErrPile> {
+        (status.as_u16() == 429).then(|| {
+            ErrPile::rate_limited(parse_retry_after(headers), url.host_str().map(String::from))
+        })
+    }
+
+    /// picks the most specific error for the response
+    pub(crate) fn from_error_parts(
+        status: StatusCode,
+        headers: HeaderMap,
+        url: Url,
+        body: ErrorBody,
+    ) -> ErrPile {
+        let snapshot = match &body {
+            ErrorBody::Read { body, truncated } if capture_snapshots() => Some(
+                ResponseSnapshot::new(status, &headers, &url, body, *truncated),
+            ),
+            _ => None,
+        };
+
+        let err = Self::decode_error_parts(status, headers, url, body);
+        match snapshot {
+            Some(snapshot) => err.captured(snapshot),
+            None => err,
+        }
+    }
+
+    fn decode_error_parts(
+        status: StatusCode,
+        headers: HeaderMap,
+        url: Url,
+        body: ErrorBody,
+    ) -> ErrPile {
+        let (body, truncated) = match body {
+            ErrorBody::Read { body, truncated } => (body, truncated),
+            ErrorBody::Failed(e) => {
+                let mut err = HttpError::new(status, headers, &[]).with_url(url);
+                err.body_snippet = format!("Failed to read error response: {e}");
+                return err.into();
+            }
+        };
+
+        if let Some(err) = run_decoders(&status, &headers, &body) {
+            return err;
+        }
+
+        // channels answer in their own envelopes, with any status
+        #[cfg(feature = "channel")]
+        if let Some(channel) = url.host_str().and_then(crate::Channel::from_host)
+            && let Some(err) = crate::ChannelError::parse(channel, status, &headers, &body)
+        {
+            return err.into();
+        }
+
+        if redirects_to_sign_in(status, &headers, Some(&url)) {
+            return ErrPile::Auth;
+        }
+
+        // parsed once, the structured shapes are tried against the value
+        // before the `HttpError` (snippet, redaction, HTML) is built
+        let json = serde_json::from_slice::<Value>(&body).ok();
+        let decoded = match &json {
+            Some(json) => Self::decode_json(status, &headers, url.host_str(), json),
+            None => Self::decode_text(status, &headers, &body),
+        };
+        if let Some(err) = decoded {
+            return err;
+        }
+
+        let mut err = HttpError::with_body(status, headers, &body, json).with_url(url);
+        if truncated {
+            err.body_truncated = true;
+            err.body_snippet
+                .push_str(&format!(" …[truncated at {} bytes]", body.len()));
+        }
+
+        if map_auth_status() {
+            match err.status {
+                StatusCode::UNAUTHORIZED => return ErrPile::Auth,
+                StatusCode::FORBIDDEN => return ErrPile::Permission,
+                _ => {}
+            }
+        }
+
+        err.into()
+    }
+
+    /// the error of a JSON body, if it has a shape with its own variant.
+    /// `host` is the scope of the throttling
+    pub(crate) fn decode_json(
+        status: StatusCode,
+        headers: &HeaderMap,
+        host: Option<&str>,
+        json: &Value,
+    ) -> Option<ErrPile> {
+        // a 5xx listing `errors` is still a failure of the server, only
+        // the answers to a bad request are about the fields
+        if matches!(status.as_u16(), 400 | 422)
+            && let Some(errors) = crate::value::field_errors(json)
+        {
+            return Some(ErrPile::Validation(errors));
+        }
+
+        // OPERA Cloud sends problem documents with its own members
+        #[cfg(feature = "ohip")]
+        if let Some(mut err) = crate::OhipError::from_body(Some(status.as_u16()), json) {
+            err.retry_after = parse_retry_after(headers);
+            return Some(err.into());
+        }
+
+        let is_problem = headers
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(ProblemDetails::is_problem_content_type);
+        if is_problem && let Ok(problem) = ProblemDetails::deserialize(json) {
+            return Some(ErrPile::Problem(Box::new(problem)));
+        }
+
+        #[cfg(feature = "sms")]
+        if let Some(mut err) = crate::SmsError::from_body(Some(status.as_u16()), json) {
+            err.retry_after = parse_retry_after(headers);
+            return Some(err.into());
+        }
+
+        if is_locked_response(status, Some(json)) {
+            return Some(ErrPile::InUse);
+        }
+
+        // Graph also throttles with 503 and 509, the code in the body tells
+        if let Some(code) = json.pointer("/error/code").and_then(Value::as_str)
+            && GraphErrorCode::from(code).is_throttling()
+        {
+            let retry_after = parse_retry_after(headers).or_else(|| {
+                ["/error/innerError", "/error/innererror"]
+                    .iter()
+                    .filter_map(|p| json.pointer(p))
+                    .find_map(inner_error_retry_after)
+            });
+            return Some(ErrPile::rate_limited(retry_after, host.map(String::from)));
+        }
+
+        if let Some(token) = TokenError::from_body(json) {
+            return Some(ErrPile::TokenAcquisition(Box::new(token)));
+        }
+
+        if let Some(errors) = GraphQLErrors::from_body(json) {
+            return Some(ErrPile::GraphQL(errors));
+        }
+
+        // Graph and Document Intelligence share the `error` shape, Graph
+        // spells `innerError` and its codes in camelCase
+        if let Some(code) = json.pointer("/error/code").and_then(Value::as_str)
+            && (json.pointer("/error/innerError").is_some()
+                || code.starts_with(|c: char| c.is_ascii_lowercase()))
+            && let Ok(ms_error) = MSResponseError::deserialize(json)
+        {
+            return Some(MicrosoftError::MS(Box::new(ms_error)).into());
+        }
+
+        // structured Document Intelligence errors have their own variant
+        if let Ok(az_error) = AZError::deserialize(json) {
+            return Some(Box::new(az_error).into());
+        }
+
+        None
+    }
+
+    /// the error of a body that isn't JSON: a SOAP fault, a locked resource
+    #[cfg_attr(not(feature = "xml"), allow(unused_variables))]
+    pub(crate) fn decode_text(
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Option<ErrPile> {
+        #[cfg(feature = "xml")]
+        {
+            let content_type = headers.get(CONTENT_TYPE).and_then(|v| v.to_str().ok());
+            let text = String::from_utf8_lossy(body);
+
+            if !HtmlSummary::looks_like_html(content_type, &text)
+                && crate::XmlFault::looks_like_xml(content_type, &text)
+                && let Some(fault) = crate::XmlFault::parse(&text)
+            {
+                return Some(ErrPile::Xml(Box::new(fault)));
+            }
+        }
+
+        is_locked_response(status, None).then_some(ErrPile::InUse)
+    }
+}
+
+pub trait ReqwestPileResExt: Sized {
+    /// converts the reponse into appropriate ErrPile
+    /// this will also take care of Azure Document Intelligence errors
+    /// based on the response
+    #[allow(async_fn_in_trait)]
+    async fn to_pile_result<T>(self) -> PileResult<T>
+    where
+        T: for<'de> serde::Deserialize<'de>;
+
+    /// returns the response untouched on success, otherwise converts
+    /// it into appropriate ErrPile. Similar to `error_for_status`
+    #[allow(async_fn_in_trait)]
+    async fn error_for_pile(self) -> PileResult<Self>;
+
+    /// raw body of a successful response, e.g. file downloads
+    #[allow(async_fn_in_trait)]
+    async fn to_pile_bytes(self) -> PileResult<Bytes>;
+
+    /// text body of a successful response
+    #[allow(async_fn_in_trait)]
+    async fn to_pile_text(self) -> PileResult<String>;
+
+    /// ignores the body of a successful response, for
+    /// 202/204 and fire-and-forget endpoints
+    #[allow(async_fn_in_trait)]
+    async fn to_pile_empty(self) -> PileResult;
+
+    /// reads the `data` of a GraphQL response, the `errors` list is
+    /// converted into `ErrPile::GraphQL` even when the status is 200
+    #[allow(async_fn_in_trait)]
+    async fn to_pile_graphql<T>(self) -> PileResult<T>
+    where
+        T: for<'de> serde::Deserialize<'de>;
+
+    /// body of a channel manager response, the error envelopes of
+    /// Booking.com and Expedia are converted into `ErrPile::Channel` even
+    /// when the status is 200, as they answer most failures
+    #[cfg(feature = "channel")]
+    #[allow(async_fn_in_trait)]
+    async fn error_for_channel(self) -> PileResult<Bytes>;
+}
+
+impl ReqwestPileResExt for reqwest::Response {
+    async fn to_pile_result<T>(self) -> PileResult<T>
+    where
+        T: for<'de> serde::Deserialize<'de>,
+    {
+        let body = self.error_for_pile().await?.bytes().await?;
+        parse_json_body(&body)
+    }
+
+    async fn error_for_pile(self) -> PileResult<Self> {
+        if self.status().is_success() {
+            return Ok(self);
+        }
+
+        Err(ErrPile::handle_error_response(self).await)
+    }
+
+    async fn to_pile_bytes(self) -> PileResult<Bytes> {
+        Ok(self.error_for_pile().await?.bytes().await?)
+    }
+
+    async fn to_pile_text(self) -> PileResult<String> {
+        Ok(self.error_for_pile().await?.text().await?)
+    }
+
+    async fn to_pile_empty(self) -> PileResult {
+        self.error_for_pile().await.map(|_| ())
+    }
+
+    async fn to_pile_graphql<T>(self) -> PileResult<T>
+    where
+        T: for<'de> serde::Deserialize<'de>,
+    {
+        let body = self.error_for_pile().await?.bytes().await?;
+        crate::graphql::graphql_result(&body)
+    }
+
+    #[cfg(feature = "channel")]
+    async fn error_for_channel(self) -> PileResult<Bytes> {
+        let res = self.error_for_pile().await?;
+        let status = res.status();
+        let headers = res.headers().clone();
+        let channel = res.url().host_str().and_then(crate::Channel::from_host);
+        let body = res.bytes().await?;
+
+        // other hosts, a proxy in front of the channel: only the
+        // envelopes that can't be anyone else's
+        let err = match channel {
+            Some(channel) => crate::ChannelError::parse(channel, status, &headers, &body),
+            None => crate::ChannelError::recognize(&body),
+        };
+        match err {
+            Some(err) => Err(err.into()),
+            None => Ok(body),
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use std::{
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue, concurrent::FluentBundle};
+use unic_langid::LanguageIdentifier;
+
+use crate::{Audience, ErrPile, PileResult};
+
+static MESSAGES: RwLock<Option<Arc<LocalizedMessages>>> = RwLock::new(None);
+
+/// the language the messages fall back to
+const FALLBACK: &str = "en";
+
+/// the arguments of `ErrPile::message_args` that are counts, passed as
+/// numbers to select plurals. The others stay text, an id like `0042`
+/// isn't a number
+const NUMERIC_ARGS: &[&str] = &["retry_after", "count", "rooms"];
+
+/// Translations of the message catalog, a Fluent resource per language.
+/// The message ids are the codes and the variant names, as in the
+/// `MessageCatalog`, the value is the guest text and the `.front-desk`
+/// and `.engineer` attributes the texts of the staff:
+///
+/// ```ftl
+/// room_unavailable = Leider ist kein Zimmer { $room_type } mehr frei
+///     .front-desk = Kein { $room_type } vom { $arrival } bis { $departure }, Upgrade anbieten
+/// NotFound = { $resource } wurde nicht gefunden
+/// ```
+///
+/// The arguments are the ones of `ErrPile::message_args`, the counts
+/// among them (`retry_after`, `count`, `rooms`) select plurals
+#[derive(Default)]
+pub struct LocalizedMessages {
+    bundles: Vec<(LanguageIdentifier, FluentBundle<FluentResource>)>,
+}
+
+impl LocalizedMessages {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the messages of the language, a second resource for the same
+    /// language overrides the messages it repeats. A resource that
+    /// doesn't parse is a `Config` error
+    pub fn add(&mut self, lang: &str, ftl: &str) -> PileResult {
+        let id: LanguageIdentifier = lang
+            .parse()
+            .map_err(|_| ErrPile::config("i18n", format!("`{lang}` is not a language tag")))?;
+        let resource = FluentResource::try_new(ftl.to_string()).map_err(|(_, errors)| {
+            ErrPile::config(
+                "i18n",
+                format!("invalid Fluent resource for {lang}: {errors:?}"),
+            )
+        })?;
+
+        let bundle = match self.bundles.iter_mut().find(|(known, _)| *known == id) {
+            Some((_, bundle)) => bundle,
+            None => {
+                let mut bundle = FluentBundle::new_concurrent(vec![id.clone()]);
+                // the texts go to plain text, not HTML
+                bundle.set_use_isolating(false);
+                self.bundles.push((id, bundle));
+                &mut self.bundles.last_mut().expect("just pushed").1
+            }
+        };
+        bundle.add_resource_overriding(resource);
+        Ok(())
+    }
+
+    /// Reads the `<lang>.ftl` files of the directory (`en.ftl`,
+    /// `de-AT.ftl`, …)
+    pub fn load_dir<P>(dir: P) -> PileResult<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let mut messages = Self::new();
+        for entry in std::fs::read_dir(dir.as_ref())? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "ftl") {
+                continue;
+            }
+            let Some(lang) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+
+            let ftl = std::fs::read_to_string(&path)?;
+            messages.add(lang, &ftl).map_err(|e| match e {
+                ErrPile::Config(err) => ErrPile::config_in_file(err.key, &path, err.reason),
+                e => e,
+            })?;
+        }
+        Ok(messages)
+    }
+
+    /// Text of the error in the language, or the closest one: the same
+    /// language of another region, then English. A message that doesn't
+    /// format (an argument the error doesn't have) is skipped like a
+    /// missing one. `None` when none of them has a message for the error
+    pub fn render(&self, err: &ErrPile, lang: &str, audience: Audience) -> Option<String> {
+        let args = fluent_args(err);
+        let ids = [err.code(), Some(err.variant_name().to_string())];
+
+        self.candidates(lang).into_iter().find_map(|bundle| {
+            let message = ids.iter().flatten().find_map(|id| bundle.get_message(id))?;
+            let pattern = match audience {
+                Audience::Guest => message.value()?,
+                Audience::FrontDesk => message.get_attribute("front-desk")?.value(),
+                Audience::Engineer => message.get_attribute("engineer")?.value(),
+            };
+
+            let mut errors = Vec::new();
+            let text = bundle.format_pattern(pattern, Some(&args), &mut errors);
+            errors.is_empty().then(|| text.into_owned())
+        })
+    }
+
+    /// the bundles of the language, of the same language in another
+    /// region, then of English
+    fn candidates(&self, lang: &str) -> Vec<&FluentBundle<FluentResource>> {
+        let wanted = lang.parse::<LanguageIdentifier>().ok();
+        let same = |id: &LanguageIdentifier| wanted.as_ref() == Some(id);
+        let same_language = |id: &LanguageIdentifier| {
+            wanted
+                .as_ref()
+                .is_some_and(|wanted| wanted.language == id.language)
+        };
+        let fallback = |id: &LanguageIdentifier| id.language.as_str() == FALLBACK;
+
+        let mut order: Vec<usize> = Vec::new();
+        for matches in [
+            &same as &dyn Fn(&LanguageIdentifier) -> bool,
+            &same_language,
+            &fallback,
+        ] {
+            for (i, (id, _)) in self.bundles.iter().enumerate() {
+                if matches(id) && !order.contains(&i) {
+                    order.push(i);
+                }
+            }
+        }
+        order.into_iter().map(|i| &self.bundles[i].1).collect()
+    }
+}
+
+/// Makes `user_message_in` and `message_for_in` use the translations,
+/// `None` goes back to the message catalog
+pub fn set_localized_messages(messages: Option<LocalizedMessages>) {
+    *MESSAGES.write().unwrap_or_else(|e| e.into_inner()) = messages.map(Arc::new);
+}
+
+/// the translations set with `set_localized_messages`
+pub fn localized_messages() -> Option<Arc<LocalizedMessages>> {
+    MESSAGES.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+impl ErrPile {
+    /// `user_message` in the language of the guest (`de`, `fr-CH`, …),
+    /// falling back to English then to the message catalog
+    pub fn user_message_in(&self, lang: &str) -> String {
+        self.message_for_in(Audience::Guest, lang)
+    }
+
+    /// `message_for` in the language, with the fallbacks of
+    /// `user_message_in`
+    pub fn message_for_in(&self, audience: Audience, lang: &str) -> String {
+        localized_messages()
+            .and_then(|messages| messages.render(self, lang, audience))
+            .unwrap_or_else(|| self.message_for(audience))
+    }
+}
+
+fn fluent_args(err: &ErrPile) -> FluentArgs<'static> {
+    let mut args = FluentArgs::new();
+    for (name, value) in err.message_args() {
+        // counts are numbers so `{ $rooms ->` selects the plural
+        let value = match value.parse::<f64>() {
+            Ok(number) if NUMERIC_ARGS.contains(&name) => FluentValue::from(number),
+            _ => FluentValue::from(value),
+        };
+        args.set(name, value);
+    }
+    args
+}
@@ -0,0 +1,76 @@
+use std::time::{Duration, Instant};
+
+use crate::{Backoff, ErrPile};
+
+/// Delays of a `Backoff` handed to `backon`, one per retry: the first
+/// call isn't counted, so `max_attempts` 4 yields 3 delays
+#[derive(Debug, Clone)]
+pub struct BackonDelays {
+    backoff: Backoff,
+    attempt: u32,
+    started: Instant,
+}
+
+impl Iterator for BackonDelays {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        if self.attempt + 1 >= self.backoff.max_attempts {
+            return None;
+        }
+
+        let delay = self.backoff.computed_delay(self.attempt);
+        if let Some(max_elapsed) = self.backoff.max_elapsed
+            && self.started.elapsed() + delay > max_elapsed
+        {
+            return None;
+        }
+        self.attempt += 1;
+        Some(delay)
+    }
+}
+
+/// `Backoff` as a `backon` backoff, pair it with `Backoff::backon_adjust`
+/// for the server's waits
+///
+/// ```ignore
+/// let backoff = Backoff::new().max_attempts(5);
+/// let rooms = fetch_rooms
+///     .retry(backoff.clone())
+///     .when(ErrPile::is_transient)
+///     .adjust(backoff.backon_adjust())
+///     .await?;
+/// ```
+impl backon::BackoffBuilder for Backoff {
+    type Backoff = BackonDelays;
+
+    fn build(self) -> BackonDelays {
+        BackonDelays {
+            backoff: self,
+            attempt: 0,
+            started: Instant::now(),
+        }
+    }
+}
+
+impl Backoff {
+    /// For `Retry::adjust`: waits as long as the server asked to
+    /// (`ErrPile::retry_after`) instead of the backoff's delay, and stops
+    /// retrying when it asks for more than `max_retry_after`, like
+    /// `retry` does. Doesn't retry past the end of the backoff
+    pub fn backon_adjust(
+        &self,
+    ) -> impl FnMut(&ErrPile, Option<Duration>) -> Option<Duration> + use<> {
+        let max_retry_after = self.max_retry_after;
+        move |err, delay| match err.retry_after() {
+            Some(retry_after) if retry_after > max_retry_after => None,
+            retry_after => delay.map(|delay| retry_after.unwrap_or(delay)),
+        }
+    }
+}
+
+/// `Backoff::backon_adjust` of the default `Backoff`, server waits over
+/// 2 minutes end the retries
+pub fn backon_adjust(err: &ErrPile, delay: Option<Duration>) -> Option<Duration> {
+    Backoff::default().backon_adjust()(err, delay)
+}
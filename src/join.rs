@@ -0,0 +1,66 @@
+use std::{borrow::Cow, future::Future};
+
+use futures_util::{StreamExt, stream};
+
+use crate::{MultiPile, PileResult};
+
+/// Runs the labeled futures concurrently, every one to completion, and
+/// gives their values in order, or `ErrPile::Multi` with the failures of
+/// every task labeled. See `try_join_all_piles_bounded`
+///
+/// ```ignore
+/// let rooms = try_join_all_piles(
+///     room_ids.iter().map(|id| (format!("room {id}"), pms.sync_room(*id))),
+/// )
+/// .await?;
+/// ```
+pub async fn try_join_all_piles<I, L, F, T>(futures: I) -> PileResult<Vec<T>>
+where
+    I: IntoIterator<Item = (L, F)>,
+    L: Into<Cow<'static, str>>,
+    F: Future<Output = PileResult<T>>,
+{
+    try_join_all_piles_bounded(futures, usize::MAX).await
+}
+
+/// Like `try_join_all_piles` with at most `limit` futures running at once,
+/// so a sync of every room doesn't open hundreds of connections to the
+/// PMS. A task failing with `ErrPile::Cancelled` is a shutdown rather than
+/// a failure of the batch: the others are dropped and the cancellation is
+/// returned as is. The `MultiPile` is transient when every failure is, a
+/// retry of the whole join then makes sense
+pub async fn try_join_all_piles_bounded<I, L, F, T>(futures: I, limit: usize) -> PileResult<Vec<T>>
+where
+    I: IntoIterator<Item = (L, F)>,
+    L: Into<Cow<'static, str>>,
+    F: Future<Output = PileResult<T>>,
+{
+    let mut tasks = stream::iter(
+        futures
+            .into_iter()
+            .enumerate()
+            .map(|(index, (label, future))| async move { (index, label, future.await) }),
+    )
+    .buffer_unordered(limit.max(1));
+
+    let mut values = Vec::new();
+    let mut failures = Vec::new();
+    while let Some((index, label, result)) = tasks.next().await {
+        match result {
+            Ok(value) => values.push((index, value)),
+            Err(err) if err.is_cancelled() => return Err(err),
+            Err(err) => failures.push((index, label, err)),
+        }
+    }
+
+    // in the order of the tasks rather than the one they finished in
+    failures.sort_by_key(|(index, ..)| *index);
+    let mut multi = MultiPile::new();
+    for (index, label, err) in failures {
+        multi.push_labeled(index, label, err);
+    }
+    multi.into_result()?;
+
+    values.sort_by_key(|(index, _)| *index);
+    Ok(values.into_iter().map(|(_, value)| value).collect())
+}
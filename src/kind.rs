@@ -0,0 +1,604 @@
+use core::fmt;
+use std::error::Error;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "booking")]
+use crate::BookingError;
+use crate::{
+    Audience, ErrPile, MicrosoftError, StorageError, TokenErrorKind, TransportError, mask_card_data,
+};
+
+/// response headers carrying a request/correlation id, with the name
+/// the id is reported under
+const CORRELATION_HEADERS: &[(&str, &str)] = &[
+    ("request-id", "request_id"),
+    ("x-ms-request-id", "request_id"),
+    ("apim-request-id", "request_id"),
+    ("client-request-id", "client_request_id"),
+    ("x-correlation-id", "correlation_id"),
+];
+
+/// Category of an error, coarse enough to filter logs and dashboards by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum PileKind {
+    Auth,
+    Permission,
+    InUse,
+    NotReady,
+    NotFound,
+    Conflict,
+    Timeout,
+    Cancelled,
+    Unsupported,
+    Config,
+    RateLimited,
+    Validation,
+    /// the card was declined, answered with 402
+    PaymentDeclined,
+    /// a service answered with an error (Graph, Document Intelligence,
+    /// any HTTP API)
+    Upstream,
+    /// the request never got an answer
+    Network,
+    Database,
+    Ssh,
+    Io,
+    /// a payload could not be parsed
+    Parse,
+    /// a PDF, image or archive could not be processed
+    Document,
+    Internal,
+}
+
+impl PileKind {
+    /// every kind, in declaration order, for filters and label checks
+    pub const ALL: [PileKind; 21] = [
+        Self::Auth,
+        Self::Permission,
+        Self::InUse,
+        Self::NotReady,
+        Self::NotFound,
+        Self::Conflict,
+        Self::Timeout,
+        Self::Cancelled,
+        Self::Unsupported,
+        Self::Config,
+        Self::RateLimited,
+        Self::Validation,
+        Self::PaymentDeclined,
+        Self::Upstream,
+        Self::Network,
+        Self::Database,
+        Self::Ssh,
+        Self::Io,
+        Self::Parse,
+        Self::Document,
+        Self::Internal,
+    ];
+
+    /// the kinds of `ALL`
+    pub fn iter() -> impl ExactSizeIterator<Item = PileKind> {
+        Self::ALL.into_iter()
+    }
+
+    /// snake case name, used as the `kind` field/label
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Auth => "auth",
+            Self::Permission => "permission",
+            Self::InUse => "in_use",
+            Self::NotReady => "not_ready",
+            Self::NotFound => "not_found",
+            Self::Conflict => "conflict",
+            Self::Timeout => "timeout",
+            Self::Cancelled => "cancelled",
+            Self::Unsupported => "unsupported",
+            Self::Config => "config",
+            Self::RateLimited => "rate_limited",
+            Self::Validation => "validation",
+            Self::PaymentDeclined => "payment_declined",
+            Self::Upstream => "upstream",
+            Self::Network => "network",
+            Self::Database => "database",
+            Self::Ssh => "ssh",
+            Self::Io => "io",
+            Self::Parse => "parse",
+            Self::Document => "document",
+            Self::Internal => "internal",
+        }
+    }
+}
+
+impl fmt::Display for PileKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How urgent an error is, `Critical` ones need someone to act (a
+/// broken configuration, revoked credentials)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+    Critical,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ErrPile {
+    /// Severity of the error: expected failures (bad input, throttling,
+    /// anything transient) are warnings, what won't fix itself without
+    /// someone changing the setup is critical
+    pub fn severity(&self) -> Severity {
+        match self.kind() {
+            PileKind::Cancelled => Severity::Info,
+            PileKind::Config => Severity::Critical,
+            PileKind::Auth
+                if matches!(
+                    self.token_error_kind(),
+                    Some(TokenErrorKind::InvalidClient | TokenErrorKind::UnauthorizedClient)
+                ) =>
+            {
+                Severity::Critical
+            }
+            PileKind::Auth
+            | PileKind::Permission
+            | PileKind::InUse
+            | PileKind::NotReady
+            | PileKind::NotFound
+            | PileKind::Conflict
+            | PileKind::RateLimited
+            | PileKind::Validation
+            | PileKind::PaymentDeclined => Severity::Warning,
+            _ if self.is_transient() => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+
+    /// Name of the variant, `Captured` and `Context` report the one they
+    /// wrap. Stable across releases, fit for a label or a filter
+    pub fn variant_name(&self) -> &'static str {
+        match self.peeled() {
+            Self::Transport(_) => "Transport",
+            Self::Storage(_) => "Storage",
+            #[cfg(any(feature = "pdf", feature = "image"))]
+            Self::Document(_) => "Document",
+            Self::Microsoft(_) => "Microsoft",
+            #[cfg(feature = "booking")]
+            Self::Booking(_) => "Booking",
+            Self::Auth => "Auth",
+            Self::Permission => "Permission",
+            Self::InUse => "InUse",
+            Self::NotReady => "NotReady",
+            Self::NotFound { .. } => "NotFound",
+            Self::Conflict { .. } => "Conflict",
+            Self::Timeout { .. } => "Timeout",
+            Self::Cancelled { .. } => "Cancelled",
+            Self::Unsupported { .. } => "Unsupported",
+            Self::Config { .. } => "Config",
+            Self::RateLimited { .. } => "RateLimited",
+            Self::PaymentDeclined { .. } => "PaymentDeclined",
+            Self::KeyEncoder { .. } => "KeyEncoder",
+            Self::WebhookVerification(_) => "WebhookVerification",
+            Self::Json(_) => "Json",
+            Self::Deserialize { .. } => "Deserialize",
+            Self::Decode(_) => "Decode",
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::Thread(_) => "Thread",
+            Self::Panic { .. } => "Panic",
+            Self::Task { .. } => "Task",
+            Self::Floor => "Floor",
+            Self::Ceil => "Ceil",
+            Self::FrameTooLarge => "FrameTooLarge",
+            #[cfg(feature = "python")]
+            Self::Python(_) => "Python",
+            #[cfg(feature = "notify")]
+            Self::Watch(_) => "Watch",
+            Self::Semver(_) => "Semver",
+            Self::Regex { .. } => "Regex",
+            #[cfg(any(feature = "tera", feature = "handlebars"))]
+            Self::Template(_) => "Template",
+            #[cfg(feature = "decimal")]
+            Self::Decimal(_) => "Decimal",
+            #[cfg(feature = "decimal")]
+            Self::Amount(_) => "Amount",
+            #[cfg(feature = "stream")]
+            Self::Download { .. } => "Download",
+            #[cfg(feature = "multipart")]
+            Self::Upload { .. } => "Upload",
+            Self::Page { .. } => "Page",
+            #[cfg(feature = "servicebus")]
+            Self::ServiceBus { .. } => "ServiceBus",
+            #[cfg(feature = "lettre")]
+            Self::Mail(_) => "Mail",
+            Self::TokenAcquisition(_) => "TokenAcquisition",
+            Self::Http(_) => "Http",
+            Self::Problem(_) => "Problem",
+            #[cfg(feature = "xml")]
+            Self::Xml(_) => "Xml",
+            #[cfg(feature = "channel")]
+            Self::Channel(_) => "Channel",
+            #[cfg(feature = "ohip")]
+            Self::Ohip(_) => "Ohip",
+            #[cfg(feature = "fiscal")]
+            Self::Fiscal(_) => "Fiscal",
+            #[cfg(feature = "sms")]
+            Self::Sms(_) => "Sms",
+            #[cfg(feature = "doorlock")]
+            Self::DoorLock(_) => "DoorLock",
+            Self::GraphQL(_) => "GraphQL",
+            Self::Validation(_) => "Validation",
+            Self::Captured(_) => "Captured",
+            Self::Context(_) => "Context",
+            Self::FromValue(_) => "FromValue",
+            Self::Multi(_) => "Multi",
+            Self::Remote(_) => "Remote",
+            Self::App(_) => "App",
+            Self::Other(_) => "Other",
+            Self::Custom(_) => "Custom",
+        }
+    }
+
+    /// category of the error, wrappers (`Page`, `Upload`) report the
+    /// kind of the error they wrap
+    pub fn kind(&self) -> PileKind {
+        match self.peeled() {
+            Self::Auth | Self::TokenAcquisition(_) | Self::WebhookVerification(_) => PileKind::Auth,
+            Self::Permission => PileKind::Permission,
+            Self::InUse => PileKind::InUse,
+            Self::NotReady => PileKind::NotReady,
+            Self::NotFound { .. } => PileKind::NotFound,
+            Self::Conflict { .. } => PileKind::Conflict,
+            Self::Timeout { .. } => PileKind::Timeout,
+            Self::Cancelled { .. } => PileKind::Cancelled,
+            Self::Unsupported { .. } => PileKind::Unsupported,
+            Self::Config { .. } => PileKind::Config,
+            Self::RateLimited { .. } => PileKind::RateLimited,
+            Self::KeyEncoder(err) => err.reason.kind(),
+            Self::Validation(_) => PileKind::Validation,
+            #[cfg(feature = "decimal")]
+            Self::Amount(_) => PileKind::Validation,
+            Self::Remote(remote) => remote.kind,
+            Self::App(app) => app.kind,
+            Self::Multi(multi) => multi.shared_kind().unwrap_or(PileKind::Internal),
+            Self::Page { source, .. } => source.kind(),
+            Self::Task(task) => task.source.kind(),
+            #[cfg(feature = "multipart")]
+            Self::Upload(upload) => upload.source.kind(),
+            Self::Transport(err) => err.kind(),
+            Self::Storage(err) => err.kind(),
+            #[cfg(any(feature = "pdf", feature = "image"))]
+            Self::Document(_) => PileKind::Document,
+            #[cfg(feature = "booking")]
+            Self::Booking(err) => err.kind(),
+            #[cfg(feature = "channel")]
+            Self::Channel(err) => err.kind(),
+            #[cfg(feature = "ohip")]
+            Self::Ohip(err) => err.kind(),
+            #[cfg(feature = "fiscal")]
+            Self::Fiscal(err) => err.failure.kind(),
+            #[cfg(feature = "sms")]
+            Self::Sms(err) => err.failure.kind(),
+            #[cfg(feature = "doorlock")]
+            Self::DoorLock(err) => err.failure.kind(),
+            Self::Microsoft(_)
+            | Self::Http(_)
+            | Self::Problem(_)
+            | Self::GraphQL(_)
+            | Self::FromValue(_) => PileKind::Upstream,
+            Self::PaymentDeclined { .. } => PileKind::PaymentDeclined,
+            #[cfg(feature = "xml")]
+            Self::Xml(_) => PileKind::Upstream,
+            #[cfg(feature = "servicebus")]
+            Self::ServiceBus { .. } => PileKind::Upstream,
+            #[cfg(feature = "stream")]
+            Self::Download { .. } => PileKind::Network,
+            #[cfg(feature = "notify")]
+            Self::Watch(_) => PileKind::Io,
+            Self::Json(_)
+            | Self::Deserialize { .. }
+            | Self::Semver(_)
+            | Self::Regex { .. }
+            | Self::Decode(_) => PileKind::Parse,
+            #[cfg(feature = "decimal")]
+            Self::Decimal(_) => PileKind::Parse,
+            _ => PileKind::Internal,
+        }
+    }
+
+    /// Text that can be shown to the end user: the message of the
+    /// variants that describe the situation, a generic one for the
+    /// internal failures so no connection strings or payloads leak out.
+    /// The guest text of the message catalog wins, see `MessageCatalog`
+    pub fn user_message(&self) -> String {
+        if let Some(text) = crate::catalog::catalog_message(self, Audience::Guest) {
+            return text;
+        }
+
+        match self.peeled() {
+            Self::Page { source, .. } => return source.user_message(),
+            Self::Task(task) => return task.source.user_message(),
+            Self::Remote(remote) if !remote.user_message.is_empty() => {
+                return mask_card_data(&remote.user_message).into_owned();
+            }
+            #[cfg(feature = "multipart")]
+            Self::Upload(upload) => return upload.source.user_message(),
+            Self::PaymentDeclined { category, .. } => return category.user_message().into(),
+            Self::KeyEncoder(err) => return err.reason.user_message().into(),
+            // the OAuth error and the Entra ID description are ours to read
+            Self::TokenAcquisition(_) => {
+                return "Authentication with a service failed, please try again later".into();
+            }
+            _ => {}
+        }
+
+        match self.kind() {
+            PileKind::Auth
+            | PileKind::Permission
+            | PileKind::InUse
+            | PileKind::NotReady
+            | PileKind::NotFound
+            | PileKind::Conflict
+            | PileKind::Cancelled
+            | PileKind::Unsupported
+            | PileKind::RateLimited
+            | PileKind::Validation => self.peeled().to_string(),
+            PileKind::Timeout => "The operation took too long, please try again later".into(),
+            _ if self.is_transient() => {
+                "The service is temporarily unavailable, please try again later".into()
+            }
+            _ => "Something went wrong, please contact support if the problem persists".into(),
+        }
+    }
+
+    /// machine readable code sent by the other side: the Graph/AZ error
+    /// code, the OAuth error, the AMQP condition, …
+    pub fn code(&self) -> Option<String> {
+        if let Some(code) = self.graph_code() {
+            return Some(code.to_string());
+        }
+
+        match self.peeled() {
+            Self::TokenAcquisition(err) => Some(err.error.clone()),
+            Self::Microsoft(MicrosoftError::AZ(err)) => Some(err.error.code.clone()),
+            Self::Remote(remote) => remote.code.clone(),
+            Self::App(app) => app.code.map(String::from),
+            Self::PaymentDeclined { processor_code, .. } => Some(processor_code.to_string()),
+            Self::KeyEncoder(err) => Some(err.reason.as_str().to_string()),
+            #[cfg(feature = "booking")]
+            Self::Booking(err) => Some(err.code().to_string()),
+            Self::Page { source, .. } => source.code(),
+            #[cfg(feature = "multipart")]
+            Self::Upload(upload) => upload.source.code(),
+            #[cfg(feature = "servicebus")]
+            Self::ServiceBus(err) => Some(err.condition.clone()),
+            #[cfg(feature = "xml")]
+            Self::Xml(fault) => fault.code.clone(),
+            #[cfg(feature = "channel")]
+            Self::Channel(err) => err.code.clone(),
+            #[cfg(feature = "ohip")]
+            Self::Ohip(err) => err.code.clone(),
+            #[cfg(feature = "fiscal")]
+            Self::Fiscal(err) => err.code.clone(),
+            #[cfg(feature = "sms")]
+            Self::Sms(err) => err.code.map(|code| code.to_string()),
+            #[cfg(feature = "doorlock")]
+            Self::DoorLock(err) => err.code.clone(),
+            #[cfg(feature = "decimal")]
+            Self::Amount(err) => Some(err.failure.as_str().to_string()),
+            Self::GraphQL(errors) => errors.0.iter().find_map(|e| e.code()).map(String::from),
+            Self::Problem(problem) => problem.type_uri.clone(),
+            Self::Http(http) => http.body_json.as_ref()?.extract_structured().code,
+            Self::FromValue(value) => value.extract_structured().code,
+            _ => None,
+        }
+    }
+
+    /// status of the response the error was decoded from, `None` when
+    /// no response was received
+    pub fn upstream_status(&self) -> Option<u16> {
+        match self.peeled() {
+            Self::Http(http) => Some(http.status.as_u16()),
+            Self::Transport(transport) => match &**transport {
+                TransportError::Req { source, .. } => source.status().map(|s| s.as_u16()),
+                _ => self.snapshot().map(|s| s.status),
+            },
+            Self::Problem(problem) => problem.status,
+            Self::Remote(remote) => remote.http_status,
+            Self::Page { source, .. } => source.upstream_status(),
+            #[cfg(feature = "multipart")]
+            Self::Upload(upload) => upload.source.upstream_status(),
+            _ => self.snapshot().map(|s| s.status),
+        }
+    }
+
+    /// Request and correlation ids the other side sent along with the
+    /// error (Graph diagnostics, token endpoint trace ids, response
+    /// headers), what their support asks for
+    pub fn correlation_ids(&self) -> Vec<(&'static str, String)> {
+        let mut ids = self.attached_correlation_ids();
+        let mut push = |name: &'static str, id: Option<&str>| {
+            if let Some(id) = id
+                && !ids.iter().any(|(n, _)| *n == name)
+            {
+                ids.push((name, id.to_string()));
+            }
+        };
+
+        match self.peeled() {
+            Self::Microsoft(MicrosoftError::MS(err)) => {
+                let diagnostics = err.error.diagnostics();
+                push("request_id", diagnostics.request_id.as_deref());
+                push(
+                    "client_request_id",
+                    diagnostics.client_request_id.as_deref(),
+                );
+            }
+            Self::TokenAcquisition(err) => {
+                push("trace_id", err.trace_id.as_deref());
+                push("correlation_id", err.correlation_id.as_deref());
+            }
+            Self::Http(http) => {
+                for (header, name) in CORRELATION_HEADERS {
+                    push(
+                        name,
+                        http.headers.get(*header).and_then(|v| v.to_str().ok()),
+                    );
+                }
+            }
+            // sent along in the metadata
+            Self::Remote(remote) => {
+                for name in [
+                    "request_id",
+                    "client_request_id",
+                    "trace_id",
+                    "correlation_id",
+                ] {
+                    push(name, remote.metadata.get(name).and_then(|v| v.as_str()));
+                }
+            }
+            #[cfg(feature = "channel")]
+            Self::Channel(err) => push("ruid", err.request_id.as_deref()),
+            Self::Page { source, .. } => {
+                for (name, id) in source.correlation_ids() {
+                    push(name, Some(&id));
+                }
+            }
+            #[cfg(feature = "multipart")]
+            Self::Upload(upload) => {
+                for (name, id) in upload.source.correlation_ids() {
+                    push(name, Some(&id));
+                }
+            }
+            _ => {}
+        }
+        ids
+    }
+
+    /// the error followed by its sources, outermost first
+    pub fn chain(&self) -> impl Iterator<Item = &(dyn Error + 'static)> {
+        let mut next = Some(self as &(dyn Error + 'static));
+        std::iter::from_fn(move || {
+            let err = next?;
+            next = err.source();
+            Some(err)
+        })
+    }
+}
+
+impl TransportError {
+    /// category of the error, see `ErrPile::kind`
+    pub fn kind(&self) -> PileKind {
+        match self {
+            Self::Req { source, .. } if source.status().is_some() => PileKind::Upstream,
+            Self::Req { .. } => PileKind::Network,
+            Self::Url(_) | Self::ReqToStr(_) => PileKind::Parse,
+            #[cfg(feature = "ssh")]
+            Self::Ssh(_) | Self::SshKey { .. } | Self::Sftp(_) => PileKind::Ssh,
+        }
+    }
+}
+
+#[cfg(feature = "booking")]
+impl BookingError {
+    /// category of the error, see `ErrPile::kind`
+    pub fn kind(&self) -> PileKind {
+        match self {
+            Self::RoomUnavailable { .. } | Self::OverbookingRisk { .. } => PileKind::Conflict,
+            Self::RateNotFound { .. } | Self::GuestProfileMissing { .. } => PileKind::NotFound,
+            Self::StayDatesInvalid { .. } => PileKind::Validation,
+        }
+    }
+
+    /// stable code for clients telling the cases apart, see `ErrPile::code`
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::RoomUnavailable { .. } => "room_unavailable",
+            Self::RateNotFound { .. } => "rate_not_found",
+            Self::StayDatesInvalid { .. } => "stay_dates_invalid",
+            Self::OverbookingRisk { .. } => "overbooking_risk",
+            Self::GuestProfileMissing { .. } => "guest_profile_missing",
+        }
+    }
+
+    /// the fields, for the templates of the message catalog
+    pub fn message_args(&self) -> Vec<(&'static str, String)> {
+        match self {
+            Self::RoomUnavailable {
+                room_type,
+                arrival,
+                departure,
+            } => vec![
+                ("room_type", room_type.clone()),
+                ("arrival", arrival.to_string()),
+                ("departure", departure.to_string()),
+            ],
+            Self::RateNotFound {
+                rate_code,
+                room_type,
+            } => {
+                let mut args = vec![("rate_code", rate_code.clone())];
+                args.extend(room_type.clone().map(|room_type| ("room_type", room_type)));
+                args
+            }
+            Self::StayDatesInvalid {
+                arrival,
+                departure,
+                reason,
+            } => vec![
+                ("arrival", arrival.to_string()),
+                ("departure", departure.to_string()),
+                ("reason", reason.to_string()),
+            ],
+            Self::OverbookingRisk {
+                room_type,
+                date,
+                rooms,
+            } => vec![
+                ("room_type", room_type.clone()),
+                ("date", date.to_string()),
+                ("rooms", rooms.to_string()),
+            ],
+            Self::GuestProfileMissing { profile_id } => vec![("profile_id", profile_id.clone())],
+        }
+    }
+}
+
+impl StorageError {
+    /// category of the error, see `ErrPile::kind`
+    pub fn kind(&self) -> PileKind {
+        match self {
+            #[cfg(feature = "sqlx")]
+            Self::DB(db) => {
+                crate::constraint::violated_constraint_kind(db).unwrap_or(PileKind::Database)
+            }
+            #[cfg(feature = "migrate")]
+            Self::Migrate(_) => PileKind::Database,
+            Self::IO(_) => PileKind::Io,
+            #[cfg(feature = "zip")]
+            Self::Zip(_) => PileKind::Document,
+        }
+    }
+}
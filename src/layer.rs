@@ -0,0 +1,234 @@
+use core::fmt;
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use http::{HeaderName, HeaderValue};
+use tokio::time::{Instant, Sleep};
+use tower::{BoxError, Layer, Service, retry::RetryLayer};
+
+use crate::{Backoff, Deadline, ErrPile, OtherError, RetryPolicy};
+
+/// Errors the tower retry policy can classify
+pub trait AsPile {
+    /// the ErrPile to classify, `None` is never retried
+    fn as_pile(&self) -> Option<&ErrPile>;
+}
+
+impl AsPile for ErrPile {
+    fn as_pile(&self) -> Option<&ErrPile> {
+        Some(self)
+    }
+}
+
+/// the error type of most tower middlewares
+impl AsPile for BoxError {
+    fn as_pile(&self) -> Option<&ErrPile> {
+        self.downcast_ref()
+    }
+}
+
+/// `tower::retry::Policy` retrying the errors `RetryPolicy` accepts
+/// (transient ones for a `Backoff`), after the server's wait when it sent
+/// one, so hyper, axum and tonic clients retry like `retry` and
+/// `PileMiddleware` do
+///
+/// ```ignore
+/// let client = ServiceBuilder::new()
+///     .layer(PileRetry::new(Backoff::new()).layer())
+///     .service(client);
+/// ```
+///
+/// Requests are cloned before each attempt, so they must be `Clone`
+#[derive(Clone)]
+pub struct PileRetry {
+    policy: Arc<dyn RetryPolicy>,
+    attempt: u32,
+    /// when the first attempt failed
+    started: Option<Instant>,
+}
+
+impl fmt::Debug for PileRetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PileRetry")
+            .field("attempt", &self.attempt)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for PileRetry {
+    fn default() -> Self {
+        Self::new(Backoff::new())
+    }
+}
+
+impl PileRetry {
+    pub fn new<P: RetryPolicy + 'static>(policy: P) -> Self {
+        Self {
+            policy: Arc::new(policy),
+            attempt: 0,
+            started: None,
+        }
+    }
+
+    /// the `RetryLayer` to wrap services in
+    pub fn layer(self) -> RetryLayer<Self> {
+        RetryLayer::new(self)
+    }
+}
+
+impl<Req: Clone, Res, E: AsPile> tower::retry::Policy<Req, Res, E> for PileRetry {
+    type Future = Sleep;
+
+    fn retry(&mut self, _req: &mut Req, result: &mut Result<Res, E>) -> Option<Sleep> {
+        let err = result.as_ref().err()?.as_pile()?;
+        let started = *self.started.get_or_insert_with(Instant::now);
+
+        let delay = self
+            .policy
+            .next_delay(self.attempt, started.elapsed(), err)?;
+        // the error can't be replaced by a `Timeout`, it's returned as is
+        Deadline::ensure(delay, "retry").ok()?;
+        self.attempt += 1;
+        Some(tokio::time::sleep(delay))
+    }
+
+    fn clone_request(&mut self, req: &Req) -> Option<Req> {
+        Some(req.clone())
+    }
+}
+
+/// errors of tower middlewares, the ErrPile when they wrap one
+impl From<BoxError> for ErrPile {
+    fn from(value: BoxError) -> Self {
+        match value.downcast::<ErrPile>() {
+            Ok(pile) => *pile,
+            Err(error) => ErrPile::Other(Box::new(OtherError {
+                type_name: "tower::BoxError",
+                error,
+            })),
+        }
+    }
+}
+
+/// header the correlation id of a request is read from and echoed in
+pub const CORRELATION_ID: HeaderName = HeaderName::from_static("x-correlation-id");
+
+/// Layer of `PileErrorService`, the one place errors of an HTTP service
+/// are turned into responses
+///
+/// ```ignore
+/// let app = Router::new()
+///     .route("/reservations", post(create_reservation))
+///     .layer(PileErrorLayer);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PileErrorLayer;
+
+impl<S> Layer<S> for PileErrorLayer {
+    type Service = PileErrorService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        PileErrorService {
+            inner,
+            failed: None,
+        }
+    }
+}
+
+/// Turns the errors of the inner service into responses: every request
+/// gets a correlation id (the `x-correlation-id` it came with or a new
+/// one) seen by the handlers and echoed in the response, errors are
+/// logged, queued on the installed `ErrorQueue` and answered like the axum
+/// integration does, with the correlation id in the body and the body
+/// redacted. The correlation id is attached to the error before it is
+/// logged and reported, see `ErrPile::with_correlation_id`
+#[derive(Debug)]
+pub struct PileErrorService<S> {
+    inner: S,
+    /// `poll_ready` of the inner service failed, the next call answers
+    /// with it
+    failed: Option<ErrPile>,
+}
+
+/// a clone polls the inner service again, the failure stays with the
+/// service it happened on
+impl<S: Clone> Clone for PileErrorService<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            failed: None,
+        }
+    }
+}
+
+impl<S, ReqB, ResB> Service<http::Request<ReqB>> for PileErrorService<S>
+where
+    S: Service<http::Request<ReqB>, Response = http::Response<ResB>>,
+    S::Error: Into<ErrPile>,
+    S::Future: Send + 'static,
+    ResB: From<String> + Send + 'static,
+{
+    type Response = http::Response<ResB>;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        if self.failed.is_some() {
+            return Poll::Ready(Ok(()));
+        }
+        self.inner.poll_ready(cx).map(|ready| {
+            if let Err(err) = ready {
+                self.failed = Some(err.into());
+            }
+            Ok(())
+        })
+    }
+
+    fn call(&mut self, mut req: http::Request<ReqB>) -> Self::Future {
+        let id = req
+            .headers()
+            .get(CORRELATION_ID)
+            .and_then(|id| id.to_str().ok())
+            .filter(|id| !id.is_empty())
+            .map(String::from)
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let header = HeaderValue::try_from(&id).ok();
+        if let Some(header) = &header {
+            req.headers_mut().insert(CORRELATION_ID, header.clone());
+        }
+
+        let call = match self.failed.take() {
+            Some(failed) => Err(failed),
+            None => Ok(self.inner.call(req)),
+        };
+        Box::pin(async move {
+            let res = match call {
+                Ok(call) => call.await.map_err(Into::into),
+                Err(failed) => Err(failed),
+            };
+            let mut res = match res {
+                Ok(res) => res,
+                Err(err) => error_response(err, &id).map(ResB::from),
+            };
+            if let Some(header) = header {
+                res.headers_mut().insert(CORRELATION_ID, header);
+            }
+            Ok(res)
+        })
+    }
+}
+
+/// logs and reports the error with the correlation id attached, so the
+/// id a customer quotes finds it
+fn error_response(err: ErrPile, correlation_id: &str) -> http::Response<String> {
+    let err = err.with_correlation_id("correlation_id", correlation_id);
+    err.log_response();
+    #[cfg(feature = "report")]
+    err.report();
+    err.http_response(Some(correlation_id))
+}
@@ -1,37 +1,296 @@
-use serde_json::Value;
-use std::{borrow::Cow, error::Error, io::ErrorKind};
+use std::{borrow::Cow, error::Error, io::ErrorKind, path::PathBuf, time::Duration};
 
+// the browser apps get the parsing of the responses and the envelope,
+// these need sockets, threads, a filesystem, C libraries or the timers of
+// tokio (retry, the circuit breaker and the deadlines, lro polling and
+// the report queue), which the browser doesn't run
+#[cfg(all(
+    target_arch = "wasm32",
+    any(
+        feature = "ssh",
+        feature = "graph",
+        feature = "zip",
+        feature = "blocking",
+        feature = "multipart",
+        feature = "spawn",
+        feature = "report",
+        feature = "retry",
+        feature = "lro",
+    )
+))]
+compile_error!(
+    "the ssh, graph, zip, blocking, multipart, spawn, report, retry and lro features are not available on wasm32"
+);
+
+#[cfg(feature = "decimal")]
+mod amount;
+mod app;
+#[cfg(feature = "test-util")]
+mod assert;
+#[cfg(feature = "retry")]
+mod batch;
+#[cfg(feature = "blocking")]
+mod blocking;
+#[cfg(feature = "tokio-util")]
+mod cancel;
+mod catalog;
+#[cfg(feature = "channel")]
+mod channel;
+#[cfg(feature = "retry")]
+mod circuit;
+#[cfg(feature = "sqlx")]
+mod constraint;
+mod context;
+mod curl;
+#[cfg(feature = "migrate")]
+mod db;
+#[cfg(any(feature = "retry", feature = "lro"))]
+mod deadline;
+mod details;
+mod domain;
+#[cfg(feature = "doorlock")]
+mod doorlock;
+#[cfg(feature = "stream")]
+mod download;
+#[cfg(feature = "lettre")]
+mod email;
+mod encoder;
+mod envelope;
+mod fingerprint;
+#[cfg(feature = "fiscal")]
+mod fiscal;
+mod graphql;
+#[cfg(feature = "tonic")]
+mod grpc;
+mod html;
+mod http;
+#[cfg(feature = "i18n")]
+mod i18n;
+#[cfg(feature = "backon")]
+mod interop;
+#[cfg(feature = "join")]
+mod join;
+mod kind;
+#[cfg(feature = "tower")]
+mod layer;
+#[cfg(feature = "log")]
+mod logging;
+#[cfg(feature = "lro")]
+mod lro;
+mod mail;
+#[cfg(feature = "metrics")]
+mod metric;
 mod microsoft;
+#[cfg(feature = "middleware")]
+mod middleware;
+#[cfg(feature = "test-util")]
+mod mock;
+mod multi;
+#[cfg(feature = "report")]
+mod ndjson;
+mod network;
+mod oauth;
+#[cfg(feature = "ohip")]
+mod ohip;
+#[cfg(feature = "utoipa")]
+mod openapi;
+#[cfg(feature = "otel")]
+mod otel;
+mod other;
+mod outcome;
+mod panic;
+mod payload;
+mod payment;
+mod pretty;
+mod problem;
+mod redact;
+#[cfg(feature = "warp")]
+mod rejection;
+#[cfg(feature = "report")]
+mod report;
+mod result;
+#[cfg(feature = "retry")]
+mod retry;
+mod rpc;
+#[cfg(feature = "sentry")]
+mod sentry;
+#[cfg(feature = "servicebus")]
+mod servicebus;
+mod sharepoint;
+mod signature;
+#[cfg(feature = "sms")]
+mod sms;
+mod snapshot;
+#[cfg(feature = "spawn")]
+mod spawn;
+#[cfg(feature = "ssh")]
+mod ssh;
+mod stats;
+#[cfg(any(feature = "store-postgres", feature = "store-sqlite"))]
+mod store;
+#[cfg(feature = "test-util")]
+mod strategy;
+mod teams;
+#[cfg(any(feature = "tera", feature = "handlebars"))]
+mod template;
+#[cfg(feature = "retry")]
+mod timeout;
+#[cfg(feature = "tracing")]
+mod trace;
+#[cfg(feature = "multipart")]
+mod upload;
+mod validation;
 pub mod value;
+mod web;
+#[cfg(feature = "report")]
+mod webhook;
+mod wire;
+#[cfg(feature = "xml")]
+mod xml;
 
+#[cfg(feature = "decimal")]
+pub use amount::*;
+pub use app::*;
+#[cfg(feature = "test-util")]
+pub use assert::*;
+#[cfg(feature = "retry")]
+pub use batch::*;
+#[cfg(feature = "blocking")]
+pub use blocking::*;
+#[cfg(feature = "tokio-util")]
+pub use cancel::*;
+pub use catalog::*;
+#[cfg(feature = "channel")]
+pub use channel::*;
+#[cfg(feature = "retry")]
+pub use circuit::*;
+#[cfg(feature = "sqlx")]
+pub use constraint::*;
+pub use context::*;
+pub use curl::*;
+#[cfg(feature = "migrate")]
+pub use db::*;
+#[cfg(any(feature = "retry", feature = "lro"))]
+pub use deadline::*;
+pub use details::*;
+pub use domain::*;
+#[cfg(feature = "doorlock")]
+pub use doorlock::*;
+#[cfg(feature = "stream")]
+pub use download::*;
+#[cfg(feature = "lettre")]
+pub use email::*;
+pub use encoder::*;
+pub use envelope::*;
+pub use fingerprint::*;
+#[cfg(feature = "fiscal")]
+pub use fiscal::*;
+pub use graphql::*;
+pub use html::*;
+pub use http::*;
+#[cfg(feature = "i18n")]
+pub use i18n::*;
+#[cfg(feature = "backon")]
+pub use interop::*;
+#[cfg(feature = "join")]
+pub use join::*;
+pub use kind::*;
+#[cfg(feature = "tower")]
+pub use layer::*;
+#[cfg(feature = "log")]
+pub use logging::*;
+#[cfg(feature = "lro")]
+pub use lro::*;
+pub use mail::*;
+#[cfg(feature = "metrics")]
+pub use metric::*;
 pub use microsoft::*;
+#[cfg(feature = "middleware")]
+pub use middleware::*;
+#[cfg(feature = "test-util")]
+pub use mock::*;
+pub use multi::*;
+#[cfg(feature = "report")]
+pub use ndjson::*;
+pub use network::*;
+pub use oauth::*;
+#[cfg(feature = "ohip")]
+pub use ohip::*;
+pub use other::*;
+pub use outcome::*;
+pub use panic::*;
+pub use payload::*;
+pub use payment::*;
+pub use pretty::*;
+pub use problem::*;
+pub use redact::*;
+#[cfg(feature = "warp")]
+pub use rejection::*;
+#[cfg(feature = "report")]
+pub use report::*;
+pub use result::*;
+#[cfg(feature = "retry")]
+pub use retry::*;
+pub use rpc::*;
+#[cfg(feature = "servicebus")]
+pub use servicebus::*;
+pub use sharepoint::*;
+pub use signature::*;
+#[cfg(feature = "sms")]
+pub use sms::*;
+pub use snapshot::*;
+#[cfg(feature = "spawn")]
+pub use spawn::*;
+#[cfg(feature = "ssh")]
+pub use ssh::*;
+pub use stats::*;
+#[cfg(any(feature = "store-postgres", feature = "store-sqlite"))]
+pub use store::*;
+pub use teams::*;
+#[cfg(any(feature = "tera", feature = "handlebars"))]
+pub use template::*;
+#[cfg(feature = "retry")]
+pub use timeout::*;
+#[cfg(feature = "multipart")]
+pub use upload::*;
+pub use validation::*;
 pub use value::*;
+#[cfg(feature = "report")]
+pub use webhook::*;
+pub use wire::*;
+#[cfg(feature = "xml")]
+pub use xml::*;
+
+#[cfg(feature = "derive")]
+pub use error_pile_derive::{IntoPile, pile_context};
+
 /// Short hand Result
 pub type PileResult<T = ()> = Result<T, ErrPile>;
 
 /// Encapsulates all the possible Error that might be encountered
 #[derive(Debug, thiserror::Error)]
 pub enum ErrPile {
-    #[error("Error connecting/ storing to DB")]
-    DB(
-        #[source]
-        #[from]
-        sqlx::Error,
-    ),
+    /// HTTP requests, URLs, SSH and SFTP, see `TransportError`
+    #[error(transparent)]
+    Transport(#[from] Box<TransportError>),
 
-    #[error("An error occurred with SSH")]
-    Ssh(
-        #[source]
-        #[from]
-        russh::Error,
-    ),
+    /// databases, files and archives, see `StorageError`
+    #[error(transparent)]
+    Storage(#[from] Box<StorageError>),
 
-    #[error("An error occurred with sftp connection")]
-    Sftp(
-        #[source]
-        #[from]
-        russh_sftp::client::error::Error,
-    ),
+    /// PDFs and images, see `DocumentError`
+    #[cfg(any(feature = "pdf", feature = "image"))]
+    #[error(transparent)]
+    Document(#[from] DocumentError),
+
+    /// Graph, the Graph SDK and Document Intelligence, see `MicrosoftError`
+    #[error(transparent)]
+    Microsoft(#[from] MicrosoftError),
+
+    /// rooms, rates and stays of a reservation, see `BookingError`
+    #[cfg(feature = "booking")]
+    #[error(transparent)]
+    Booking(#[from] Box<BookingError>),
 
     #[error("Invalid username or password was provided. Please try again")]
     Auth,
@@ -45,19 +304,59 @@ pub enum ErrPile {
     #[error("The resource is not ready yet, please try again later")]
     NotReady,
 
-    #[error("An error occurred while getting data using Microsoft Graph")]
-    Graph(
-        #[source]
-        #[from]
-        Box<graph_rs_sdk::GraphFailure>,
-    ),
+    /// see `NotFoundError`
+    #[error(transparent)]
+    NotFound(Box<NotFoundError>),
 
-    #[error("Graph Error Message")]
-    GraphErrMSg(
-        #[source]
-        #[from]
-        Box<graph_rs_sdk::error::ErrorMessage>,
-    ),
+    /// Optimistic concurrency failure, see `ConflictError`
+    #[error(transparent)]
+    Conflict(Box<ConflictError>),
+
+    /// Operation level timeout, see `TimeoutError`
+    #[error(transparent)]
+    Timeout(Box<TimeoutError>),
+
+    /// Operation was aborted on purpose (graceful shutdown),
+    /// not a real failure
+    #[error(
+        "The operation was cancelled{}",
+        Opt(": ", reason.as_ref(), "")
+    )]
+    Cancelled { reason: Option<Cow<'static, str>> },
+
+    /// The other side (e.g. an older agent) does not support
+    /// the requested capability
+    #[error("`{feature}` is not supported")]
+    Unsupported { feature: Cow<'static, str> },
+
+    /// Invalid or missing configuration value, see `ConfigError`
+    #[error(transparent)]
+    Config(Box<ConfigError>),
+
+    /// Upstream is throttling the requests, see `RateLimitError`
+    #[error(transparent)]
+    RateLimited(Box<RateLimitError>),
+
+    /// The payment processor declined the card, `retryable` tells the
+    /// retry machinery whether charging again without the guest can
+    /// succeed, see `ErrPile::payment_declined`
+    #[error("The payment was declined: {category} (code {processor_code})")]
+    PaymentDeclined {
+        category: DeclineCategory,
+        processor_code: Box<str>,
+        retryable: bool,
+    },
+
+    /// The key card encoder could not write the room key, see
+    /// `KeyEncoderError`
+    #[error(transparent)]
+    KeyEncoder(Box<KeyEncoderError>),
+
+    /// An incoming webhook whose signature could not be verified, see
+    /// `verify_signature`. Never transient, the same request is refused
+    /// again
+    #[error("The webhook could not be verified: {0}")]
+    WebhookVerification(Box<WebhookVerificationFailure>),
 
     #[error("Error parsing Json Data (Serde)")]
     Json(
@@ -66,22 +365,10 @@ pub enum ErrPile {
         serde_json::Error,
     ),
 
-    #[error("Request responded with an error")]
-    MS(MSResponseError),
-
-    #[error("An error occurred while parsing the PDF text (PDF_Extract)")]
-    ExtractPdf(
-        #[source]
-        #[from]
-        pdfium_render::prelude::PdfiumError,
-    ),
-
-    #[error("Error opening zip archive")]
-    Zip(
-        #[source]
-        #[from]
-        zip::result::ZipError,
-    ),
+    /// Response was successful but the body did not match
+    /// the expected shape, see `DeserializeError`
+    #[error(transparent)]
+    Deserialize(Box<DeserializeError>),
 
     #[error("Error decoding from base64 content bytes")]
     Decode(
@@ -90,6 +377,7 @@ pub enum ErrPile {
         base64::DecodeError,
     ),
 
+    #[cfg(not(target_arch = "wasm32"))]
     #[error("A thread panicked while executing a task")]
     Thread(
         #[source]
@@ -97,12 +385,14 @@ pub enum ErrPile {
         tokio::task::JoinError,
     ),
 
-    #[error("An error occurred while performing an operation on a Image")]
-    Image(
-        #[source]
-        #[from]
-        image::ImageError,
-    ),
+    /// A panic caught by `pile_catch`, see `PanicError`
+    #[error(transparent)]
+    Panic(Box<PanicError>),
+
+    /// A task started with `spawn_pile` panicked or was aborted, see
+    /// `TaskError`
+    #[error(transparent)]
+    Task(Box<TaskError>),
 
     /// Unable to floor the time
     /// to a given window
@@ -118,58 +408,239 @@ pub enum ErrPile {
     )]
     FrameTooLarge,
 
-    #[error("IO Err: {0}")]
-    IO(
+    #[cfg(feature = "python")]
+    #[error("An error occurred on Python Side: {0}")]
+    Python(
+        #[from]
+        #[source]
+        Box<pyo3::PyErr>,
+    ),
+
+    #[cfg(feature = "notify")]
+    #[error("An error occurred while watching the folder for changes")]
+    Watch(
         #[source]
         #[from]
-        std::io::Error,
+        Box<notify::Error>,
     ),
 
-    #[cfg(feature = "python")]
-    #[error("An error occurred on Python Side: {0}")]
-    Python(
+    #[error("Invalid version string, unable to compare agent versions")]
+    Semver(
+        #[source]
+        #[from]
+        semver::Error,
+    ),
+
+    /// An invoice or email template that doesn't parse or render, see
+    /// `TemplateError`
+    #[cfg(any(feature = "tera", feature = "handlebars"))]
+    #[error(transparent)]
+    Template(Box<TemplateError>),
+
+    #[cfg(feature = "decimal")]
+    #[error("Invalid decimal value: {0}")]
+    Decimal(
+        #[source]
         #[from]
+        Box<rust_decimal::Error>,
+    ),
+
+    /// An amount of a rate import or a folio that doesn't parse, with the
+    /// raw input and the expected currency, see `parse_amount`
+    #[cfg(feature = "decimal")]
+    #[error(transparent)]
+    Amount(#[from] Box<AmountError>),
+
+    /// see `RegexError`
+    #[error(transparent)]
+    Regex(Box<RegexError>),
+
+    /// see `DownloadError`
+    #[cfg(feature = "stream")]
+    #[error(transparent)]
+    Download(Box<DownloadError>),
+
+    /// Multipart upload failed, see `UploadError`
+    #[cfg(feature = "multipart")]
+    #[error(transparent)]
+    Upload(Box<UploadError>),
+
+    /// A page of a paginated Graph listing failed, the pages
+    /// before it were fetched fine
+    #[error("Failed to fetch page {index} of the listing")]
+    Page {
+        /// zero based index of the failed page
+        index: usize,
+        #[source]
+        source: Box<ErrPile>,
+    },
+
+    /// Azure Service Bus (AMQP) error, see `ServiceBusError`
+    #[cfg(feature = "servicebus")]
+    #[error(transparent)]
+    ServiceBus(Box<ServiceBusError>),
+
+    /// The alert email could not be built or sent
+    #[cfg(feature = "lettre")]
+    #[error("Failed to send the alert email")]
+    Mail(#[source] Box<dyn Error + Send + Sync>),
+
+    /// The OAuth token endpoint refused to issue a token
+    #[error("Failed to acquire a token: {0}")]
+    TokenAcquisition(#[source] Box<TokenError>),
+
+    #[error("Request responded with an error ({})", .0.status)]
+    Http(
         #[source]
-        pyo3::PyErr,
+        #[from]
+        Box<HttpError>,
     ),
 
-    #[error("An error occurred while parsing the URL")]
-    Url(
+    #[error("Request responded with a problem: {0}")]
+    Problem(
         #[source]
         #[from]
-        url::ParseError,
+        Box<ProblemDetails>,
     ),
 
-    #[error("An error occurred while sending request")]
-    Req(
+    #[cfg(feature = "xml")]
+    #[error("Request responded with a XML fault: {0}")]
+    Xml(
         #[source]
         #[from]
-        reqwest::Error,
+        Box<XmlFault>,
     ),
 
-    #[error("An error occurred while converting Http Header to string")]
-    ReqToStr(
+    /// Booking.com or Expedia refused the request, see `ChannelError`
+    #[cfg(feature = "channel")]
+    #[error(transparent)]
+    Channel(#[from] Box<ChannelError>),
+
+    /// OPERA Cloud refused the request, see `OhipError`
+    #[cfg(feature = "ohip")]
+    #[error(transparent)]
+    Ohip(#[from] Box<OhipError>),
+
+    /// The e-invoicing gateway of the tax authority refused the invoice,
+    /// see `FiscalError`
+    #[cfg(feature = "fiscal")]
+    #[error(transparent)]
+    Fiscal(#[from] Box<FiscalError>),
+
+    /// The SMS gateway refused the message, see `SmsError`
+    #[cfg(feature = "sms")]
+    #[error(transparent)]
+    Sms(#[from] Box<SmsError>),
+
+    /// The Visionline or Salto door lock system refused the request, see
+    /// `DoorLockError`
+    #[cfg(feature = "doorlock")]
+    #[error(transparent)]
+    DoorLock(#[from] Box<DoorLockError>),
+
+    #[error("GraphQL request returned errors: {0}")]
+    GraphQL(
         #[source]
         #[from]
-        reqwest::header::ToStrError,
+        GraphQLErrors,
     ),
 
-    #[error("Document Intelligence Services returned with an error")]
-    AZ(
+    #[error("Validation failed: {0}")]
+    Validation(
         #[source]
         #[from]
-        Box<AZError>,
+        FieldErrors,
     ),
 
+    /// error decoded from a response captured with `set_capture_snapshots`
+    #[error(transparent)]
+    Captured(Box<CapturedError>),
+
+    /// error with the functions it went through, see `#[pile_context]`
+    #[error(transparent)]
+    Context(Box<ContextError>),
+
     #[error("{0}")]
     FromValue(
         #[source]
         #[from]
-        SerdeValue,
+        Box<SerdeValue>,
     ),
 
+    /// the failures of a batch, every one of them, see `MultiPile`
+    #[error(transparent)]
+    Multi(Box<MultiPile>),
+
+    /// error received from another service, see `RemoteError`
     #[error("{0}")]
-    Custom(String),
+    Remote(Box<RemoteError>),
+
+    /// error of a service's own enum, see `AppError`
+    #[error(transparent)]
+    App(Box<AppError>),
+
+    /// error of a dependency without a variant, see `IntoPileExt`
+    #[error(transparent)]
+    Other(Box<OtherError>),
+
+    /// a message without a variant, borrowed when it is fixed, see
+    /// `custom_static`. Card data is masked when displayed
+    #[error("{}", mask_card_data(.0))]
+    Custom(Cow<'static, str>),
+}
+
+// Every `PileResult` carries the largest variant, the payloads of more
+// than a `Cow` are boxed (see `details`) so the tag and a `Cow` set the
+// size. Fails the build when a new variant makes it grow
+const _: () = assert!(std::mem::size_of::<ErrPile>() <= 4 * std::mem::size_of::<usize>());
+
+// `PileResult` crosses `tokio::spawn` and is shared between tasks, a
+// variant holding something `!Send` or `!Sync` fails the build here
+// rather than in the services
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync + 'static>() {}
+    assert_send_sync::<ErrPile>();
+};
+
+/// `" (request-id: …)"` suffix for Graph errors, empty when Graph sent none
+fn graph_diagnostics(err: &MSResponseErrorInner) -> String {
+    let diagnostics = err.diagnostics();
+    if diagnostics.is_empty() {
+        String::new()
+    } else {
+        format!(" ({diagnostics})")
+    }
+}
+
+/// `prefix`, the value and `suffix` when there is a value, nothing
+/// otherwise. The optional parts of the messages, written straight to the
+/// formatter instead of going through a `format!` on every `Display`
+struct Opt<T>(&'static str, Option<T>, &'static str);
+
+impl<T: std::fmt::Display> std::fmt::Display for Opt<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.1 {
+            Some(value) => write!(f, "{}{value}{}", self.0, self.2),
+            None => Ok(()),
+        }
+    }
+}
+
+/// status answered for an error that only carries a kind (`Remote`, `App`)
+fn kind_status(kind: PileKind) -> u16 {
+    match kind {
+        PileKind::Auth => 401,
+        PileKind::Permission => 403,
+        PileKind::NotFound => 404,
+        PileKind::InUse | PileKind::Conflict => 409,
+        PileKind::Validation => 422,
+        PileKind::PaymentDeclined => 402,
+        PileKind::RateLimited => 429,
+        PileKind::Timeout => 504,
+        PileKind::Unsupported => 501,
+        PileKind::NotReady | PileKind::Cancelled => 503,
+        _ => 500,
+    }
 }
 
 impl ErrPile {
@@ -178,28 +649,344 @@ impl ErrPile {
         I: Into<Cow<'a, str>>,
     {
         let s = msg.into().into_owned();
-        Self::Custom(s)
+        Self::Custom(Cow::Owned(s))
+    }
+
+    /// `custom` for a fixed message, nothing is allocated. Being `const`
+    /// the error can be kept in a `const` next to the code raising it
+    ///
+    /// ```ignore
+    /// const ROOM_TYPE_NOT_MAPPED: ErrPile = ErrPile::custom_static("room type not mapped");
+    /// ```
+    pub const fn custom_static(msg: &'static str) -> Self {
+        Self::Custom(Cow::Borrowed(msg))
+    }
+
+    /// invalid regex pattern, keeps the pattern text so the
+    /// offending rule can be identified
+    pub fn regex<P>(pattern: P, source: regex::Error) -> Self
+    where
+        P: Into<String>,
+    {
+        Self::Regex(Box::new(RegexError {
+            pattern: Some(pattern.into()),
+            source,
+        }))
+    }
+
+    /// validation failed for the given fields
+    pub fn validation<I>(errors: I) -> Self
+    where
+        I: IntoIterator<Item = FieldError>,
+    {
+        Self::Validation(FieldErrors(errors.into_iter().collect()))
+    }
+
+    /// per field errors, if this is a validation error
+    pub fn field_errors(&self) -> Option<&FieldErrors> {
+        match self.peeled() {
+            Self::Validation(errors) => Some(errors),
+            _ => None,
+        }
+    }
+
+    /// the requested resource does not exist
+    /// e.g. `ErrPile::not_found("reservation", id)`
+    pub fn not_found<I>(resource: &'static str, id: I) -> Self
+    where
+        I: ToString,
+    {
+        Self::NotFound(Box::new(NotFoundError {
+            resource,
+            id: id.to_string(),
+        }))
+    }
+
+    /// the resource was changed concurrently
+    pub fn conflict<R>(resource: R) -> Self
+    where
+        R: Into<String>,
+    {
+        Self::Conflict(Box::new(ConflictError {
+            resource: resource.into(),
+            current_version: None,
+        }))
+    }
+
+    /// the resource was changed concurrently and is now at
+    /// the given version / etag
+    pub fn conflict_with_version<R, V>(resource: R, version: V) -> Self
+    where
+        R: Into<String>,
+        V: Into<String>,
+    {
+        Self::Conflict(Box::new(ConflictError {
+            resource: resource.into(),
+            current_version: Some(version.into()),
+        }))
+    }
+
+    /// the operation did not complete in the given time
+    pub fn timeout<O>(operation: O, after: Duration) -> Self
+    where
+        O: Into<Cow<'static, str>>,
+    {
+        Self::Timeout(Box::new(TimeoutError {
+            operation: operation.into(),
+            after,
+        }))
+    }
+
+    /// the operation was cancelled
+    pub const fn cancelled() -> Self {
+        Self::Cancelled { reason: None }
+    }
+
+    /// the operation was cancelled for the given reason
+    pub fn cancelled_because<R>(reason: R) -> Self
+    where
+        R: Into<Cow<'static, str>>,
+    {
+        Self::Cancelled {
+            reason: Some(reason.into()),
+        }
+    }
+
+    /// the requested capability is not supported
+    pub fn unsupported<F>(feature: F) -> Self
+    where
+        F: Into<Cow<'static, str>>,
+    {
+        Self::Unsupported {
+            feature: feature.into(),
+        }
+    }
+
+    /// the configuration value for the key is invalid
+    pub fn config<K, R>(key: K, reason: R) -> Self
+    where
+        K: Into<String>,
+        R: Into<String>,
+    {
+        Self::Config(Box::new(ConfigError {
+            key: key.into(),
+            source_file: None,
+            reason: reason.into(),
+        }))
+    }
+
+    /// the configuration value for the key, read from the given
+    /// file, is invalid
+    pub fn config_in_file<K, P, R>(key: K, file: P, reason: R) -> Self
+    where
+        K: Into<String>,
+        P: Into<PathBuf>,
+        R: Into<String>,
+    {
+        Self::Config(Box::new(ConfigError {
+            key: key.into(),
+            source_file: Some(file.into()),
+            reason: reason.into(),
+        }))
+    }
+
+    /// the configuration value for the key is missing
+    pub fn config_missing<K>(key: K) -> Self
+    where
+        K: Into<String>,
+    {
+        Self::config(key, "value is missing")
+    }
+
+    /// upstream throttled the request
+    pub fn rate_limited(retry_after: Option<Duration>, scope: Option<String>) -> Self {
+        Self::RateLimited(Box::new(RateLimitError { retry_after, scope }))
     }
 
     /// the error is related to invalid credentials
     pub fn is_encrypted(&self) -> bool {
-        matches!(self, Self::Auth)
+        matches!(self.peeled(), Self::Auth) || self.remote_kind() == Some(PileKind::Auth)
     }
 
-    /// get the string version of the source error
-    pub fn source_str(&self) -> String {
+    /// Message of the source error, the error's own without one. Borrowed
+    /// for the fixed messages and `Custom`, what the request logging calls
+    /// on every failure
+    pub fn source_str(&self) -> Cow<'_, str> {
         if let Self::FromValue(val) = &self {
-            return val.extract_error_from_json();
+            return val.extract_error_from_json().into();
         }
 
-        self.source()
-            .map(|e| e.to_string())
-            .unwrap_or_else(|| self.to_string())
+        match self.source() {
+            Some(source) => source.to_string().into(),
+            None => self.message(),
+        }
+    }
+
+    /// the message of the error, without a `String` for the variants
+    /// that display a fixed text
+    fn message(&self) -> Cow<'_, str> {
+        match self {
+            Self::Auth => "Invalid username or password was provided. Please try again".into(),
+            Self::Permission => "User does not have permission to perform this action.".into(),
+            Self::InUse => {
+                "This action can't be performed as it is being currently used elsewhere".into()
+            }
+            Self::NotReady => "The resource is not ready yet, please try again later".into(),
+            Self::Floor => "An error occurred while adjusting(f) the time".into(),
+            Self::Ceil => "An error occurred while adjusting(c) the time".into(),
+            Self::FrameTooLarge => "Provided timeframe is too large to process. Try reducing the timeframe to fewer days/ weeks".into(),
+            Self::Custom(msg) => mask_card_data(msg),
+            Self::Remote(remote) => mask_card_data(&remote.message),
+            Self::Captured(captured) => captured.error.message(),
+            Self::Context(context) => context.error.message(),
+            err => err.to_string().into(),
+        }
     }
 
     /// checks if this error is not ready error
     pub fn is_not_ready(&self) -> bool {
-        matches!(self, Self::NotReady)
+        matches!(self.peeled(), Self::NotReady) || self.remote_kind() == Some(PileKind::NotReady)
+    }
+
+    /// checks if this error is not found error
+    pub fn is_not_found(&self) -> bool {
+        matches!(self.peeled(), Self::NotFound { .. })
+            || self.remote_kind() == Some(PileKind::NotFound)
+    }
+
+    /// HTTP status code that best represents this error
+    /// when it is returned to a client
+    pub fn status_code(&self) -> u16 {
+        match self.peeled() {
+            Self::Auth | Self::WebhookVerification(_) => 401,
+            Self::Permission => 403,
+            Self::NotFound { .. } => 404,
+            Self::InUse | Self::Conflict { .. } => 409,
+            Self::Validation(_) => 422,
+            Self::FrameTooLarge => 400,
+            Self::Remote(remote) => kind_status(remote.kind),
+            Self::Multi(multi) => multi.status_code(),
+            #[cfg(feature = "booking")]
+            Self::Booking(booking) => kind_status(booking.kind()),
+            #[cfg(feature = "channel")]
+            Self::Channel(channel) => kind_status(channel.kind()),
+            #[cfg(feature = "ohip")]
+            Self::Ohip(ohip) => kind_status(ohip.kind()),
+            #[cfg(feature = "fiscal")]
+            Self::Fiscal(fiscal) => kind_status(fiscal.failure.kind()),
+            #[cfg(feature = "sms")]
+            Self::Sms(sms) => kind_status(sms.failure.kind()),
+            #[cfg(feature = "doorlock")]
+            Self::DoorLock(lock) => kind_status(lock.failure.kind()),
+            #[cfg(feature = "decimal")]
+            Self::Amount(_) => 422,
+            Self::App(app) => app.status.unwrap_or_else(|| kind_status(app.kind)),
+            Self::RateLimited { .. } => 429,
+            Self::PaymentDeclined { .. } => 402,
+            Self::KeyEncoder(err) => kind_status(err.reason.kind()),
+            Self::Timeout { .. } => 504,
+            Self::Unsupported { .. } => 501,
+            Self::NotReady | Self::Cancelled { .. } => 503,
+            #[cfg(feature = "sqlx")]
+            Self::Storage(storage) => match &**storage {
+                StorageError::DB(db) => {
+                    constraint::violated_constraint_kind(db).map_or(500, kind_status)
+                }
+                _ => 500,
+            },
+            _ => 500,
+        }
+    }
+
+    /// checks if this error is a concurrency conflict
+    pub fn is_conflict(&self) -> bool {
+        matches!(self.peeled(), Self::Conflict { .. })
+            || self.remote_kind() == Some(PileKind::Conflict)
+    }
+
+    /// checks if the operation timed out
+    pub fn is_timeout(&self) -> bool {
+        matches!(self.peeled(), Self::Timeout { .. })
+            || self.remote_kind() == Some(PileKind::Timeout)
+    }
+
+    /// checks if the operation was cancelled, these are usually
+    /// not worth reporting
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.peeled(), Self::Cancelled { .. })
+            || self.remote_kind() == Some(PileKind::Cancelled)
+    }
+
+    /// checks if the requested capability is not supported
+    pub fn is_unsupported(&self) -> bool {
+        matches!(self.peeled(), Self::Unsupported { .. })
+            || self.remote_kind() == Some(PileKind::Unsupported)
+    }
+
+    /// checks if this error is a configuration error
+    pub fn is_config(&self) -> bool {
+        matches!(self.peeled(), Self::Config { .. }) || self.remote_kind() == Some(PileKind::Config)
+    }
+
+    /// checks if the upstream throttled the request
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(self.peeled(), Self::RateLimited { .. })
+            || self.remote_kind() == Some(PileKind::RateLimited)
+    }
+
+    /// how long to wait before trying again, if the error
+    /// carries such hint: `Retry-After` and the Graph throttling
+    /// fields, or the headers of the captured response
+    pub fn retry_after(&self) -> Option<Duration> {
+        let hint = match self.peeled() {
+            Self::RateLimited(err) => err.retry_after,
+            Self::Http(http) => http.retry_after,
+            Self::Microsoft(MicrosoftError::MS(err)) => {
+                inner_error_retry_after(&err.error.inner_error)
+            }
+            Self::Remote(remote) => remote.retry_after(),
+            Self::Multi(multi) => multi.retry_after(),
+            #[cfg(feature = "channel")]
+            Self::Channel(channel) => channel.retry_after,
+            #[cfg(feature = "ohip")]
+            Self::Ohip(ohip) => match ohip.retry_guidance() {
+                OhipRetry::Retry(after) => after,
+                _ => None,
+            },
+            #[cfg(feature = "fiscal")]
+            Self::Fiscal(fiscal) => fiscal.retry_after,
+            #[cfg(feature = "sms")]
+            Self::Sms(sms) => sms.retry_after,
+            #[cfg(feature = "doorlock")]
+            Self::DoorLock(lock) => lock.retry_after,
+            Self::Page { source, .. } => source.retry_after(),
+            #[cfg(feature = "multipart")]
+            Self::Upload(upload) => upload.source.retry_after(),
+            _ => None,
+        };
+        hint.or_else(|| self.snapshot()?.retry_after())
+    }
+
+    /// `retry_after` in the whole seconds of a `Retry-After` header,
+    /// rounded up so the client doesn't come back too early, at least 1
+    pub fn retry_after_secs(&self) -> Option<u64> {
+        self.retry_after().map(|wait| {
+            let secs = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+            secs.max(1)
+        })
+    }
+
+    /// the request failed because of redirects, either too many of
+    /// them (a loop) or the redirect policy refused to follow
+    pub fn is_redirect_error(&self) -> bool {
+        match self.peeled() {
+            Self::Transport(transport) => match &**transport {
+                TransportError::Req { source, .. } => source.is_redirect(),
+                _ => false,
+            },
+            Self::Http(http) => http.status.is_redirection(),
+            _ => false,
+        }
     }
 
     /// checks if this error is transcient error
@@ -207,33 +994,160 @@ impl ErrPile {
     /// again. This will be useful when using retry functions with backoff
     /// feature.
     pub fn is_transient(&self) -> bool {
-        if let Self::Req(req) = &self {
-            if let Some(status) = req.status() {
-                return matches!(status.as_u16(), 408 | 429 | 500 | 502 | 503 | 504);
-            }
+        if let Self::Captured(captured) = self {
+            return captured.error.is_transient();
+        }
+
+        if let Self::Context(context) = self {
+            return context.error.is_transient();
+        }
+
+        if let Self::Remote(remote) = &self {
+            return remote.transient;
+        }
+
+        if let Self::Multi(multi) = &self {
+            return multi.is_transient();
+        }
+
+        if let Self::App(app) = &self {
+            return app.transient;
+        }
+
+        if let Self::PaymentDeclined { retryable, .. } = &self {
+            return *retryable;
+        }
+
+        if let Self::KeyEncoder(err) = &self {
+            return err.reason.is_retryable();
+        }
+
+        if let Self::WebhookVerification(_) = &self {
+            return false;
+        }
+
+        #[cfg(feature = "channel")]
+        if let Self::Channel(channel) = &self {
+            return channel.is_transient();
+        }
+
+        #[cfg(feature = "ohip")]
+        if let Self::Ohip(ohip) = &self {
+            return matches!(ohip.retry_guidance(), OhipRetry::Retry(_));
+        }
+
+        #[cfg(feature = "fiscal")]
+        if let Self::Fiscal(fiscal) = &self {
+            return fiscal.is_transient();
+        }
+
+        #[cfg(feature = "sms")]
+        if let Self::Sms(sms) = &self {
+            return sms.is_transient();
+        }
+
+        #[cfg(feature = "doorlock")]
+        if let Self::DoorLock(lock) = &self {
+            return lock.failure.is_transient();
+        }
+
+        if let Self::Transport(transport) = &self
+            && let TransportError::Req { source: req, .. } = &**transport
+            && let Some(status) = req.status()
+        {
+            return matches!(status.as_u16(), 408 | 429 | 500 | 502 | 503 | 504);
+        }
+
+        if let Self::Http(http) = &self {
+            return http.is_transient();
+        }
+
+        if let Self::Problem(problem) = &self {
+            return matches!(problem.status, Some(408 | 429 | 500 | 502 | 503 | 504));
+        }
+
+        #[cfg(feature = "stream")]
+        if let Self::Download(download) = &self {
+            return download.failure.is_transient();
         }
 
-        if let Self::IO(io) = &self {
-            return Self::is_io_transient(io.kind());
+        #[cfg(feature = "multipart")]
+        if let Self::Upload(upload) = &self {
+            return upload.source.is_transient();
         }
 
-        if let Self::DB(db) = self {
-            return match &db {
-                sqlx::Error::Io(err) if Self::is_io_transient(err.kind()) => true,
-                sqlx::Error::Database(_) => true, // Database errors can be transient
-                sqlx::Error::PoolTimedOut => true,
-                sqlx::Error::PoolClosed => true,
+        if let Self::Page { source, .. } = &self {
+            return source.is_transient();
+        }
+
+        if let Self::Storage(storage) = &self {
+            return match &**storage {
+                StorageError::IO(io) => Self::is_io_transient(io.kind()),
+                #[cfg(feature = "sqlx")]
+                StorageError::DB(db) => Self::is_db_transient(db),
+                #[cfg(feature = "migrate")]
+                StorageError::Migrate(_) => matches!(
+                    self.migration_failure(),
+                    Some(MigrationFailure::Connectivity)
+                ),
+                #[allow(unreachable_patterns)]
+                _ => false,
+            };
+        }
+
+        #[cfg(feature = "notify")]
+        if let Self::Watch(watch) = self {
+            return match &watch.kind {
+                notify::ErrorKind::Io(err) => Self::is_io_transient(err.kind()),
+                // the backends report a full event queue as a generic error,
+                // events were dropped so re-scanning the folder fixes it
+                notify::ErrorKind::Generic(msg) => msg.to_lowercase().contains("overflow"),
                 _ => false,
             };
         }
 
-        if let Self::NotReady = self {
-            return true; // Not ready errors are transient
+        #[cfg(feature = "servicebus")]
+        if let Self::ServiceBus(err) = self {
+            return is_service_bus_transient(&err.source);
+        }
+
+        if let Self::TokenAcquisition(err) = self {
+            return err.kind() == TokenErrorKind::TemporarilyUnavailable;
+        }
+
+        if let Self::Microsoft(MicrosoftError::AZ(az)) = self
+            && az.is_transient()
+        {
+            return true;
+        }
+
+        if self.retry_with_consistency() == Some(ConsistencyHint::ReplicationLag) {
+            return true;
+        }
+
+        if self.graph_code().is_some_and(|c| c.is_throttling()) {
+            return true;
+        }
+
+        if let Self::NotReady | Self::RateLimited { .. } | Self::Timeout { .. } = self {
+            return true; // Not ready, throttled and timed out errors are transient
         }
 
         false
     }
 
+    #[cfg(feature = "sqlx")]
+    fn is_db_transient(db: &sqlx::Error) -> bool {
+        match db {
+            sqlx::Error::Io(err) if Self::is_io_transient(err.kind()) => true,
+            // a broken constraint fails again, the others can be transient
+            sqlx::Error::Database(err) => !constraint::is_constraint_violation(err.as_ref()),
+            sqlx::Error::PoolTimedOut => true,
+            sqlx::Error::PoolClosed => true,
+            _ => false,
+        }
+    }
+
     fn is_io_transient(kind: std::io::ErrorKind) -> bool {
         matches!(
             kind,
@@ -252,98 +1166,69 @@ impl ErrPile {
                 | ErrorKind::ResourceBusy
         )
     }
+}
 
-    /// Handle all types of HTTP errors comprehensively
-    async fn handle_error_response(response: reqwest::Response) -> ErrPile {
-        let status = response.status();
-        let status_code = status.as_u16();
-
-        // Categorize the error type
-        let error_category = match status_code {
-            // 1xx - Informational (shouldn't be errors, but handle just in case)
-            100..=199 => "Informational",
-            // 3xx - Redirection errors
-            300..=399 => "Redirection",
-            // 4xx - Client errors
-            400 => "Bad Request",
-            401 => "Unauthorized",
-            403 => "Forbidden",
-            404 => "Not Found",
-            408 => "Request Timeout",
-            409 => "Conflict",
-            410 => "Gone",
-            413 => "Payload Too Large",
-            414 => "URI Too Long",
-            415 => "Unsupported Media Type",
-            422 => "Unprocessable Entity",
-            429 => "Too Many Requests",
-            430..=499 => "Client Error",
-            // 5xx - Server errors
-            500 => "Internal Server Error",
-            501 => "Not Implemented",
-            502 => "Bad Gateway",
-            503 => "Service Unavailable",
-            504 => "Gateway Timeout",
-            507 => "Insufficient Storage",
-            508..=599 => "Server Error",
-            _ => "Unknown Error",
-        };
+/// card data in the value is masked, see `mask_card_data`
+impl From<serde_json::Value> for ErrPile {
+    fn from(mut value: serde_json::Value) -> Self {
+        redact::mask_card_json(&mut value);
+        ErrPile::FromValue(Box::new(SerdeValue(value)))
+    }
+}
 
-        // Try to get response body
-        match response.json::<Value>().await {
-            Ok(body) => {
-                // First try to parse as structured AZError
-                if let Ok(az_error) = serde_json::from_value::<AZError>(body.clone()) {
-                    return ErrPile::AZ(Box::new(az_error));
-                }
+// the large errors are boxed to keep `ErrPile` small, these keep `?`
+// working on the unboxed ones
+impl From<SerdeValue> for ErrPile {
+    fn from(value: SerdeValue) -> Self {
+        value.0.into()
+    }
+}
 
-                SerdeValue(body).into()
-            }
-            Err(e) => ErrPile::Custom(format!(
-                "{error_category} ({status_code}): Failed to read error response: {e}"
-            )),
-        }
+#[cfg(feature = "notify")]
+impl From<notify::Error> for ErrPile {
+    fn from(value: notify::Error) -> Self {
+        ErrPile::Watch(Box::new(value))
     }
 }
 
-impl From<serde_json::Value> for ErrPile {
-    fn from(value: serde_json::Value) -> Self {
-        ErrPile::FromValue(SerdeValue(value))
+#[cfg(feature = "python")]
+impl From<pyo3::PyErr> for ErrPile {
+    fn from(value: pyo3::PyErr) -> Self {
+        ErrPile::Python(Box::new(value))
     }
 }
 
-impl<T> From<ErrPile> for PileResult<T> {
-    fn from(value: ErrPile) -> Self {
-        Err(value)
+#[cfg(feature = "decimal")]
+impl From<rust_decimal::Error> for ErrPile {
+    fn from(value: rust_decimal::Error) -> Self {
+        ErrPile::Decimal(Box::new(value))
     }
 }
 
-impl From<&str> for ErrPile {
-    fn from(value: &str) -> Self {
-        ErrPile::custom(value)
+impl From<regex::Error> for ErrPile {
+    fn from(source: regex::Error) -> Self {
+        ErrPile::Regex(Box::new(RegexError {
+            pattern: None,
+            source,
+        }))
     }
 }
 
-pub trait ReqwestPileResExt {
-    /// converts the reponse into appropriate ErrPile
-    /// this will also take care of Azure Document Intelligence errors
-    /// based on the response
-    #[allow(async_fn_in_trait)]
-    async fn to_pile_result<T>(self) -> PileResult<T>
-    where
-        T: for<'de> serde::Deserialize<'de>;
+#[cfg(feature = "validator")]
+impl From<validator::ValidationErrors> for ErrPile {
+    fn from(value: validator::ValidationErrors) -> Self {
+        ErrPile::Validation(value.into())
+    }
 }
 
-impl ReqwestPileResExt for reqwest::Response {
-    async fn to_pile_result<T>(self) -> PileResult<T>
-    where
-        T: for<'de> serde::Deserialize<'de>,
-    {
-        let status = self.status();
-        if status.is_success() {
-            return Ok(self.json::<T>().await?);
-        }
+impl<T> From<ErrPile> for PileResult<T> {
+    fn from(value: ErrPile) -> Self {
+        Err(value)
+    }
+}
 
-        Err(ErrPile::handle_error_response(self).await)
+impl From<&str> for ErrPile {
+    fn from(value: &str) -> Self {
+        ErrPile::custom(value)
     }
 }
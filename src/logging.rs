@@ -0,0 +1,42 @@
+use crate::ErrPile;
+
+#[doc(hidden)]
+pub use log as __log;
+
+impl ErrPile {
+    /// Logs the error and its sources (`error: source: source`) at the
+    /// given level and hands it back, for one-liners like
+    /// `return err.log(Level::Warn).into()`
+    pub fn log(self, level: log::Level) -> Self {
+        log::log!(level, "{}", self.chain_text());
+        self
+    }
+
+    /// the text logged by `log` and `log_err!`
+    #[doc(hidden)]
+    pub fn chain_text(&self) -> String {
+        #[cfg(feature = "metrics")]
+        self.count_reported();
+
+        self.redacted_chain().join(": ")
+    }
+}
+
+/// Logs an `ErrPile` with its sources and evaluates to it, like
+/// `ErrPile::log` but the record carries the caller's module as target.
+/// The level defaults to `Error`
+///
+/// ```ignore
+/// return log_err!(Level::Warn, ErrPile::NotReady).into();
+/// ```
+#[macro_export]
+macro_rules! log_err {
+    ($level:expr, $err:expr $(,)?) => {{
+        let err: $crate::ErrPile = $err;
+        $crate::__log::log!($level, "{}", err.chain_text());
+        err
+    }};
+    ($err:expr $(,)?) => {
+        $crate::log_err!($crate::__log::Level::Error, $err)
+    };
+}
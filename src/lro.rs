@@ -0,0 +1,165 @@
+use std::time::Duration;
+
+use reqwest::{
+    Client, Response,
+    header::{HeaderMap, LOCATION},
+};
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::time::Instant;
+use url::Url;
+
+use crate::{
+    AZError, AZErrorDetails, Deadline, ErrPile, MSResponseError, MSResponseErrorInner,
+    MicrosoftError, PileResult, RequestBuilderPileExt, parse_json_body, parse_retry_after,
+};
+
+/// Polls a long-running operation (Document Intelligence analyze,
+/// Graph export jobs, …) until it reaches a terminal state.
+///
+/// The status url is taken from the `operation-location` or `Location`
+/// header of the accepted response. Failed operations are decoded with
+/// the AZ/Graph error types, running past the deadline is `ErrPile::Timeout`
+#[derive(Debug, Clone)]
+pub struct LroPoller {
+    interval: Duration,
+    deadline: Duration,
+    headers: HeaderMap,
+}
+
+impl Default for LroPoller {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            deadline: Duration::from_secs(120),
+            headers: HeaderMap::new(),
+        }
+    }
+}
+
+/// where the operation is at, from its `status` field
+enum LroState {
+    Running,
+    Succeeded,
+    Failed,
+    Canceled,
+}
+
+impl LroState {
+    fn of(body: &Value) -> Self {
+        let status = body
+            .get("status")
+            .and_then(Value::as_str)
+            .unwrap_or("succeeded")
+            .to_ascii_lowercase();
+
+        match status.as_str() {
+            "notstarted" | "running" | "inprogress" | "accepted" | "pending" => Self::Running,
+            "failed" => Self::Failed,
+            "canceled" | "cancelled" => Self::Canceled,
+            _ => Self::Succeeded,
+        }
+    }
+}
+
+impl LroPoller {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// wait between two polls, a `Retry-After` sent by the service wins
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// how long the operation may take in total
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// headers sent with every poll, usually the authorization
+    pub fn headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Follows the operation started by `accepted` and deserializes the
+    /// final status payload
+    pub async fn poll<T>(&self, client: &Client, accepted: Response) -> PileResult<T>
+    where
+        T: for<'de> serde::Deserialize<'de>,
+    {
+        let url = Self::status_url(accepted.headers())?;
+        let started = Instant::now();
+        let mut wait = parse_retry_after(accepted.headers()).unwrap_or(self.interval);
+
+        loop {
+            if started.elapsed() + wait > self.deadline {
+                return Err(ErrPile::timeout("long-running operation", self.deadline));
+            }
+            Deadline::ensure(wait, "long-running operation")?;
+            tokio::time::sleep(wait).await;
+
+            let res = client
+                .get(url.clone())
+                .headers(self.headers.clone())
+                .send_pile()
+                .await;
+
+            let res = match res {
+                Ok(res) => res,
+                Err(err) if err.is_transient() => {
+                    wait = err.retry_after().unwrap_or(self.interval);
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+
+            wait = parse_retry_after(res.headers()).unwrap_or(self.interval);
+            let body = res.bytes().await?;
+            let status: Value = parse_json_body(&body)?;
+
+            match LroState::of(&status) {
+                LroState::Running => continue,
+                LroState::Succeeded => return parse_json_body(&body),
+                LroState::Canceled => {
+                    return Err(ErrPile::cancelled_because(
+                        "long-running operation was canceled",
+                    ));
+                }
+                LroState::Failed => return Err(Self::failure(status)),
+            }
+        }
+    }
+
+    fn status_url(headers: &HeaderMap) -> PileResult<Url> {
+        let location = headers
+            .get("operation-location")
+            .or_else(|| headers.get(LOCATION))
+            .ok_or_else(|| {
+                ErrPile::custom_static(
+                    "Accepted response has no operation-location or Location header",
+                )
+            })?
+            .to_str()?;
+
+        Ok(Url::parse(location)?)
+    }
+
+    /// decodes the `error` of a failed operation
+    fn failure(mut status: Value) -> ErrPile {
+        let error = status.get_mut("error").map(Value::take).unwrap_or_default();
+
+        if let Ok(error) = AZErrorDetails::deserialize(&error) {
+            return Box::new(AZError { error }).into();
+        }
+
+        if let Ok(error) = MSResponseErrorInner::deserialize(&error) {
+            return MicrosoftError::MS(Box::new(MSResponseError { error })).into();
+        }
+
+        ErrPile::custom(format!("Long-running operation failed: {error}"))
+    }
+}
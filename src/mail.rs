@@ -0,0 +1,80 @@
+use reqwest::StatusCode;
+
+use crate::ErrPile;
+
+/// Why Graph refused to send or save a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MailFailure {
+    /// the sender's mailbox is over its quota
+    MailboxFull,
+    /// a recipient address does not exist or could not be resolved
+    RecipientNotResolved,
+    /// the message and its attachments are over the size limit
+    MessageTooLarge,
+    /// the app or user may not send as / on behalf of the sender
+    SendAsDenied,
+    /// Exchange is throttling the mailbox or the app
+    Throttled,
+    /// the mailbox store is temporarily unavailable
+    Unavailable,
+}
+
+/// What the notification service should do with the message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MailAction {
+    /// the message can never be delivered as is
+    Drop,
+    /// try again later
+    Retry,
+    /// an operator has to fix the configuration
+    Alert,
+}
+
+impl MailFailure {
+    pub fn action(&self) -> MailAction {
+        match self {
+            Self::RecipientNotResolved | Self::MessageTooLarge => MailAction::Drop,
+            Self::Throttled | Self::Unavailable => MailAction::Retry,
+            Self::MailboxFull | Self::SendAsDenied => MailAction::Alert,
+        }
+    }
+}
+
+impl ErrPile {
+    /// category of a failed Graph `sendMail` / message request
+    pub fn mail_failure(&self) -> Option<MailFailure> {
+        match self.peeled() {
+            Self::RateLimited { .. } => return Some(MailFailure::Throttled),
+            Self::Http(http) if http.status == StatusCode::PAYLOAD_TOO_LARGE => {
+                return Some(MailFailure::MessageTooLarge);
+            }
+            _ => {}
+        }
+
+        let (code, message) = self.graph_error_text()?;
+        let code = code.to_ascii_lowercase();
+        let message = message.to_ascii_lowercase();
+
+        let failure = match code.as_str() {
+            "errorquotaexceeded" | "mailboxquotaexceeded" => MailFailure::MailboxFull,
+            "errorinvalidrecipients" | "errorrecipientnotfound" | "errorinvalidemailaddress" => {
+                MailFailure::RecipientNotResolved
+            }
+            "errormessagesizeexceeded" | "requestbodytoolarge" => MailFailure::MessageTooLarge,
+            "errorsendasdenied" => MailFailure::SendAsDenied,
+            "applicationthrottled" | "mailboxconcurrency" | "errorserverbusy" => {
+                MailFailure::Throttled
+            }
+            "errormailboxstoreunavailable" | "errormailboxmoveinprogress" => {
+                MailFailure::Unavailable
+            }
+            // older endpoints only say it in the message
+            _ if message.contains("send as") && message.contains("denied") => {
+                MailFailure::SendAsDenied
+            }
+            _ if message.contains("mailbox is full") => MailFailure::MailboxFull,
+            _ => return None,
+        };
+        Some(failure)
+    }
+}
@@ -0,0 +1,46 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::ErrPile;
+
+static COUNT_ERRORS: AtomicBool = AtomicBool::new(false);
+
+/// Counts the errors reported through `emit`, `log`, `log_err!`, the
+/// span helpers and `report` in `errpile_total{kind, code, transient}`,
+/// off by default
+pub fn set_count_errors(enabled: bool) {
+    COUNT_ERRORS.store(enabled, Ordering::Relaxed);
+}
+
+pub fn count_errors() -> bool {
+    COUNT_ERRORS.load(Ordering::Relaxed)
+}
+
+impl ErrPile {
+    /// increments `errpile_total` for the error, errors without a code
+    /// are counted under `code="none"`
+    pub fn count(&self) {
+        metrics::counter!(
+            "errpile_total",
+            "kind" => self.kind().as_str(),
+            "code" => self.code().unwrap_or_else(|| "none".into()),
+            "transient" => self.is_transient().to_string(),
+        )
+        .increment(1);
+    }
+
+    /// counts the error if `set_count_errors` is on
+    #[cfg_attr(
+        not(any(
+            feature = "tracing",
+            feature = "log",
+            feature = "otel",
+            feature = "report"
+        )),
+        allow(dead_code)
+    )]
+    pub(crate) fn count_reported(&self) {
+        if count_errors() {
+            self.count();
+        }
+    }
+}
@@ -1,55 +1,539 @@
 use core::fmt;
-use std::error::Error;
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    time::Duration,
+};
 
+use reqwest::{
+    Method, RequestBuilder, StatusCode,
+    header::{AUTHORIZATION, COOKIE, HeaderMap, HeaderName, HeaderValue, PROXY_AUTHORIZATION},
+};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use url::Url;
 
-use crate::{ErrPile, PileResult};
+use crate::{
+    ErrPile, ErrorBody, MicrosoftError, PileResult, RequestBuilderPileExt, parse_json_body, redact,
+};
 
 /// Accomdate the use for mapping to correct response
 /// from Microsoft Graph response
-/// TODO implement ERROR trait this struct
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct MSResponseErrorInner {
     pub code: String,
-    pub inner_error: Value,
+    #[serde(default)]
+    pub inner_error: GraphInnerError,
     pub message: String,
 }
 
+impl fmt::Display for MSResponseErrorInner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} - {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for MSResponseErrorInner {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        (!self.inner_error.is_empty()).then_some(&self.inner_error as &dyn Error)
+    }
+}
+
+/// `innerError` object of a Graph error, its shape depends on the
+/// workload so it is kept as json
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(transparent)]
+pub struct GraphInnerError(pub Value);
+
+impl GraphInnerError {
+    /// Graph sent no inner error, or an empty one
+    pub fn is_empty(&self) -> bool {
+        match &self.0 {
+            Value::Null => true,
+            Value::Object(obj) => obj.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+impl std::ops::Deref for GraphInnerError {
+    type Target = Value;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl fmt::Display for GraphInnerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.get("code").and_then(Value::as_str) {
+            Some(code) => f.write_str(code),
+            None => self.0.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for GraphInnerError {}
+
+impl MSResponseErrorInner {
+    /// typed version of `code`
+    pub fn code_enum(&self) -> GraphErrorCode {
+        GraphErrorCode::from(self.code.as_str())
+    }
+
+    /// identifiers from `innerError` that Microsoft support asks for
+    pub fn diagnostics(&self) -> GraphDiagnostics {
+        let field = |name: &str| {
+            self.inner_error
+                .get(name)
+                .and_then(Value::as_str)
+                .map(String::from)
+        };
+
+        GraphDiagnostics {
+            request_id: field("request-id"),
+            client_request_id: field("client-request-id"),
+            date: field("date"),
+        }
+    }
+}
+
+/// Request identifiers Graph puts in `innerError`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphDiagnostics {
+    pub request_id: Option<String>,
+    pub client_request_id: Option<String>,
+    /// server time, as sent by Graph
+    pub date: Option<String>,
+}
+
+impl GraphDiagnostics {
+    pub fn is_empty(&self) -> bool {
+        self.request_id.is_none() && self.client_request_id.is_none() && self.date.is_none()
+    }
+}
+
+/// writes the known identifiers, e.g. `request-id: …, date: …`
+impl fmt::Display for GraphDiagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fields = [
+            ("request-id", &self.request_id),
+            ("client-request-id", &self.client_request_id),
+            ("date", &self.date),
+        ];
+
+        let mut first = true;
+        for (name, value) in fields {
+            if let Some(value) = value {
+                if !first {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{name}: {value}")?;
+                first = false;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct MSResponseError {
     pub error: MSResponseErrorInner,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl fmt::Display for MSResponseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+/// the wrapper only mirrors the json shape, the chain continues
+/// with the inner error
+impl std::error::Error for MSResponseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+/// Graph (and older OData) response, deserializes from any of the
+/// shapes in `MSResponse::SHAPES`
+#[derive(Debug, Serialize)]
 pub struct MSResponse<T> {
     value: Option<T>,
     error: Option<MSResponseError>,
+    #[serde(rename = "@odata.nextLink", skip_serializing_if = "Option::is_none")]
+    next_link: Option<String>,
+    #[serde(rename = "@odata.deltaLink", skip_serializing_if = "Option::is_none")]
+    delta_link: Option<String>,
+}
+
+impl<T> MSResponse<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    /// payload locations tried, in order
+    const SHAPES: &[&str] = &[
+        "`value`",
+        "`value` array with a single item",
+        "`d.results`",
+        "`d`",
+        "top level",
+    ];
+
+    fn payload(body: &Value) -> Result<T, String> {
+        let value = body.get("value");
+        let single = value
+            .and_then(Value::as_array)
+            .filter(|items| items.len() == 1)
+            .map(|items| &items[0]);
+        let d = body.get("d");
+        let results = d.and_then(|d| d.get("results"));
+
+        let mut last_err = None;
+        for candidate in [value, single, results, d, Some(body)]
+            .into_iter()
+            .flatten()
+        {
+            match T::deserialize(candidate) {
+                Ok(payload) => return Ok(payload),
+                Err(e) => last_err = Some(e),
+            }
+        }
+
+        Err(format!(
+            "response matched none of the shapes {} (last error: {})",
+            Self::SHAPES.join(", "),
+            last_err.map_or_else(|| "empty response".into(), |e| e.to_string())
+        ))
+    }
+
+    /// the error object, Graph sends `{"error": {code, message}}`,
+    /// the wrapped `{"error": {"error": …}}` form is accepted as well
+    fn error(body: &Value) -> Option<MSResponseError> {
+        let error = body.get("error")?;
+        MSResponseError::deserialize(error)
+            .or_else(|_| MSResponseError::deserialize(body))
+            .ok()
+    }
+}
+
+impl<'de, T> Deserialize<'de> for MSResponse<T>
+where
+    T: for<'a> Deserialize<'a>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let body = Value::deserialize(deserializer)?;
+        let link = |name: &str| body.get(name).and_then(Value::as_str).map(String::from);
+        let next_link = link("@odata.nextLink");
+        let delta_link = link("@odata.deltaLink");
+
+        if let Some(error) = Self::error(&body) {
+            return Ok(Self {
+                value: None,
+                error: Some(error),
+                next_link,
+                delta_link,
+            });
+        }
+
+        let value = Self::payload(&body).map_err(serde::de::Error::custom)?;
+        Ok(Self {
+            value: Some(value),
+            error: None,
+            next_link,
+            delta_link,
+        })
+    }
+}
+
+impl<T> MSResponse<T> {
+    /// url of the next page, the listing is incomplete while this is set
+    pub fn next_link(&self) -> Option<&str> {
+        self.next_link.as_deref()
+    }
+
+    /// url to request the changes since this listing, on the last page
+    /// of a delta query
+    pub fn delta_link(&self) -> Option<&str> {
+        self.delta_link.as_deref()
+    }
+}
+
+/// The items of every page of a listing, with the `@odata.deltaLink`
+/// of the last page to resume a delta query from
+#[derive(Debug, Clone, PartialEq)]
+pub struct MSPages<T> {
+    pub items: Vec<T>,
+    pub delta_link: Option<String>,
+}
+
+impl<T> MSResponse<Vec<T>>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    /// Sends the request and follows `@odata.nextLink` until the last
+    /// page. The headers of the first request (auth) go with the pages
+    /// on the same scheme and host only, a link to another host gets
+    /// them without `Authorization`, `Proxy-Authorization` and
+    /// `Cookie`. A failing page is reported as `ErrPile::Page`
+    pub async fn collect_all(request: RequestBuilder) -> PileResult<MSPages<T>> {
+        let (client, first) = request.build_split();
+        let first = first?;
+        let origin = first.url().clone();
+        let headers = first.headers().clone();
+        let mut foreign_headers = headers.clone();
+        for name in [AUTHORIZATION, PROXY_AUTHORIZATION, COOKIE] {
+            foreign_headers.remove(name);
+        }
+
+        let mut pages = MSPages {
+            items: Vec::new(),
+            delta_link: None,
+        };
+        let mut next = Some(first);
+        let mut index = 0;
+
+        while let Some(request) = next.take() {
+            let page = Self::fetch_page(&client, request)
+                .await
+                .map_err(|e| ErrPile::Page {
+                    index,
+                    source: Box::new(e),
+                })?;
+
+            if let Some(link) = page.next_link {
+                // the page after this one can't be requested
+                let url = Url::parse(&link).map_err(|e| ErrPile::Page {
+                    index: index + 1,
+                    source: Box::new(e.into()),
+                })?;
+                let same_origin = url.scheme() == origin.scheme()
+                    && url.host() == origin.host()
+                    && url.port_or_known_default() == origin.port_or_known_default();
+                let mut request = reqwest::Request::new(Method::GET, url);
+                *request.headers_mut() = if same_origin {
+                    headers.clone()
+                } else {
+                    foreign_headers.clone()
+                };
+                next = Some(request);
+            }
+
+            pages.items.extend(page.value.unwrap_or_default());
+            pages.delta_link = page.delta_link;
+            index += 1;
+        }
+
+        Ok(pages)
+    }
+
+    async fn fetch_page(client: &reqwest::Client, request: reqwest::Request) -> PileResult<Self> {
+        let body = RequestBuilder::from_parts(client.clone(), request)
+            .send_pile()
+            .await?
+            .bytes()
+            .await?;
+
+        let page: Self = parse_json_body(&body)?;
+        match page.error {
+            Some(err) => Err(MicrosoftError::MS(Box::new(err)).into()),
+            None => Ok(page),
+        }
+    }
 }
 
 impl<T: std::fmt::Debug> From<MSResponse<T>> for PileResult<T> {
     fn from(value: MSResponse<T>) -> Self {
         if let Some(err) = value.error {
-            return Err(ErrPile::MS(err));
+            return Err(MicrosoftError::MS(Box::new(err)).into());
         }
 
         if let Some(val) = value.value {
             return Ok(val);
         }
 
-        Err(ErrPile::Custom(format!(
+        Err(ErrPile::custom(format!(
             "Could not parse Ok variant or the Err variant | Response: {value:?}"
         )))
     }
 }
 
+/// Documented Microsoft Graph OData error codes, see
+/// <https://learn.microsoft.com/en-us/graph/errors#code-property>
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GraphErrorCode {
+    AccessDenied,
+    ActivityLimitReached,
+    ExtensionError,
+    GeneralException,
+    InvalidRange,
+    InvalidRequest,
+    ItemNotFound,
+    MalwareDetected,
+    NameAlreadyExists,
+    NotAllowed,
+    NotSupported,
+    QuotaLimitReached,
+    ResourceModified,
+    ResyncRequired,
+    ServiceNotAvailable,
+    SyncStateNotFound,
+    Unauthenticated,
+    /// a code not in the list above
+    Other(String),
+}
+
+impl GraphErrorCode {
+    const KNOWN: &[(&str, GraphErrorCode)] = &[
+        ("accessDenied", Self::AccessDenied),
+        ("activityLimitReached", Self::ActivityLimitReached),
+        ("extensionError", Self::ExtensionError),
+        ("generalException", Self::GeneralException),
+        ("invalidRange", Self::InvalidRange),
+        ("invalidRequest", Self::InvalidRequest),
+        ("itemNotFound", Self::ItemNotFound),
+        ("malwareDetected", Self::MalwareDetected),
+        ("nameAlreadyExists", Self::NameAlreadyExists),
+        ("notAllowed", Self::NotAllowed),
+        ("notSupported", Self::NotSupported),
+        ("quotaLimitReached", Self::QuotaLimitReached),
+        ("resourceModified", Self::ResourceModified),
+        ("resyncRequired", Self::ResyncRequired),
+        ("serviceNotAvailable", Self::ServiceNotAvailable),
+        ("syncStateNotFound", Self::SyncStateNotFound),
+        ("unauthenticated", Self::Unauthenticated),
+    ];
+
+    /// Graph is throttling the app or tenant, the request can be
+    /// retried after a while
+    pub fn is_throttling(&self) -> bool {
+        matches!(self, Self::ActivityLimitReached | Self::ServiceNotAvailable)
+    }
+
+    /// the code as Graph spells it
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Other(code) => code,
+            known => Self::KNOWN
+                .iter()
+                .find(|(_, c)| c == known)
+                .map_or("", |(s, _)| s),
+        }
+    }
+}
+
+/// codes are matched case-insensitively, Graph is not consistent about it
+impl From<&str> for GraphErrorCode {
+    fn from(code: &str) -> Self {
+        Self::KNOWN
+            .iter()
+            .find(|(s, _)| s.eq_ignore_ascii_case(code))
+            .map_or_else(|| Self::Other(code.to_string()), |(_, c)| c.clone())
+    }
+}
+
+impl fmt::Display for GraphErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ErrPile {
+    /// Graph error code of the error, for errors returned by Graph
+    /// (through `MSResponse`, the Graph SDK or a raw OData error body)
+    pub fn graph_code(&self) -> Option<GraphErrorCode> {
+        match self.peeled() {
+            Self::Microsoft(MicrosoftError::MS(err)) => Some(err.error.code_enum()),
+            #[cfg(feature = "graph")]
+            Self::Microsoft(MicrosoftError::GraphErrMSg(msg)) => {
+                msg.error.code.as_deref().map(GraphErrorCode::from)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// wait time suggested in a Graph `innerError` object, in seconds
+pub(crate) fn inner_error_retry_after(inner: &Value) -> Option<Duration> {
+    ["retryAfterSeconds", "retryAfter", "retry-after"]
+        .iter()
+        .filter_map(|key| inner.get(key))
+        .find_map(|v| {
+            v.as_u64()
+                .or_else(|| v.as_str().and_then(|s| s.trim().parse().ok()))
+        })
+        .map(Duration::from_secs)
+}
+
+/// Graph directory failures caused by replication lag or missing
+/// `ConsistencyLevel`, see
+/// <https://learn.microsoft.com/en-us/graph/aad-advanced-queries>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConsistencyHint {
+    /// the object was probably created moments ago and has not
+    /// reached every replica, retry after a short delay
+    ReplicationLag,
+    /// advanced queries need the `ConsistencyLevel: eventual` header
+    /// (and `$count=true`)
+    ConsistencyLevelRequired,
+}
+
+impl ErrPile {
+    /// Whether the directory query should be repeated, after a delay or
+    /// with `ConsistencyLevel: eventual`. Graph can't tell a missing object
+    /// from one still replicating, so retry budgets should stay small
+    pub fn retry_with_consistency(&self) -> Option<ConsistencyHint> {
+        let (code, message) = self.graph_error_text()?;
+
+        if code.eq_ignore_ascii_case("Request_ResourceNotFound") {
+            return Some(ConsistencyHint::ReplicationLag);
+        }
+
+        let message = message.to_lowercase();
+        if code.eq_ignore_ascii_case("Request_UnsupportedQuery")
+            && (message.contains("consistencylevel") || message.contains("advanced query"))
+        {
+            return Some(ConsistencyHint::ConsistencyLevelRequired);
+        }
+
+        None
+    }
+}
+
 /////////////////////////////// AZURE Document Intelligence Errors ////////////////////////
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct AZError {
     pub error: AZErrorDetails,
 }
 
+impl AZError {
+    /// Whether retrying can help, the nested `details` and `innererror`
+    /// codes are consulted as well. A permanent code anywhere in the
+    /// tree wins, e.g. an `InternalServerError` caused by `InvalidContent`
+    pub fn is_transient(&self) -> bool {
+        let mut codes = Vec::new();
+        self.error.collect_codes(&mut codes);
+
+        let verdicts = codes.iter().filter_map(AZErrorCode::is_transient);
+        let mut transient = false;
+        for verdict in verdicts {
+            if !verdict {
+                return false;
+            }
+            transient = true;
+        }
+        transient
+    }
+}
+
 impl fmt::Display for AZError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.error.fmt(f)
@@ -63,6 +547,7 @@ impl std::error::Error for AZError {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct AZErrorDetails {
     pub code: String,
     pub message: String,
@@ -73,7 +558,7 @@ pub struct AZErrorDetails {
 
 impl fmt::Display for AZErrorDetails {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} - {}", self.code, self.message,)
+        write!(f, "{} - {}", self.code, redact(&self.message))
     }
 }
 
@@ -83,22 +568,149 @@ impl std::error::Error for AZErrorDetails {
     }
 }
 
+impl AZErrorDetails {
+    /// typed version of `code`
+    pub fn code_enum(&self) -> AZErrorCode {
+        AZErrorCode::from(self.code.as_str())
+    }
+
+    fn collect_codes(&self, codes: &mut Vec<AZErrorCode>) {
+        codes.push(self.code_enum());
+
+        let mut inner = self.innererror.as_ref();
+        while let Some(err) = inner {
+            codes.extend(err.code_enum());
+            inner = err.innererror.as_deref();
+        }
+
+        for detail in self.details.iter().flatten() {
+            detail.error.collect_codes(codes);
+        }
+    }
+}
+
+/// Documented Document Intelligence error codes, top level and inner
+/// codes share the enum
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AZErrorCode {
+    BadArgument,
+    Conflict,
+    Forbidden,
+    InternalServerError,
+    InvalidArgument,
+    InvalidContent,
+    InvalidContentDimensions,
+    InvalidContentLength,
+    InvalidContentSourceFormat,
+    InvalidParameter,
+    InvalidRequest,
+    ModelNotFound,
+    ModelNotReady,
+    NotFound,
+    OperationNotFound,
+    OutOfQuota,
+    ServiceUnavailable,
+    Timeout,
+    TooManyRequests,
+    Unauthorized,
+    UnsupportedMediaType,
+    /// a code not in the list above
+    Other(String),
+}
+
+impl AZErrorCode {
+    const KNOWN: &[(&str, AZErrorCode)] = &[
+        ("BadArgument", Self::BadArgument),
+        ("Conflict", Self::Conflict),
+        ("Forbidden", Self::Forbidden),
+        ("InternalServerError", Self::InternalServerError),
+        ("InvalidArgument", Self::InvalidArgument),
+        ("InvalidContent", Self::InvalidContent),
+        ("InvalidContentDimensions", Self::InvalidContentDimensions),
+        ("InvalidContentLength", Self::InvalidContentLength),
+        (
+            "InvalidContentSourceFormat",
+            Self::InvalidContentSourceFormat,
+        ),
+        ("InvalidParameter", Self::InvalidParameter),
+        ("InvalidRequest", Self::InvalidRequest),
+        ("ModelNotFound", Self::ModelNotFound),
+        ("ModelNotReady", Self::ModelNotReady),
+        ("NotFound", Self::NotFound),
+        ("OperationNotFound", Self::OperationNotFound),
+        ("OutOfQuota", Self::OutOfQuota),
+        ("ServiceUnavailable", Self::ServiceUnavailable),
+        ("Timeout", Self::Timeout),
+        ("TooManyRequests", Self::TooManyRequests),
+        ("Unauthorized", Self::Unauthorized),
+        ("UnsupportedMediaType", Self::UnsupportedMediaType),
+    ];
+
+    /// `Some(true)` when retrying can help, `Some(false)` when the
+    /// request itself is wrong, `None` for codes that don't tell
+    pub fn is_transient(&self) -> Option<bool> {
+        match self {
+            Self::InternalServerError
+            | Self::ServiceUnavailable
+            | Self::Timeout
+            | Self::TooManyRequests
+            | Self::ModelNotReady => Some(true),
+            Self::Other(_) | Self::Conflict => None,
+            _ => Some(false),
+        }
+    }
+
+    /// the code as the service spells it
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Other(code) => code,
+            known => Self::KNOWN
+                .iter()
+                .find(|(_, c)| c == known)
+                .map_or("", |(s, _)| s),
+        }
+    }
+}
+
+impl From<&str> for AZErrorCode {
+    fn from(code: &str) -> Self {
+        Self::KNOWN
+            .iter()
+            .find(|(s, _)| s.eq_ignore_ascii_case(code))
+            .map_or_else(|| Self::Other(code.to_string()), |(_, c)| c.clone())
+    }
+}
+
+impl fmt::Display for AZErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 type BoxAZErrorInner = Box<AZErrorInner>;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct AZErrorInner {
     pub code: Option<String>,
     pub message: Option<String>,
     pub innererror: Option<BoxAZErrorInner>,
 }
 
+impl AZErrorInner {
+    /// typed version of `code`
+    pub fn code_enum(&self) -> Option<AZErrorCode> {
+        self.code.as_deref().map(AZErrorCode::from)
+    }
+}
+
 impl fmt::Display for AZErrorInner {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
             "{} - {}",
             self.code.as_ref().map_or("", |v| v),
-            self.message.as_ref().map_or("", |v| v)
+            redact(self.message.as_deref().unwrap_or_default())
         )
     }
 }
@@ -109,7 +721,7 @@ impl std::error::Error for AZErrorInner {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AZWarning {
     /** One of a server-defined set of warning codes. */
     code: String,
@@ -119,4 +731,176 @@ pub struct AZWarning {
     target: Option<String>,
 }
 
+impl AZWarning {
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn target(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+}
+
+impl fmt::Display for AZWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} - {}", self.code, self.message)?;
+        if let Some(target) = &self.target {
+            write!(f, " ({target})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Successful Document Intelligence result together with the warnings
+/// it came with (low confidence, partially read pages, …)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AZOutcome<T> {
+    #[serde(flatten)]
+    pub value: T,
+    #[serde(default)]
+    pub warnings: Vec<AZWarning>,
+}
+
+impl<T> AZOutcome<T> {
+    /// drops the warnings
+    pub fn into_value(self) -> T {
+        self.value
+    }
+}
+
+/// State of a Document Intelligence analyze operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AZOperationStatus {
+    NotStarted,
+    Running,
+    Succeeded,
+    Failed,
+    Canceled,
+}
+
+/// Body returned when polling the `operation-location` of an analyze
+/// request, `T` is the analyze result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AZOperation<T> {
+    pub status: AZOperationStatus,
+    pub created_date_time: Option<String>,
+    pub last_updated_date_time: Option<String>,
+    pub analyze_result: Option<T>,
+    pub error: Option<AZErrorDetails>,
+}
+
+impl<T> AZOperation<T> {
+    /// Result of the operation, still running operations are
+    /// `NotReady` so they are retried like any other transient error
+    pub fn into_result(self) -> PileResult<T> {
+        match self.status {
+            AZOperationStatus::NotStarted | AZOperationStatus::Running => Err(ErrPile::NotReady),
+            AZOperationStatus::Canceled => {
+                Err(ErrPile::cancelled_because("analyze operation was canceled"))
+            }
+            AZOperationStatus::Failed => Err(match self.error {
+                Some(error) => Box::new(AZError { error }).into(),
+                None => ErrPile::custom_static("Analyze operation failed without an error"),
+            }),
+            AZOperationStatus::Succeeded => self.analyze_result.ok_or_else(|| {
+                ErrPile::custom_static("Analyze operation succeeded without a result")
+            }),
+        }
+    }
+}
+
+/// Url to poll for an accepted analyze request
+pub fn operation_location(headers: &HeaderMap) -> PileResult<Url> {
+    let location = headers
+        .get("operation-location")
+        .ok_or_else(|| {
+            ErrPile::custom_static("Analyze response is missing the operation-location header")
+        })?
+        .to_str()?;
+
+    Ok(Url::parse(location)?)
+}
+
 /////////////////////////////// END OF AZURE Document Intelligence Errors ////////////////////////
+
+/////////////////////////////// Microsoft Graph $batch ////////////////////////
+
+/// endpoint the batch items are reported against, the sub-request
+/// urls are not echoed back by Graph
+const GRAPH_BATCH_URL: &str = "https://graph.microsoft.com/v1.0/$batch";
+
+/// Response of a Graph JSON `$batch` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphBatchResponse {
+    pub responses: Vec<GraphBatchItem>,
+}
+
+/// One sub-response of a `$batch` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphBatchItem {
+    /// id given to the sub-request
+    pub id: String,
+    pub status: u16,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub body: Value,
+}
+
+impl GraphBatchItem {
+    /// converts the sub-response the same way a standalone response
+    /// is converted by `to_pile_result`
+    pub fn into_result<T>(self) -> PileResult<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let status = StatusCode::from_u16(self.status)
+            .map_err(|_| ErrPile::custom(format!("Invalid batch status {}", self.status)))?;
+
+        let body = serde_json::to_vec(&self.body)?;
+        if status.is_success() {
+            return parse_json_body(&body);
+        }
+
+        let headers = self
+            .headers
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((
+                    HeaderName::from_bytes(name.as_bytes()).ok()?,
+                    HeaderValue::from_str(value).ok()?,
+                ))
+            })
+            .collect();
+
+        let url = Url::parse(GRAPH_BATCH_URL).expect("valid batch url");
+        if let Some(err) = ErrPile::throttled(status, &headers, &url) {
+            return Err(err);
+        }
+
+        let body = ErrorBody::Read {
+            body,
+            truncated: false,
+        };
+        Err(ErrPile::from_error_parts(status, headers, url, body))
+    }
+}
+
+impl GraphBatchResponse {
+    /// per request id results, in id order
+    pub fn into_results<T>(self) -> BTreeMap<String, PileResult<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        self.responses
+            .into_iter()
+            .map(|item| (item.id.clone(), item.into_result()))
+            .collect()
+    }
+}
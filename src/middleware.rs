@@ -0,0 +1,173 @@
+use core::fmt;
+use std::{sync::Arc, time::Duration};
+
+use http::{Extensions, HeaderValue};
+use reqwest::{Request, Response};
+use reqwest_middleware::{Middleware, Next};
+
+use crate::{Backoff, Deadline, ErrPile, IDEMPOTENCY_KEY, Jitter, ReqwestPileResExt, RetryPolicy};
+
+/// `reqwest_middleware` middleware converting error responses into
+/// ErrPile and, optionally, retrying transient failures with backoff.
+///
+/// Errors are returned as `reqwest_middleware::Error::Middleware`
+/// wrapping the ErrPile, `ErrPile::from` unwraps it again.
+///
+/// Only idempotent requests are retried: GET, PUT, DELETE and co., and
+/// those carrying an `Idempotency-Key` header, see `idempotency_keys`.
+#[derive(Clone)]
+pub struct PileMiddleware {
+    backoff: Backoff,
+    /// replaces `backoff` when set
+    policy: Option<Arc<dyn RetryPolicy>>,
+    idempotency_key: Option<Arc<dyn Fn() -> String + Send + Sync>>,
+}
+
+impl Default for PileMiddleware {
+    fn default() -> Self {
+        Self {
+            backoff: Backoff::new().max_attempts(1).jitter(Jitter::None),
+            policy: None,
+            idempotency_key: None,
+        }
+    }
+}
+
+impl fmt::Debug for PileMiddleware {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PileMiddleware")
+            .field("backoff", &self.backoff)
+            .field("custom_policy", &self.policy.is_some())
+            .field("idempotency_keys", &self.idempotency_key.is_some())
+            .finish()
+    }
+}
+
+impl PileMiddleware {
+    /// converts failures only, no retries
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// retries transient failures up to `max_retries` times, waiting
+    /// `base_delay * 2^attempt` between the attempts unless the server
+    /// asked for a specific wait time
+    pub fn with_retries(max_retries: u32, base_delay: Duration) -> Self {
+        Self {
+            backoff: Backoff::new()
+                .max_attempts(max_retries.saturating_add(1))
+                .base_delay(base_delay)
+                .jitter(Jitter::None),
+            ..Self::default()
+        }
+    }
+
+    /// retries as the policy decides, e.g. a `Backoff` shared with the
+    /// `retry` calls of the service
+    pub fn with_policy<P: RetryPolicy + 'static>(policy: P) -> Self {
+        Self {
+            policy: Some(Arc::new(policy)),
+            ..Self::default()
+        }
+    }
+
+    /// upper bound for a single wait between the attempts of
+    /// `with_retries`
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.backoff = self.backoff.max_delay(max_delay);
+        self
+    }
+
+    /// makes POST and PATCH requests retryable by adding an
+    /// `Idempotency-Key` header from `supplier` to those without one, all
+    /// attempts of a request send the same key
+    pub fn idempotency_keys<K>(mut self, supplier: K) -> Self
+    where
+        K: Fn() -> String + Send + Sync + 'static,
+    {
+        self.idempotency_key = Some(Arc::new(supplier));
+        self
+    }
+
+    /// whether failed requests may be sent again, saves cloning them
+    fn retries(&self) -> bool {
+        self.policy.is_some() || self.backoff.max_attempts > 1
+    }
+
+    /// whether sending the request twice is safe, adds the idempotency
+    /// key when there's a supplier
+    fn idempotent(&self, req: &mut Request) -> bool {
+        if req.method().is_idempotent() || req.headers().contains_key(IDEMPOTENCY_KEY) {
+            return true;
+        }
+        let Some(supplier) = &self.idempotency_key else {
+            return false;
+        };
+        match HeaderValue::try_from(supplier()) {
+            Ok(key) => {
+                req.headers_mut().insert(IDEMPOTENCY_KEY, key);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    fn policy(&self) -> &dyn RetryPolicy {
+        match &self.policy {
+            Some(policy) => policy.as_ref(),
+            None => &self.backoff,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Middleware for PileMiddleware {
+    async fn handle(
+        &self,
+        mut req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let started = tokio::time::Instant::now();
+        let mut attempt = 0;
+        let retries = self.retries() && self.idempotent(&mut req);
+
+        loop {
+            // bodies that are streams can't be cloned, those are sent once
+            let retry_req = retries.then(|| req.try_clone()).flatten();
+
+            let err = match next.clone().run(req, extensions).await {
+                Ok(res) => match res.error_for_pile().await {
+                    Ok(res) => return Ok(res),
+                    Err(err) => err,
+                },
+                Err(reqwest_middleware::Error::Reqwest(err)) => ErrPile::from(err),
+                Err(err) => return Err(err),
+            };
+
+            let delay = self.policy().next_delay(attempt, started.elapsed(), &err);
+            match (retry_req, delay) {
+                (Some(retry_req), Some(delay)) => {
+                    Deadline::ensure(delay, "retry")
+                        .map_err(reqwest_middleware::Error::middleware)?;
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                    req = retry_req;
+                }
+                _ => return Err(reqwest_middleware::Error::middleware(err)),
+            }
+        }
+    }
+}
+
+impl From<reqwest_middleware::Error> for ErrPile {
+    fn from(value: reqwest_middleware::Error) -> Self {
+        match value {
+            reqwest_middleware::Error::Reqwest(err) => err.into(),
+            reqwest_middleware::Error::Middleware(err) => match err.downcast::<ErrPile>() {
+                Ok(pile) => pile,
+                Err(err) => ErrPile::custom(format!("{err:#}")),
+            },
+        }
+    }
+}
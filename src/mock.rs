@@ -0,0 +1,177 @@
+use std::time::Duration;
+
+use reqwest::{
+    StatusCode,
+    header::{CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue, RETRY_AFTER, WWW_AUTHENTICATE},
+};
+use serde_json::{Map, Value, json};
+
+use crate::ProblemDetails;
+
+/// the ids Graph puts in the body and the headers of every error
+const REQUEST_ID: &str = "3f1c2d4e-0000-4000-8000-000000000000";
+
+/// An error response shaped like the ones Graph, Document Intelligence
+/// and the problem+json APIs send, for testing the handling of
+/// throttling, auth and validation failures without calling them.
+/// `into_http` gives an `http::Response` to hand to a mock server,
+/// `reqwest::Response::from` one to run through `error_for_pile` directly
+///
+/// ```ignore
+/// let res = MockErrorResponse::graph_throttled(Duration::from_secs(3));
+/// let err = reqwest::Response::from(res).error_for_pile().await.unwrap_err();
+/// assert_eq!(err.retry_after(), Some(Duration::from_secs(3)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockErrorResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Value,
+}
+
+impl MockErrorResponse {
+    /// `{"error": {"code", "message", "innerError"}}` with the request ids
+    /// Graph sends, in the body and the headers
+    pub fn graph(status: u16, code: &str, message: &str) -> Self {
+        let body = json!({
+            "error": {
+                "code": code,
+                "message": message,
+                "innerError": {
+                    "date": "2025-06-02T10:00:00",
+                    "request-id": REQUEST_ID,
+                    "client-request-id": REQUEST_ID,
+                },
+            }
+        });
+
+        Self::json(status, "application/json", body)
+            .header("request-id", REQUEST_ID)
+            .header("client-request-id", REQUEST_ID)
+    }
+
+    /// 429 `activityLimitReached` with a `Retry-After`
+    pub fn graph_throttled(retry_after: Duration) -> Self {
+        Self::graph(
+            429,
+            "activityLimitReached",
+            "The request has been throttled",
+        )
+        .header(RETRY_AFTER, &retry_after.as_secs().to_string())
+    }
+
+    /// 401 `InvalidAuthenticationToken`, the answer to an expired token
+    pub fn graph_unauthorized() -> Self {
+        Self::graph(
+            401,
+            "InvalidAuthenticationToken",
+            "Lifetime validation failed, the token is expired.",
+        )
+        .header(
+            WWW_AUTHENTICATE,
+            r#"Bearer realm="", authorization_uri="https://login.microsoftonline.com/common/oauth2/authorize", error="invalid_token""#,
+        )
+    }
+
+    /// 423 `resourceLocked`, a file open in the browser
+    pub fn graph_locked() -> Self {
+        Self::graph(
+            423,
+            "resourceLocked",
+            "The resource you are attempting to access is locked",
+        )
+    }
+
+    /// `{"error": {"code", "message"}}` of Document Intelligence
+    pub fn document_intelligence(status: u16, code: &str, message: &str) -> Self {
+        let body = json!({ "error": { "code": code, "message": message } });
+        Self::json(status, "application/json", body).header("apim-request-id", REQUEST_ID)
+    }
+
+    /// 400 `InvalidRequest` caused by `InvalidContent`, what a corrupted
+    /// or unsupported file is answered with
+    pub fn document_intelligence_invalid_content() -> Self {
+        let mut res = Self::document_intelligence(400, "InvalidRequest", "Invalid request.");
+        res.body["error"]["innererror"] = json!({
+            "code": "InvalidContent",
+            "message": "The file is corrupted or format is unsupported. Refer to documentation for the list of supported formats.",
+        });
+        res
+    }
+
+    /// an `application/problem+json` body
+    pub fn problem(status: u16, title: &str, detail: &str) -> Self {
+        let body = json!({
+            "type": "about:blank",
+            "title": title,
+            "status": status,
+            "detail": detail,
+        });
+        Self::json(status, ProblemDetails::CONTENT_TYPE, body)
+    }
+
+    /// 400 problem+json with `errors` per field, the ASP.NET shape
+    pub fn validation<'a, I>(errors: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let mut fields = Map::new();
+        for (field, message) in errors {
+            let messages = fields.entry(field).or_insert_with(|| json!([]));
+            if let Value::Array(messages) = messages {
+                messages.push(message.into());
+            }
+        }
+
+        let mut res = Self::problem(
+            400,
+            "One or more validation errors occurred.",
+            "See the errors for details.",
+        );
+        res.body["errors"] = Value::Object(fields);
+        res
+    }
+
+    fn json(status: u16, content_type: &'static str, body: Value) -> Self {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+
+        Self {
+            status: StatusCode::from_u16(status).expect("status between 100 and 999"),
+            headers,
+            body,
+        }
+    }
+
+    /// adds a header, e.g. a vendor specific one the service relies on
+    pub fn header<N>(mut self, name: N, value: &str) -> Self
+    where
+        N: TryInto<HeaderName>,
+        N::Error: std::fmt::Debug,
+    {
+        let name = name.try_into().expect("valid header name");
+        let value = HeaderValue::from_str(value).expect("valid header value");
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// the body as sent
+    pub fn body_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(&self.body).expect("JSON values serialize")
+    }
+
+    /// the response to serve from a mock server
+    pub fn into_http(self) -> http::Response<Vec<u8>> {
+        let body = self.body_bytes();
+        let mut res = http::Response::new(body);
+        *res.status_mut() = self.status;
+        *res.headers_mut() = self.headers;
+        res
+    }
+}
+
+impl From<MockErrorResponse> for reqwest::Response {
+    fn from(value: MockErrorResponse) -> Self {
+        value.into_http().into()
+    }
+}
@@ -0,0 +1,250 @@
+use core::fmt;
+use std::{borrow::Cow, error::Error, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ErrPile, PileKind, PileResult, kind_status};
+
+/// how many failures the `Display` of a `MultiPile` lists, the rest are
+/// counted
+const DISPLAYED_ITEMS: usize = 5;
+
+/// One failure of a `MultiPile`, the error is serialized as
+/// `ErrPile` is, see `RemoteError`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PileItem {
+    /// position of the item in the batch
+    pub index: usize,
+    /// what the item is, the folio or the room
+    pub label: Option<Cow<'static, str>>,
+    pub error: ErrPile,
+}
+
+impl fmt::Display for PileItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.index)?;
+        if let Some(label) = &self.label {
+            write!(f, " ({label})")?;
+        }
+        write!(f, ": {}", self.error)
+    }
+}
+
+/// The failures of a batch, every one of them rather than the first. The
+/// batch goes on and reports them all at the end:
+///
+/// ```ignore
+/// let mut failures = MultiPile::new();
+/// for (index, folio) in folios.iter().enumerate() {
+///     if let Err(err) = export_folio(folio).await {
+///         failures.push_labeled(index, format!("folio {}", folio.id), err);
+///     }
+/// }
+/// failures.into_result()?;
+/// ```
+#[derive(Debug, Default)]
+pub struct MultiPile {
+    items: Vec<PileItem>,
+}
+
+impl MultiPile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, index: usize, error: ErrPile) {
+        self.items.push(PileItem {
+            index,
+            label: None,
+            error,
+        });
+    }
+
+    pub fn push_labeled<L>(&mut self, index: usize, label: L, error: ErrPile)
+    where
+        L: Into<Cow<'static, str>>,
+    {
+        self.items.push(PileItem {
+            index,
+            label: Some(label.into()),
+            error,
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn items(&self) -> &[PileItem] {
+        &self.items
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, PileItem> {
+        self.items.iter()
+    }
+
+    pub fn into_items(self) -> Vec<PileItem> {
+        self.items
+    }
+
+    /// `Ok` without failures, `ErrPile::Multi` otherwise
+    pub fn into_result(self) -> PileResult {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.into())
+        }
+    }
+
+    /// the kind all the failures share, `None` when they differ
+    pub fn shared_kind(&self) -> Option<PileKind> {
+        let kind = self.items.first()?.error.kind();
+        self.items
+            .iter()
+            .all(|item| item.error.kind() == kind)
+            .then_some(kind)
+    }
+
+    /// the status all the failures share, 400 when they are all client
+    /// errors, 500 otherwise
+    pub fn status_code(&self) -> u16 {
+        let mut statuses = self.items.iter().map(|item| item.error.status_code());
+        let Some(first) = statuses.next() else {
+            return kind_status(PileKind::Internal);
+        };
+
+        let mut client = (400..500).contains(&first);
+        let mut same = true;
+        for status in statuses {
+            same &= status == first;
+            client &= (400..500).contains(&status);
+        }
+        match (same, client) {
+            (true, _) => first,
+            (false, true) => 400,
+            (false, false) => 500,
+        }
+    }
+
+    /// Running the failed items again can work: every failure is transient
+    pub fn is_transient(&self) -> bool {
+        !self.is_empty() && self.items.iter().all(|item| item.error.is_transient())
+    }
+
+    /// the longest wait the failures ask for
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.items
+            .iter()
+            .filter_map(|item| item.error.retry_after())
+            .max()
+    }
+}
+
+impl fmt::Display for MultiPile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.items.as_slice() {
+            [] => f.write_str("No errors"),
+            [item] => write!(f, "1 item failed: {item}"),
+            items => {
+                write!(f, "{} items failed", items.len())?;
+                for item in items.iter().take(DISPLAYED_ITEMS) {
+                    write!(f, "; {item}")?;
+                }
+                if items.len() > DISPLAYED_ITEMS {
+                    write!(f, "; and {} more", items.len() - DISPLAYED_ITEMS)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// the first failure is the source, the others are in `items`
+impl Error for MultiPile {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.items
+            .first()
+            .map(|item| &item.error as &(dyn Error + 'static))
+    }
+}
+
+impl From<MultiPile> for ErrPile {
+    fn from(value: MultiPile) -> Self {
+        ErrPile::Multi(Box::new(value))
+    }
+}
+
+/// the errors indexed in their order
+impl FromIterator<ErrPile> for MultiPile {
+    fn from_iter<I: IntoIterator<Item = ErrPile>>(iter: I) -> Self {
+        let mut multi = Self::new();
+        for (index, error) in iter.into_iter().enumerate() {
+            multi.push(index, error);
+        }
+        multi
+    }
+}
+
+impl IntoIterator for MultiPile {
+    type Item = PileItem;
+    type IntoIter = std::vec::IntoIter<PileItem>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a MultiPile {
+    type Item = &'a PileItem;
+    type IntoIter = std::slice::Iter<'a, PileItem>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+/// Collecting the results of a batch without stopping at the first failure
+pub trait PileIteratorExt<T>: Iterator<Item = PileResult<T>> + Sized {
+    /// The values and the failures, indexed by their position, so the
+    /// import of hundreds of folio rows goes on and reports every bad one:
+    ///
+    /// ```ignore
+    /// let (rows, failures) = lines.map(parse_folio_row).collect_piles();
+    /// import(rows).await?;
+    /// failures.into_result()?;
+    /// ```
+    fn collect_piles(self) -> (Vec<T>, MultiPile) {
+        let mut values = Vec::new();
+        let mut failures = MultiPile::new();
+        for (index, result) in self.enumerate() {
+            match result {
+                Ok(value) => values.push(value),
+                Err(err) => failures.push(index, err),
+            }
+        }
+        (values, failures)
+    }
+
+    /// every value, or `ErrPile::Multi` with every failure when one of the
+    /// results failed
+    fn collect_piles_strict(self) -> PileResult<Vec<T>> {
+        let (values, failures) = self.collect_piles();
+        failures.into_result().map(|()| values)
+    }
+}
+
+impl<T, I> PileIteratorExt<T> for I where I: Iterator<Item = PileResult<T>> {}
+
+impl ErrPile {
+    /// the failures of a batch, if this is an `ErrPile::Multi`
+    pub fn multi(&self) -> Option<&MultiPile> {
+        match self.peeled() {
+            Self::Multi(multi) => Some(multi),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+
+use crate::{ErrorReport, ErrorSink, PileResult};
+
+/// Appends every report as one JSON object per line, for Filebeat (or
+/// any shipper tailing files) to collect where nothing can be posted out.
+///
+/// The file is rotated once a line would grow it past `max_bytes` or it
+/// has been written to for longer than `max_age`: it's renamed to
+/// `<name>.<UTC timestamp>` next to it and a new one is started. Only the
+/// `keep` most recent rotated files are kept
+#[derive(Debug)]
+pub struct NdjsonSink {
+    path: PathBuf,
+    max_bytes: u64,
+    max_age: Duration,
+    keep: usize,
+    current: Mutex<Option<Current>>,
+}
+
+#[derive(Debug)]
+struct Current {
+    file: File,
+    size: u64,
+    opened: Instant,
+}
+
+impl NdjsonSink {
+    /// writes to `path`, 10 MiB or a day per file, 7 rotated files kept
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes: 10 * 1024 * 1024,
+            max_age: Duration::from_secs(24 * 60 * 60),
+            keep: 7,
+            current: Mutex::default(),
+        }
+    }
+
+    pub fn max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// rotated files to keep, older ones are deleted
+    pub fn keep(mut self, keep: usize) -> Self {
+        self.keep = keep;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// appends the report, rotating the file first when it's due
+    pub fn write(&self, report: &ErrorReport) -> PileResult {
+        let mut line = serde_json::to_vec(report)?;
+        line.push(b'\n');
+
+        let mut current = self.current.lock().unwrap_or_else(|e| e.into_inner());
+        if current.is_none() {
+            *current = Some(self.open()?);
+        }
+        if let Some(cur) = current.as_ref()
+            && cur.size > 0
+            && (cur.size + line.len() as u64 > self.max_bytes
+                || cur.opened.elapsed() >= self.max_age)
+        {
+            *current = None;
+            self.rotate()?;
+        }
+
+        let cur = match current.as_mut() {
+            Some(cur) => cur,
+            None => current.insert(self.open()?),
+        };
+        cur.file.write_all(&line)?;
+        cur.size += line.len() as u64;
+        Ok(())
+    }
+
+    fn open(&self) -> std::io::Result<Current> {
+        if let Some(dir) = self.path.parent()
+            && !dir.as_os_str().is_empty()
+        {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        Ok(Current {
+            size: file.metadata()?.len(),
+            file,
+            opened: Instant::now(),
+        })
+    }
+
+    fn rotate(&self) -> std::io::Result<()> {
+        let stamp = Utc::now().format("%Y%m%dT%H%M%S%.3fZ");
+        let mut rotated = self.path.clone().into_os_string();
+        rotated.push(format!(".{stamp}"));
+        fs::rename(&self.path, rotated)?;
+        self.prune()
+    }
+
+    /// deletes the oldest rotated files beyond `keep`
+    fn prune(&self) -> std::io::Result<()> {
+        let Some(name) = self.path.file_name().and_then(|n| n.to_str()) else {
+            return Ok(());
+        };
+        let prefix = format!("{name}.");
+        let dir = match self.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+
+        // the timestamps have a fixed width, so names sort by age
+        let mut rotated: Vec<PathBuf> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with(&prefix))
+            })
+            .collect();
+        rotated.sort();
+
+        let excess = rotated.len().saturating_sub(self.keep);
+        for path in &rotated[..excess] {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ErrorSink for NdjsonSink {
+    async fn send(&self, report: &ErrorReport) -> PileResult {
+        self.write(report)
+    }
+}
@@ -0,0 +1,114 @@
+use core::fmt;
+use std::error::Error;
+
+use crate::{ErrPile, TransportError};
+
+/// Why a request never got a response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NetworkFailure {
+    /// the host name could not be resolved
+    Dns,
+    /// the TLS handshake failed, e.g. an expired or untrusted certificate
+    Tls,
+    /// no connection was established in time
+    ConnectTimeout,
+    /// connected, but the response did not arrive in time
+    ReadTimeout,
+    /// the server refused the connection
+    Refused,
+    /// the connection was closed or reset mid request
+    Reset,
+    /// any other connection failure
+    Other,
+}
+
+impl fmt::Display for NetworkFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Dns => "DNS resolution failed",
+            Self::Tls => "TLS handshake failed",
+            Self::ConnectTimeout => "connect timed out",
+            Self::ReadTimeout => "read timed out",
+            Self::Refused => "connection refused",
+            Self::Reset => "connection reset",
+            Self::Other => "connection failed",
+        })
+    }
+}
+
+impl NetworkFailure {
+    /// Classifies a transport error by walking its source chain,
+    /// `None` when the request got a response or failed for other reasons
+    /// (building the request, decoding the body, redirects)
+    pub fn classify(err: &reqwest::Error) -> Option<Self> {
+        if err.is_timeout() {
+            return Some(if is_connect(err) {
+                Self::ConnectTimeout
+            } else {
+                Self::ReadTimeout
+            });
+        }
+
+        if !is_connect(err) && !err.is_request() {
+            return None;
+        }
+
+        let mut source: Option<&(dyn Error + 'static)> = err.source();
+        let mut found = None;
+
+        while let Some(cause) = source {
+            if let Some(io) = cause.downcast_ref::<std::io::Error>() {
+                match io.kind() {
+                    std::io::ErrorKind::ConnectionRefused => return Some(Self::Refused),
+                    std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::BrokenPipe
+                    | std::io::ErrorKind::UnexpectedEof => found = found.or(Some(Self::Reset)),
+                    std::io::ErrorKind::TimedOut => return Some(Self::ConnectTimeout),
+                    _ => {}
+                }
+            }
+
+            // hyper and the TLS backends only expose these as messages
+            let msg = cause.to_string().to_lowercase();
+            if msg.contains("dns error") || msg.contains("failed to lookup address") {
+                return Some(Self::Dns);
+            }
+            if msg.contains("certificate") || msg.contains("tls") || msg.contains("handshake") {
+                return Some(Self::Tls);
+            }
+
+            source = cause.source();
+        }
+
+        if is_connect(err) {
+            Some(found.unwrap_or(Self::Other))
+        } else {
+            found
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn is_connect(err: &reqwest::Error) -> bool {
+    err.is_connect()
+}
+
+/// fetch doesn't tell a failed connection from a failed request
+#[cfg(target_arch = "wasm32")]
+fn is_connect(_: &reqwest::Error) -> bool {
+    false
+}
+
+impl ErrPile {
+    /// why the request never got a response, for transport errors
+    pub fn network_failure(&self) -> Option<NetworkFailure> {
+        match self.peeled() {
+            Self::Transport(transport) => match &**transport {
+                TransportError::Req { source, .. } => NetworkFailure::classify(source),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
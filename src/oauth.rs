@@ -0,0 +1,200 @@
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{ErrPile, redact};
+
+/// error codes of RFC 6749, RFC 8628 and OpenID Connect
+const OAUTH_CODES: &[&str] = &[
+    "invalid_request",
+    "invalid_client",
+    "invalid_grant",
+    "unauthorized_client",
+    "unsupported_grant_type",
+    "invalid_scope",
+    "access_denied",
+    "server_error",
+    "temporarily_unavailable",
+    "consent_required",
+    "interaction_required",
+    "login_required",
+    "authorization_pending",
+    "slow_down",
+    "expired_token",
+];
+
+/// OAuth 2.0 error returned by a token endpoint (RFC 6749 §5.2),
+/// Entra ID adds the `error_codes` and ids
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenError {
+    pub error: String,
+    pub error_description: Option<String>,
+    #[serde(default)]
+    pub error_codes: Vec<u64>,
+    pub trace_id: Option<String>,
+    pub correlation_id: Option<String>,
+}
+
+/// What went wrong while acquiring the token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenErrorKind {
+    /// wrong client id or secret, or the secret expired
+    InvalidClient,
+    /// the scope does not exist or is malformed
+    InvalidScope,
+    /// an admin has to grant consent to the app
+    ConsentRequired,
+    /// the refresh token or authorization code is no longer valid
+    InvalidGrant,
+    /// the app may not use this grant type
+    UnauthorizedClient,
+    /// the token endpoint is down, try again later
+    TemporarilyUnavailable,
+    Other,
+}
+
+impl TokenError {
+    /// parses the token endpoint response body, `None` when the body
+    /// is not an OAuth error
+    pub fn from_body(body: &Value) -> Option<Self> {
+        // plenty of APIs send `{"error": "Not Found"}`, only the
+        // registered OAuth codes are trusted
+        let code = body.get("error")?.as_str()?;
+        if !OAUTH_CODES.contains(&code) {
+            return None;
+        }
+        Self::deserialize(body).ok()
+    }
+
+    pub fn kind(&self) -> TokenErrorKind {
+        match self.error.as_str() {
+            "invalid_client" => TokenErrorKind::InvalidClient,
+            "invalid_scope" => TokenErrorKind::InvalidScope,
+            "consent_required" | "interaction_required" => TokenErrorKind::ConsentRequired,
+            "invalid_grant" => TokenErrorKind::InvalidGrant,
+            "unauthorized_client" => TokenErrorKind::UnauthorizedClient,
+            "temporarily_unavailable" | "server_error" => TokenErrorKind::TemporarilyUnavailable,
+            _ => TokenErrorKind::Other,
+        }
+    }
+}
+
+impl TokenErrorKind {
+    /// what the operator should do about it
+    pub fn remedy(&self) -> &'static str {
+        match self {
+            Self::InvalidClient => "check the client id and rotate the client secret",
+            Self::InvalidScope => "check the requested scopes",
+            Self::ConsentRequired => "grant admin consent for the app registration",
+            Self::InvalidGrant => "sign in again to get a new grant",
+            Self::UnauthorizedClient => "enable the grant type on the app registration",
+            Self::TemporarilyUnavailable => "retry later",
+            Self::Other => "see the error description",
+        }
+    }
+}
+
+impl fmt::Display for TokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.error)?;
+        if let Some(description) = &self.error_description {
+            // Entra ID descriptions carry trace ids on extra lines
+            let first_line = description.lines().next().unwrap_or_default();
+            write!(f, " - {}", redact(first_line))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TokenError {}
+
+impl ErrPile {
+    /// kind of token acquisition failure, if this is one
+    pub fn token_error_kind(&self) -> Option<TokenErrorKind> {
+        match self.peeled() {
+            Self::TokenAcquisition(err) => Some(err.kind()),
+            _ => None,
+        }
+    }
+}
+
+/// Entra ID `AADSTS#####` error code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AadstsCode(pub u32);
+
+/// Meaning of the `AADSTS` codes we run into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AadstsKind {
+    InvalidClientSecret,
+    ExpiredClientSecret,
+    ConditionalAccessBlocked,
+    MfaRequired,
+    ConsentRequired,
+    /// the app, user or tenant belongs to another tenant
+    TenantMismatch,
+    TenantNotFound,
+    AppNotFound,
+    InvalidCredentials,
+    AccountLocked,
+    ClockSkew,
+    Unknown,
+}
+
+impl AadstsCode {
+    const KNOWN: &[(u32, AadstsKind)] = &[
+        (7000215, AadstsKind::InvalidClientSecret),
+        (7000222, AadstsKind::ExpiredClientSecret),
+        (53003, AadstsKind::ConditionalAccessBlocked),
+        (50076, AadstsKind::MfaRequired),
+        (50079, AadstsKind::MfaRequired),
+        (65001, AadstsKind::ConsentRequired),
+        (50020, AadstsKind::TenantMismatch),
+        (700016, AadstsKind::AppNotFound),
+        (500011, AadstsKind::TenantMismatch),
+        (90002, AadstsKind::TenantNotFound),
+        (50126, AadstsKind::InvalidCredentials),
+        (50053, AadstsKind::AccountLocked),
+        (700024, AadstsKind::ClockSkew),
+    ];
+
+    /// first `AADSTS` code in the text
+    pub fn find(text: &str) -> Option<Self> {
+        text.match_indices("AADSTS").find_map(|(idx, prefix)| {
+            let digits: String = text[idx + prefix.len()..]
+                .chars()
+                .take_while(char::is_ascii_digit)
+                .collect();
+            digits.parse().ok().map(Self)
+        })
+    }
+
+    pub fn kind(&self) -> AadstsKind {
+        Self::KNOWN
+            .iter()
+            .find(|(code, _)| *code == self.0)
+            .map_or(AadstsKind::Unknown, |(_, kind)| *kind)
+    }
+}
+
+impl fmt::Display for AadstsCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AADSTS{}", self.0)
+    }
+}
+
+impl ErrPile {
+    /// `AADSTS` code of an Entra ID failure, taken from the token
+    /// error or from the text of the error
+    pub fn aadsts_code(&self) -> Option<AadstsCode> {
+        match self.peeled() {
+            Self::TokenAcquisition(err) => err
+                .error_codes
+                .first()
+                .and_then(|code| u32::try_from(*code).ok().map(AadstsCode))
+                .or_else(|| AadstsCode::find(err.error_description.as_deref()?)),
+            Self::Http(http) => AadstsCode::find(&http.body_snippet),
+            err => AadstsCode::find(&err.to_string()),
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use core::fmt;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::{ErrPile, PileKind, mask_card_data};
+
+/// Wait before asking a resort that is not available again, OPERA keeps
+/// a property closed for the few minutes of its End of Day
+pub const RESORT_NOT_AVAILABLE_DELAY: Duration = Duration::from_secs(300);
+
+/// What went wrong, told by the status and the texts of the response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OhipFailure {
+    /// the token was refused or has expired
+    Auth,
+    /// the integration has no access to the resort or the operation
+    Forbidden,
+    NotFound,
+    RateLimited,
+    /// the resort is closed for its End of Day, not yet activated or
+    /// not enabled for the integration
+    ResortNotAvailable,
+    /// OPERA refused the operation with one of its rule codes: a room
+    /// that is occupied, a reservation that was already checked in, …
+    BusinessRule,
+    /// the request doesn't match the API
+    Validation,
+    /// OPERA Cloud or the gateway failed
+    Unavailable,
+}
+
+/// What to do with the request that failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OhipRetry {
+    /// send the same request again, after the delay when one is known
+    Retry(Option<Duration>),
+    /// get a new OAuth token, then send the request again
+    RefreshToken,
+    /// OPERA will give the same answer until the request changes
+    DoNotRetry,
+}
+
+/// one entry of `o:errorDetails`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OhipErrorDetail {
+    pub code: Option<String>,
+    pub title: Option<String>,
+    pub detail: Option<String>,
+    /// `o:errorPath`, the member of the request the entry is about
+    pub path: Option<String>,
+}
+
+/// Error response of the Oracle Hospitality Integration Platform (OPERA
+/// Cloud REST APIs): a problem document extended with `o:errorCode`,
+/// `o:errorPath` and the `o:errorDetails` array (`o-errors` on some
+/// gateways)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OhipError {
+    pub failure: OhipFailure,
+    pub status: Option<u16>,
+    /// `o:errorCode`, the one of the first detail when the document has
+    /// none
+    pub code: Option<String>,
+    pub title: Option<String>,
+    pub detail: Option<String>,
+    pub errors: Vec<OhipErrorDetail>,
+    pub retry_after: Option<Duration>,
+}
+
+impl OhipError {
+    /// Parses an OHIP error body, `None` when it has none of the `o:`
+    /// members. `status` is the one of the response, the `status` of the
+    /// document wins when present
+    pub fn from_body(status: Option<u16>, json: &Value) -> Option<Self> {
+        let errors = ["o:errorDetails", "o-errors"]
+            .iter()
+            .find_map(|key| json.get(key)?.as_array())
+            .map(|errors| errors.iter().map(detail).collect::<Vec<_>>());
+        let code = text(json, "o:errorCode");
+
+        if errors.is_none() && code.is_none() {
+            return None;
+        }
+
+        let errors = errors.unwrap_or_default();
+        let status = json
+            .get("status")
+            .and_then(Value::as_u64)
+            .and_then(|s| u16::try_from(s).ok())
+            .or(status);
+
+        let mut err = Self {
+            failure: OhipFailure::Validation,
+            status,
+            code: code.or_else(|| errors.iter().find_map(|e| e.code.clone())),
+            title: masked(json, "title"),
+            detail: masked(json, "detail"),
+            errors,
+            retry_after: None,
+        };
+        err.failure = err.classify();
+        Some(err)
+    }
+
+    fn classify(&self) -> OhipFailure {
+        match self.status {
+            Some(401) => OhipFailure::Auth,
+            Some(403) => OhipFailure::Forbidden,
+            Some(429) => OhipFailure::RateLimited,
+            Some(409 | 422) => OhipFailure::BusinessRule,
+            // OPERA answers a closed resort with 400 and its code
+            _ if self.codes().any(|code| {
+                RESORT_NOT_AVAILABLE_CODES
+                    .iter()
+                    .any(|known| code.ends_with(known))
+            }) =>
+            {
+                OhipFailure::ResortNotAvailable
+            }
+            // the gateway's own 404 and 503 only have the texts
+            Some(404 | 503) if self.texts().any(resort_not_available) => {
+                OhipFailure::ResortNotAvailable
+            }
+            Some(404) => OhipFailure::NotFound,
+            Some(500..) => OhipFailure::Unavailable,
+            // the rule violations are 400 with an OPERA code
+            _ if self.code.is_some() => OhipFailure::BusinessRule,
+            _ => OhipFailure::Validation,
+        }
+    }
+
+    fn codes(&self) -> impl Iterator<Item = &str> {
+        self.code
+            .iter()
+            .chain(self.errors.iter().filter_map(|e| e.code.as_ref()))
+            .map(String::as_str)
+    }
+
+    fn texts(&self) -> impl Iterator<Item = &str> {
+        [&self.title, &self.detail]
+            .into_iter()
+            .chain(self.errors.iter().flat_map(|e| [&e.title, &e.detail]))
+            .filter_map(|text| text.as_deref())
+    }
+
+    /// what to do with the request, see `OhipRetry`
+    pub fn retry_guidance(&self) -> OhipRetry {
+        match self.failure {
+            OhipFailure::ResortNotAvailable => {
+                OhipRetry::Retry(Some(self.retry_after.unwrap_or(RESORT_NOT_AVAILABLE_DELAY)))
+            }
+            OhipFailure::RateLimited | OhipFailure::Unavailable => {
+                OhipRetry::Retry(self.retry_after)
+            }
+            OhipFailure::Auth => OhipRetry::RefreshToken,
+            OhipFailure::Forbidden
+            | OhipFailure::NotFound
+            | OhipFailure::BusinessRule
+            | OhipFailure::Validation => OhipRetry::DoNotRetry,
+        }
+    }
+
+    /// category of the error, see `ErrPile::kind`
+    pub fn kind(&self) -> PileKind {
+        match self.failure {
+            OhipFailure::Auth => PileKind::Auth,
+            OhipFailure::Forbidden => PileKind::Permission,
+            OhipFailure::NotFound => PileKind::NotFound,
+            OhipFailure::RateLimited => PileKind::RateLimited,
+            OhipFailure::ResortNotAvailable => PileKind::NotReady,
+            OhipFailure::BusinessRule => PileKind::Conflict,
+            OhipFailure::Validation => PileKind::Validation,
+            OhipFailure::Unavailable => PileKind::Upstream,
+        }
+    }
+}
+
+impl fmt::Display for OhipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("OPERA Cloud refused the request")?;
+        if let Some(code) = &self.code {
+            write!(f, " ({code})")?;
+        }
+
+        let first = self.errors.first();
+        let title = self
+            .title
+            .as_ref()
+            .or_else(|| first.and_then(|e| e.title.as_ref()));
+        let detail = self
+            .detail
+            .as_ref()
+            .or_else(|| first.and_then(|e| e.detail.as_ref()))
+            .filter(|detail| Some(*detail) != title);
+        match (title, detail) {
+            (Some(title), Some(detail)) => write!(f, ": {title}: {detail}"),
+            (Some(text), None) | (None, Some(text)) => write!(f, ": {text}"),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for OhipError {}
+
+impl From<OhipError> for ErrPile {
+    fn from(value: OhipError) -> Self {
+        ErrPile::Ohip(Box::new(value))
+    }
+}
+
+impl ErrPile {
+    /// what to do with a request OPERA Cloud refused
+    pub fn ohip_retry(&self) -> Option<OhipRetry> {
+        match self.peeled() {
+            Self::Ohip(err) => Some(err.retry_guidance()),
+            _ => None,
+        }
+    }
+}
+
+/// the OPERA codes of a resort that is closed for its End of Day or not
+/// active, `OPERA-` prefixed or not
+const RESORT_NOT_AVAILABLE_CODES: &[&str] = &["FOF00086"];
+
+fn resort_not_available(text: &str) -> bool {
+    let text = text.to_ascii_lowercase();
+    ["resort", "hotel", "property"]
+        .iter()
+        .any(|w| text.contains(w))
+        && ["not available", "unavailable", "end of day", "not active"]
+            .iter()
+            .any(|w| text.contains(w))
+}
+
+fn detail(json: &Value) -> OhipErrorDetail {
+    OhipErrorDetail {
+        code: text(json, "o:errorCode"),
+        title: masked(json, "title"),
+        detail: masked(json, "detail"),
+        path: text(json, "o:errorPath"),
+    }
+}
+
+fn text(json: &Value, key: &str) -> Option<String> {
+    json.get(key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(String::from)
+}
+
+/// the texts echo the request, guarantee and deposit cards included
+fn masked(json: &Value, key: &str) -> Option<String> {
+    text(json, key).map(|text| mask_card_data(&text).into_owned())
+}
@@ -0,0 +1,63 @@
+use std::{borrow::Cow, collections::BTreeMap};
+
+use utoipa::{
+    IntoResponses, PartialSchema, ToSchema,
+    openapi::{ContentBuilder, Ref, RefOr, ResponseBuilder, Schema, response::Response},
+};
+
+use crate::{ErrPile, RemoteError};
+
+/// `ErrPile` serializes to a `RemoteError`, so it documents as one
+impl PartialSchema for ErrPile {
+    fn schema() -> RefOr<Schema> {
+        RemoteError::schema()
+    }
+}
+
+impl ToSchema for ErrPile {
+    fn name() -> Cow<'static, str> {
+        RemoteError::name()
+    }
+
+    fn schemas(schemas: &mut Vec<(String, RefOr<Schema>)>) {
+        schemas.push((RemoteError::name().into(), RemoteError::schema()));
+        RemoteError::schemas(schemas);
+    }
+}
+
+/// The statuses handlers answer errors with (see `ErrPile::status_code`),
+/// each with an example body, for `#[utoipa::path(responses(ErrPile))]`
+impl IntoResponses for ErrPile {
+    fn responses() -> BTreeMap<String, RefOr<Response>> {
+        let examples = [
+            ("401", "Missing or invalid credentials", ErrPile::Auth),
+            (
+                "403",
+                "The caller is not allowed to do this",
+                ErrPile::Permission,
+            ),
+            ("409", "The resource is in use elsewhere", ErrPile::InUse),
+            ("503", "Not ready yet, try again later", ErrPile::NotReady),
+            (
+                "500",
+                "Unexpected failure",
+                ErrPile::custom("Something went wrong"),
+            ),
+        ];
+
+        examples
+            .into_iter()
+            .map(|(status, description, err)| {
+                let content = ContentBuilder::new()
+                    .schema(Some(Ref::from_schema_name(RemoteError::name())))
+                    .example(serde_json::to_value(&err).ok())
+                    .build();
+                let response = ResponseBuilder::new()
+                    .description(description)
+                    .content("application/json", content)
+                    .build();
+                (status.to_string(), RefOr::T(response))
+            })
+            .collect()
+    }
+}
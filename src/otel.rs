@@ -0,0 +1,51 @@
+use opentelemetry::{
+    KeyValue,
+    trace::{Span, Status, get_active_span},
+};
+
+use crate::ErrPile;
+
+impl ErrPile {
+    /// Semantic convention attributes of the error: `error.type` (the
+    /// kind), `http.response.status_code` and `db.system` where they apply
+    pub fn otel_attributes(&self) -> Vec<KeyValue> {
+        let mut attributes = vec![KeyValue::new("error.type", self.kind().as_str())];
+        if let Some(status) = self.upstream_status() {
+            attributes.push(KeyValue::new(
+                "http.response.status_code",
+                i64::from(status),
+            ));
+        }
+        #[cfg(feature = "sqlx")]
+        if let Some(system) = self.db_system() {
+            attributes.push(KeyValue::new("db.system", system));
+        }
+        if let Some(code) = self.code() {
+            attributes.push(KeyValue::new("error.code", code));
+        }
+        attributes
+    }
+
+    /// Marks the span as failed: sets the error status and attributes
+    /// and records the error as an `exception` event
+    pub fn record_on_span<S: Span>(&self, span: &mut S) {
+        #[cfg(feature = "metrics")]
+        self.count_reported();
+
+        span.set_attributes(self.otel_attributes());
+        span.record_error(self);
+        span.set_status(Status::error(self.to_string()));
+    }
+
+    /// `record_on_span` for the span active in the current context
+    pub fn record_on_current_span(&self) {
+        #[cfg(feature = "metrics")]
+        self.count_reported();
+
+        get_active_span(|span| {
+            span.set_attributes(self.otel_attributes());
+            span.record_error(self);
+            span.set_status(Status::error(self.to_string()));
+        });
+    }
+}
@@ -0,0 +1,57 @@
+use core::fmt;
+use std::error::Error;
+
+use crate::ErrPile;
+
+/// An error of a dependency that has no variant of its own, kept whole
+/// with the name of its type. Displays as the error
+#[derive(Debug)]
+pub struct OtherError {
+    /// `std::any::type_name` of the error
+    pub type_name: &'static str,
+    pub error: Box<dyn Error + Send + Sync>,
+}
+
+impl OtherError {
+    /// the error, if it is an `E`
+    pub fn downcast_ref<E: Error + 'static>(&self) -> Option<&E> {
+        self.error.downcast_ref()
+    }
+}
+
+impl fmt::Display for OtherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl Error for OtherError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.error.source()
+    }
+}
+
+/// `.pile()` on any error, for the one-off dependency errors not worth a
+/// variant: `csv::Error`, `lopdf::Error`, … An `ErrPile` is handed back
+/// as it is
+///
+/// ```ignore
+/// let rates = reader.deserialize().collect::<Result<Vec<Rate>, _>>().map_err(|e| e.pile())?;
+/// ```
+pub trait IntoPileExt {
+    fn pile(self) -> ErrPile;
+}
+
+impl<E> IntoPileExt for E
+where
+    E: Error + Send + Sync + 'static,
+{
+    fn pile(self) -> ErrPile {
+        let type_name = std::any::type_name::<E>();
+        let error: Box<dyn Error + Send + Sync> = Box::new(self);
+        match error.downcast::<ErrPile>() {
+            Ok(err) => *err,
+            Err(error) => ErrPile::Other(Box::new(OtherError { type_name, error })),
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize, Serializer, ser::SerializeStruct};
+
+use crate::{MultiPile, PileItem, PileIteratorExt, PileResult};
+
+/// How many failures a `PileOutcome` tolerates before it is an error
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OutcomeThreshold {
+    /// any failure
+    #[default]
+    Strict,
+    /// more than this many failures
+    MaxFailures(usize),
+    /// more than this share of the items failing, between 0 and 1
+    MaxFailureRatio(f64),
+}
+
+/// Counts of a `PileOutcome`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutcomeSummary {
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+/// The partial success of a sync job, "83 of 85 rooms synced" being the
+/// normal case rather than a failure. Serialized as
+/// `{succeeded, failed, summary}` for the job history:
+///
+/// ```ignore
+/// let outcome: PileOutcome<RoomId> = rooms.iter().map(sync_room).collect();
+/// tracing::info!(synced = outcome.summary().succeeded, "rooms synced");
+/// let outcome = outcome.into_result(OutcomeThreshold::MaxFailureRatio(0.1))?;
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct PileOutcome<T> {
+    pub succeeded: Vec<T>,
+    pub failed: Vec<PileItem>,
+}
+
+impl<T> Default for PileOutcome<T> {
+    fn default() -> Self {
+        Self {
+            succeeded: Vec::new(),
+            failed: Vec::new(),
+        }
+    }
+}
+
+impl<T> PileOutcome<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// adds the result of the next item, indexed by its position
+    pub fn push(&mut self, result: PileResult<T>) {
+        match result {
+            Ok(value) => self.succeeded.push(value),
+            Err(error) => self.failed.push(PileItem {
+                index: self.summary().total,
+                label: None,
+                error,
+            }),
+        }
+    }
+
+    /// adds the result of the next item, the label naming it on failure
+    pub fn push_labeled<L>(&mut self, label: L, result: PileResult<T>)
+    where
+        L: Into<Cow<'static, str>>,
+    {
+        match result {
+            Ok(value) => self.succeeded.push(value),
+            Err(error) => self.failed.push(PileItem {
+                index: self.summary().total,
+                label: Some(label.into()),
+                error,
+            }),
+        }
+    }
+
+    pub fn summary(&self) -> OutcomeSummary {
+        OutcomeSummary {
+            total: self.succeeded.len() + self.failed.len(),
+            succeeded: self.succeeded.len(),
+            failed: self.failed.len(),
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// whether the failures go past the threshold
+    pub fn exceeds(&self, threshold: OutcomeThreshold) -> bool {
+        let summary = self.summary();
+        match threshold {
+            OutcomeThreshold::Strict => summary.failed > 0,
+            OutcomeThreshold::MaxFailures(max) => summary.failed > max,
+            OutcomeThreshold::MaxFailureRatio(ratio) => {
+                summary.total > 0 && summary.failed as f64 / summary.total as f64 > ratio
+            }
+        }
+    }
+
+    /// The outcome as is, failures included, while they stay within the
+    /// threshold, `ErrPile::Multi` with every failure past it
+    pub fn into_result(self, threshold: OutcomeThreshold) -> PileResult<Self> {
+        if !self.exceeds(threshold) {
+            return Ok(self);
+        }
+
+        let mut failures = MultiPile::new();
+        for item in self.failed {
+            match item.label {
+                Some(label) => failures.push_labeled(item.index, label, item.error),
+                None => failures.push(item.index, item.error),
+            }
+        }
+        Err(failures.into())
+    }
+}
+
+impl<T> FromIterator<PileResult<T>> for PileOutcome<T> {
+    fn from_iter<I: IntoIterator<Item = PileResult<T>>>(iter: I) -> Self {
+        let (succeeded, failed) = iter.into_iter().collect_piles();
+        Self {
+            succeeded,
+            failed: failed.into_items(),
+        }
+    }
+}
+
+impl<T: Serialize> Serialize for PileOutcome<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("PileOutcome", 3)?;
+        state.serialize_field("succeeded", &self.succeeded)?;
+        state.serialize_field("failed", &self.failed)?;
+        state.serialize_field("summary", &self.summary())?;
+        state.end()
+    }
+}
@@ -0,0 +1,109 @@
+use std::{
+    any::Any,
+    cell::RefCell,
+    future::Future,
+    panic::{AssertUnwindSafe, catch_unwind},
+    sync::Once,
+    task::Poll,
+};
+
+use crate::{ErrPile, PanicError, PileResult};
+
+thread_local! {
+    /// where the last panic of the thread happened, recorded by the hook
+    /// and only read by the `catch` that the panic unwound to
+    static LOCATION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// the location recorded since the last call, cleared either way so a
+/// panic caught elsewhere can't be attached to the next one
+fn take_location() -> Option<String> {
+    LOCATION.with(|cell| cell.borrow_mut().take())
+}
+
+/// chains a hook recording the panic location in front of the current
+/// one, the payload alone doesn't carry it
+fn record_locations() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let location = info.location().map(|l| l.to_string());
+            LOCATION.with(|cell| *cell.borrow_mut() = location);
+            previous(info);
+        }));
+    });
+}
+
+impl ErrPile {
+    /// `ErrPile::Panic` from the payload of a caught panic, without a
+    /// location: only `pile_catch` and `spawn_pile` know where the panic
+    /// they caught happened
+    pub fn from_panic(payload: &(dyn Any + Send)) -> Self {
+        Self::Panic(Box::new(PanicError {
+            message: panic_message(payload),
+            location: None,
+        }))
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|m| m.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Box<dyn Any>".into())
+}
+
+/// runs `f` with the location cleared before and taken after, a panic
+/// being the error
+fn catch_located<R, F: FnOnce() -> R>(f: F) -> Result<R, ErrPile> {
+    take_location();
+    let result = catch_unwind(AssertUnwindSafe(f));
+    let location = take_location();
+    result.map_err(|payload| {
+        ErrPile::Panic(Box::new(PanicError {
+            message: panic_message(&*payload),
+            location,
+        }))
+    })
+}
+
+/// runs `f`, a panic being the error
+pub(crate) fn catch<R, F: FnOnce() -> R>(f: F) -> Result<R, ErrPile> {
+    record_locations();
+    catch_located(f)
+}
+
+/// polls `fut`, a panic in any of the polls being the error
+pub(crate) async fn catch_async<F: Future>(fut: F) -> Result<F::Output, ErrPile> {
+    record_locations();
+    let mut fut = std::pin::pin!(fut);
+    std::future::poll_fn(|cx| match catch_located(|| fut.as_mut().poll(cx)) {
+        Ok(poll) => poll.map(Ok),
+        Err(err) => Poll::Ready(Err(err)),
+    })
+    .await
+}
+
+/// Runs `f`, turning a panic into `ErrPile::Panic` instead of unwinding
+/// further, for code we don't trust to not panic (Python hooks, report
+/// generators). The default hook still prints the panic.
+///
+/// The closure is assumed unwind safe: state it shares with the caller
+/// may be left half updated
+pub fn pile_catch<T, F>(f: F) -> PileResult<T>
+where
+    F: FnOnce() -> PileResult<T>,
+{
+    catch(f)?
+}
+
+/// `pile_catch` for a future, a panic in any of its polls ends it with
+/// `ErrPile::Panic`
+pub async fn pile_catch_async<T, F>(fut: F) -> PileResult<T>
+where
+    F: Future<Output = PileResult<T>>,
+{
+    catch_async(fut).await?
+}
@@ -0,0 +1,68 @@
+use reqwest::{
+    StatusCode,
+    header::{CONTENT_TYPE, HeaderMap, HeaderValue},
+};
+use serde_json::Value;
+
+use crate::{ErrPile, ProblemDetails};
+
+/// Recognizes an error body on its own, without the status and the
+/// headers of the response: Graph and Document Intelligence errors,
+/// problem documents, field errors (of documents with a `status` of 400
+/// or 422), OAuth and GraphQL errors, with the
+/// `xml` feature SOAP faults and OTA `<Errors>`, and with the `channel`
+/// feature the Booking.com envelopes carrying a RUID and the Expedia
+/// QuickConnect messages, and with the `sms` feature the Twilio-style
+/// SMS gateway errors. The shapes are the
+/// ones `error_for_pile` decodes, `None` means the body would have ended
+/// up as a plain `HttpError`
+///
+/// The payloads under `tests/fixtures/payloads` are written after the
+/// documented error shapes of the services, not captured from them; each
+/// release checks they are all still recognized
+pub fn parse_known_error_payload(body: &[u8]) -> Option<ErrPile> {
+    let mut headers = HeaderMap::new();
+
+    // no status either, a lock is only told by its code
+    let mut status = StatusCode::OK;
+
+    #[cfg(feature = "channel")]
+    if let Some(err) = crate::ChannelError::recognize(body) {
+        return Some(err.into());
+    }
+
+    match serde_json::from_slice::<Value>(body) {
+        Ok(json) => {
+            // documents naming their status, the field errors are only
+            // read from a 400 or 422
+            if let Some(named) = json
+                .get("status")
+                .and_then(Value::as_u64)
+                .and_then(|code| StatusCode::from_u16(u16::try_from(code).ok()?).ok())
+            {
+                status = named;
+            }
+            // without the content type a problem document is told by its
+            // members
+            if looks_like_problem(&json) {
+                headers.insert(
+                    CONTENT_TYPE,
+                    HeaderValue::from_static(ProblemDetails::CONTENT_TYPE),
+                );
+            }
+            ErrPile::decode_json(status, &headers, None, &json)
+        }
+        Err(_) => {
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/xml"));
+            ErrPile::decode_text(status, &headers, body)
+        }
+    }
+}
+
+/// `status` and `title` or `type`, what RFC 9457 documents are sent with
+fn looks_like_problem(json: &Value) -> bool {
+    json.get("status").is_some_and(Value::is_u64)
+        && ["type", "title"]
+            .iter()
+            .any(|key| json.get(key).is_some_and(Value::is_string))
+}
@@ -0,0 +1,107 @@
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ErrPile;
+
+/// Why the card was declined, what decides the next step at the front
+/// desk: ask for another card, have the guest confirm with their bank,
+/// or send them through 3-D Secure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeclineCategory {
+    InsufficientFunds,
+    /// the issuer declined without a reason (`05`)
+    DoNotHonor,
+    ExpiredCard,
+    FraudSuspected,
+    /// the issuer wants the cardholder authenticated (SCA)
+    ThreeDsRequired,
+    Other,
+}
+
+impl DeclineCategory {
+    /// Category of an ISO 8583 response code, as the processors pass
+    /// them through. `1A` and `65` are the Visa and Mastercard answers
+    /// asking for strong customer authentication
+    pub fn from_processor_code(code: &str) -> Self {
+        match code.trim() {
+            "51" | "61" => Self::InsufficientFunds,
+            "05" | "5" => Self::DoNotHonor,
+            "54" | "33" => Self::ExpiredCard,
+            "34" | "59" | "41" | "43" | "63" => Self::FraudSuspected,
+            "1A" | "65" => Self::ThreeDsRequired,
+            _ => Self::Other,
+        }
+    }
+
+    /// Whether the same card may go through later, once the guest
+    /// topped it up or talked to their bank. Worth asking the guest to
+    /// try again, never retried without them: charging a declined card
+    /// again gets it flagged. A 3-D Secure challenge needs the cardholder
+    /// and the others need another card
+    pub fn is_soft(&self) -> bool {
+        matches!(self, Self::InsufficientFunds | Self::DoNotHonor)
+    }
+
+    /// the next step, shown to the guest or the front desk
+    pub fn user_message(&self) -> &'static str {
+        match self {
+            Self::InsufficientFunds => {
+                "The card was declined for insufficient funds, please use another card"
+            }
+            Self::DoNotHonor => {
+                "The card was declined by the bank, please contact the bank or use another card"
+            }
+            Self::ExpiredCard => "The card has expired, please use another card",
+            Self::FraudSuspected => "The card was declined, please use another card",
+            Self::ThreeDsRequired => {
+                "The bank requires the cardholder to confirm the payment (3-D Secure)"
+            }
+            Self::Other => "The card was declined, please use another card",
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InsufficientFunds => "insufficient_funds",
+            Self::DoNotHonor => "do_not_honor",
+            Self::ExpiredCard => "expired_card",
+            Self::FraudSuspected => "fraud_suspected",
+            Self::ThreeDsRequired => "three_ds_required",
+            Self::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for DeclineCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ErrPile {
+    /// Decline answered by the payment processor, categorized from its
+    /// response code. Not `retryable`, whatever the category: the guest
+    /// decides about another attempt, see `DeclineCategory::is_soft`
+    pub fn payment_declined<C>(processor_code: C) -> Self
+    where
+        C: Into<String>,
+    {
+        let processor_code = processor_code.into();
+        let category = DeclineCategory::from_processor_code(&processor_code);
+        Self::PaymentDeclined {
+            category,
+            processor_code: processor_code.into(),
+            retryable: false,
+        }
+    }
+
+    /// category of a payment decline
+    pub fn decline_category(&self) -> Option<DeclineCategory> {
+        match self.peeled() {
+            Self::PaymentDeclined { category, .. } => Some(*category),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use core::fmt;
+
+use crate::{ErrPile, PileKind, Severity, TransportError, redact, redact_url};
+
+/// Multi-line rendering of an error, see `ErrPile::pretty`
+///
+/// ```text
+/// Error: Failed to fetch page 2 of the listing
+///
+/// Caused by:
+///     0: Request responded with an error (404 Not Found)
+///     1: Not Found (404) GET https://graph.microsoft.com/v1.0/me itemNotFound: …
+///
+/// Location: GET https://graph.microsoft.com/v1.0/me (page 2)
+///
+/// Metadata:
+///     kind: upstream
+///     severity: error
+///     code: itemNotFound
+///     http_status: 404
+/// ```
+///
+/// `color` adds ANSI colors for terminals, `auto_color` honors `NO_COLOR`
+#[derive(Clone, Copy)]
+pub struct PrettyError<'a> {
+    err: &'a ErrPile,
+    color: bool,
+}
+
+const RED: &str = "\x1b[1;31m";
+const DIM: &str = "\x1b[2m";
+const YELLOW: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+impl PrettyError<'_> {
+    /// ANSI colors: the error in red, the sources dimmed, the hint in
+    /// yellow
+    pub fn color(mut self, enabled: bool) -> Self {
+        self.color = enabled;
+        self
+    }
+
+    /// colors when stderr is a terminal and `NO_COLOR` isn't set, for
+    /// the CLI tools
+    pub fn auto_color(self) -> Self {
+        use std::io::IsTerminal;
+
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        self.color(!no_color && std::io::stderr().is_terminal())
+    }
+
+    /// `text` wrapped in the escape code when colors are on
+    fn paint<'t>(&self, code: &str, text: &'t str) -> std::borrow::Cow<'t, str> {
+        if self.color {
+            format!("{code}{text}{RESET}").into()
+        } else {
+            text.into()
+        }
+    }
+}
+
+impl ErrPile {
+    /// The error, its sources as an indented "Caused by:" list, where it
+    /// happened, a hint and its metadata, one item per line, for logs
+    /// and CLI output. Messages go through `redact`
+    pub fn pretty(&self) -> PrettyError<'_> {
+        PrettyError {
+            err: self,
+            color: false,
+        }
+    }
+
+    /// where the error happened, as far as it knows: the request that
+    /// failed or the service that sent it
+    pub fn location(&self) -> Option<String> {
+        match self.peeled() {
+            Self::Http(http) => {
+                let url = http.url.as_ref().map(redact_url)?;
+                Some(match &http.method {
+                    Some(method) => format!("{method} {url}"),
+                    None => url,
+                })
+            }
+            Self::Transport(transport) => {
+                let TransportError::Req { method, url, .. } = &**transport else {
+                    return None;
+                };
+                let url = redact(url.as_deref()?).into_owned();
+                Some(match method {
+                    Some(method) => format!("{method} {url}"),
+                    None => url,
+                })
+            }
+            Self::Remote(remote) => remote.origin.as_ref().map(|o| format!("service {o}")),
+            Self::Panic(panic) => panic.location.clone(),
+            Self::Task(task) => Some(match task.source.location() {
+                Some(location) => format!("{location} (task {})", task.name),
+                None => format!("task {}", task.name),
+            }),
+            Self::Page { index, source } => Some(match source.location() {
+                Some(location) => format!("{location} (page {index})"),
+                None => format!("page {index}"),
+            }),
+            #[cfg(feature = "multipart")]
+            Self::Upload(upload) => upload.source.location(),
+            _ => None,
+        }
+    }
+
+    /// what to do about the error, shown as "Hint:"
+    fn pretty_hint(&self) -> Option<String> {
+        if let Some(retry_after) = self.retry_after() {
+            return Some(format!("the service asked to retry in {retry_after:?}"));
+        }
+        if self.is_transient() {
+            return Some("this is usually temporary, retrying should help".into());
+        }
+        match self.kind() {
+            PileKind::Config => Some("check the configuration of the service".into()),
+            PileKind::Auth if self.severity() == Severity::Critical => {
+                Some("check the client id and secret of the app registration".into())
+            }
+            _ => None,
+        }
+    }
+
+    /// name/value pairs shown under "Metadata:"
+    fn pretty_metadata(&self) -> Vec<(&'static str, String)> {
+        let mut metadata = vec![
+            ("kind", self.kind().to_string()),
+            ("severity", self.severity().to_string()),
+        ];
+        if let Some(code) = self.code() {
+            metadata.push(("code", code));
+        }
+        if let Self::Other(other) = self.peeled() {
+            metadata.push(("type", other.type_name.into()));
+        }
+        if let Some(status) = self.upstream_status() {
+            metadata.push(("http_status", status.to_string()));
+        }
+        if self.is_transient() {
+            metadata.push(("transient", "true".into()));
+        }
+        if let Some(retry_after) = self.retry_after() {
+            metadata.push(("retry_after", format!("{retry_after:?}")));
+        }
+        metadata.extend(self.correlation_ids());
+        metadata
+    }
+}
+
+/// writes `text` with every line after the first indented by `indent`
+fn write_indented(f: &mut fmt::Formatter<'_>, text: &str, indent: &str) -> fmt::Result {
+    for (i, line) in text.lines().enumerate() {
+        if i > 0 {
+            write!(f, "\n{indent}")?;
+        }
+        f.write_str(line)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for PrettyError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let chain = self.err.redacted_chain();
+        write!(f, "{}", self.paint(RED, "Error:"))?;
+        f.write_str(" ")?;
+        write_indented(f, &self.paint(RED, &chain[0]), "       ")?;
+
+        let causes = &chain[1..];
+        if !causes.is_empty() {
+            f.write_str("\n\nCaused by:")?;
+            for (i, cause) in causes.iter().enumerate() {
+                let cause = self.paint(DIM, cause);
+                if causes.len() == 1 {
+                    f.write_str("\n    ")?;
+                    write_indented(f, &cause, "    ")?;
+                } else {
+                    write!(f, "\n    {i}: ")?;
+                    write_indented(f, &cause, "       ")?;
+                }
+            }
+        }
+
+        if let Some(location) = self.err.location() {
+            write!(f, "\n\nLocation: {location}")?;
+        }
+
+        let frames = self.err.context_frames();
+        if !frames.is_empty() {
+            f.write_str("\n\nContext:")?;
+            for frame in frames {
+                write!(f, "\n    {frame}")?;
+            }
+        }
+
+        if let Some(hint) = self.err.pretty_hint() {
+            write!(f, "\n\n{}", self.paint(YELLOW, &format!("Hint: {hint}")))?;
+        }
+
+        f.write_str("\n\nMetadata:")?;
+        for (name, value) in self.err.pretty_metadata() {
+            write!(f, "\n    {name}: {value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// same as `Display`, so `{:?}` in a `main` returning the report stays
+/// readable
+impl fmt::Debug for PrettyError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
@@ -0,0 +1,134 @@
+use core::fmt;
+use std::sync::{
+    RwLock,
+    atomic::{AtomicBool, Ordering},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{ErrPile, mask_card_data};
+
+static TYPE_BASE: RwLock<Option<String>> = RwLock::new(None);
+static PROBLEM_RESPONSES: AtomicBool = AtomicBool::new(false);
+
+/// Prefix of the `type` URIs of `ErrPile::to_problem_details`, the code
+/// is appended: `https://errors.example.com/` gives
+/// `https://errors.example.com/not_found`. `urn:error-pile:` by default
+pub fn set_problem_type_base<B: Into<String>>(base: B) {
+    *TYPE_BASE.write().unwrap_or_else(|e| e.into_inner()) = Some(base.into());
+}
+
+/// the prefix set with `set_problem_type_base`
+pub fn problem_type_base() -> String {
+    TYPE_BASE
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_else(|| "urn:error-pile:".into())
+}
+
+/// Makes the web integrations (axum, actix, warp, Rocket,
+/// `PileErrorLayer`) answer with `application/problem+json` documents
+/// instead of the `ErrorEnvelope`
+pub fn set_problem_responses(enabled: bool) {
+    PROBLEM_RESPONSES.store(enabled, Ordering::Relaxed);
+}
+
+pub fn problem_responses() -> bool {
+    PROBLEM_RESPONSES.load(Ordering::Relaxed)
+}
+
+/// RFC 7807 problem details (`application/problem+json`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProblemDetails {
+    /// URI identifying the problem type
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub type_uri: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    /// any other member of the problem document
+    #[serde(flatten)]
+    pub extensions: Map<String, Value>,
+}
+
+impl ProblemDetails {
+    pub const CONTENT_TYPE: &'static str = "application/problem+json";
+
+    /// checks if the content type is `application/problem+json`
+    pub fn is_problem_content_type(content_type: &str) -> bool {
+        content_type
+            .split(';')
+            .next()
+            .is_some_and(|mime| mime.trim().eq_ignore_ascii_case(Self::CONTENT_TYPE))
+    }
+
+    pub fn extension(&self, key: &str) -> Option<&Value> {
+        self.extensions.get(key)
+    }
+}
+
+impl fmt::Display for ProblemDetails {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let title = self
+            .title
+            .as_deref()
+            .or(self.type_uri.as_deref())
+            .unwrap_or("Problem");
+        f.write_str(title)?;
+
+        if let Some(status) = self.status {
+            write!(f, " ({status})")?;
+        }
+
+        if let Some(detail) = &self.detail {
+            write!(f, ": {}", mask_card_data(detail))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ProblemDetails {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+impl ErrPile {
+    /// RFC 7807 document for a client: the `type` URI of the code (see
+    /// `set_problem_type_base`), the reason phrase of `status_code` as
+    /// title, `user_message` as detail, and `code`, `correlation_id` and
+    /// `retry_after` (seconds) as extensions
+    pub fn to_problem_details(&self) -> ProblemDetails {
+        let code = self.kind().as_str();
+        let status = self.status_code();
+
+        let mut extensions = Map::new();
+        extensions.insert("code".into(), code.into());
+        if let Some((_, id)) = self.correlation_ids().into_iter().next() {
+            extensions.insert("correlation_id".into(), id.into());
+        }
+        if let Some(retry_after) = self.retry_after_secs() {
+            extensions.insert("retry_after".into(), retry_after.into());
+        }
+
+        ProblemDetails {
+            type_uri: Some(format!("{}{code}", problem_type_base())),
+            title: reqwest::StatusCode::from_u16(status)
+                .ok()
+                .and_then(|s| s.canonical_reason())
+                .map(String::from),
+            status: Some(status),
+            detail: Some(self.user_message()),
+            instance: None,
+            extensions,
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use warp::{
+    Rejection,
+    filters::body::BodyDeserializeError,
+    reject::{InvalidHeader, InvalidQuery, MissingHeader},
+};
+
+use crate::{ErrPile, FieldError};
+
+/// handlers reject with the error (`?` converts it), `recover_pile`
+/// answers it
+impl warp::reject::Reject for ErrPile {}
+
+/// Recovery of the warp routes: an `ErrPile` rejection is answered like
+/// the axum integration does (status, `Retry-After` and body) and a body,
+/// query or header warp couldn't read becomes a `Validation` error.
+/// The rest (no route, wrong method) is handed back to warp
+///
+/// ```ignore
+/// let routes = reservations.or(folios).recover(recover_pile);
+/// ```
+pub async fn recover_pile(rejection: Rejection) -> Result<http::Response<String>, Rejection> {
+    if let Some(err) = rejection.find::<ErrPile>() {
+        err.log_response();
+        return Ok(err.http_response(None));
+    }
+
+    let invalid = if let Some(err) = rejection.find::<BodyDeserializeError>() {
+        FieldError::new("body", "invalid", err.to_string())
+    } else if let Some(err) = rejection.find::<InvalidQuery>() {
+        FieldError::new("query", "invalid", err.to_string())
+    } else if let Some(err) = rejection.find::<MissingHeader>() {
+        FieldError::new(err.name(), "required", err.to_string())
+    } else if let Some(err) = rejection.find::<InvalidHeader>() {
+        FieldError::new(err.name(), "invalid", err.to_string())
+    } else {
+        return Err(rejection);
+    };
+    Ok(ErrPile::validation([invalid]).http_response(None))
+}
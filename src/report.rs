@@ -0,0 +1,507 @@
+use core::fmt;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, VecDeque},
+    sync::{
+        Arc, Mutex, RwLock,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Serializer, ser::SerializeMap};
+use tokio::{sync::Notify, task::JoinHandle};
+
+use crate::{
+    ErrPile, PiiScrubbing, PileKind, PileResult, Severity, pii_scrubbing, redact, scrub_pii,
+};
+
+/// Owned summary of an error, what the sinks receive. The messages
+/// have gone through `redact`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ErrorReport {
+    pub kind: PileKind,
+    pub severity: Severity,
+    pub code: Option<String>,
+    pub message: String,
+    /// see `ErrPile::user_message`
+    pub user_message: String,
+    /// the sources of the error, outermost first
+    pub chain: Vec<String>,
+    pub fingerprint: String,
+    pub transient: bool,
+    /// serialized as an object, `{"request_id": "…"}`
+    #[serde(serialize_with = "serialize_pairs")]
+    pub correlation_ids: Vec<(String, String)>,
+    /// how many occurrences the report stands for, more than one for
+    /// the summaries of `ErrorReporter`
+    pub count: u64,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    /// guest data was scrubbed out of the messages, see
+    /// `set_pii_scrubbing`
+    pub pii_scrubbed: bool,
+}
+
+impl ErrorReport {
+    pub fn new(err: &ErrPile) -> Self {
+        let now = Utc::now();
+        let pii_scrubbed = pii_scrubbing() != PiiScrubbing::Off
+            && err
+                .chain()
+                .map(|err| err.to_string())
+                .chain([err.user_message()])
+                .any(|text| matches!(scrub_pii(&text), Cow::Owned(_)));
+
+        Self {
+            kind: err.kind(),
+            severity: err.severity(),
+            code: err.code(),
+            message: err.redacted(),
+            user_message: redact(&err.user_message()).into_owned(),
+            chain: err.redacted_chain().into_iter().skip(1).collect(),
+            fingerprint: err.fingerprint(),
+            transient: err.is_transient(),
+            correlation_ids: err
+                .correlation_ids()
+                .into_iter()
+                .map(|(name, id)| (name.to_string(), id))
+                .collect(),
+            count: 1,
+            first_seen: now,
+            last_seen: now,
+            pii_scrubbed,
+        }
+    }
+}
+
+/// `message`, with `(seen N times since …)` for summaries
+impl fmt::Display for ErrorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)?;
+        if self.count > 1 {
+            write!(
+                f,
+                " (seen {} times since {})",
+                self.count,
+                self.first_seen.format("%Y-%m-%d %H:%M:%S UTC")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn serialize_pairs<S: Serializer>(pairs: &[(String, String)], ser: S) -> Result<S::Ok, S::Error> {
+    let mut map = ser.serialize_map(Some(pairs.len()))?;
+    for (name, value) in pairs {
+        map.serialize_entry(name, value)?;
+    }
+    map.end()
+}
+
+impl From<&ErrPile> for ErrorReport {
+    fn from(err: &ErrPile) -> Self {
+        Self::new(err)
+    }
+}
+
+/// Where reported errors end up (a chat webhook, Sentry, a table)
+#[async_trait::async_trait]
+pub trait ErrorSink: Send + Sync {
+    async fn send(&self, report: &ErrorReport) -> PileResult;
+
+    /// called by `ErrorQueue` every `flush_interval`, for the sinks
+    /// holding reports back until some time has passed
+    async fn tick(&self) -> PileResult {
+        Ok(())
+    }
+
+    /// called by `ErrorQueue` once the last queued reports went out, for
+    /// the sinks holding reports back
+    async fn shutdown(&self) -> PileResult {
+        Ok(())
+    }
+}
+
+/// occurrences of one fingerprint within the current window
+struct Seen {
+    window_start: Instant,
+    /// occurrences not sent yet
+    suppressed: u64,
+    first_seen: DateTime<Utc>,
+    last_seen: DateTime<Utc>,
+    last: ErrorReport,
+}
+
+/// Wraps a sink and drops repeated errors: the first occurrence of a
+/// fingerprint is sent, the next ones within `window` are only counted
+/// and sent as one "seen N times" summary once the window is over (or
+/// on `flush`). A flapping SFTP connection ends up as one report per
+/// window instead of one per attempt.
+///
+/// The summaries go out with the next report, on `flush_expired`, or,
+/// behind an `ErrorQueue`, on its ticks and its shutdown
+pub struct ErrorReporter<S> {
+    sink: S,
+    window: Duration,
+    seen: Mutex<HashMap<String, Seen>>,
+}
+
+impl<S: ErrorSink> ErrorReporter<S> {
+    pub fn new(sink: S, window: Duration) -> Self {
+        Self {
+            sink,
+            window,
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    /// sends the error unless the same fingerprint was sent within the
+    /// window
+    pub async fn report(&self, err: &ErrPile) -> PileResult {
+        self.submit(ErrorReport::new(err)).await
+    }
+
+    async fn submit(&self, report: ErrorReport) -> PileResult {
+        let send = {
+            let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+            self.track(&mut seen, report)
+        };
+
+        match send {
+            Some(report) => self.sink.send(&report).await,
+            None => Ok(()),
+        }
+    }
+
+    /// sends the summaries of the suppressed occurrences right away,
+    /// e.g. on shutdown
+    pub async fn flush(&self) -> PileResult {
+        let pending: Vec<ErrorReport> = {
+            let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+            seen.drain()
+                .filter_map(|(_, seen)| seen.summary())
+                .collect()
+        };
+
+        self.send_all(pending).await
+    }
+
+    /// sends the summaries of the windows that are over, the queue
+    /// calls it every `flush_interval`
+    pub async fn flush_expired(&self) -> PileResult {
+        let now = Instant::now();
+        let pending: Vec<ErrorReport> = {
+            let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+            let expired: Vec<String> = seen
+                .iter()
+                .filter(|(_, s)| now.duration_since(s.window_start) >= self.window)
+                .map(|(fingerprint, _)| fingerprint.clone())
+                .collect();
+            expired
+                .into_iter()
+                .filter_map(|fingerprint| seen.remove(&fingerprint)?.summary())
+                .collect()
+        };
+
+        self.send_all(pending).await
+    }
+
+    async fn send_all(&self, reports: Vec<ErrorReport>) -> PileResult {
+        for report in reports {
+            self.sink.send(&report).await?;
+        }
+        Ok(())
+    }
+
+    /// what to send for the occurrence, if anything
+    fn track(&self, seen: &mut HashMap<String, Seen>, report: ErrorReport) -> Option<ErrorReport> {
+        let now = Instant::now();
+        // fingerprints that went quiet
+        seen.retain(|_, s| s.suppressed > 0 || now.duration_since(s.window_start) < self.window);
+
+        match seen.get_mut(&report.fingerprint) {
+            Some(entry) if now.duration_since(entry.window_start) < self.window => {
+                entry.suppressed += 1;
+                entry.last_seen = report.last_seen;
+                entry.last = report;
+                None
+            }
+            Some(entry) => {
+                // the window is over, this occurrence carries the
+                // suppressed ones along
+                let mut report = report;
+                report.count += entry.suppressed;
+                report.first_seen = entry.first_seen;
+                *entry = Seen::new(now, &report);
+                Some(report)
+            }
+            None => {
+                seen.insert(report.fingerprint.clone(), Seen::new(now, &report));
+                Some(report)
+            }
+        }
+    }
+}
+
+/// lets a reporter sit behind the `ErrorQueue`
+#[async_trait::async_trait]
+impl<S: ErrorSink> ErrorSink for ErrorReporter<S> {
+    async fn send(&self, report: &ErrorReport) -> PileResult {
+        self.submit(report.clone()).await
+    }
+
+    async fn tick(&self) -> PileResult {
+        self.flush_expired().await?;
+        self.sink.tick().await
+    }
+
+    async fn shutdown(&self) -> PileResult {
+        self.flush().await?;
+        self.sink.shutdown().await
+    }
+}
+
+impl Seen {
+    fn new(window_start: Instant, report: &ErrorReport) -> Self {
+        Self {
+            window_start,
+            suppressed: 0,
+            first_seen: report.last_seen,
+            last_seen: report.last_seen,
+            last: report.clone(),
+        }
+    }
+
+    fn summary(self) -> Option<ErrorReport> {
+        (self.suppressed > 0).then_some(ErrorReport {
+            count: self.suppressed,
+            first_seen: self.first_seen,
+            last_seen: self.last_seen,
+            ..self.last
+        })
+    }
+}
+
+/// What `ErrorQueue` does with a report when it is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// the new report is dropped
+    DropNewest,
+    /// the oldest queued report makes room for the new one
+    DropOldest,
+}
+
+/// state shared between the error sites and the worker
+struct Shared {
+    queue: Mutex<VecDeque<ErrorReport>>,
+    capacity: usize,
+    overflow: Overflow,
+    notify: Notify,
+    closed: AtomicBool,
+    dropped: AtomicU64,
+}
+
+/// queue installed with `ErrorQueue::install`, what `ErrPile::report`
+/// pushes to
+static INSTALLED: RwLock<Option<Arc<Shared>>> = RwLock::new(None);
+
+/// Bounded queue handing reports to the sinks from a background task,
+/// so reporting never blocks the error site.
+///
+/// ```ignore
+/// let queue = ErrorQueue::new(1024)
+///     .sink(ErrorReporter::new(WebhookSink::teams(client, url), window))
+///     .install();
+/// err.report();
+/// queue.shutdown().await;
+/// ```
+pub struct ErrorQueue {
+    capacity: usize,
+    overflow: Overflow,
+    flush_interval: Duration,
+    sinks: Vec<Arc<dyn ErrorSink>>,
+}
+
+impl ErrorQueue {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            overflow: Overflow::DropNewest,
+            flush_interval: Duration::from_secs(60),
+            sinks: Vec::new(),
+        }
+    }
+
+    /// every report is sent to every sink, in the order they were added
+    pub fn sink<S: ErrorSink + 'static>(mut self, sink: S) -> Self {
+        self.sinks.push(Arc::new(sink));
+        self
+    }
+
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// how often the sinks `tick`, so the summaries of an `ErrorReporter`
+    /// go out when no new report comes along. A minute by default
+    pub fn flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval.max(Duration::from_millis(1));
+        self
+    }
+
+    /// Spawns the worker on the current tokio runtime
+    pub fn start(self) -> ErrorQueueHandle {
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(VecDeque::with_capacity(self.capacity)),
+            capacity: self.capacity,
+            overflow: self.overflow,
+            notify: Notify::new(),
+            closed: AtomicBool::new(false),
+            dropped: AtomicU64::new(0),
+        });
+
+        let worker = tokio::spawn(work(shared.clone(), self.sinks, self.flush_interval));
+        ErrorQueueHandle {
+            shared,
+            worker: Mutex::new(Some(worker)),
+        }
+    }
+
+    /// `start`s the queue and makes it the target of `ErrPile::report`,
+    /// replacing the queue installed before
+    pub fn install(self) -> ErrorQueueHandle {
+        let handle = self.start();
+        *INSTALLED.write().unwrap_or_else(|e| e.into_inner()) = Some(handle.shared.clone());
+        handle
+    }
+}
+
+/// Handle of a running `ErrorQueue`
+pub struct ErrorQueueHandle {
+    shared: Arc<Shared>,
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl ErrorQueueHandle {
+    /// queues the report, `false` if it was dropped
+    pub fn push(&self, report: ErrorReport) -> bool {
+        self.shared.push(report)
+    }
+
+    /// reports dropped because the queue was full or closed
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Stops taking reports and waits until the queued ones reached the
+    /// sinks and the sinks are shut down, which sends the pending
+    /// summaries. Uninstalls the queue if it is the installed one
+    pub async fn shutdown(&self) {
+        self.shared.closed.store(true, Ordering::Release);
+        self.shared.notify.notify_one();
+
+        {
+            let mut installed = INSTALLED.write().unwrap_or_else(|e| e.into_inner());
+            if installed
+                .as_ref()
+                .is_some_and(|q| Arc::ptr_eq(q, &self.shared))
+            {
+                *installed = None;
+            }
+        }
+
+        let worker = self.worker.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(worker) = worker {
+            let _ = worker.await;
+        }
+    }
+}
+
+impl Shared {
+    fn push(&self, report: ErrorReport) -> bool {
+        if self.closed.load(Ordering::Acquire) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+
+        {
+            let mut queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+            if queue.len() >= self.capacity {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                match self.overflow {
+                    Overflow::DropNewest => return false,
+                    Overflow::DropOldest => {
+                        queue.pop_front();
+                    }
+                }
+            }
+            queue.push_back(report);
+        }
+        self.notify.notify_one();
+        true
+    }
+}
+
+/// hands the queued reports to the sinks until the queue is shut down,
+/// ticking them every `flush_interval`
+async fn work(shared: Arc<Shared>, sinks: Vec<Arc<dyn ErrorSink>>, flush_interval: Duration) {
+    let mut next_tick = tokio::time::Instant::now() + flush_interval;
+    loop {
+        if tokio::time::Instant::now() >= next_tick {
+            for sink in &sinks {
+                let _ = sink.tick().await;
+            }
+            next_tick = tokio::time::Instant::now() + flush_interval;
+        }
+
+        let batch: Vec<ErrorReport> = shared
+            .queue
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .drain(..)
+            .collect();
+
+        if batch.is_empty() {
+            if shared.closed.load(Ordering::Acquire) {
+                for sink in &sinks {
+                    let _ = sink.shutdown().await;
+                }
+                return;
+            }
+            // woken by a report, a shutdown or the next tick
+            let _ = tokio::time::timeout_at(next_tick, shared.notify.notified()).await;
+            continue;
+        }
+
+        for report in &batch {
+            for sink in &sinks {
+                // a failing sink must not take the others down, and
+                // there is nowhere left to report its failure to
+                let _ = sink.send(report).await;
+            }
+        }
+    }
+}
+
+impl ErrPile {
+    /// Queues the error on the installed `ErrorQueue`, `false` when no
+    /// queue is installed or the report was dropped
+    pub fn report(&self) -> bool {
+        let installed = INSTALLED.read().unwrap_or_else(|e| e.into_inner()).clone();
+        let Some(queue) = installed else {
+            return false;
+        };
+
+        #[cfg(feature = "metrics")]
+        self.count_reported();
+
+        queue.push(ErrorReport::new(self))
+    }
+}
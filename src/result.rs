@@ -0,0 +1,18 @@
+use crate::PileResult;
+
+/// Helpers on `PileResult`
+pub trait PileResultExt<T>: Sized {
+    /// records the error with `ErrPile::emit` and hands the result back
+    #[cfg(feature = "tracing")]
+    fn traced(self) -> Self;
+}
+
+impl<T> PileResultExt<T> for PileResult<T> {
+    #[cfg(feature = "tracing")]
+    fn traced(self) -> Self {
+        if let Err(err) = &self {
+            err.emit();
+        }
+        self
+    }
+}
@@ -0,0 +1,247 @@
+use std::{
+    future::Future,
+    hash::{BuildHasher, RandomState},
+    time::Duration,
+};
+
+use crate::{Deadline, ErrPile, PileKind, PileResult};
+
+/// Decides whether a failed attempt is tried again and after how long,
+/// consumed by `retry` and `PileMiddleware`
+pub trait RetryPolicy: Send + Sync {
+    /// wait before the next attempt, `None` to give up. `attempt` is the
+    /// 0 based number of the attempt that failed, `elapsed` the time
+    /// since the first one started
+    fn next_delay(&self, attempt: u32, elapsed: Duration, err: &ErrPile) -> Option<Duration>;
+}
+
+/// How much randomness is added to the computed backoff
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Jitter {
+    /// the computed delay as is
+    None,
+    /// anywhere between zero and the computed delay
+    Full,
+    /// between half and all of the computed delay
+    #[default]
+    Equal,
+}
+
+/// Exponential backoff: attempt `n` waits `base_delay * multiplier^n`,
+/// capped at `max_delay` and spread with `jitter`.
+///
+/// A wait asked for by the server (`Retry-After`, Graph throttling, see
+/// `ErrPile::retry_after`) replaces the computed one: retrying earlier
+/// gets us throttled longer. When the server asks for more than
+/// `max_retry_after` the error is returned instead.
+///
+/// Transient errors are retried, `retry_kind` overrides that per kind
+/// ("never retry `Auth`", "retry `Conflict`")
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    pub(crate) max_attempts: u32,
+    pub(crate) max_elapsed: Option<Duration>,
+    base_delay: Duration,
+    multiplier: f64,
+    max_delay: Duration,
+    pub(crate) max_retry_after: Duration,
+    jitter: Jitter,
+    kinds: Vec<(PileKind, bool)>,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            max_elapsed: None,
+            base_delay: Duration::from_millis(500),
+            multiplier: 2.0,
+            max_delay: Duration::from_secs(30),
+            max_retry_after: Duration::from_secs(120),
+            jitter: Jitter::default(),
+            kinds: Vec::new(),
+        }
+    }
+}
+
+impl Backoff {
+    /// 4 attempts, 500ms base delay doubling up to 30s, equal jitter,
+    /// server waits up to 2 minutes
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// attempts in total, the first call included
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// gives up instead of waiting past this time since the first attempt
+    pub fn max_elapsed(mut self, max_elapsed: Duration) -> Self {
+        self.max_elapsed = Some(max_elapsed);
+        self
+    }
+
+    pub fn base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// growth of the delay per attempt, 2 by default
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// upper bound for a single wait between the attempts
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// longest wait asked for by the server that is honored, longer
+    /// ones end the retries
+    pub fn max_retry_after(mut self, max_retry_after: Duration) -> Self {
+        self.max_retry_after = max_retry_after;
+        self
+    }
+
+    pub fn jitter(mut self, jitter: Jitter) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// retries (or never retries) errors of the kind, whether they are
+    /// transient or not
+    pub fn retry_kind(mut self, kind: PileKind, retry: bool) -> Self {
+        self.kinds.retain(|(k, _)| *k != kind);
+        self.kinds.push((kind, retry));
+        self
+    }
+
+    /// whether the error is worth another attempt
+    pub fn should_retry(&self, err: &ErrPile) -> bool {
+        let kind = err.kind();
+        self.kinds
+            .iter()
+            .find(|(k, _)| *k == kind)
+            .map_or_else(|| err.is_transient(), |(_, retry)| *retry)
+    }
+
+    /// how long to wait after the failed attempt `attempt` (0 based),
+    /// the server's wait when it sent one
+    pub fn delay(&self, attempt: u32, err: &ErrPile) -> Duration {
+        err.retry_after()
+            .unwrap_or_else(|| self.computed_delay(attempt))
+    }
+
+    /// the jittered exponential delay of attempt `attempt`
+    pub(crate) fn computed_delay(&self, attempt: u32) -> Duration {
+        let factor = self.multiplier.powi(attempt.min(i32::MAX as u32) as i32);
+        let backoff = Duration::try_from_secs_f64(self.base_delay.as_secs_f64() * factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+
+        match self.jitter {
+            Jitter::None => backoff,
+            Jitter::Full => random_up_to(backoff),
+            Jitter::Equal => {
+                let half = backoff / 2;
+                half + random_up_to(backoff - half)
+            }
+        }
+    }
+}
+
+impl RetryPolicy for Backoff {
+    fn next_delay(&self, attempt: u32, elapsed: Duration, err: &ErrPile) -> Option<Duration> {
+        if attempt + 1 >= self.max_attempts || !self.should_retry(err) {
+            return None;
+        }
+        if err
+            .retry_after()
+            .is_some_and(|retry_after| retry_after > self.max_retry_after)
+        {
+            return None;
+        }
+
+        let delay = self.delay(attempt, err);
+        match self.max_elapsed {
+            Some(max_elapsed) if elapsed + delay > max_elapsed => None,
+            _ => Some(delay),
+        }
+    }
+}
+
+/// random duration in `0..=max`, `RandomState` is seeded randomly per
+/// instance which is plenty for spreading retries
+fn random_up_to(max: Duration) -> Duration {
+    let nanos = max.as_nanos() as u64;
+    if nanos == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_nanos(RandomState::new().hash_one(nanos) % (nanos + 1))
+}
+
+/// Runs `op` until it succeeds or the policy gives up, sleeping the
+/// delay the policy asks for between the attempts. The last error is
+/// returned, a `Timeout` when the wait would run past the current
+/// `Deadline`
+///
+/// ```ignore
+/// let rooms = retry(&Backoff::new(), || async {
+///     client.get(url).send_pile().await?.to_pile_result::<Rooms>().await
+/// })
+/// .await?;
+/// ```
+pub async fn retry<T, P, F, Fut>(policy: &P, mut op: F) -> PileResult<T>
+where
+    P: RetryPolicy + ?Sized,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = PileResult<T>>,
+{
+    let started = tokio::time::Instant::now();
+    let mut attempt = 0;
+    loop {
+        let err = match op().await {
+            Ok(value) => return Ok(value),
+            Err(err) => err,
+        };
+
+        match policy.next_delay(attempt, started.elapsed(), &err) {
+            Some(delay) => {
+                Deadline::ensure(delay, "retry")?;
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            None => return Err(err),
+        }
+    }
+}
+
+/// Header carrying the key a server uses to apply a repeated request
+/// only once
+pub const IDEMPOTENCY_KEY: &str = "Idempotency-Key";
+
+/// `retry` for operations that aren't idempotent, payment posts or new
+/// reservations: `key` is asked once and every attempt gets the same key
+/// to send along (see `IDEMPOTENCY_KEY`), so a retry after a lost response
+/// doesn't charge or book twice
+///
+/// ```ignore
+/// let payment = retry_idempotent(&Backoff::new(), || Uuid::new_v4().to_string(), |key| {
+///     client.post(url).header(IDEMPOTENCY_KEY, key).json(&charge).send_pile()
+/// })
+/// .await?;
+/// ```
+pub async fn retry_idempotent<T, P, K, F, Fut>(policy: &P, key: K, mut op: F) -> PileResult<T>
+where
+    P: RetryPolicy + ?Sized,
+    K: FnOnce() -> String,
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = PileResult<T>>,
+{
+    let key = key();
+    retry(policy, || op(key.clone())).await
+}
@@ -0,0 +1,139 @@
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{ErrPile, PileKind, RemoteError};
+
+/// Error object of a JSON-RPC 2.0 response, what the hardware bridge
+/// exchanges with the door locks and POS terminals
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+    /// the serialized error (`RemoteError`) when it comes from ErrPile,
+    /// whatever the device sent otherwise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl JsonRpcError {
+    pub const PARSE_ERROR: i64 = -32700;
+    pub const INVALID_REQUEST: i64 = -32600;
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    pub const INVALID_PARAMS: i64 = -32602;
+    pub const INTERNAL_ERROR: i64 = -32603;
+
+    /// codes of the specification (`-32768..=-32000`), anything else is an
+    /// application error of the device
+    pub fn is_reserved(&self) -> bool {
+        (-32768..=-32000).contains(&self.code)
+    }
+}
+
+impl fmt::Display for JsonRpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} - {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for JsonRpcError {}
+
+/// JSON-RPC code of a kind, from the range left to the servers
+/// (`-32099..=-32000`) when the specification has none
+fn rpc_code(kind: PileKind) -> i64 {
+    match kind {
+        PileKind::Parse => JsonRpcError::PARSE_ERROR,
+        PileKind::Unsupported => JsonRpcError::METHOD_NOT_FOUND,
+        PileKind::Validation => JsonRpcError::INVALID_PARAMS,
+        PileKind::NotFound => -32001,
+        PileKind::Auth => -32002,
+        PileKind::Permission => -32003,
+        PileKind::Conflict => -32004,
+        PileKind::InUse => -32005,
+        PileKind::Timeout => -32006,
+        PileKind::Cancelled => -32007,
+        PileKind::RateLimited => -32008,
+        PileKind::NotReady => -32009,
+        PileKind::Upstream => -32010,
+        PileKind::Network => -32011,
+        PileKind::PaymentDeclined => -32012,
+        _ => JsonRpcError::INTERNAL_ERROR,
+    }
+}
+
+/// kind of an error received without a serialized pile, application
+/// codes of the devices are `Upstream`
+fn code_kind(code: i64) -> PileKind {
+    match code {
+        JsonRpcError::PARSE_ERROR => PileKind::Parse,
+        JsonRpcError::INVALID_REQUEST | JsonRpcError::INVALID_PARAMS => PileKind::Validation,
+        JsonRpcError::METHOD_NOT_FOUND => PileKind::Unsupported,
+        JsonRpcError::INTERNAL_ERROR => PileKind::Internal,
+        -32001 => PileKind::NotFound,
+        -32002 => PileKind::Auth,
+        -32003 => PileKind::Permission,
+        -32004 => PileKind::Conflict,
+        -32005 => PileKind::InUse,
+        -32006 => PileKind::Timeout,
+        -32007 => PileKind::Cancelled,
+        -32008 => PileKind::RateLimited,
+        -32009 => PileKind::NotReady,
+        -32011 => PileKind::Network,
+        -32012 => PileKind::PaymentDeclined,
+        _ => PileKind::Upstream,
+    }
+}
+
+impl ErrPile {
+    /// the error object of a JSON-RPC response, see `JsonRpcError`
+    pub fn to_json_rpc(&self) -> JsonRpcError {
+        JsonRpcError {
+            code: rpc_code(self.kind()),
+            message: self.user_message(),
+            data: serde_json::to_value(self).ok(),
+        }
+    }
+}
+
+/// The code of the kind with `user_message` as the message, the error
+/// itself (`RemoteError` as JSON) travels in `data`
+impl From<ErrPile> for JsonRpcError {
+    fn from(err: ErrPile) -> Self {
+        err.to_json_rpc()
+    }
+}
+
+/// `ErrPile::Remote` from the `data` sent by a service using ErrPile, from
+/// the code and message otherwise, with the `data` of the device kept in
+/// the metadata
+impl From<JsonRpcError> for ErrPile {
+    fn from(err: JsonRpcError) -> Self {
+        if let Some(data) = &err.data
+            && let Ok(remote) = RemoteError::deserialize(data)
+        {
+            return remote.into();
+        }
+
+        let kind = code_kind(err.code);
+        let mut metadata = serde_json::Map::new();
+        if let Some(data) = err.data {
+            metadata.insert("data".into(), data);
+        }
+        RemoteError {
+            kind,
+            code: Some(err.code.to_string()),
+            message: err.message,
+            user_message: String::new(),
+            transient: matches!(
+                kind,
+                PileKind::Timeout | PileKind::RateLimited | PileKind::NotReady | PileKind::Network
+            ),
+            http_status: None,
+            chain: Vec::new(),
+            origin: None,
+            metadata,
+        }
+        .into()
+    }
+}
@@ -0,0 +1,52 @@
+use std::borrow::Cow;
+
+use sentry_core::protocol::{Event, Level};
+
+use crate::{ErrPile, Severity, redact};
+
+impl From<Severity> for Level {
+    fn from(value: Severity) -> Self {
+        match value {
+            Severity::Info => Level::Info,
+            Severity::Warning => Level::Warning,
+            Severity::Error => Level::Error,
+            Severity::Critical => Level::Fatal,
+        }
+    }
+}
+
+impl ErrPile {
+    /// Sentry event for the error: the source chain as exceptions, the
+    /// kind, code and correlation ids as tags, grouped by
+    /// `ErrPile::fingerprint` rather than by the message. The messages
+    /// go through `redact`
+    pub fn to_sentry_event(&self) -> Event<'static> {
+        let mut event = sentry_core::event_from_error(self);
+        let kind = self.kind();
+        for exception in event.exception.values.iter_mut() {
+            if let Some(value) = &mut exception.value
+                && let Cow::Owned(redacted) = redact(value)
+            {
+                *value = redacted;
+            }
+        }
+
+        event.level = self.severity().into();
+        event.tags.insert("kind".into(), kind.to_string());
+        event
+            .tags
+            .insert("transient".into(), self.is_transient().to_string());
+        if let Some(code) = self.code() {
+            event.tags.insert("code".into(), code);
+        }
+        if let Some(status) = self.upstream_status() {
+            event.tags.insert("http_status".into(), status.to_string());
+        }
+        for (name, id) in self.correlation_ids() {
+            event.tags.insert(name.into(), id);
+        }
+
+        event.fingerprint = Cow::Owned(vec![Cow::Owned(self.fingerprint())]);
+        event
+    }
+}
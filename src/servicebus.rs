@@ -0,0 +1,67 @@
+use fe2o3_amqp_types::definitions::{Error as AmqpError, ErrorCondition};
+
+use crate::{ErrPile, Opt};
+
+/// conditions Service Bus documents as safe to retry
+const TRANSIENT_CONDITIONS: &[&str] = &[
+    "com.microsoft:server-busy",
+    "com.microsoft:timeout",
+    "com.microsoft:operation-cancelled",
+    "amqp:link:detach-forced",
+    "amqp:connection:forced",
+    "amqp:connection:framing-error",
+    "amqp:internal-error",
+];
+
+/// AMQP symbol of the condition, e.g. `com.microsoft:server-busy`
+pub fn amqp_condition(condition: &ErrorCondition) -> String {
+    // every condition serializes to its symbol, this also covers
+    // the variants behind fe2o3's optional features
+    serde_json::to_value(condition)
+        .ok()
+        .and_then(|v| v.as_str().map(String::from))
+        .unwrap_or_else(|| format!("{condition:?}"))
+}
+
+/// the Service Bus error can be retried, e.g. the namespace is
+/// throttling or the link was detached during an upgrade
+pub fn is_service_bus_transient(err: &AmqpError) -> bool {
+    TRANSIENT_CONDITIONS.contains(&amqp_condition(&err.condition).as_str())
+}
+
+/// Azure Service Bus (AMQP) error, `condition` is the AMQP symbol
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "Service Bus error{} ({condition})",
+    Opt(" on `", entity.as_ref(), "`")
+)]
+pub struct ServiceBusError {
+    pub entity: Option<String>,
+    pub condition: String,
+    #[source]
+    pub source: AmqpError,
+}
+
+impl ErrPile {
+    /// Service Bus error raised while using the given queue or topic
+    pub fn service_bus<E>(entity: E, source: AmqpError) -> Self
+    where
+        E: Into<String>,
+    {
+        Self::ServiceBus(Box::new(ServiceBusError {
+            entity: Some(entity.into()),
+            condition: amqp_condition(&source.condition),
+            source,
+        }))
+    }
+}
+
+impl From<AmqpError> for ErrPile {
+    fn from(source: AmqpError) -> Self {
+        Self::ServiceBus(Box::new(ServiceBusError {
+            entity: None,
+            condition: amqp_condition(&source.condition),
+            source,
+        }))
+    }
+}
@@ -0,0 +1,154 @@
+use reqwest::StatusCode;
+use serde_json::Value;
+
+use crate::{ErrPile, MicrosoftError};
+
+/// SharePoint reports a list view threshold violation with this
+/// HRESULT / exception, Graph passes the text through
+const THRESHOLD_MARKERS: &[&str] = &[
+    "list view threshold",
+    "spquerythrottledexception",
+    "-2147024860",
+];
+
+fn is_checked_out_message(message: &str) -> bool {
+    message.to_lowercase().contains("checked out")
+}
+
+/// `code` and `message` of an OData error body
+fn odata_error(body: &Value) -> Option<(&str, &str)> {
+    let error = body.get("error")?;
+    let code = error
+        .get("code")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let message = error
+        .get("message")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    Some((code, message))
+}
+
+/// a locked file (423 / `resourceLocked`), checked out documents are
+/// left alone so `is_sharepoint_checked_out` can tell them apart
+pub(crate) fn is_locked_response(status: StatusCode, body: Option<&Value>) -> bool {
+    let (code, message) = body.and_then(odata_error).unwrap_or_default();
+    (status == StatusCode::LOCKED || code.eq_ignore_ascii_case("resourceLocked"))
+        && !is_checked_out_message(message)
+}
+
+impl ErrPile {
+    /// `code` and `message` of a Graph/SharePoint error, whatever shape it was decoded to
+    pub(crate) fn graph_error_text(&self) -> Option<(String, String)> {
+        match self.peeled() {
+            Self::Microsoft(MicrosoftError::MS(err)) => {
+                Some((err.error.code.clone(), err.error.message.clone()))
+            }
+            Self::Microsoft(MicrosoftError::AZ(err)) => {
+                Some((err.error.code.clone(), err.error.message.clone()))
+            }
+            #[cfg(feature = "graph")]
+            Self::Microsoft(MicrosoftError::GraphErrMSg(msg)) => Some((
+                msg.error.code.clone().unwrap_or_default(),
+                msg.error.message.clone().unwrap_or_default(),
+            )),
+            Self::Http(http) => http
+                .body_json
+                .as_ref()
+                .and_then(|json| odata_error(&json.0))
+                .map(|(code, message)| (code.to_string(), message.to_string())),
+            _ => None,
+        }
+    }
+
+    /// the file is locked by another user or process, responses with
+    /// `resourceLocked` or 423 are decoded to `ErrPile::InUse`
+    pub fn is_sharepoint_locked(&self) -> bool {
+        if matches!(self.peeled(), Self::InUse) {
+            return true;
+        }
+
+        self.graph_error_text()
+            .is_some_and(|(code, _)| code.eq_ignore_ascii_case("resourceLocked"))
+            || self.is_sharepoint_checked_out()
+    }
+
+    /// the document is checked out to someone else and has to be
+    /// checked in before it can be changed
+    pub fn is_sharepoint_checked_out(&self) -> bool {
+        self.graph_error_text()
+            .is_some_and(|(_, message)| is_checked_out_message(&message))
+    }
+
+    /// the query touched more items than the list view threshold allows,
+    /// it needs an indexed column filter or paging
+    pub fn is_list_threshold(&self) -> bool {
+        self.graph_error_text().is_some_and(|(code, message)| {
+            let text = format!("{code} {message}").to_lowercase();
+            THRESHOLD_MARKERS.iter().any(|m| text.contains(m))
+        })
+    }
+}
+
+/// OneDrive/SharePoint drive errors an upload loop can react to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DriveConflict {
+    /// an item with the same name exists in the folder
+    NameAlreadyExists,
+    /// the name or path has characters or a length SharePoint rejects
+    InvalidPath,
+    /// the drive is out of storage
+    QuotaExceeded,
+}
+
+/// What to do about a `DriveConflict`, maps to Graph's
+/// `@microsoft.graph.conflictBehavior`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DriveResolution {
+    /// upload again under a new (or sanitized) name
+    Rename,
+    /// overwrite the existing item
+    Replace,
+    /// retrying won't help
+    Abort,
+}
+
+impl DriveConflict {
+    /// safest resolution, existing items are never replaced
+    pub fn suggested_resolution(&self) -> DriveResolution {
+        match self {
+            Self::NameAlreadyExists | Self::InvalidPath => DriveResolution::Rename,
+            Self::QuotaExceeded => DriveResolution::Abort,
+        }
+    }
+}
+
+impl DriveResolution {
+    /// value for the `@microsoft.graph.conflictBehavior` parameter
+    pub fn conflict_behavior(&self) -> &'static str {
+        match self {
+            Self::Rename => "rename",
+            Self::Replace => "replace",
+            Self::Abort => "fail",
+        }
+    }
+}
+
+impl ErrPile {
+    /// the drive error behind a failed upload or move, if it is one
+    pub fn drive_conflict(&self) -> Option<DriveConflict> {
+        if let Self::Http(http) = self.peeled()
+            && http.status == StatusCode::INSUFFICIENT_STORAGE
+        {
+            return Some(DriveConflict::QuotaExceeded);
+        }
+
+        let (code, _) = self.graph_error_text()?;
+        match code.to_ascii_lowercase().as_str() {
+            "namealreadyexists" => Some(DriveConflict::NameAlreadyExists),
+            "invalidpath" | "invalidfilename" => Some(DriveConflict::InvalidPath),
+            "quotalimitreached" | "insufficientstorage" => Some(DriveConflict::QuotaExceeded),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use core::fmt;
+use std::{borrow::Cow, time::Duration};
+
+use chrono::Utc;
+use reqwest::header::HeaderMap;
+
+use crate::{ErrPile, PileResult};
+
+/// Why an incoming webhook (payment processor, OTA) was refused
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebhookVerificationFailure {
+    /// the request has no signature header
+    MissingSignature { header: Cow<'static, str> },
+    /// the signed timestamp is too old or in the future, a replay
+    TimestampOutsideTolerance {
+        /// seconds between the timestamp and now, negative in the future
+        age: i64,
+        tolerance: Duration,
+    },
+    /// the signature doesn't match the payload
+    SignatureMismatch,
+}
+
+impl fmt::Display for WebhookVerificationFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingSignature { header } => write!(f, "the `{header}` header is missing"),
+            Self::TimestampOutsideTolerance { age, tolerance } => write!(
+                f,
+                "the timestamp is {age}s old, outside the tolerance of {}s",
+                tolerance.as_secs()
+            ),
+            Self::SignatureMismatch => f.write_str("the signature doesn't match"),
+        }
+    }
+}
+
+impl From<WebhookVerificationFailure> for ErrPile {
+    fn from(value: WebhookVerificationFailure) -> Self {
+        ErrPile::WebhookVerification(Box::new(value))
+    }
+}
+
+impl ErrPile {
+    /// why the webhook was refused, if this is a verification error
+    pub fn webhook_verification(&self) -> Option<&WebhookVerificationFailure> {
+        match self.peeled() {
+            Self::WebhookVerification(failure) => Some(failure),
+            _ => None,
+        }
+    }
+}
+
+/// Compares the bytes in a time that doesn't depend on where they differ,
+/// so a signature can't be guessed a byte at a time. Only the length
+/// leaks, which is the length of the digest anyway. The HMACs of the
+/// `hmac` feature are checked with `Mac::verify_slice` instead
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    // `black_box` keeps the compiler from stopping at the first difference
+    let diff = a
+        .iter()
+        .zip(b)
+        .fold(0u8, |diff, (x, y)| std::hint::black_box(diff | (x ^ y)));
+    std::hint::black_box(diff) == 0
+}
+
+/// `SignatureMismatch` unless the signature received is the expected
+/// one, compared with `constant_time_eq`
+pub fn verify_signature(expected: &[u8], received: &[u8]) -> PileResult {
+    if constant_time_eq(expected, received) {
+        Ok(())
+    } else {
+        Err(WebhookVerificationFailure::SignatureMismatch.into())
+    }
+}
+
+/// the signature header of the request, `MissingSignature` when it is
+/// absent or empty
+pub fn signature_header<'h>(headers: &'h HeaderMap, header: &'static str) -> PileResult<&'h str> {
+    headers
+        .get(header)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| {
+            WebhookVerificationFailure::MissingSignature {
+                header: Cow::Borrowed(header),
+            }
+            .into()
+        })
+}
+
+/// Refuses the webhooks signed more than `tolerance` ago or ahead, the
+/// timestamp being the unix seconds the provider signs along with the
+/// payload (`t=` of Stripe)
+pub fn check_webhook_timestamp(timestamp: i64, tolerance: Duration) -> PileResult {
+    // the sender picks the timestamp, one far enough out overflows and
+    // is as far outside the tolerance as it gets
+    let Some(age) = Utc::now().timestamp().checked_sub(timestamp) else {
+        let age = if timestamp < 0 { i64::MAX } else { i64::MIN };
+        return Err(
+            WebhookVerificationFailure::TimestampOutsideTolerance { age, tolerance }.into(),
+        );
+    };
+
+    if age.unsigned_abs() > tolerance.as_secs() {
+        return Err(
+            WebhookVerificationFailure::TimestampOutsideTolerance { age, tolerance }.into(),
+        );
+    }
+    Ok(())
+}
+
+/// Checks the HMAC-SHA256 of the raw payload against the signature,
+/// written in hex or base64 with an optional `sha256=` prefix (GitHub's
+/// `X-Hub-Signature-256`). Stripe signs the timestamp along, see
+/// `verify_stripe_signature`
+#[cfg(feature = "hmac")]
+pub fn verify_hmac_sha256(secret: &[u8], payload: &[u8], signature: &str) -> PileResult {
+    use base64::Engine;
+    use hmac::Mac;
+
+    let signature = signature.trim();
+    let signature = signature.strip_prefix("sha256=").unwrap_or(signature);
+
+    let received = hex_decode(signature)
+        .or_else(|| {
+            base64::engine::general_purpose::STANDARD
+                .decode(signature)
+                .ok()
+        })
+        .ok_or(WebhookVerificationFailure::SignatureMismatch)?;
+
+    let mut mac = hmac_sha256(secret);
+    mac.update(payload);
+    mac.verify_slice(&received)
+        .map_err(|_| WebhookVerificationFailure::SignatureMismatch.into())
+}
+
+/// Checks a `Stripe-Signature` header, `t=<unix seconds>,v1=<hex>`: the
+/// timestamp has to be within `tolerance` and one of the `v1` signatures
+/// the HMAC-SHA256 of `{t}.{payload}`
+#[cfg(feature = "hmac")]
+pub fn verify_stripe_signature(
+    secret: &[u8],
+    payload: &[u8],
+    header: &str,
+    tolerance: Duration,
+) -> PileResult {
+    use hmac::Mac;
+
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+            Some(("v1", value)) => signatures.extend(hex_decode(value)),
+            _ => {}
+        }
+    }
+
+    let timestamp = timestamp.ok_or(WebhookVerificationFailure::SignatureMismatch)?;
+    check_webhook_timestamp(timestamp, tolerance)?;
+
+    let mut mac = hmac_sha256(secret);
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(payload);
+
+    // several `v1` while the secret is rolled
+    if signatures
+        .iter()
+        .any(|signature| mac.clone().verify_slice(signature).is_ok())
+    {
+        Ok(())
+    } else {
+        Err(WebhookVerificationFailure::SignatureMismatch.into())
+    }
+}
+
+#[cfg(feature = "hmac")]
+fn hmac_sha256(secret: &[u8]) -> hmac::Hmac<sha2::Sha256> {
+    use hmac::Mac;
+
+    hmac::Hmac::new_from_slice(secret).expect("HMAC takes keys of any length")
+}
+
+#[cfg(feature = "hmac")]
+fn hex_decode(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
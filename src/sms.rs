@@ -0,0 +1,170 @@
+use core::fmt;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::{ErrPile, PileKind};
+
+/// What the SMS gateway refused the message for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SmsFailure {
+    /// the number is not a valid mobile number (21211, 21614)
+    InvalidNumber,
+    /// the guest replied STOP, nothing can be sent until they opt in
+    /// again (21610)
+    OptedOut,
+    /// too many requests or messages queued (20429, 14107)
+    Throttled,
+    /// the account credentials were refused (20003)
+    Auth,
+    /// the gateway failed on its side
+    Unavailable,
+    /// any other refusal of the message
+    Rejected,
+}
+
+impl SmsFailure {
+    /// failure of a Twilio error code, the status of the response for the
+    /// codes not listed
+    pub fn from_code(code: Option<u32>, status: Option<u16>) -> Self {
+        match (code, status) {
+            (Some(21211 | 21614), _) => Self::InvalidNumber,
+            (Some(21610), _) => Self::OptedOut,
+            (Some(20429 | 14107), _) | (_, Some(429)) => Self::Throttled,
+            (Some(20003), _) | (_, Some(401)) => Self::Auth,
+            (Some(20500 | 20503), _) => Self::Unavailable,
+            (_, Some(status)) if status >= 500 => Self::Unavailable,
+            _ => Self::Rejected,
+        }
+    }
+
+    /// category of the error, see `ErrPile::kind`
+    pub fn kind(&self) -> PileKind {
+        match self {
+            Self::InvalidNumber => PileKind::Validation,
+            Self::OptedOut => PileKind::Permission,
+            Self::Throttled => PileKind::RateLimited,
+            Self::Auth => PileKind::Config,
+            Self::Unavailable | Self::Rejected => PileKind::Upstream,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InvalidNumber => "invalid_number",
+            Self::OptedOut => "opted_out",
+            Self::Throttled => "throttled",
+            Self::Auth => "auth",
+            Self::Unavailable => "unavailable",
+            Self::Rejected => "rejected",
+        }
+    }
+}
+
+impl fmt::Display for SmsFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error response of a Twilio-style SMS gateway,
+/// `{code, message, more_info, status}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmsError {
+    pub failure: SmsFailure,
+    pub code: Option<u32>,
+    pub message: String,
+    /// link to the documentation of the code
+    pub more_info: Option<String>,
+    pub status: Option<u16>,
+    pub retry_after: Option<Duration>,
+}
+
+impl SmsError {
+    pub fn new<M>(failure: SmsFailure, message: M) -> Self
+    where
+        M: Into<String>,
+    {
+        Self {
+            failure,
+            code: None,
+            message: message.into(),
+            more_info: None,
+            status: None,
+            retry_after: None,
+        }
+    }
+
+    /// Reads the error body, `None` unless it has a numeric `code`, a
+    /// `message` and the `more_info` url of the code's documentation,
+    /// which plain `{code, message, status}` API errors don't have. The
+    /// `status` of the body wins over the one of the response
+    pub fn from_body(status: Option<u16>, json: &Value) -> Option<Self> {
+        let code = json
+            .get("code")
+            .and_then(Value::as_u64)
+            .and_then(|c| u32::try_from(c).ok())?;
+        let message = json.get("message").and_then(Value::as_str)?;
+        let more_info = json
+            .get("more_info")
+            .and_then(Value::as_str)
+            .filter(|url| url.starts_with("https://") || url.starts_with("http://"))?
+            .to_string();
+        let body_status = json
+            .get("status")
+            .and_then(Value::as_u64)
+            .and_then(|s| u16::try_from(s).ok());
+
+        let status = body_status.or(status);
+        Some(Self {
+            failure: SmsFailure::from_code(Some(code), status),
+            code: Some(code),
+            more_info: Some(more_info),
+            status,
+            ..Self::new(SmsFailure::Rejected, message)
+        })
+    }
+
+    /// the same message can go out again, later
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self.failure,
+            SmsFailure::Throttled | SmsFailure::Unavailable
+        )
+    }
+
+    /// the guest opted out, the message must not be sent again
+    pub fn is_opted_out(&self) -> bool {
+        self.failure == SmsFailure::OptedOut
+    }
+}
+
+impl fmt::Display for SmsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The SMS could not be sent ({})", self.failure)?;
+        if let Some(code) = self.code {
+            write!(f, " {code}")?;
+        }
+        if !self.message.is_empty() {
+            write!(f, ": {}", self.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for SmsError {}
+
+impl From<SmsError> for ErrPile {
+    fn from(value: SmsError) -> Self {
+        ErrPile::Sms(Box::new(value))
+    }
+}
+
+impl ErrPile {
+    /// Checks if the SMS gateway refused the message because the guest
+    /// opted out. Not worth retrying, nor sending the next confirmations
+    /// by SMS
+    pub fn sms_opted_out(&self) -> bool {
+        matches!(self.peeled(), Self::Sms(err) if err.is_opted_out())
+    }
+}
@@ -0,0 +1,164 @@
+use core::fmt;
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use reqwest::{
+    StatusCode,
+    header::{HeaderMap, HeaderName, HeaderValue},
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{ErrPile, REDACTED, is_secret_header, parse_retry_after, redact, redact_url};
+
+static CAPTURE_SNAPSHOTS: AtomicBool = AtomicBool::new(false);
+
+/// When enabled, error responses keep a `ResponseSnapshot` of what the
+/// server sent, to be picked up by the error reporting layer
+pub fn set_capture_snapshots(enabled: bool) {
+    CAPTURE_SNAPSHOTS.store(enabled, Ordering::Relaxed);
+}
+
+/// whether error responses are captured
+pub fn capture_snapshots() -> bool {
+    CAPTURE_SNAPSHOTS.load(Ordering::Relaxed)
+}
+
+/// Raw error response kept for offline analysis, credentials in the
+/// headers, url and body are redacted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResponseSnapshot {
+    pub status: u16,
+    pub method: Option<String>,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    /// body as far as it was buffered, see `max_error_body()`
+    pub body: String,
+    pub body_truncated: bool,
+    /// time from sending the request until the response arrived,
+    /// only known for requests sent with `send_pile`
+    pub elapsed_ms: Option<u64>,
+    pub captured_at: DateTime<Utc>,
+}
+
+impl ResponseSnapshot {
+    pub fn new(
+        status: StatusCode,
+        headers: &HeaderMap,
+        url: &Url,
+        body: &[u8],
+        body_truncated: bool,
+    ) -> Self {
+        let headers = headers
+            .iter()
+            .map(|(name, value)| {
+                let value = if is_secret_header(name) {
+                    REDACTED
+                } else {
+                    value.to_str().unwrap_or("<binary>")
+                };
+                (name.to_string(), value.to_string())
+            })
+            .collect();
+
+        Self {
+            status: status.as_u16(),
+            method: None,
+            url: redact_url(url),
+            headers,
+            body: redact(&String::from_utf8_lossy(body)).into_owned(),
+            body_truncated,
+            elapsed_ms: None,
+            captured_at: Utc::now(),
+        }
+    }
+
+    pub fn elapsed(&self) -> Option<Duration> {
+        self.elapsed_ms.map(Duration::from_millis)
+    }
+
+    /// the wait asked for in the headers, see `parse_retry_after`
+    pub fn retry_after(&self) -> Option<Duration> {
+        let headers = self
+            .headers
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((
+                    HeaderName::from_bytes(name.as_bytes()).ok()?,
+                    HeaderValue::from_str(value).ok()?,
+                ))
+            })
+            .collect();
+        parse_retry_after(&headers)
+    }
+}
+
+/// An error decoded from a captured response, displays and
+/// behaves as the inner error
+#[derive(Debug)]
+pub struct CapturedError {
+    pub snapshot: ResponseSnapshot,
+    pub error: ErrPile,
+}
+
+impl fmt::Display for CapturedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl std::error::Error for CapturedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        std::error::Error::source(&self.error)
+    }
+}
+
+impl ErrPile {
+    /// wraps the error together with the response it was decoded from
+    pub fn captured(self, snapshot: ResponseSnapshot) -> Self {
+        match self {
+            Self::Captured(mut captured) => {
+                captured.snapshot = snapshot;
+                Self::Captured(captured)
+            }
+            error => Self::Captured(Box::new(CapturedError { snapshot, error })),
+        }
+    }
+
+    /// the error response, when `set_capture_snapshots` is enabled
+    pub fn snapshot(&self) -> Option<&ResponseSnapshot> {
+        match self {
+            Self::Captured(captured) => Some(&captured.snapshot),
+            Self::Context(context) => context.error.snapshot(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn snapshot_mut(&mut self) -> Option<&mut ResponseSnapshot> {
+        match self {
+            Self::Captured(captured) => Some(&mut captured.snapshot),
+            Self::Context(context) => context.error.snapshot_mut(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn peeled_mut(&mut self) -> &mut ErrPile {
+        match self {
+            Self::Captured(captured) => captured.error.peeled_mut(),
+            Self::Context(context) => context.error.peeled_mut(),
+            err => err,
+        }
+    }
+
+    /// the error without the captured response or the context around it
+    pub fn peeled(&self) -> &ErrPile {
+        match self {
+            Self::Captured(captured) => captured.error.peeled(),
+            Self::Context(context) => context.error.peeled(),
+            err => err,
+        }
+    }
+}
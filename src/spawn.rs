@@ -0,0 +1,106 @@
+use std::{
+    borrow::Cow,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use tokio::task::{JoinError, JoinHandle};
+
+use crate::{
+    ErrPile, PileResult, TaskError,
+    panic::{catch, catch_async},
+};
+
+/// Handle of a task started with `spawn_pile`, resolves to the result of
+/// the task, a panic or an abort being an `ErrPile::Task` naming it
+#[derive(Debug)]
+pub struct PileTask<T> {
+    name: Cow<'static, str>,
+    /// the panic caught in the task as the outer error
+    handle: JoinHandle<Result<PileResult<T>, ErrPile>>,
+}
+
+impl<T> PileTask<T> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// stops the task, awaiting it then fails with a cancelled
+    /// `ErrPile::Task`
+    pub fn abort(&self) {
+        self.handle.abort();
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+}
+
+impl<T> Future for PileTask<T> {
+    type Output = PileResult<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<PileResult<T>> {
+        let this = self.get_mut();
+        Poll::Ready(match ready!(Pin::new(&mut this.handle).poll(cx)) {
+            Ok(Ok(result)) => result,
+            Ok(Err(panic)) => Err(ErrPile::Task(Box::new(TaskError {
+                name: this.name.clone(),
+                source: panic,
+            }))),
+            Err(err) => Err(ErrPile::task(this.name.clone(), err)),
+        })
+    }
+}
+
+impl ErrPile {
+    /// the task `name` could not complete, panics keep their message
+    pub fn task<N>(name: N, err: JoinError) -> Self
+    where
+        N: Into<Cow<'static, str>>,
+    {
+        let source = if err.is_cancelled() {
+            ErrPile::cancelled_because("the task was aborted")
+        } else {
+            match err.try_into_panic() {
+                Ok(payload) => ErrPile::from_panic(&*payload),
+                Err(err) => ErrPile::Thread(err),
+            }
+        };
+        Self::Task(Box::new(TaskError {
+            name: name.into(),
+            source,
+        }))
+    }
+}
+
+/// `tokio::spawn` for a named task: panics are caught in the task, with
+/// their location, and reported as `ErrPile::Task` naming it
+///
+/// ```ignore
+/// let exported = spawn_pile("night-audit-export", export(day)).await?;
+/// ```
+pub fn spawn_pile<T, N, F>(name: N, fut: F) -> PileTask<T>
+where
+    T: Send + 'static,
+    N: Into<Cow<'static, str>>,
+    F: Future<Output = PileResult<T>> + Send + 'static,
+{
+    PileTask {
+        name: name.into(),
+        handle: tokio::spawn(catch_async(fut)),
+    }
+}
+
+/// `tokio::task::spawn_blocking` for a named closure, see `spawn_pile`
+pub fn spawn_blocking_pile<T, N, F>(name: N, f: F) -> PileTask<T>
+where
+    T: Send + 'static,
+    N: Into<Cow<'static, str>>,
+    F: FnOnce() -> PileResult<T> + Send + 'static,
+{
+    PileTask {
+        name: name.into(),
+        handle: tokio::task::spawn_blocking(move || catch(f)),
+    }
+}
@@ -0,0 +1,112 @@
+use core::fmt;
+use std::path::{Path, PathBuf};
+
+use russh::keys::{self, PrivateKey, ssh_key};
+
+use crate::{ErrPile, PileResult, TransportError};
+
+/// What went wrong while loading a SSH key, most SFTP onboarding
+/// failures are one of these
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SshKeyIssue {
+    /// key is encrypted but no passphrase was provided
+    PassphraseRequired,
+    /// passphrase was provided but could not decrypt the key
+    BadPassphrase,
+    /// key algorithm / format is not supported
+    UnsupportedKeyType,
+    /// key file could not be read
+    Unreadable,
+    /// key content is malformed
+    Corrupt,
+    Other,
+}
+
+impl SshKeyIssue {
+    fn from_keys(err: &keys::Error) -> Self {
+        match err {
+            keys::Error::KeyIsEncrypted | keys::Error::SshKey(ssh_key::Error::Encrypted) => {
+                Self::PassphraseRequired
+            }
+            keys::Error::SshKey(ssh_key::Error::Crypto) => Self::BadPassphrase,
+            keys::Error::UnsupportedKeyType { .. }
+            | keys::Error::UnknownAlgorithm(_)
+            | keys::Error::SshKey(
+                ssh_key::Error::AlgorithmUnknown | ssh_key::Error::AlgorithmUnsupported { .. },
+            ) => Self::UnsupportedKeyType,
+            keys::Error::CouldNotReadKey
+            | keys::Error::IO(_)
+            | keys::Error::SshKey(ssh_key::Error::Io(_)) => Self::Unreadable,
+            keys::Error::KeyIsCorrupt
+            | keys::Error::Decode(_)
+            | keys::Error::Der(_)
+            | keys::Error::Pkcs1(_)
+            | keys::Error::Pkcs8(_)
+            | keys::Error::Sec1(_)
+            | keys::Error::SshEncoding(_)
+            | keys::Error::SshKey(_) => Self::Corrupt,
+            _ => Self::Other,
+        }
+    }
+}
+
+impl fmt::Display for SshKeyIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            Self::PassphraseRequired => "key is encrypted, a passphrase is required",
+            Self::BadPassphrase => "invalid passphrase",
+            Self::UnsupportedKeyType => "unsupported key type",
+            Self::Unreadable => "key file could not be read",
+            Self::Corrupt => "key is corrupt",
+            Self::Other => "unknown error",
+        };
+
+        f.write_str(msg)
+    }
+}
+
+impl ErrPile {
+    /// SSH key could not be loaded from the given path
+    pub fn ssh_key<P>(path: P, source: keys::Error) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        TransportError::SshKey {
+            path: Some(path.into()),
+            issue: SshKeyIssue::from_keys(&source),
+            source: Box::new(source),
+        }
+        .into()
+    }
+
+    /// what went wrong with the SSH key, if this is a key error
+    pub fn ssh_key_issue(&self) -> Option<SshKeyIssue> {
+        match self {
+            Self::Transport(transport) => match &**transport {
+                TransportError::SshKey { issue, .. } => Some(*issue),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl From<keys::Error> for ErrPile {
+    fn from(source: keys::Error) -> Self {
+        TransportError::SshKey {
+            path: None,
+            issue: SshKeyIssue::from_keys(&source),
+            source: Box::new(source),
+        }
+        .into()
+    }
+}
+
+/// Loads the private key, the key path is kept in the error
+pub fn load_ssh_key<P>(path: P, passphrase: Option<&str>) -> PileResult<PrivateKey>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    keys::load_secret_key(path, passphrase).map_err(|e| ErrPile::ssh_key(path, e))
+}
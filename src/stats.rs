@@ -0,0 +1,337 @@
+use std::{cmp::Reverse, collections::VecDeque, sync::Mutex, time::Duration};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "report")]
+use std::sync::Arc;
+
+use crate::{ErrPile, PileKind};
+#[cfg(feature = "report")]
+use crate::{ErrorReport, ErrorSink, PileResult};
+
+/// seconds the occurrences are counted together for, the window is cut at
+/// a bucket boundary
+const BUCKET_SECS: i64 = 60;
+
+/// what is recorded of an occurrence
+struct Occurrence<'a> {
+    at: DateTime<Utc>,
+    kind: PileKind,
+    code: Option<&'a str>,
+    transient: bool,
+    count: u64,
+}
+
+/// Counts of the occurrences sharing a kind or a code
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatCount {
+    pub count: u64,
+    /// how many of them were transient, the rest being permanent
+    pub transient: u64,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+/// `StatCount` of a kind
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KindStats {
+    pub kind: PileKind,
+    #[serde(flatten)]
+    pub stats: StatCount,
+}
+
+/// `StatCount` of a code
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CodeStats {
+    pub code: String,
+    #[serde(flatten)]
+    pub stats: StatCount,
+}
+
+/// What `ErrStats::snapshot` returns, the "health" panel of the admin
+/// dashboard. The kinds and codes are sorted by count, most frequent
+/// first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatsSnapshot {
+    pub window_secs: u64,
+    pub total: u64,
+    pub transient: u64,
+    pub permanent: u64,
+    /// share of the transient occurrences, 0 without any
+    pub transient_ratio: f64,
+    pub first_seen: Option<DateTime<Utc>>,
+    pub last_seen: Option<DateTime<Utc>>,
+    pub by_kind: Vec<KindStats>,
+    pub by_code: Vec<CodeStats>,
+}
+
+/// the counts of one minute
+#[derive(Debug)]
+struct Bucket {
+    /// unix seconds divided by `BUCKET_SECS`
+    index: i64,
+    by_kind: Vec<KindStats>,
+    by_code: Vec<CodeStats>,
+}
+
+/// Counts the errors of the last `window`, by kind and by code, for the
+/// health panel. The counts are kept per minute, the memory depends on
+/// the window and the kinds and codes seen, not on the number of errors.
+/// A service keeps one per property:
+///
+/// ```ignore
+/// let stats = ErrStats::new(Duration::from_secs(3600));
+/// if let Err(err) = sync_rates(&property).await {
+///     stats.record(&err);
+/// }
+/// Json(stats.snapshot())
+/// ```
+#[derive(Debug)]
+pub struct ErrStats {
+    window: Duration,
+    /// sorted by index, the oldest first
+    buckets: Mutex<VecDeque<Bucket>>,
+}
+
+impl ErrStats {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            buckets: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    pub fn record(&self, err: &ErrPile) {
+        self.record_at(err, Utc::now());
+    }
+
+    /// records an occurrence that happened at `at`, for backfills from
+    /// the error store
+    pub fn record_at(&self, err: &ErrPile, at: DateTime<Utc>) {
+        let code = err.code();
+        self.push(Occurrence {
+            at,
+            kind: err.kind(),
+            code: code.as_deref(),
+            transient: err.is_transient(),
+            count: 1,
+        });
+    }
+
+    /// records a report, at its `last_seen` and as many times as it
+    /// stands for
+    #[cfg(feature = "report")]
+    pub fn record_report(&self, report: &ErrorReport) {
+        self.push(Occurrence {
+            at: report.last_seen,
+            kind: report.kind,
+            code: report.code.as_deref(),
+            transient: report.transient,
+            count: report.count.max(1),
+        });
+    }
+
+    fn push(&self, occurrence: Occurrence) {
+        let oldest = self.oldest_index(Utc::now());
+        let index = occurrence.at.timestamp().div_euclid(BUCKET_SECS);
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        while buckets.front().is_some_and(|b| b.index < oldest) {
+            buckets.pop_front();
+        }
+        if index < oldest {
+            return;
+        }
+
+        // backfills may come out of order, the buckets stay sorted
+        let at = buckets.partition_point(|b| b.index < index);
+        if buckets.get(at).is_none_or(|b| b.index != index) {
+            buckets.insert(
+                at,
+                Bucket {
+                    index,
+                    by_kind: Vec::new(),
+                    by_code: Vec::new(),
+                },
+            );
+        }
+        let bucket = &mut buckets[at];
+
+        let counted = StatCount::of(&occurrence);
+        match bucket
+            .by_kind
+            .iter_mut()
+            .find(|k| k.kind == occurrence.kind)
+        {
+            Some(entry) => entry.stats.merge(&counted),
+            None => bucket.by_kind.push(KindStats {
+                kind: occurrence.kind,
+                stats: counted.clone(),
+            }),
+        }
+        if let Some(code) = occurrence.code {
+            match bucket.by_code.iter_mut().find(|c| c.code == code) {
+                Some(entry) => entry.stats.merge(&counted),
+                None => bucket.by_code.push(CodeStats {
+                    code: code.to_string(),
+                    stats: counted,
+                }),
+            }
+        }
+    }
+
+    /// the index of the oldest bucket of the window ending at `now`
+    fn oldest_index(&self, now: DateTime<Utc>) -> i64 {
+        let window = TimeDelta::from_std(self.window).unwrap_or(TimeDelta::MAX);
+        let start = now
+            .checked_sub_signed(window)
+            .unwrap_or(DateTime::<Utc>::MIN_UTC);
+        start.timestamp().div_euclid(BUCKET_SECS)
+    }
+
+    pub fn snapshot(&self) -> StatsSnapshot {
+        self.snapshot_at(Utc::now())
+    }
+
+    /// the statistics of the window ending at `now`, to the minute
+    pub fn snapshot_at(&self, now: DateTime<Utc>) -> StatsSnapshot {
+        let oldest = self.oldest_index(now);
+        let newest = now.timestamp().div_euclid(BUCKET_SECS);
+
+        let mut by_kind: Vec<KindStats> = Vec::new();
+        let mut by_code: Vec<CodeStats> = Vec::new();
+        let buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        for bucket in buckets
+            .iter()
+            .filter(|b| (oldest..=newest).contains(&b.index))
+        {
+            for entry in &bucket.by_kind {
+                match by_kind.iter_mut().find(|k| k.kind == entry.kind) {
+                    Some(total) => total.stats.merge(&entry.stats),
+                    None => by_kind.push(entry.clone()),
+                }
+            }
+            for entry in &bucket.by_code {
+                match by_code.iter_mut().find(|c| c.code == entry.code) {
+                    Some(total) => total.stats.merge(&entry.stats),
+                    None => by_code.push(entry.clone()),
+                }
+            }
+        }
+        drop(buckets);
+
+        let total = by_kind.iter().map(|k| k.stats.count).sum::<u64>();
+        let transient = by_kind.iter().map(|k| k.stats.transient).sum::<u64>();
+        let mut snapshot = StatsSnapshot {
+            window_secs: self.window.as_secs(),
+            total,
+            transient,
+            permanent: total - transient,
+            transient_ratio: 0.0,
+            first_seen: by_kind.iter().map(|k| k.stats.first_seen).min(),
+            last_seen: by_kind.iter().map(|k| k.stats.last_seen).max(),
+            by_kind,
+            by_code,
+        };
+
+        if snapshot.total > 0 {
+            snapshot.transient_ratio = snapshot.transient as f64 / snapshot.total as f64;
+        }
+        // stable, ties keep the order they were first seen in
+        snapshot.by_kind.sort_by_key(|k| Reverse(k.stats.count));
+        snapshot.by_code.sort_by_key(|c| Reverse(c.stats.count));
+        snapshot
+    }
+
+    /// forgets every occurrence
+    pub fn clear(&self) {
+        self.buckets
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+}
+
+impl StatCount {
+    fn of(occurrence: &Occurrence) -> Self {
+        Self {
+            count: occurrence.count,
+            transient: if occurrence.transient {
+                occurrence.count
+            } else {
+                0
+            },
+            first_seen: occurrence.at,
+            last_seen: occurrence.at,
+        }
+    }
+
+    fn merge(&mut self, other: &StatCount) {
+        self.count += other.count;
+        self.transient += other.transient;
+        self.first_seen = self.first_seen.min(other.first_seen);
+        self.last_seen = self.last_seen.max(other.last_seen);
+    }
+}
+
+/// Wraps a sink and records every report it is sent into an `ErrStats`
+/// before passing it on. Behind an `ErrorReporter` the suppressed
+/// occurrences only show up with its summaries, put the reporter inside
+/// to count them right away:
+///
+/// ```ignore
+/// let sink = StatsSink::new(
+///     ErrorReporter::new(WebhookSink::teams(client, url), window),
+///     Duration::from_secs(3600),
+/// );
+/// let stats = sink.stats();
+/// let queue = ErrorQueue::new(1024).sink(sink).install();
+/// ```
+#[cfg(feature = "report")]
+pub struct StatsSink<S> {
+    sink: S,
+    stats: Arc<ErrStats>,
+}
+
+#[cfg(feature = "report")]
+impl<S: ErrorSink> StatsSink<S> {
+    pub fn new(sink: S, window: Duration) -> Self {
+        Self::with_stats(sink, Arc::new(ErrStats::new(window)))
+    }
+
+    /// records into statistics shared with other sinks
+    pub fn with_stats(sink: S, stats: Arc<ErrStats>) -> Self {
+        Self { sink, stats }
+    }
+
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    /// the statistics, for the handler of the health panel
+    pub fn stats(&self) -> Arc<ErrStats> {
+        self.stats.clone()
+    }
+}
+
+#[cfg(feature = "report")]
+#[async_trait::async_trait]
+impl<S: ErrorSink> ErrorSink for StatsSink<S> {
+    async fn send(&self, report: &ErrorReport) -> PileResult {
+        self.stats.record_report(report);
+        self.sink.send(report).await
+    }
+
+    async fn tick(&self) -> PileResult {
+        self.sink.tick().await
+    }
+
+    async fn shutdown(&self) -> PileResult {
+        self.sink.shutdown().await
+    }
+}
@@ -0,0 +1,192 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::{Row, types::Json};
+
+use crate::{ErrorReport, ErrorSink, PileResult};
+
+/// An error read back from an error store
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredError {
+    pub id: i64,
+    pub kind: String,
+    pub severity: String,
+    pub code: Option<String>,
+    pub message: String,
+    pub user_message: String,
+    /// the sources, outermost first
+    pub chain: Vec<String>,
+    /// correlation ids, by name, and `pii_scrubbed`
+    pub metadata: Value,
+    pub fingerprint: String,
+    pub transient: bool,
+    /// occurrences the row stands for, see `ErrorReporter`
+    pub occurrences: i64,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// columns of the `errpile_errors` table, in `StoredError` order
+const COLUMNS: &str = "id, kind, severity, code, message, user_message, chain, metadata, \
+                       fingerprint, transient, occurrences, occurred_at";
+
+const INSERT: &str = "INSERT INTO errpile_errors \
+    (kind, severity, code, message, user_message, chain, metadata, fingerprint, transient, occurrences, occurred_at) \
+    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)";
+
+/// correlation ids as a json object, with `pii_scrubbed` when guest
+/// data was scrubbed
+fn metadata(report: &ErrorReport) -> Value {
+    let mut metadata = report
+        .correlation_ids
+        .iter()
+        .map(|(name, id)| (name.clone(), Value::String(id.clone())))
+        .collect::<serde_json::Map<_, _>>();
+    if report.pii_scrubbed {
+        metadata.insert("pii_scrubbed".into(), Value::Bool(true));
+    }
+    metadata.into()
+}
+
+/// Error store for one sqlx driver, the SQL is shared, only the schema
+/// differs
+macro_rules! error_store {
+    ($(#[$doc:meta])* $name:ident, $pool:ty, $row:ty, $migration:literal) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone)]
+        pub struct $name {
+            pool: $pool,
+        }
+
+        impl $name {
+            /// schema of the `errpile_errors` table, for deployments that
+            /// run their own migration tooling
+            pub const MIGRATION: &'static str = include_str!($migration);
+
+            pub fn new(pool: $pool) -> Self {
+                Self { pool }
+            }
+
+            /// creates the table and its indexes if they don't exist yet
+            pub async fn migrate(&self) -> PileResult {
+                sqlx::raw_sql(Self::MIGRATION).execute(&self.pool).await?;
+                Ok(())
+            }
+
+            pub async fn insert(&self, report: &ErrorReport) -> PileResult<i64> {
+                let id = sqlx::query(&format!("{INSERT} RETURNING id"))
+                    .bind(report.kind.as_str())
+                    .bind(report.severity.as_str())
+                    .bind(report.code.as_deref())
+                    .bind(&report.message)
+                    .bind(&report.user_message)
+                    .bind(Json(&report.chain))
+                    .bind(Json(metadata(report)))
+                    .bind(&report.fingerprint)
+                    .bind(report.transient)
+                    .bind(i64::try_from(report.count).unwrap_or(i64::MAX))
+                    .bind(report.last_seen)
+                    .fetch_one(&self.pool)
+                    .await?
+                    .try_get(0)?;
+                Ok(id)
+            }
+
+            /// the latest errors, newest first
+            pub async fn recent(&self, limit: i64) -> PileResult<Vec<StoredError>> {
+                let sql = format!(
+                    "SELECT {COLUMNS} FROM errpile_errors ORDER BY occurred_at DESC, id DESC LIMIT $1"
+                );
+                let rows = sqlx::query(&sql).bind(limit).fetch_all(&self.pool).await?;
+                rows.iter().map(Self::stored).collect()
+            }
+
+            /// errors of one fingerprint, newest first
+            pub async fn by_fingerprint(
+                &self,
+                fingerprint: &str,
+                limit: i64,
+            ) -> PileResult<Vec<StoredError>> {
+                let sql = format!(
+                    "SELECT {COLUMNS} FROM errpile_errors WHERE fingerprint = $1 \
+                     ORDER BY occurred_at DESC, id DESC LIMIT $2"
+                );
+                let rows = sqlx::query(&sql)
+                    .bind(fingerprint)
+                    .bind(limit)
+                    .fetch_all(&self.pool)
+                    .await?;
+                rows.iter().map(Self::stored).collect()
+            }
+
+            /// occurrences per kind since the given time, most frequent first
+            pub async fn counts_by_kind(
+                &self,
+                since: DateTime<Utc>,
+            ) -> PileResult<Vec<(String, i64)>> {
+                let rows = sqlx::query(
+                    "SELECT kind, CAST(SUM(occurrences) AS BIGINT) AS total FROM errpile_errors \
+                     WHERE occurred_at >= $1 GROUP BY kind ORDER BY total DESC, kind",
+                )
+                .bind(since)
+                .fetch_all(&self.pool)
+                .await?;
+
+                rows.iter()
+                    .map(|row| Ok((row.try_get("kind")?, row.try_get("total")?)))
+                    .collect()
+            }
+
+            /// deletes the errors older than the given time, returns how
+            /// many were removed
+            pub async fn purge_before(&self, before: DateTime<Utc>) -> PileResult<u64> {
+                let done = sqlx::query("DELETE FROM errpile_errors WHERE occurred_at < $1")
+                    .bind(before)
+                    .execute(&self.pool)
+                    .await?;
+                Ok(done.rows_affected())
+            }
+
+            fn stored(row: &$row) -> PileResult<StoredError> {
+                Ok(StoredError {
+                    id: row.try_get("id")?,
+                    kind: row.try_get("kind")?,
+                    severity: row.try_get("severity")?,
+                    code: row.try_get("code")?,
+                    message: row.try_get("message")?,
+                    user_message: row.try_get("user_message")?,
+                    chain: row.try_get::<Json<Vec<String>>, _>("chain")?.0,
+                    metadata: row.try_get::<Json<Value>, _>("metadata")?.0,
+                    fingerprint: row.try_get("fingerprint")?,
+                    transient: row.try_get("transient")?,
+                    occurrences: row.try_get("occurrences")?,
+                    occurred_at: row.try_get("occurred_at")?,
+                })
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl ErrorSink for $name {
+            async fn send(&self, report: &ErrorReport) -> PileResult {
+                self.insert(report).await.map(drop)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "store-postgres")]
+error_store!(
+    /// Keeps reported errors in a PostgreSQL `errpile_errors` table
+    PgErrorStore,
+    sqlx::PgPool,
+    sqlx::postgres::PgRow,
+    "../migrations/error_store/postgres.sql"
+);
+
+#[cfg(feature = "store-sqlite")]
+error_store!(
+    /// Keeps reported errors in a SQLite `errpile_errors` table, the
+    /// local error history of an agent
+    SqliteErrorStore,
+    sqlx::SqlitePool,
+    sqlx::sqlite::SqliteRow,
+    "../migrations/error_store/sqlite.sql"
+);
@@ -0,0 +1,178 @@
+use std::time::Duration;
+
+use proptest::{
+    prelude::*,
+    sample::select,
+    strategy::{BoxedStrategy, Just, LazyJust},
+};
+
+use crate::{
+    AZError, AZErrorDetails, AZErrorInner, ErrPile, FieldError, GraphInnerError, MSResponseError,
+    MSResponseErrorInner, PileKind, RemoteError,
+};
+
+/// codes Graph sends, so the generated errors reach the code specific
+/// handling (throttling, locks, auth) as often as the fallback
+const GRAPH_CODES: &[&str] = &[
+    "itemNotFound",
+    "accessDenied",
+    "activityLimitReached",
+    "resourceLocked",
+    "invalidRequest",
+    "generalException",
+    "serviceNotAvailable",
+    "InvalidAuthenticationToken",
+];
+
+const AZ_CODES: &[&str] = &[
+    "InvalidRequest",
+    "InvalidContent",
+    "InvalidArgument",
+    "InternalServerError",
+    "ServiceUnavailable",
+    "Timeout",
+    "Unauthorized",
+    "NotFound",
+];
+
+/// a known code most of the time, anything else now and then
+fn code(known: &'static [&'static str]) -> impl Strategy<Value = String> {
+    prop_oneof![
+        3 => select(known).prop_map(String::from),
+        1 => "[A-Za-z]{1,24}",
+    ]
+}
+
+fn message() -> impl Strategy<Value = String> {
+    "[ -~]{0,64}"
+}
+
+impl Arbitrary for PileKind {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        select(&PileKind::ALL[..]).boxed()
+    }
+}
+
+impl Arbitrary for MSResponseError {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        let inner_error = prop_oneof![
+            Just(serde_json::Value::Null),
+            "[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}"
+                .prop_map(|id| serde_json::json!({ "request-id": id })),
+        ];
+
+        (code(GRAPH_CODES), message(), inner_error)
+            .prop_map(|(code, message, inner_error)| MSResponseError {
+                error: MSResponseErrorInner {
+                    code,
+                    inner_error: GraphInnerError(inner_error),
+                    message,
+                },
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for AZError {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        let innererror = proptest::option::of(
+            (
+                proptest::option::of(code(AZ_CODES)),
+                proptest::option::of(message()),
+            )
+                .prop_map(|(code, message)| AZErrorInner {
+                    code,
+                    message,
+                    innererror: None,
+                }),
+        );
+        let leaf = (
+            code(AZ_CODES),
+            message(),
+            proptest::option::of("[a-z]{1,12}"),
+            innererror,
+        )
+            .prop_map(|(code, message, target, innererror)| AZError {
+                error: AZErrorDetails {
+                    code,
+                    message,
+                    target,
+                    details: None,
+                    innererror,
+                },
+            })
+            .boxed();
+
+        // `details` nests the same shape, a couple of levels is what the
+        // service sends
+        leaf.clone()
+            .prop_recursive(2, 8, 3, move |inner| {
+                (leaf.clone(), proptest::collection::vec(inner, 1..3)).prop_map(
+                    |(mut err, details)| {
+                        err.error.details = Some(details);
+                        err
+                    },
+                )
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for ErrPile {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        let secs = || proptest::option::of((1..3600u64).prop_map(Duration::from_secs));
+
+        prop_oneof![
+            LazyJust::new(|| ErrPile::Auth),
+            LazyJust::new(|| ErrPile::Permission),
+            LazyJust::new(|| ErrPile::InUse),
+            LazyJust::new(|| ErrPile::NotReady),
+            (
+                select(&["reservation", "room", "folio", "guest"][..]),
+                "[A-Z0-9]{1,8}"
+            )
+                .prop_map(|(resource, id)| ErrPile::not_found(resource, id)),
+            "[a-z]{1,12}".prop_map(ErrPile::conflict),
+            ("[a-z_]{1,16}", 1..600u64)
+                .prop_map(|(op, secs)| ErrPile::timeout(op, Duration::from_secs(secs))),
+            (secs(), proptest::option::of("[a-z.]{1,24}"))
+                .prop_map(|(wait, scope)| ErrPile::rate_limited(wait, scope)),
+            proptest::collection::vec(
+                ("[a-z_]{1,12}", "[a-z_]{1,12}", message())
+                    .prop_map(|(field, code, msg)| FieldError::new(field, code, msg)),
+                1..4
+            )
+            .prop_map(ErrPile::validation),
+            message().prop_map(ErrPile::custom),
+            (
+                any::<PileKind>(),
+                proptest::option::of(code(GRAPH_CODES)),
+                message(),
+                any::<bool>()
+            )
+                .prop_map(|(kind, code, message, transient)| {
+                    let mut remote = RemoteError::new(&ErrPile::custom(message));
+                    remote.kind = kind;
+                    remote.code = code;
+                    remote.transient = transient;
+                    remote.into()
+                }),
+            any::<MSResponseError>()
+                .prop_map(|err| crate::MicrosoftError::MS(Box::new(err)).into()),
+            any::<AZError>().prop_map(|err| Box::new(err).into()),
+        ]
+        .boxed()
+    }
+}
@@ -0,0 +1,71 @@
+use reqwest::{Client, StatusCode, header::HeaderMap};
+use serde::Serialize;
+use url::Url;
+
+use crate::{ErrPile, FieldError, HttpError, PileResult, RequestBuilderPileExt, parse_retry_after};
+
+/// Posts a message card to a Teams incoming webhook.
+///
+/// Teams answers in plain text and sometimes reports a failure inside a
+/// 200 response (`Microsoft Teams endpoint returned HTTP error 429 …`),
+/// both are turned into the matching ErrPile: throttling becomes
+/// `RateLimited`, oversized cards a `Validation` error on `body`
+pub async fn post_teams_webhook<B>(client: &Client, webhook: &str, card: &B) -> PileResult<()>
+where
+    B: Serialize + ?Sized,
+{
+    let url = Url::parse(webhook)?;
+    let res = match client.post(url.clone()).json(card).send_pile().await {
+        Ok(res) => res,
+        Err(ErrPile::Http(http)) => {
+            let text = http.body_snippet.clone();
+            return Err(
+                teams_error(http.status, &http.headers, &url, &text).unwrap_or(ErrPile::Http(http))
+            );
+        }
+        Err(err) => return Err(err),
+    };
+
+    let status = res.status();
+    let headers = res.headers().clone();
+    let text = res.text().await?;
+
+    match teams_error(status, &headers, &url, &text) {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// error described by a Teams webhook response, if any
+fn teams_error(status: StatusCode, headers: &HeaderMap, url: &Url, text: &str) -> Option<ErrPile> {
+    let lower = text.to_lowercase();
+
+    // the status Teams got from the connector, hidden in a 200 body
+    let inner_status = lower
+        .split_once("returned http error ")
+        .and_then(|(_, rest)| rest.get(..3))
+        .and_then(|code| code.parse::<u16>().ok())
+        .and_then(|code| StatusCode::from_u16(code).ok());
+    let status = inner_status.unwrap_or(status);
+
+    if status == StatusCode::TOO_MANY_REQUESTS || lower.contains("throttl") {
+        let scope = url.host_str().map(String::from);
+        return Some(ErrPile::rate_limited(parse_retry_after(headers), scope));
+    }
+
+    if lower.contains("size exceeded") || status == StatusCode::PAYLOAD_TOO_LARGE {
+        return Some(ErrPile::validation([FieldError::new(
+            "body",
+            "size_exceeded",
+            text.trim(),
+        )]));
+    }
+
+    if status.is_success() {
+        return None;
+    }
+
+    Some(ErrPile::Http(Box::new(
+        HttpError::new(status, headers.clone(), text.as_bytes()).with_url(url.clone()),
+    )))
+}
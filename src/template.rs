@@ -0,0 +1,213 @@
+use core::fmt;
+use std::error::Error;
+
+use crate::ErrPile;
+
+/// The templating engine the error comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TemplateEngine {
+    Tera,
+    Handlebars,
+}
+
+impl fmt::Display for TemplateEngine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Tera => "tera",
+            Self::Handlebars => "handlebars",
+        })
+    }
+}
+
+/// An invoice or confirmation email template that doesn't parse or
+/// render, with the template and the line the engine reported
+#[derive(Debug)]
+pub struct TemplateError {
+    pub engine: TemplateEngine,
+    pub template: Option<String>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    /// what the engine said, without the location
+    pub message: String,
+    pub source: Box<dyn Error + Send + Sync>,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to render the template")?;
+        if let Some(template) = &self.template {
+            write!(f, " `{template}`")?;
+        }
+        if let Some(line) = self.line {
+            write!(f, " at line {line}")?;
+            if let Some(column) = self.column {
+                write!(f, ":{column}")?;
+            }
+        }
+        write!(f, " ({}): {}", self.engine, self.message)
+    }
+}
+
+impl Error for TemplateError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+impl From<TemplateError> for ErrPile {
+    fn from(value: TemplateError) -> Self {
+        ErrPile::Template(Box::new(value))
+    }
+}
+
+impl ErrPile {
+    /// the template that failed and the line, `(template, line)`
+    pub fn template_location(&self) -> Option<(Option<&str>, Option<usize>)> {
+        match self.peeled() {
+            Self::Template(err) => Some((err.template.as_deref(), err.line)),
+            _ => None,
+        }
+    }
+}
+
+/// Tera puts the template in the outer message (`Failed to render
+/// 'invoice.html'`) and the cause in the sources, the line only for the
+/// syntax errors (` --> 3:14`)
+#[cfg(feature = "tera")]
+impl From<tera::Error> for TemplateError {
+    fn from(value: tera::Error) -> Self {
+        let mut template = match &value.kind {
+            tera::ErrorKind::TemplateNotFound(name) => Some(name.clone()),
+            tera::ErrorKind::MissingParent { current, .. } => Some(current.clone()),
+            tera::ErrorKind::CircularExtend { tpl, .. } => Some(tpl.clone()),
+            _ => None,
+        };
+        let (mut line, mut column) = (None, None);
+
+        // the innermost source says what went wrong
+        let mut message = String::new();
+        let mut next: Option<&(dyn Error + 'static)> = Some(&value);
+        while let Some(err) = next {
+            let text = err.to_string();
+            if template.is_none() {
+                template = quoted_name(&text);
+            }
+            message = match pest_position(&text) {
+                Some((l, c)) => {
+                    (line, column) = (Some(l), Some(c));
+                    // the last line of the report, `= expected …`
+                    let reason = text.lines().last().unwrap_or_default().trim();
+                    reason.trim_start_matches("= ").to_string()
+                }
+                None => text,
+            };
+            next = err.source();
+        }
+
+        Self {
+            engine: TemplateEngine::Tera,
+            template,
+            line,
+            column,
+            message: message.trim().to_string(),
+            source: Box::new(value),
+        }
+    }
+}
+
+#[cfg(feature = "tera")]
+impl From<tera::Error> for ErrPile {
+    fn from(value: tera::Error) -> Self {
+        TemplateError::from(value).into()
+    }
+}
+
+#[cfg(feature = "handlebars")]
+impl From<handlebars::RenderError> for TemplateError {
+    fn from(value: handlebars::RenderError) -> Self {
+        let (template, line, column, message) = match value.reason() {
+            // a template that doesn't parse, found while rendering
+            handlebars::RenderErrorReason::TemplateError(parse) => {
+                let (template, line, column, message) = handlebars_parts(parse);
+                (
+                    template.or_else(|| value.template_name.clone()),
+                    line,
+                    column,
+                    message,
+                )
+            }
+            reason => (
+                value.template_name.clone(),
+                value.line_no,
+                value.column_no,
+                reason.to_string(),
+            ),
+        };
+
+        Self {
+            engine: TemplateEngine::Handlebars,
+            template,
+            line,
+            column,
+            message,
+            source: Box::new(value),
+        }
+    }
+}
+
+#[cfg(feature = "handlebars")]
+impl From<handlebars::TemplateError> for TemplateError {
+    fn from(value: handlebars::TemplateError) -> Self {
+        let (template, line, column, message) = handlebars_parts(&value);
+        Self {
+            engine: TemplateEngine::Handlebars,
+            template,
+            line,
+            column,
+            message,
+            source: Box::new(value),
+        }
+    }
+}
+
+#[cfg(feature = "handlebars")]
+impl From<handlebars::RenderError> for ErrPile {
+    fn from(value: handlebars::RenderError) -> Self {
+        TemplateError::from(value).into()
+    }
+}
+
+#[cfg(feature = "handlebars")]
+impl From<handlebars::TemplateError> for ErrPile {
+    fn from(value: handlebars::TemplateError) -> Self {
+        TemplateError::from(value).into()
+    }
+}
+
+/// the template, line, column and reason of a syntax error
+#[cfg(feature = "handlebars")]
+fn handlebars_parts(
+    err: &handlebars::TemplateError,
+) -> (Option<String>, Option<usize>, Option<usize>, String) {
+    let (line, column) = err.pos().unzip();
+    (err.name().cloned(), line, column, err.reason().to_string())
+}
+
+/// the name between quotes of `Failed to parse 'invoice.html'`
+#[cfg(feature = "tera")]
+fn quoted_name(text: &str) -> Option<String> {
+    let start = text.find(['\'', '"'])?;
+    let quote = text[start..].chars().next()?;
+    let rest = &text[start + 1..];
+    let end = rest.find(quote)?;
+    Some(rest[..end].to_string()).filter(|name| !name.is_empty())
+}
+
+/// `line:column` of the ` --> 3:14` of a syntax error
+#[cfg(feature = "tera")]
+fn pest_position(text: &str) -> Option<(usize, usize)> {
+    let (_, rest) = text.split_once("--> ")?;
+    let position = rest.split_whitespace().next()?;
+    let (line, column) = position.split_once(':')?;
+    Some((line.parse().ok()?, column.parse().ok()?))
+}
@@ -0,0 +1,32 @@
+use std::{borrow::Cow, future::Future, time::Duration};
+
+use crate::{Deadline, ErrPile, PileResult};
+
+/// Combinators for futures resolving to a `PileResult`
+pub trait PileFutureExt<T>: Future<Output = PileResult<T>> + Sized {
+    /// Fails with `ErrPile::Timeout` naming the operation when the future
+    /// hasn't completed within `after`, transient so `retry` tries again.
+    /// The current `Deadline` shortens `after` when it's closer
+    ///
+    /// ```ignore
+    /// let folio = pms.folio(id).with_timeout(Duration::from_secs(10), "fetch folio").await?;
+    /// ```
+    fn with_timeout<O>(self, after: Duration, operation: O) -> impl Future<Output = PileResult<T>>
+    where
+        O: Into<Cow<'static, str>>,
+    {
+        async move {
+            let deadline = Deadline::current().filter(|d| d.remaining() < after);
+            let limit = deadline.map_or(after, |d| d.remaining());
+            match tokio::time::timeout(limit, self).await {
+                Ok(res) => res,
+                Err(_) => Err(match deadline {
+                    Some(deadline) => deadline.timeout(operation),
+                    None => ErrPile::timeout(operation, after),
+                }),
+            }
+        }
+    }
+}
+
+impl<T, F> PileFutureExt<T> for F where F: Future<Output = PileResult<T>> {}
@@ -0,0 +1,25 @@
+use crate::ErrPile;
+
+impl ErrPile {
+    /// Records an `error!` event with the error as structured fields
+    /// (`kind`, `code`, `transient`, `http_status`, `chain`) so the logs
+    /// can be filtered by them instead of grepping the message
+    pub fn emit(&self) {
+        #[cfg(feature = "metrics")]
+        self.count_reported();
+
+        let mut chain = self.redacted_chain();
+        let message = chain.remove(0);
+        let chain = chain.join(" <- ");
+
+        tracing::error!(
+            kind = %self.kind(),
+            code = self.code(),
+            transient = self.is_transient(),
+            http_status = self.upstream_status(),
+            chain = %chain,
+            correlation_ids = ?self.correlation_ids(),
+            "{message}"
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, AtomicUsize, Ordering},
+};
+
+use bytes::Bytes;
+use reqwest::{
+    Body, RequestBuilder, Response,
+    multipart::{Form, Part},
+};
+
+use crate::{ErrPile, Opt, PileResult, ReqwestPileResExt};
+
+/// size of the chunks the parts are streamed in
+const CHUNK_LEN: usize = 64 * 1024;
+
+#[derive(Debug, Default)]
+struct Progress {
+    sent: AtomicU64,
+    /// index + 1 of the part being sent, 0 when nothing was sent yet
+    part: AtomicUsize,
+}
+
+/// Multipart upload failed, keeps track of what was sent so the caller
+/// can decide whether to resume
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "Upload failed{} after sending {sent} of {total} bytes",
+    Opt(" while sending `", field.as_ref(), "`")
+)]
+pub struct UploadError {
+    pub field: Option<String>,
+    pub completed_fields: Vec<String>,
+    pub sent: u64,
+    pub total: u64,
+    #[source]
+    pub source: ErrPile,
+}
+
+/// Multipart form that keeps track of what was sent, so a failed
+/// upload reports the field and the number of bytes already sent
+#[derive(Debug, Default)]
+pub struct PileForm {
+    form: Form,
+    fields: Vec<String>,
+    total: u64,
+    progress: Arc<Progress>,
+}
+
+impl PileForm {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// adds a text field
+    pub fn text<N, V>(self, name: N, value: V) -> Self
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        self.bytes(name, None, value.into().into_bytes())
+    }
+
+    /// adds a file / binary field
+    pub fn bytes<N, D>(mut self, name: N, file_name: Option<String>, data: D) -> Self
+    where
+        N: Into<String>,
+        D: Into<Bytes>,
+    {
+        let name = name.into();
+        let data = data.into();
+        let len = data.len() as u64;
+        let index = self.fields.len() + 1;
+        let progress = self.progress.clone();
+
+        let chunks = (0..data.len())
+            .step_by(CHUNK_LEN)
+            .map(move |start| data.slice(start..(start + CHUNK_LEN).min(data.len())))
+            .collect::<Vec<_>>();
+
+        let stream = futures_util::stream::iter(chunks.into_iter().map(move |chunk| {
+            progress.part.store(index, Ordering::Relaxed);
+            progress
+                .sent
+                .fetch_add(chunk.len() as u64, Ordering::Relaxed);
+            Ok::<_, std::io::Error>(chunk)
+        }));
+
+        let mut part = Part::stream_with_length(Body::wrap_stream(stream), len);
+        if let Some(file_name) = file_name {
+            part = part.file_name(file_name);
+        }
+
+        self.form = self.form.part(name.clone(), part);
+        self.fields.push(name);
+        self.total += len;
+        self
+    }
+
+    /// sends the form with the request and converts the response,
+    /// failures become `ErrPile::Upload`
+    pub async fn send(self, request: RequestBuilder) -> PileResult<Response> {
+        let Self {
+            form,
+            fields,
+            total,
+            progress,
+        } = self;
+
+        let result = match request.multipart(form).send().await {
+            Ok(res) => res.error_for_pile().await,
+            Err(err) => Err(err.into()),
+        };
+
+        result.map_err(|source| {
+            let part = progress.part.load(Ordering::Relaxed);
+            let sent = progress.sent.load(Ordering::Relaxed);
+
+            // every byte went out, the server rejected the upload as a whole
+            let (field, completed) = if sent >= total {
+                (None, fields.clone())
+            } else {
+                let idx = part.saturating_sub(1);
+                (fields.get(idx).cloned(), fields[..idx].to_vec())
+            };
+
+            ErrPile::Upload(Box::new(UploadError {
+                field,
+                completed_fields: completed,
+                sent,
+                total,
+                source,
+            }))
+        })
+    }
+}
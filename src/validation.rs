@@ -0,0 +1,178 @@
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ErrPile, PileResult, redact};
+
+/// A single invalid field reported back to the caller
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldError {
+    /// name (or dotted path) of the offending field
+    pub field: String,
+    /// machine readable code, e.g. `length`, `required`
+    pub code: String,
+    /// human readable message
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new<F, C, M>(field: F, code: C, message: M) -> Self
+    where
+        F: Into<String>,
+        C: Into<String>,
+        M: Into<String>,
+    {
+        Self {
+            field: field.into(),
+            code: code.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, redact(&self.message))
+    }
+}
+
+/// List of field errors, used by the `ErrPile::Validation` variant
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FieldErrors(pub Vec<FieldError>);
+
+impl FieldErrors {
+    /// errors reported for the given field
+    pub fn for_field<'a>(&'a self, field: &'a str) -> impl Iterator<Item = &'a FieldError> {
+        self.0.iter().filter(move |e| e.field == field)
+    }
+}
+
+impl fmt::Display for FieldErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, err) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            err.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for FieldErrors {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+impl From<Vec<FieldError>> for FieldErrors {
+    fn from(value: Vec<FieldError>) -> Self {
+        Self(value)
+    }
+}
+
+/// Collects the errors of every field rather than failing on the first,
+/// the booking form then shows all of them at once:
+///
+/// ```ignore
+/// let mut check = ValidationBuilder::new();
+/// check
+///     .require("check_in", form.check_in.is_some(), "check-in date is required")
+///     .require("guests", form.guests > 0, "at least one guest");
+/// if !is_valid_email(&form.email) {
+///     check.push("email", "email");
+/// }
+/// check.finish()?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ValidationBuilder {
+    errors: Vec<FieldError>,
+}
+
+impl ValidationBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// records `message` with the `invalid` code unless `condition` holds
+    pub fn require<F, M>(&mut self, field: F, condition: bool, message: M) -> &mut Self
+    where
+        F: Into<String>,
+        M: Into<String>,
+    {
+        if !condition {
+            self.errors.push(FieldError::new(field, "invalid", message));
+        }
+        self
+    }
+
+    /// records the code, which is also the message, as the validator
+    /// errors without one
+    pub fn push<F, C>(&mut self, field: F, code: C) -> &mut Self
+    where
+        F: Into<String>,
+        C: Into<String>,
+    {
+        let code = code.into();
+        self.errors.push(FieldError::new(field, code.clone(), code));
+        self
+    }
+
+    pub fn push_error(&mut self, error: FieldError) -> &mut Self {
+        self.errors.push(error);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// `Ok` when every field is valid, `ErrPile::Validation` with all the
+    /// errors otherwise
+    pub fn finish(&mut self) -> PileResult {
+        if self.errors.is_empty() {
+            return Ok(());
+        }
+        Err(ErrPile::validation(std::mem::take(&mut self.errors)))
+    }
+}
+
+#[cfg(feature = "validator")]
+impl From<validator::ValidationErrors> for FieldErrors {
+    fn from(value: validator::ValidationErrors) -> Self {
+        let mut errors = Vec::new();
+        flatten_validator(&value, "", &mut errors);
+        // HashMap order is random, keep the output stable
+        errors.sort_by(|a, b| a.field.cmp(&b.field));
+        Self(errors)
+    }
+}
+
+#[cfg(feature = "validator")]
+fn flatten_validator(errs: &validator::ValidationErrors, prefix: &str, out: &mut Vec<FieldError>) {
+    use validator::ValidationErrorsKind;
+
+    for (field, kind) in errs.errors() {
+        let path = if prefix.is_empty() {
+            field.to_string()
+        } else {
+            format!("{prefix}.{field}")
+        };
+
+        match kind {
+            ValidationErrorsKind::Field(list) => {
+                out.extend(list.iter().map(|e| {
+                    let message = e.message.as_ref().unwrap_or(&e.code);
+                    FieldError::new(path.clone(), e.code.as_ref(), message.as_ref())
+                }));
+            }
+            ValidationErrorsKind::Struct(nested) => flatten_validator(nested, &path, out),
+            ValidationErrorsKind::List(items) => {
+                for (idx, nested) in items {
+                    flatten_validator(nested, &format!("{path}[{idx}]"), out);
+                }
+            }
+        }
+    }
+}
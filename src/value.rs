@@ -1,53 +1,548 @@
 use core::fmt;
+use std::{borrow::Cow, collections::VecDeque, sync::RwLock};
 
-use serde_json::Value;
+use serde_json::{Map, Value};
+
+use crate::{FieldError, FieldErrors};
 
 #[derive(Debug)]
 pub struct SerdeValue(pub serde_json::Value);
 
-impl SerdeValue {
-    /// Extract error information from any JSON response format
-    pub fn extract_error_from_json(&self) -> String {
-        let json_value = &self.0;
-
-        if let Value::Object(obj) = json_value {
-            // Common error field names to check
-            let error_fields = [
-                "error",
-                "err",
-                "message",
-                "detail",
-                "details",
-                "description",
-                "errorMessage",
-                "error_message",
-                "reason",
-                "title",
-            ];
-
-            for field in &error_fields {
-                if let Some(error_value) = obj.get(*field) {
-                    match error_value {
-                        Value::String(s) => return s.clone(),
-                        Value::Object(_) => {
-                            let nested = Self::extract_error_from_json(&Self(error_value.clone()));
-                            if !nested.is_empty() {
-                                return nested;
-                            }
+/// Common error field names, in the order they are checked
+const ERROR_FIELDS: &[&str] = &[
+    "error",
+    "errors",
+    "err",
+    "message",
+    "detail",
+    "details",
+    "description",
+    "errorMessage",
+    "error_message",
+    "reason",
+    "title",
+];
+
+/// field list set by `set_error_fields` / `prefer_error_fields`,
+/// `None` keeps `ERROR_FIELDS`
+static CUSTOM_FIELDS: RwLock<Option<Vec<String>>> = RwLock::new(None);
+
+/// Replaces the error field names checked by `extract_error_from_json`,
+/// in priority order. Dotted names follow nested objects, e.g.
+/// `fault.faultstring`
+pub fn set_error_fields<I, S>(fields: I)
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    let fields = fields.into_iter().map(Into::into).collect();
+    *CUSTOM_FIELDS.write().unwrap_or_else(|e| e.into_inner()) = Some(fields);
+}
+
+/// Checks the given field names before the current ones, e.g. to
+/// prefer a vendor's `developerMessage` over its generic `message`
+pub fn prefer_error_fields<I, S>(fields: I)
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    let mut custom = CUSTOM_FIELDS.write().unwrap_or_else(|e| e.into_inner());
+    let current = custom
+        .take()
+        .unwrap_or_else(|| ERROR_FIELDS.iter().map(|f| f.to_string()).collect());
+
+    let mut fields: Vec<String> = fields.into_iter().map(Into::into).collect();
+    let rest: Vec<String> = current
+        .into_iter()
+        .filter(|f| !fields.contains(f))
+        .collect();
+    fields.extend(rest);
+    *custom = Some(fields);
+}
+
+/// goes back to the built-in field list
+pub fn reset_error_fields() {
+    *CUSTOM_FIELDS.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// text of the payload, going by its BOM
+fn decode_text(bytes: &[u8]) -> Cow<'_, str> {
+    let utf16 = |bytes: &[u8], from: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| from([pair[0], pair[1]]))
+            .collect();
+        Cow::Owned(String::from_utf16_lossy(&units))
+    };
+
+    match bytes {
+        [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8_lossy(rest),
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        _ => match std::str::from_utf8(bytes) {
+            Ok(text) => Cow::Borrowed(text),
+            // Latin-1 maps every byte to the code point of the same value
+            Err(_) => Cow::Owned(bytes.iter().map(|b| char::from(*b)).collect()),
+        },
+    }
+}
+
+fn truncate_message(mut message: String, max_len: usize) -> String {
+    if message.len() <= max_len {
+        return message;
+    }
+
+    let total = message.len();
+    let mut end = max_len;
+    while !message.is_char_boundary(end) {
+        end -= 1;
+    }
+    message.truncate(end);
+    message.push_str(&format!("… ({total} bytes)"));
+    message
+}
+
+/// value of a (dotted) field
+fn lookup<'v>(obj: &'v Map<String, Value>, field: &str) -> Option<&'v Value> {
+    let mut segments = field.split('.');
+    let mut value = obj.get(segments.next()?)?;
+    for segment in segments {
+        value = value.get(segment)?;
+    }
+    Some(value)
+}
+
+/// how deep nested error objects are followed
+const MAX_DEPTH: usize = 16;
+
+/// default cap on the length of an extracted message, in bytes
+pub const DEFAULT_MAX_EXTRACTED_LEN: usize = 2048;
+
+/// how many messages of an error array are joined
+const MAX_ARRAY_MESSAGES: usize = 5;
+
+/// where the error code sits next to the message
+const CODE_FIELDS: &[&str] = &["code", "errorCode", "error_code", "type"];
+
+/// what the error is about (a field, a parameter)
+const TARGET_FIELDS: &[&str] = &["target", "field", "param", "parameter", "property"];
+
+/// Error found in a JSON body, see `SerdeValue::extract_structured`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtractedError {
+    pub code: Option<String>,
+    pub message: String,
+    pub target: Option<String>,
+    /// the whole body
+    pub raw: Value,
+}
+
+/// `code`, `message` and `request-id` of an OData error
+struct ODataEnvelope<'v> {
+    code: &'v str,
+    message: &'v str,
+    request_id: Option<&'v str>,
+}
+
+/// `code: message (request-id: …)`
+impl fmt::Display for ODataEnvelope<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)?;
+        if let Some(id) = self.request_id {
+            write!(f, " (request-id: {id})")?;
+        }
+        Ok(())
+    }
+}
+
+/// objects from the root down to the one the message was found in
+type Path<'v> = Vec<&'v Map<String, Value>>;
+
+/// How `extract_error` reads a payload: the error field names and the
+/// limits of the walk
+#[derive(Debug, Clone)]
+pub struct ExtractOptions {
+    fields: Vec<String>,
+    max_len: usize,
+    max_depth: usize,
+}
+
+impl Default for ExtractOptions {
+    fn default() -> Self {
+        Self {
+            fields: ERROR_FIELDS.iter().map(|f| f.to_string()).collect(),
+            max_len: DEFAULT_MAX_EXTRACTED_LEN,
+            max_depth: MAX_DEPTH,
+        }
+    }
+}
+
+impl ExtractOptions {
+    /// built-in field list and limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the field list set by `set_error_fields` / `prefer_error_fields`,
+    /// what `SerdeValue` extracts with
+    pub fn configured() -> Self {
+        let custom = CUSTOM_FIELDS.read().unwrap_or_else(|e| e.into_inner());
+        match custom.as_ref() {
+            Some(fields) => Self::default().fields(fields.iter().cloned()),
+            None => Self::default(),
+        }
+    }
+
+    /// error field names in priority order, dotted names follow nested
+    /// objects
+    pub fn fields<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.fields = fields.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// cap on the length of the message, in bytes
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// how deep nested error objects are followed
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+}
+
+/// what a rule found in the payload
+struct Found<'v> {
+    message: String,
+    /// the message with its context (the OData code and request id),
+    /// used by `extract_error_from_json`
+    summary: Option<String>,
+    path: Path<'v>,
+}
+
+impl<'v> Found<'v> {
+    fn new(message: String, path: Path<'v>) -> Self {
+        Self {
+            message,
+            summary: None,
+            path,
+        }
+    }
+}
+
+/// recognises one payload shape
+type Rule = for<'v> fn(&'v Value, &ExtractOptions) -> Option<Found<'v>>;
+
+/// payload shapes, most specific first, the first match wins. Anything
+/// no rule matches (strings, numbers, arrays) is its own message
+const RULES: &[Rule] = &[validation_rule, odata_rule, fields_rule];
+
+/// Finds the error in any JSON payload: the message, capped at
+/// `opts.max_len` bytes, and the code and target found next to it (or in
+/// the objects around it)
+pub fn extract_error(value: &Value, opts: &ExtractOptions) -> ExtractedError {
+    let found = find(value, opts);
+    let nearest = |names: &[&str]| {
+        found.path.iter().rev().find_map(|obj| {
+            names.iter().find_map(|name| match obj.get(*name)? {
+                Value::String(s) if !s.is_empty() => Some(s.clone()),
+                Value::Number(n) => Some(n.to_string()),
+                _ => None,
+            })
+        })
+    };
+
+    let code = nearest(CODE_FIELDS);
+    let target = nearest(TARGET_FIELDS);
+
+    ExtractedError {
+        code,
+        target,
+        message: truncate_message(found.message, opts.max_len),
+        raw: value.clone(),
+    }
+}
+
+/// Every error message in the payload instead of the first one,
+/// deduplicated and ordered by depth, the shallowest first. The whole
+/// tree is searched, Azure Document Intelligence for one keeps the page
+/// level reason in nested `details` / `innererror` objects
+pub fn extract_all_errors(value: &Value, opts: &ExtractOptions) -> Vec<String> {
+    let mut messages = Vec::new();
+    let mut push = |message: &str| {
+        let message = truncate_message(message.to_string(), opts.max_len);
+        if !message.is_empty() && !messages.contains(&message) {
+            messages.push(message);
+        }
+    };
+
+    if let Some(errors) = field_errors(value) {
+        errors.0.iter().for_each(|e| push(&e.to_string()));
+    }
+
+    let mut queue = VecDeque::from([(value, 0)]);
+    while let Some((value, depth)) = queue.pop_front() {
+        match value {
+            Value::String(s) if depth == 0 => push(s),
+            Value::Object(obj) => {
+                for field in &opts.fields {
+                    match lookup(obj, field) {
+                        Some(Value::String(s)) => push(s),
+                        Some(Value::Array(items)) => {
+                            items.iter().filter_map(Value::as_str).for_each(&mut push)
                         }
-                        _ => continue,
+                        _ => {}
                     }
                 }
+                if depth < opts.max_depth {
+                    queue.extend(obj.values().map(|v| (v, depth + 1)));
+                }
+            }
+            Value::Array(items) if depth < opts.max_depth => {
+                queue.extend(items.iter().map(|v| (v, depth + 1)));
+            }
+            _ => {}
+        }
+    }
+
+    messages
+}
+
+fn find<'v>(value: &'v Value, opts: &ExtractOptions) -> Found<'v> {
+    RULES
+        .iter()
+        .find_map(|rule| rule(value, opts))
+        .unwrap_or_else(|| match value {
+            Value::String(s) => Found::new(s.clone(), Vec::new()),
+            other => Found::new(other.to_string(), Vec::new()),
+        })
+}
+
+/// ASP.NET Core `ValidationProblemDetails`
+fn validation_rule<'v>(value: &'v Value, _: &ExtractOptions) -> Option<Found<'v>> {
+    let errors = field_errors(value)?;
+    Some(Found::new(
+        errors.to_string(),
+        value.as_object().into_iter().collect(),
+    ))
+}
+
+/// Standard OData envelope, `{"error": {"code", "message", "innererror"}}`
+fn odata_rule<'v>(value: &'v Value, _: &ExtractOptions) -> Option<Found<'v>> {
+    let root = value.as_object()?;
+    let error = root.get("error")?.as_object()?;
+    let code = error.get("code")?.as_str()?;
+    let message = error.get("message")?.as_str()?;
+
+    let request_id = ["innererror", "innerError"]
+        .iter()
+        .filter_map(|name| error.get(*name))
+        .find_map(|inner| inner.get("request-id")?.as_str());
+
+    let envelope = ODataEnvelope {
+        code,
+        message,
+        request_id,
+    };
+    Some(Found {
+        message: message.to_string(),
+        summary: Some(envelope.to_string()),
+        path: vec![root, error],
+    })
+}
+
+/// any other object, its error fields are walked
+fn fields_rule<'v>(value: &'v Value, opts: &ExtractOptions) -> Option<Found<'v>> {
+    let (message, path) = extract_from_object(value.as_object()?, &opts.fields, opts.max_depth);
+    Some(Found::new(message, path))
+}
+
+/// `{"errors": {"field": ["message", ...]}}`
+pub(crate) fn field_errors(value: &Value) -> Option<FieldErrors> {
+    let errors = value.get("errors")?.as_object()?;
+    if errors.is_empty() {
+        return None;
+    }
+
+    let mut list = Vec::new();
+    for (field, messages) in errors {
+        match messages {
+            Value::String(message) => list.push(FieldError::new(field, "invalid", message)),
+            Value::Array(messages) => {
+                for message in messages {
+                    list.push(FieldError::new(field, "invalid", message.as_str()?));
+                }
             }
+            _ => return None,
+        }
+    }
+    Some(FieldErrors(list))
+}
 
+/// Walks the error fields depth first without cloning the tree, the
+/// first string wins. An object without any error field is returned
+/// formatted as JSON
+fn extract_from_object<'v>(
+    root: &'v Map<String, Value>,
+    fields: &[String],
+    max_depth: usize,
+) -> (String, Path<'v>) {
+    let mut stack = vec![(root, 0)];
+    let path =
+        |stack: &[(&'v Map<String, Value>, usize)]| stack.iter().map(|(obj, _)| *obj).collect();
+
+    while let Some((obj, idx)) = stack.pop() {
+        let Some(field) = fields.get(idx) else {
             // Return formatted JSON if no specific error field found
-            serde_json::to_string_pretty(&json_value)
-                .unwrap_or_else(|_| "Unknown error format".to_string())
-        } else if let Value::String(s) = json_value {
-            s.clone()
-        } else {
-            json_value.to_string()
+            let message = serde_json::to_string_pretty(obj)
+                .unwrap_or_else(|_| "Unknown error format".to_string());
+            stack.push((obj, idx));
+            return (message, path(&stack));
+        };
+        stack.push((obj, idx + 1));
+
+        match lookup(obj, field) {
+            Some(Value::String(s)) if !s.is_empty() => return (s.clone(), path(&stack)),
+            // an empty message gives up on this object, the parent
+            // carries on with its next field
+            Some(Value::String(_)) => {
+                stack.pop();
+            }
+            Some(Value::Object(nested)) if stack.len() < max_depth => stack.push((nested, 0)),
+            Some(Value::Array(items)) if !items.is_empty() && stack.len() < max_depth => {
+                let message = join_messages(items, fields, max_depth - stack.len());
+                return (message, path(&stack));
+            }
+            _ => {}
+        }
+    }
+
+    (String::new(), Vec::new())
+}
+
+/// `{"errors": [...]}` style payloads, the messages of the first few
+/// items are joined
+fn join_messages(items: &[Value], fields: &[String], max_depth: usize) -> String {
+    let mut joined = items
+        .iter()
+        .take(MAX_ARRAY_MESSAGES)
+        .map(|item| match item {
+            Value::Object(obj) => extract_from_object(obj, fields, max_depth).0,
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("; ");
+
+    if items.len() > MAX_ARRAY_MESSAGES {
+        joined.push_str(&format!(" (+{} more)", items.len() - MAX_ARRAY_MESSAGES));
+    }
+    joined
+}
+
+impl SerdeValue {
+    /// Extract error information from any JSON response format,
+    /// capped at `DEFAULT_MAX_EXTRACTED_LEN` bytes
+    pub fn extract_error_from_json(&self) -> String {
+        self.extract_error_with_limit(DEFAULT_MAX_EXTRACTED_LEN)
+    }
+
+    /// `extract_error_from_json` with a custom length cap, longer
+    /// messages (e.g. a formatted body) end with an ellipsis and the
+    /// original size
+    pub fn extract_error_with_limit(&self, max_len: usize) -> String {
+        let found = find(&self.0, &ExtractOptions::configured());
+        truncate_message(found.summary.unwrap_or(found.message), max_len)
+    }
+
+    /// Like `extract_error_from_json`, also picks up the code and target
+    /// found next to the message (or in the objects around it)
+    pub fn extract_structured(&self) -> ExtractedError {
+        extract_error(&self.0, &ExtractOptions::configured())
+    }
+
+    /// every error message of the payload, see `extract_all_errors`
+    pub fn extract_all(&self) -> Vec<String> {
+        extract_all_errors(&self.0, &ExtractOptions::configured())
+    }
+
+    /// Field errors of an ASP.NET Core `ValidationProblemDetails` body,
+    /// `{"errors": {"field": ["message", ...]}}`
+    pub fn validation_errors(&self) -> Option<FieldErrors> {
+        field_errors(&self.0)
+    }
+}
+
+impl SerdeValue {
+    /// Parses a raw error payload (a file, a proxy response), BOMs and
+    /// UTF-16 are handled and non UTF-8 text is read as Latin-1. When the
+    /// text is not JSON it is kept as a JSON string so the extraction
+    /// still describes the error
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let text = decode_text(bytes);
+        let text = text.trim();
+
+        match serde_json::from_str(text) {
+            Ok(value) => Self(value),
+            Err(_) => Self(Value::String(text.to_string())),
+        }
+    }
+
+    /// Value at a JSON Pointer (`/error/innererror/code`) or a dotted
+    /// path (`error.details[0].code`)
+    pub fn at(&self, path: &str) -> Option<&Value> {
+        if path.is_empty() || path.starts_with('/') {
+            return self.0.pointer(path);
         }
+
+        let mut value = &self.0;
+        for segment in path.split('.') {
+            let (key, indexes) = match segment.find('[') {
+                Some(pos) => segment.split_at(pos),
+                None => (segment, ""),
+            };
+
+            if !key.is_empty() {
+                value = value.get(key)?;
+            }
+
+            for index in indexes.split('[').skip(1) {
+                let index: usize = index.strip_suffix(']')?.parse().ok()?;
+                value = value.get(index)?;
+            }
+        }
+        Some(value)
+    }
+
+    /// string at the path, see `at`
+    pub fn str_at(&self, path: &str) -> Option<&str> {
+        self.at(path)?.as_str()
+    }
+
+    pub fn i64_at(&self, path: &str) -> Option<i64> {
+        self.at(path)?.as_i64()
+    }
+
+    pub fn u64_at(&self, path: &str) -> Option<u64> {
+        self.at(path)?.as_u64()
+    }
+
+    pub fn bool_at(&self, path: &str) -> Option<bool> {
+        self.at(path)?.as_bool()
+    }
+
+    /// deserializes the value at the path
+    pub fn get_at<T>(&self, path: &str) -> Option<T>
+    where
+        T: for<'de> serde::Deserialize<'de>,
+    {
+        T::deserialize(self.at(path)?).ok()
     }
 }
 
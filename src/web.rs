@@ -0,0 +1,132 @@
+use serde_json::Value;
+
+use crate::ErrPile;
+
+impl ErrPile {
+    /// Body handlers answer errors with, the `ErrorEnvelope` as JSON
+    pub fn response_body(&self) -> Value {
+        serde_json::to_value(self.envelope()).unwrap_or_default()
+    }
+
+    /// logs the whole chain before the error is turned into a response,
+    /// the client only gets to see the body
+    #[cfg(any(
+        feature = "axum",
+        feature = "actix",
+        feature = "tower",
+        feature = "warp",
+        feature = "rocket",
+        feature = "async-graphql"
+    ))]
+    pub(crate) fn log_response(&self) {
+        #[cfg(feature = "tracing")]
+        self.emit();
+        #[cfg(all(feature = "log", not(feature = "tracing")))]
+        match self.correlation_ids().as_slice() {
+            [] => log::error!("{}", self.chain_text()),
+            ids => log::error!("{} ({ids:?})", self.chain_text()),
+        }
+    }
+
+    /// Content type and redacted body of the response: the `ErrorEnvelope`,
+    /// or `to_problem_details` once `set_problem_responses` is on.
+    /// `correlation_id` replaces the one of the error
+    #[cfg(any(
+        feature = "axum",
+        feature = "actix",
+        feature = "tower",
+        feature = "warp",
+        feature = "rocket"
+    ))]
+    pub(crate) fn response_payload(&self, correlation_id: Option<&str>) -> (&'static str, String) {
+        if crate::problem_responses() {
+            let mut problem = serde_json::to_value(self.to_problem_details()).unwrap_or_default();
+            if let Some(id) = correlation_id {
+                problem["correlation_id"] = Value::from(id);
+            }
+            let problem = crate::redact(&problem.to_string()).into_owned();
+            return (crate::ProblemDetails::CONTENT_TYPE, problem);
+        }
+
+        let mut envelope = self.envelope();
+        if let Some(id) = correlation_id {
+            envelope = envelope.with_correlation_id(id);
+        }
+        ("application/json", envelope.to_json())
+    }
+
+    /// `status_code` with `response_payload`, plus `Retry-After` when the
+    /// error carries a wait
+    #[cfg(any(feature = "axum", feature = "tower", feature = "warp"))]
+    pub(crate) fn http_response(&self, correlation_id: Option<&str>) -> http::Response<String> {
+        use http::{
+            HeaderValue, StatusCode,
+            header::{CONTENT_TYPE, RETRY_AFTER},
+        };
+
+        let (content_type, body) = self.response_payload(correlation_id);
+        let mut res = http::Response::new(body);
+        *res.status_mut() =
+            StatusCode::from_u16(self.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        res.headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+        if let Some(retry_after) = self.retry_after_secs() {
+            res.headers_mut().insert(RETRY_AFTER, retry_after.into());
+        }
+        res
+    }
+}
+
+/// Answers with `status_code` and the `ErrorEnvelope` (a problem
+/// document with `set_problem_responses`), plus `Retry-After` when the
+/// error carries a wait, so handlers can return `PileResult<Json<T>>`.
+/// The chain is logged through `tracing` (`emit`) or `log`, whichever is
+/// enabled
+#[cfg(feature = "axum")]
+impl axum_core::response::IntoResponse for ErrPile {
+    fn into_response(self) -> axum_core::response::Response {
+        self.log_response();
+        self.http_response(None).map(axum_core::body::Body::from)
+    }
+}
+
+/// Same status, `Retry-After` and body as the axum integration, for the
+/// services still on actix
+#[cfg(feature = "actix")]
+impl actix_web::ResponseError for ErrPile {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        actix_web::http::StatusCode::from_u16(ErrPile::status_code(self))
+            .unwrap_or(actix_web::http::StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    fn error_response(&self) -> actix_web::HttpResponse {
+        use actix_web::http::header::{CONTENT_TYPE, RETRY_AFTER};
+
+        self.log_response();
+        let mut res = actix_web::HttpResponse::build(actix_web::ResponseError::status_code(self));
+        let (content_type, body) = self.response_payload(None);
+        res.insert_header((CONTENT_TYPE, content_type));
+        if let Some(retry_after) = self.retry_after_secs() {
+            res.insert_header((RETRY_AFTER, retry_after));
+        }
+        res.body(body)
+    }
+}
+
+/// Same status, `Retry-After` and body as the axum integration, for the
+/// staff portal
+#[cfg(feature = "rocket")]
+impl<'r> rocket::response::Responder<'r, 'static> for ErrPile {
+    fn respond_to(self, _req: &'r rocket::Request<'_>) -> rocket::response::Result<'static> {
+        self.log_response();
+        let (content_type, body) = self.response_payload(None);
+        let mut res = rocket::Response::build();
+        res.status(rocket::http::Status::new(self.status_code()))
+            .raw_header("Content-Type", content_type)
+            .sized_body(body.len(), std::io::Cursor::new(body));
+        if let Some(retry_after) = self.retry_after_secs() {
+            res.raw_header("Retry-After", retry_after.to_string());
+        }
+        res.ok()
+    }
+}
@@ -0,0 +1,190 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use reqwest::Client;
+use serde_json::{Value, json};
+
+use crate::{ErrorReport, ErrorSink, PileResult, RequestBuilderPileExt, post_teams_webhook};
+
+/// Chat the webhook posts to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookChat {
+    Teams,
+    Slack,
+}
+
+/// Posts error cards to a Teams or Slack incoming webhook.
+///
+/// Reports are batched: the first one waits `delay` for others to join
+/// it, a full batch (`max_batch`) is posted right away. Every card lists
+/// the kind, code, user message, correlation id and first/last seen
+#[derive(Debug, Clone)]
+pub struct WebhookSink {
+    client: Client,
+    url: String,
+    chat: WebhookChat,
+    max_batch: usize,
+    delay: Duration,
+    pending: Arc<Mutex<Vec<ErrorReport>>>,
+}
+
+impl WebhookSink {
+    pub fn new<U: Into<String>>(client: Client, chat: WebhookChat, url: U) -> Self {
+        Self {
+            client,
+            url: url.into(),
+            chat,
+            max_batch: 10,
+            delay: Duration::from_secs(5),
+            pending: Arc::default(),
+        }
+    }
+
+    pub fn teams<U: Into<String>>(client: Client, url: U) -> Self {
+        Self::new(client, WebhookChat::Teams, url)
+    }
+
+    pub fn slack<U: Into<String>>(client: Client, url: U) -> Self {
+        Self::new(client, WebhookChat::Slack, url)
+    }
+
+    /// reports per message, a full batch is posted without waiting
+    pub fn max_batch(mut self, max_batch: usize) -> Self {
+        self.max_batch = max_batch.max(1);
+        self
+    }
+
+    /// how long the first report of a batch waits for others
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// posts the pending reports now
+    pub async fn flush(&self) -> PileResult {
+        let batch = std::mem::take(&mut *self.pending.lock().unwrap_or_else(|e| e.into_inner()));
+        if batch.is_empty() {
+            return Ok(());
+        }
+
+        match self.chat {
+            WebhookChat::Teams => {
+                post_teams_webhook(&self.client, &self.url, &teams_card(&batch)).await
+            }
+            WebhookChat::Slack => {
+                let body = slack_message(&batch);
+                self.client
+                    .post(&self.url)
+                    .json(&body)
+                    .send_pile()
+                    .await
+                    .map(drop)
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ErrorSink for WebhookSink {
+    async fn send(&self, report: &ErrorReport) -> PileResult {
+        let queued = {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            pending.push(report.clone());
+            pending.len()
+        };
+
+        if queued >= self.max_batch {
+            return self.flush().await;
+        }
+
+        if queued == 1 {
+            let sink = self.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(sink.delay).await;
+                // nowhere to report a failing webhook to
+                let _ = sink.flush().await;
+            });
+        }
+        Ok(())
+    }
+}
+
+/// label and value of the facts shown for a report
+fn facts(report: &ErrorReport) -> Vec<(&'static str, String)> {
+    let mut facts = vec![("Kind", report.kind.to_string())];
+    if let Some(code) = &report.code {
+        facts.push(("Code", code.clone()));
+    }
+    facts.push(("User message", report.user_message.clone()));
+    if let Some((name, id)) = report.correlation_ids.first() {
+        facts.push(("Correlation id", format!("{id} ({name})")));
+    }
+    if report.count > 1 {
+        facts.push(("Occurrences", report.count.to_string()));
+    }
+    facts.push(("First seen", report.first_seen.to_rfc3339()));
+    facts.push(("Last seen", report.last_seen.to_rfc3339()));
+    facts
+}
+
+/// adaptive card with a title and a fact set per report
+fn teams_card(batch: &[ErrorReport]) -> Value {
+    let body: Vec<Value> = batch
+        .iter()
+        .flat_map(|report| {
+            let facts: Vec<Value> = facts(report)
+                .into_iter()
+                .map(|(title, value)| json!({"title": title, "value": value}))
+                .collect();
+            [
+                json!({
+                    "type": "TextBlock",
+                    "text": report.message,
+                    "weight": "Bolder",
+                    "color": "Attention",
+                    "wrap": true,
+                    "separator": true,
+                }),
+                json!({"type": "FactSet", "facts": facts}),
+            ]
+        })
+        .collect();
+
+    json!({
+        "type": "message",
+        "attachments": [{
+            "contentType": "application/vnd.microsoft.card.adaptive",
+            "content": {
+                "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                "type": "AdaptiveCard",
+                "version": "1.4",
+                "body": body,
+            },
+        }],
+    })
+}
+
+/// a section block per report, `text` is what the notification shows
+fn slack_message(batch: &[ErrorReport]) -> Value {
+    let blocks: Vec<Value> = batch
+        .iter()
+        .map(|report| {
+            let fields: Vec<Value> = facts(report)
+                .into_iter()
+                .map(|(title, value)| json!({"type": "mrkdwn", "text": format!("*{title}*\n{value}")}))
+                .collect();
+            json!({
+                "type": "section",
+                "text": {"type": "mrkdwn", "text": format!(":rotating_light: *{}*", report.message)},
+                "fields": fields,
+            })
+        })
+        .collect();
+
+    json!({
+        "text": format!("{} error(s) reported", batch.len()),
+        "blocks": blocks,
+    })
+}
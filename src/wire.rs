@@ -0,0 +1,207 @@
+use core::fmt;
+use std::{borrow::Cow, sync::RwLock, time::Duration};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+use crate::{ErrPile, PileKind, mask_card_data, redact};
+
+static SERVICE_NAME: RwLock<Option<String>> = RwLock::new(None);
+
+/// Name of this service, sent as the `origin` of the errors it serializes
+pub fn set_service_name<N: Into<String>>(name: N) {
+    *SERVICE_NAME.write().unwrap_or_else(|e| e.into_inner()) = Some(name.into());
+}
+
+/// the name set with `set_service_name`
+pub fn service_name() -> Option<String> {
+    SERVICE_NAME
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+/// The serialized form of an `ErrPile`, and what a received one is
+/// deserialized back into (`ErrPile::Remote`): the error keeps the kind,
+/// code and transient flag of the service it came from, so a gateway
+/// answers and retries as that service would
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct RemoteError {
+    pub kind: PileKind,
+    #[serde(default)]
+    pub code: Option<String>,
+    pub message: String,
+    #[serde(default)]
+    pub user_message: String,
+    #[serde(default)]
+    pub transient: bool,
+    #[serde(default)]
+    pub http_status: Option<u16>,
+    /// the sources, outermost first
+    #[serde(default)]
+    pub chain: Vec<String>,
+    /// the service that raised the error, see `set_service_name`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+    /// correlation ids, `retry_after_ms`, `field_errors`
+    #[serde(default)]
+    #[cfg_attr(feature = "utoipa", schema(value_type = Object))]
+    pub metadata: Map<String, Value>,
+}
+
+impl RemoteError {
+    pub fn new(err: &ErrPile) -> Self {
+        // forwarded errors are masked again, the sender may not have
+        if let ErrPile::Remote(remote) = err.peeled() {
+            return remote.redacted();
+        }
+
+        let mut chain = err.redacted_chain();
+        let message = chain.remove(0);
+        Self {
+            kind: err.kind(),
+            code: err.code(),
+            message,
+            user_message: redact(&err.user_message()).into_owned(),
+            transient: err.is_transient(),
+            http_status: err.upstream_status(),
+            chain,
+            origin: service_name(),
+            metadata: err.wire_metadata(),
+        }
+    }
+
+    /// the error with its messages, chain and metadata through `redact`
+    pub fn redacted(&self) -> Self {
+        let mut metadata = self.metadata.clone();
+        metadata.values_mut().for_each(redact_value);
+        Self {
+            message: redact(&self.message).into_owned(),
+            user_message: redact(&self.user_message).into_owned(),
+            chain: self
+                .chain
+                .iter()
+                .map(|source| redact(source).into_owned())
+                .collect(),
+            metadata,
+            ..self.clone()
+        }
+    }
+
+    /// the `retry_after_ms` sent in the metadata
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.metadata
+            .get("retry_after_ms")
+            .and_then(Value::as_u64)
+            .map(Duration::from_millis)
+    }
+}
+
+impl fmt::Display for RemoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&mask_card_data(&self.message))
+    }
+}
+
+impl From<RemoteError> for ErrPile {
+    fn from(value: RemoteError) -> Self {
+        Self::Remote(Box::new(value))
+    }
+}
+
+impl ErrPile {
+    /// correlation ids, the retry hint and the invalid fields, the
+    /// `metadata` of the serialized error
+    fn wire_metadata(&self) -> Map<String, Value> {
+        let mut metadata = Map::new();
+        for (name, id) in self.correlation_ids() {
+            metadata.insert(name.into(), id.into());
+        }
+        if let Some(retry_after) = self.retry_after() {
+            metadata.insert(
+                "retry_after_ms".into(),
+                (retry_after.as_millis() as u64).into(),
+            );
+        }
+        if let Some(fields) = self.field_errors()
+            && let Ok(mut fields) = serde_json::to_value(fields)
+        {
+            redact_value(&mut fields);
+            metadata.insert("field_errors".into(), fields);
+        }
+        metadata
+    }
+
+    /// kind of the error a remote service sent, or an application,
+    /// booking, channel, OPERA or fiscal error or a registered constraint
+    /// violation was mapped to
+    pub(crate) fn remote_kind(&self) -> Option<PileKind> {
+        match self.peeled() {
+            Self::Remote(remote) => Some(remote.kind),
+            Self::App(app) => Some(app.kind),
+            Self::Multi(multi) => multi.shared_kind(),
+            #[cfg(feature = "booking")]
+            Self::Booking(booking) => Some(booking.kind()),
+            #[cfg(feature = "channel")]
+            Self::Channel(channel) => Some(channel.kind()),
+            #[cfg(feature = "ohip")]
+            Self::Ohip(ohip) => Some(ohip.kind()),
+            #[cfg(feature = "fiscal")]
+            Self::Fiscal(fiscal) => Some(fiscal.failure.kind()),
+            #[cfg(feature = "sms")]
+            Self::Sms(sms) => Some(sms.failure.kind()),
+            #[cfg(feature = "doorlock")]
+            Self::DoorLock(lock) => Some(lock.failure.kind()),
+            #[cfg(feature = "sqlx")]
+            Self::Storage(storage) => match &**storage {
+                crate::StorageError::DB(db) => crate::constraint::violated_constraint_kind(db),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// the strings of a metadata value through `redact`
+fn redact_value(value: &mut Value) {
+    match value {
+        Value::String(text) => {
+            if let Cow::Owned(masked) = redact(text) {
+                *text = masked;
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_value),
+        Value::Object(map) => map.values_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+/// `{kind, code, message, user_message, transient, http_status, chain,
+/// origin, metadata}`, the messages and the metadata go through `redact`,
+/// errors received from another service included
+impl Serialize for ErrPile {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RemoteError::new(self).serialize(serializer)
+    }
+}
+
+/// always an `ErrPile::Remote`
+impl<'de> Deserialize<'de> for ErrPile {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        RemoteError::deserialize(deserializer).map(Self::from)
+    }
+}
+
+/// the schema of `RemoteError`, which is what `ErrPile` serializes to
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for ErrPile {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        RemoteError::schema_name()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        RemoteError::json_schema(generator)
+    }
+}
@@ -0,0 +1,142 @@
+use core::fmt;
+
+use roxmltree::{Document, Node};
+
+use crate::mask_card_data;
+
+/// Fault / error returned as XML, either a SOAP `<Fault>`, the
+/// `<Errors><Error Code="" ShortText=""/></Errors>` of an OTA response or
+/// a plain `<Error><Code/><Message/></Error>` document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XmlFault {
+    /// the body was a SOAP fault
+    pub soap: bool,
+    /// `faultcode` (SOAP 1.1), `Code/Value` (SOAP 1.2), the `Code`
+    /// attribute (OTA) or `Code`
+    pub code: Option<String>,
+    /// `faultstring` (SOAP 1.1), `Reason/Text` (SOAP 1.2), `ShortText`
+    /// (OTA) or `Message`
+    pub message: Option<String>,
+    /// flattened text of the `detail` element
+    pub detail: Option<String>,
+    /// name of the document root element
+    pub root: String,
+}
+
+impl XmlFault {
+    /// checks if the body looks like a XML document
+    pub fn looks_like_xml(content_type: Option<&str>, body: &str) -> bool {
+        if content_type.is_some_and(|ct| ct.to_ascii_lowercase().contains("xml")) {
+            return true;
+        }
+
+        body.trim_start().starts_with("<?xml")
+    }
+
+    /// parses the fault out of the body, `None` if the body is not XML
+    /// or has no recognizable error elements
+    pub fn parse(body: &str) -> Option<Self> {
+        let doc = Document::parse(body).ok()?;
+        let root = doc.root_element();
+
+        let fault = root
+            .descendants()
+            .find(|n| n.is_element() && n.tag_name().name().eq_ignore_ascii_case("fault"));
+
+        // OTA responses carry the first error as attributes, the text of
+        // the element being the longer explanation
+        let ota_error = root
+            .tag_name()
+            .name()
+            .starts_with("OTA_")
+            .then(|| child(root, "Errors").and_then(|errors| child(errors, "Error")))
+            .flatten();
+
+        let fault = match (fault, ota_error) {
+            (Some(fault), _) => Self {
+                soap: true,
+                code: text_of(fault, &["faultcode"])
+                    .or_else(|| child(fault, "Code").and_then(|c| text_of(c, &["Value"]))),
+                message: text_of(fault, &["faultstring"])
+                    .or_else(|| child(fault, "Reason").and_then(|r| text_of(r, &["Text"]))),
+                detail: child(fault, "detail")
+                    .map(flatten)
+                    .filter(|d| !d.is_empty()),
+                root: root.tag_name().name().to_string(),
+            },
+            (None, Some(error)) => {
+                let text = Some(flatten(error)).filter(|t| !t.is_empty());
+                let short_text = error.attribute("ShortText").map(String::from);
+                Self {
+                    soap: false,
+                    code: error.attribute("Code").map(String::from),
+                    detail: short_text.is_some().then_some(text.clone()).flatten(),
+                    message: short_text.or(text),
+                    root: root.tag_name().name().to_string(),
+                }
+            }
+            (None, None) => Self {
+                soap: false,
+                code: text_of(root, &["Code", "ErrorCode"]),
+                message: text_of(root, &["Message", "ErrorMessage", "Description"]),
+                detail: text_of(root, &["Detail", "Details"]),
+                root: root.tag_name().name().to_string(),
+            },
+        };
+
+        // payment gateways answering in XML echo the request
+        let mask = |text: Option<String>| text.map(|t| mask_card_data(&t).into_owned());
+        let fault = Self {
+            message: mask(fault.message),
+            detail: mask(fault.detail),
+            ..fault
+        };
+
+        (fault.code.is_some() || fault.message.is_some()).then_some(fault)
+    }
+}
+
+impl fmt::Display for XmlFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.code, &self.message) {
+            (Some(code), Some(message)) => write!(f, "{code} - {message}")?,
+            (Some(text), None) | (None, Some(text)) => f.write_str(text)?,
+            (None, None) => f.write_str(&self.root)?,
+        }
+
+        if let Some(detail) = &self.detail {
+            write!(f, " ({detail})")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for XmlFault {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+/// first descendant with the local name (case insensitive)
+fn child<'a, 'i>(node: Node<'a, 'i>, name: &str) -> Option<Node<'a, 'i>> {
+    node.descendants()
+        .find(|n| n.is_element() && n.tag_name().name().eq_ignore_ascii_case(name))
+}
+
+fn text_of(node: Node, names: &[&str]) -> Option<String> {
+    names
+        .iter()
+        .filter_map(|name| child(node, name))
+        .map(flatten)
+        .find(|t| !t.is_empty())
+}
+
+fn flatten(node: Node) -> String {
+    node.descendants()
+        .filter(|n| n.is_text())
+        .filter_map(|n| n.text())
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "decimal")]
+
+use std::{error::Error, str::FromStr};
+
+use error_pile::{AmountFailure, ErrPile, PileKind, PileResult, parse_amount};
+use rust_decimal::Decimal;
+
+fn dec(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap()
+}
+
+#[test]
+fn amounts_with_their_currency() {
+    assert_eq!(parse_amount("89.50", None), Ok(dec("89.50")));
+    assert_eq!(parse_amount("EUR 89.50", Some("EUR")), Ok(dec("89.50")));
+    assert_eq!(parse_amount("89.50 chf", Some("CHF")), Ok(dec("89.50")));
+    assert_eq!(parse_amount("€89.50", Some("eur")), Ok(dec("89.50")));
+    assert_eq!(parse_amount(" -12 ", Some("EUR")), Ok(dec("-12")));
+}
+
+#[test]
+fn comma_decimal_separators_are_told_apart() {
+    for input in ["89,50", "EUR 1.234,50"] {
+        let err = parse_amount(input, Some("EUR")).unwrap_err();
+        assert_eq!(err.failure, AmountFailure::CommaDecimalSeparator, "{input}");
+        assert_eq!(err.input, input);
+        assert_eq!(err.expected_currency.as_deref(), Some("EUR"));
+        assert!(err.source().is_some());
+    }
+
+    let err = parse_amount("EUR 1.234,50", Some("EUR")).unwrap_err();
+    assert_eq!(err.comma_decimal(), Some(dec("1234.50")));
+    assert_eq!(
+        err.to_string(),
+        "Invalid amount `EUR 1.234,50`: a comma is used as decimal separator"
+    );
+
+    let err = parse_amount("n/a", None).unwrap_err();
+    assert_eq!(err.failure, AmountFailure::InvalidNumber);
+    assert_eq!(err.comma_decimal(), None);
+}
+
+#[test]
+fn thousands_commas_are_not_decimal_commas() {
+    // 1234 in the US, 1.234 in Germany
+    let err = parse_amount("1,234", Some("EUR")).unwrap_err();
+    assert_eq!(err.failure, AmountFailure::AmbiguousSeparator);
+    assert_eq!(err.comma_decimal(), None);
+    assert_eq!(
+        err.to_string(),
+        "Invalid amount `1,234`: the comma may separate the thousands or the decimals"
+    );
+
+    let err = parse_amount("USD 1,234,567", None).unwrap_err();
+    assert_eq!(err.failure, AmountFailure::InvalidNumber);
+
+    // the dot tells
+    let err = parse_amount("1.234,567", None).unwrap_err();
+    assert_eq!(err.comma_decimal(), Some(dec("1234.567")));
+}
+
+#[test]
+fn currencies_are_checked() {
+    let err = parse_amount("USD 120.00", Some("EUR")).unwrap_err();
+    assert_eq!(err.failure, AmountFailure::CurrencyMismatch);
+    assert_eq!(err.currency.as_deref(), Some("USD"));
+    assert_eq!(
+        err.to_string(),
+        "Invalid amount `USD 120.00`: in USD, expected EUR"
+    );
+
+    let err = parse_amount("Euro 120.00", Some("EUR")).unwrap_err();
+    assert_eq!(err.failure, AmountFailure::UnknownCurrency);
+}
+
+#[test]
+fn amounts_are_validation_errors() {
+    fn import(line: &str) -> PileResult<Decimal> {
+        Ok(parse_amount(line, Some("EUR"))?)
+    }
+
+    let err = import("89,50").unwrap_err();
+    assert_eq!(err.variant_name(), "Amount");
+    assert_eq!(err.kind(), PileKind::Validation);
+    assert_eq!(err.status_code(), 422);
+    assert_eq!(err.code().as_deref(), Some("comma_decimal_separator"));
+    assert!(!err.is_transient());
+    assert_eq!(err.amount_error().unwrap().input, "89,50");
+    assert!(
+        err.message_args()
+            .contains(&("currency", "EUR".to_string()))
+    );
+}
+
+#[test]
+fn decimal_errors_are_parse_errors() {
+    let err = ErrPile::from(Decimal::from_str("12..5").unwrap_err());
+    assert_eq!(err.variant_name(), "Decimal");
+    assert_eq!(err.kind(), PileKind::Parse);
+    assert!(err.amount_error().is_none());
+}
@@ -0,0 +1,59 @@
+#![cfg(feature = "test-util")]
+
+use std::panic::{AssertUnwindSafe, Location, catch_unwind};
+
+use error_pile::{ContextFrame, ErrPile, PileResult, RemoteError, assert_pile, assert_pile_code};
+
+fn lookup(id: &str) -> PileResult<u32> {
+    Err(ErrPile::not_found("room", id))
+}
+
+fn panic_message(f: impl FnOnce()) -> String {
+    let payload = catch_unwind(AssertUnwindSafe(f)).unwrap_err();
+    payload
+        .downcast_ref::<String>()
+        .cloned()
+        .unwrap_or_default()
+}
+
+#[test]
+fn matching_errors_pass() {
+    assert_pile!(lookup("101"), ErrPile::NotFound(err) if err.resource == "room");
+    assert_pile!(lookup("101"), ErrPile::NotFound(err) if err.id == "101");
+    assert_pile!(ErrPile::Auth, ErrPile::Auth);
+
+    // the context frames are looked through
+    let err = ErrPile::InUse.with_frame(ContextFrame::new("rooms::assign", Location::caller()));
+    assert_pile!(err, ErrPile::InUse);
+}
+
+#[test]
+fn mismatches_print_the_chain() {
+    let message = panic_message(|| {
+        let err: ErrPile = std::io::Error::other("disk full").into();
+        assert_pile!(err, ErrPile::Auth);
+    });
+    assert!(message.contains("expected an error matching `ErrPile::Auth`"));
+    assert!(message.contains("disk full"), "{message}");
+}
+
+#[test]
+fn ok_values_are_shown() {
+    let message = panic_message(|| assert_pile!(Ok::<_, ErrPile>(7), ErrPile::Auth));
+    assert_eq!(
+        message,
+        "expected an error matching `ErrPile::Auth`, got Ok(7)"
+    );
+}
+
+#[test]
+fn codes() {
+    let mut remote = RemoteError::new(&ErrPile::custom("no rate for the stay"));
+    remote.code = Some("PILE_DB_001".into());
+    let res: PileResult<()> = Err(remote.into());
+    assert_pile_code!(res, "PILE_DB_001");
+
+    let message = panic_message(move || assert_pile_code!(res, "PILE_DB_002"));
+    assert!(message.contains("code \"PILE_DB_002\""));
+    assert!(message.contains("no rate for the stay"));
+}
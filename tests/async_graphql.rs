@@ -0,0 +1,27 @@
+#![cfg(feature = "async-graphql")]
+
+use std::time::Duration;
+
+use async_graphql::{ErrorExtensions, Value};
+use error_pile::ErrPile;
+
+#[test]
+fn graphql_errors_carry_the_kind_not_the_chain() {
+    let err = ErrPile::custom("connection string host=db password=hunter2").extend();
+    assert!(!err.message.contains("hunter2"));
+
+    let extensions = err.extensions.unwrap();
+    assert_eq!(extensions.get("code"), Some(&Value::from("internal")));
+    assert_eq!(extensions.get("transient"), Some(&Value::from(false)));
+    assert_eq!(extensions.get("retryAfter"), None);
+}
+
+#[test]
+fn graphql_errors_tell_clients_when_to_retry() {
+    let err = ErrPile::rate_limited(Some(Duration::from_secs(30)), None).extend();
+
+    let extensions = err.extensions.unwrap();
+    assert_eq!(extensions.get("code"), Some(&Value::from("rate_limited")));
+    assert_eq!(extensions.get("transient"), Some(&Value::from(true)));
+    assert_eq!(extensions.get("retryAfter"), Some(&Value::from(30)));
+}
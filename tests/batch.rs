@@ -0,0 +1,52 @@
+#![cfg(feature = "retry")]
+
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use error_pile::{Backoff, ErrPile, retry_batch};
+
+#[tokio::test]
+async fn only_transient_items_are_retried() {
+    let calls = Mutex::new(HashMap::<u32, u32>::new());
+    let backoff = Backoff::new()
+        .base_delay(Duration::from_millis(1))
+        .max_attempts(3);
+
+    let summary = retry_batch(&backoff, [1, 2, 3, 4, 5], |folio| {
+        let call = {
+            let mut calls = calls.lock().unwrap();
+            let call = calls.entry(folio).or_default();
+            *call += 1;
+            *call
+        };
+        async move {
+            match (folio, call) {
+                // a 503 that goes away
+                (2, 1) => Err(ErrPile::NotReady),
+                (3, _) => Err(ErrPile::not_found("folio", folio)),
+                // keeps timing out
+                (5, _) => Err(ErrPile::timeout("export", Duration::from_secs(1))),
+                _ => Ok(folio * 10),
+            }
+        }
+    })
+    .await;
+
+    assert!(!summary.is_complete());
+    let mut succeeded = summary.succeeded.clone();
+    succeeded.sort();
+    assert_eq!(succeeded, [(1, 10), (2, 20), (4, 40)]);
+
+    assert_eq!(summary.failed.len(), 1);
+    assert_eq!(summary.failed[0].0, 3);
+    assert!(summary.failed[0].1.is_not_found());
+
+    assert_eq!(summary.exhausted.len(), 1);
+    assert_eq!(summary.exhausted[0].0, 5);
+    assert!(summary.exhausted[0].1.is_timeout());
+
+    let calls = calls.into_inner().unwrap();
+    assert_eq!(calls[&1], 1, "succeeded items run once");
+    assert_eq!(calls[&2], 2);
+    assert_eq!(calls[&3], 1, "permanent failures aren't retried");
+    assert_eq!(calls[&5], 3);
+}
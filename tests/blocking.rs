@@ -0,0 +1,25 @@
+#![cfg(feature = "blocking")]
+
+use error_pile::{ErrPile, ReqwestBlockingPileResExt};
+
+mod common;
+
+use common::blocking_response;
+
+#[test]
+fn blocking_response_uses_the_same_error_handling() {
+    let value: serde_json::Value = blocking_response(200, r#"{"ok":true}"#)
+        .to_pile_result()
+        .unwrap();
+    assert_eq!(value["ok"], true);
+
+    let err = blocking_response(503, r#"{"message":"maintenance"}"#)
+        .to_pile_empty()
+        .unwrap_err();
+    assert!(err.is_transient());
+
+    let ErrPile::Http(http) = err else {
+        panic!("expected Http variant");
+    };
+    assert_eq!(http.message(), "maintenance");
+}
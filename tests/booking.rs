@@ -0,0 +1,82 @@
+#![cfg(feature = "booking")]
+
+use chrono::NaiveDate;
+use error_pile::{BookingError, ErrPile, PileKind};
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 8, day).unwrap()
+}
+
+#[test]
+fn rooms_and_rates() {
+    let err: ErrPile = BookingError::RoomUnavailable {
+        room_type: "DBL".into(),
+        arrival: date(14),
+        departure: date(17),
+    }
+    .into();
+    assert_eq!(err.kind(), PileKind::Conflict);
+    assert_eq!(err.status_code(), 409);
+    assert!(err.is_conflict());
+    assert_eq!(err.code().as_deref(), Some("room_unavailable"));
+    assert_eq!(
+        err.user_message(),
+        "No DBL room is available from 2025-08-14 to 2025-08-17"
+    );
+
+    let err: ErrPile = BookingError::RateNotFound {
+        rate_code: "BAR-FLEX".into(),
+        room_type: Some("DBL".into()),
+    }
+    .into();
+    assert_eq!(err.status_code(), 404);
+    assert!(err.is_not_found());
+    assert_eq!(
+        err.to_string(),
+        "The rate plan BAR-FLEX was not found for DBL"
+    );
+}
+
+#[test]
+fn stays_and_guests() {
+    let err: ErrPile = BookingError::StayDatesInvalid {
+        arrival: date(17),
+        departure: date(14),
+        reason: "departure is before arrival".into(),
+    }
+    .into();
+    assert_eq!(err.kind(), PileKind::Validation);
+    assert_eq!(err.status_code(), 422);
+    assert!(!err.is_transient());
+
+    let err: ErrPile = BookingError::OverbookingRisk {
+        room_type: "SGL".into(),
+        date: date(15),
+        rooms: 2,
+    }
+    .into();
+    assert_eq!(err.code().as_deref(), Some("overbooking_risk"));
+    assert_eq!(err.variant_name(), "Booking");
+
+    let err: ErrPile = BookingError::GuestProfileMissing {
+        profile_id: "G-20931".into(),
+    }
+    .into();
+    assert_eq!(err.kind(), PileKind::NotFound);
+    assert_eq!(
+        err.user_message(),
+        "The guest profile G-20931 was not found"
+    );
+}
+
+#[test]
+fn the_code_survives_the_wire() {
+    let err: ErrPile = BookingError::GuestProfileMissing {
+        profile_id: "G-20931".into(),
+    }
+    .into();
+    let received: ErrPile = serde_json::from_value(serde_json::to_value(&err).unwrap()).unwrap();
+    assert_eq!(received.kind(), PileKind::NotFound);
+    assert_eq!(received.code().as_deref(), Some("guest_profile_missing"));
+    assert_eq!(received.status_code(), 404);
+}
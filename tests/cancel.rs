@@ -0,0 +1,38 @@
+#![cfg(feature = "tokio-util")]
+
+use std::time::Duration;
+
+use error_pile::{ErrPile, PileKind, check_cancelled, until_cancelled};
+use tokio_util::sync::CancellationToken;
+
+#[tokio::test]
+async fn cancelled_work_stops_with_cancelled() {
+    let token = CancellationToken::new();
+    check_cancelled(&token).unwrap();
+    let rooms = until_cancelled(&token, async { Ok(12) }).await.unwrap();
+    assert_eq!(rooms, 12);
+
+    let shutdown = token.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        shutdown.cancel();
+    });
+    let err = until_cancelled(&token, async {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        Ok(())
+    })
+    .await
+    .unwrap_err();
+    assert!(err.is_cancelled());
+    assert_eq!(err.kind(), PileKind::Cancelled);
+    assert_eq!(err.status_code(), 503);
+
+    // checked between the batches of a long loop
+    assert!(check_cancelled(&token).unwrap_err().is_cancelled());
+
+    let err = ErrPile::cancelled_because("shutting down");
+    assert_eq!(
+        err.to_string(),
+        "The operation was cancelled: shutting down"
+    );
+}
@@ -0,0 +1,142 @@
+use std::{fs, path::PathBuf};
+
+use error_pile::{
+    Audience, ErrPile, MessageCatalog, PileKind, load_message_catalog, message_catalog,
+    set_message_catalog,
+};
+
+const CATALOG: &str = r#"{
+    "NotFound": {
+        "guest": "We could not find this {resource}",
+        "front_desk": "No {resource} with id {id}, check the folio"
+    },
+    "RateLimited": { "engineer": "throttled by {scope}, retry in {retry_after}s" },
+    "51": { "guest": "Your card was declined ({category}), {{please}} use another one" }
+}"#;
+
+fn catalog() -> MessageCatalog {
+    MessageCatalog::from_json(CATALOG.as_bytes()).unwrap()
+}
+
+fn not_found() -> ErrPile {
+    ErrPile::not_found("reservation", "R-1042")
+}
+
+fn temp_file(name: &str, content: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("{}-{name}", std::process::id()));
+    fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn templates_are_filled() {
+    let catalog = catalog();
+
+    assert_eq!(
+        catalog.render(&not_found(), Audience::FrontDesk).as_deref(),
+        Some("No reservation with id R-1042, check the folio")
+    );
+    // looked up by code first
+    assert_eq!(
+        catalog
+            .render(&ErrPile::payment_declined("51"), Audience::Guest)
+            .as_deref(),
+        Some("Your card was declined (insufficient_funds), {please} use another one")
+    );
+    assert_eq!(catalog.render(&not_found(), Audience::Engineer), None);
+    assert_eq!(catalog.render(&ErrPile::Auth, Audience::Guest), None);
+}
+
+#[test]
+fn unknown_placeholders_are_kept() {
+    let catalog = MessageCatalog::from_json(br#"{"Auth": {"guest": "{nope} {kind}"}}"#).unwrap();
+    assert_eq!(
+        catalog.render(&ErrPile::Auth, Audience::Guest).as_deref(),
+        Some("{nope} auth")
+    );
+}
+
+#[cfg(feature = "booking")]
+#[test]
+fn booking_fields_are_arguments() {
+    use chrono::NaiveDate;
+    use error_pile::BookingError;
+
+    let catalog = MessageCatalog::from_json(
+        br#"{"room_unavailable": {"guest": "No {room_type} left from {arrival} to {departure}"}}"#,
+    )
+    .unwrap();
+    let err = ErrPile::from(BookingError::RoomUnavailable {
+        room_type: "DLX".into(),
+        arrival: NaiveDate::from_ymd_opt(2025, 7, 1).unwrap(),
+        departure: NaiveDate::from_ymd_opt(2025, 7, 4).unwrap(),
+    });
+
+    assert_eq!(
+        catalog.render(&err, Audience::Guest).as_deref(),
+        Some("No DLX left from 2025-07-01 to 2025-07-04")
+    );
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn toml_catalogs() {
+    let catalog = MessageCatalog::from_toml(
+        r#"
+        [NotFound]
+        guest = "We could not find this {resource}"
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        catalog.render(&not_found(), Audience::Guest).as_deref(),
+        Some("We could not find this reservation")
+    );
+}
+
+// the catalog in use is global, the steps share one test
+#[test]
+fn catalog_in_use() {
+    let builtin = not_found().user_message();
+
+    let path = temp_file("catalog.json", CATALOG);
+    load_message_catalog(&path).unwrap();
+    assert_eq!(
+        not_found().user_message(),
+        "We could not find this reservation"
+    );
+    assert_eq!(
+        not_found().message_for(Audience::FrontDesk),
+        "No reservation with id R-1042, check the folio"
+    );
+    // without an entry the built-in messages are kept
+    assert_eq!(
+        not_found().message_for(Audience::Engineer),
+        not_found().to_string()
+    );
+    assert_eq!(
+        ErrPile::Auth.message_for(Audience::FrontDesk),
+        ErrPile::Auth.user_message()
+    );
+
+    // an invalid file keeps the catalog in use
+    let invalid = temp_file("invalid.json", "{ not json");
+    let err = load_message_catalog(&invalid).unwrap_err();
+    assert_eq!(err.kind(), PileKind::Config);
+    assert!(err.to_string().contains("invalid.json"), "{err}");
+    assert!(message_catalog().is_some());
+
+    // a reload is seen right away, on this thread and the others
+    let reloaded = br#"{ "NotFound": { "guest": "No {resource} here" } }"#;
+    set_message_catalog(Some(MessageCatalog::from_json(reloaded).unwrap()));
+    assert_eq!(not_found().user_message(), "No reservation here");
+    let elsewhere = std::thread::spawn(|| not_found().user_message());
+    assert_eq!(elsewhere.join().unwrap(), "No reservation here");
+
+    set_message_catalog(None);
+    assert_eq!(not_found().user_message(), builtin);
+
+    fs::remove_file(path).unwrap();
+    fs::remove_file(invalid).unwrap();
+}
@@ -0,0 +1,241 @@
+#![cfg(feature = "channel")]
+
+use std::time::Duration;
+
+use error_pile::{Channel, ChannelError, ChannelRejection, ErrPile, PileKind, ReqwestPileResExt};
+use reqwest::{ResponseBuilderExt, StatusCode, header::HeaderMap};
+use url::Url;
+
+fn booking(status: u16, body: &str) -> ChannelError {
+    ChannelError::parse(
+        Channel::BookingCom,
+        StatusCode::from_u16(status).unwrap(),
+        &HeaderMap::new(),
+        body.as_bytes(),
+    )
+    .unwrap()
+}
+
+fn expedia(body: &str) -> ChannelError {
+    ChannelError::parse(
+        Channel::Expedia,
+        StatusCode::OK,
+        &HeaderMap::new(),
+        body.as_bytes(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn hosts() {
+    assert_eq!(
+        Channel::from_host("supply-xml.booking.com"),
+        Some(Channel::BookingCom)
+    );
+    assert_eq!(
+        Channel::from_host("services.expediapartnercentral.com"),
+        Some(Channel::Expedia)
+    );
+    assert_eq!(Channel::from_host("notbooking.com"), None);
+    assert_eq!(Channel::from_host("graph.microsoft.com"), None);
+}
+
+#[test]
+fn booking_json_envelope() {
+    let err = booking(
+        200,
+        r#"{"data":{"success":0},"errors":[{"code":"392","message":"Invalid hotel code"}],
+            "warnings":[],"meta":{"ruid":"UmFuZG9tSVYk"}}"#,
+    );
+
+    assert_eq!(err.reason, ChannelRejection::PropertyNotMapped);
+    assert_eq!(err.request_id.as_deref(), Some("UmFuZG9tSVYk"));
+    assert_eq!(
+        err.to_string(),
+        "Booking.com rejected the request (code 392): Invalid hotel code"
+    );
+
+    let err = ErrPile::from(err);
+    assert!(err.is_config());
+    assert!(!err.is_transient());
+    assert_eq!(err.variant_name(), "Channel");
+    assert_eq!(
+        err.correlation_ids(),
+        [("ruid", "UmFuZG9tSVYk".to_string())]
+    );
+}
+
+#[test]
+fn booking_ota_ari_rejections() {
+    let ota = |code: &str, text: &str| {
+        booking(
+            200,
+            &format!(
+                r#"<OTA_HotelAvailNotifRS><Errors><Error Code="{code}">{text}</Error></Errors></OTA_HotelAvailNotifRS>"#
+            ),
+        )
+    };
+
+    assert_eq!(
+        ota("402", "Room 1 unknown").reason,
+        ChannelRejection::RoomOrRateNotMapped
+    );
+    assert_eq!(
+        ota("15", "Invalid date").reason,
+        ChannelRejection::DateOutOfRange
+    );
+    assert_eq!(
+        ota("321", "Price is too low for rate 5501").reason,
+        ChannelRejection::PriceOutOfRange
+    );
+    assert_eq!(
+        ota("321", "Required field missing").reason,
+        ChannelRejection::InvalidRequest
+    );
+
+    let err = ota("448", "System error");
+    assert_eq!(err.reason, ChannelRejection::Unavailable);
+    assert!(err.is_transient());
+}
+
+#[test]
+fn booking_bxml_error() {
+    let err = booking(
+        200,
+        "<error>Authorization failed for user rhm_xml</error>\n<!-- RUID: [UmFuZG9t] -->",
+    );
+
+    assert_eq!(err.reason, ChannelRejection::Auth);
+    assert_eq!(err.request_id.as_deref(), Some("UmFuZG9t"));
+    assert_eq!(ErrPile::from(err).kind(), PileKind::Config);
+}
+
+#[test]
+fn status_decides_unknown_messages() {
+    let body = r#"{"errors":[{"message":"Something went wrong"}]}"#;
+
+    assert_eq!(booking(200, body).reason, ChannelRejection::InvalidRequest);
+    assert_eq!(booking(429, body).reason, ChannelRejection::RateLimited);
+    assert_eq!(booking(503, body).reason, ChannelRejection::Unavailable);
+}
+
+#[test]
+fn expedia_eqc_codes() {
+    let eqc = |code: &str, text: &str| {
+        expedia(&format!(
+            r#"<AvailRateUpdateRS xmlns="http://www.expediaconnect.com/EQC/AR/2011/06"><Error code="{code}">{text}</Error></AvailRateUpdateRS>"#
+        ))
+    };
+
+    assert_eq!(eqc("1001", "Login failed").reason, ChannelRejection::Auth);
+    assert_eq!(
+        eqc("2010", "Schema validation failed").reason,
+        ChannelRejection::InvalidRequest
+    );
+    assert_eq!(
+        eqc("3010", "Room type 20394 not found for hotel 1234").reason,
+        ChannelRejection::RoomOrRateNotMapped
+    );
+    assert_eq!(
+        eqc("3103", "Date 2027-01-01 is too far in the future").reason,
+        ChannelRejection::DateOutOfRange
+    );
+    assert_eq!(
+        eqc("4001", "Internal error").reason,
+        ChannelRejection::Unavailable
+    );
+}
+
+#[test]
+fn expedia_json_errors() {
+    let err = expedia(r#"{"errors":[{"code":1002,"message":"Not authorized for property 1234"}]}"#);
+
+    assert_eq!(err.code.as_deref(), Some("1002"));
+    assert_eq!(err.reason, ChannelRejection::Auth);
+}
+
+#[test]
+fn card_numbers_are_masked() {
+    let err = booking(
+        200,
+        r#"{"errors":[{"code":"1","message":"Card 4111 1111 1111 1111 refused"}]}"#,
+    );
+
+    assert!(!err.message.contains("4111 1111 1111 1111"));
+}
+
+#[tokio::test]
+async fn decoded_from_channel_responses() {
+    let res: reqwest::Response = http::Response::builder()
+        .status(400)
+        .header("retry-after", "30")
+        .url(Url::parse("https://services.expediapartnercentral.com/eqc/ar").unwrap())
+        .body(
+            r#"<AvailRateUpdateRS xmlns="http://www.expediaconnect.com/EQC/AR/2011/06"><Error code="4000">Internal system error</Error></AvailRateUpdateRS>"#
+                .to_string(),
+        )
+        .unwrap()
+        .into();
+
+    let err = res.error_for_pile().await.unwrap_err();
+    assert_eq!(err.channel_rejection(), Some(ChannelRejection::Unavailable));
+    assert!(err.is_transient());
+    assert_eq!(err.retry_after(), Some(Duration::from_secs(30)));
+    assert_eq!(err.code().as_deref(), Some("4000"));
+}
+
+#[tokio::test]
+async fn other_hosts_keep_their_decoding() {
+    let res: reqwest::Response = http::Response::builder()
+        .status(400)
+        .header("content-type", "application/xml")
+        .url(Url::parse("https://pms.ramhotels.example/ota").unwrap())
+        .body(
+            r#"<OTA_HotelAvailNotifRS><Errors><Error Code="392">Invalid hotel code</Error></Errors></OTA_HotelAvailNotifRS>"#
+                .to_string(),
+        )
+        .unwrap()
+        .into();
+
+    let err = res.error_for_pile().await.unwrap_err();
+    assert!(matches!(err, ErrPile::Xml(_)));
+    assert_eq!(err.channel_rejection(), None);
+}
+
+#[tokio::test]
+async fn envelopes_sent_with_200_are_errors() {
+    let response = |url: &str, body: &str| -> reqwest::Response {
+        http::Response::builder()
+            .status(200)
+            .url(Url::parse(url).unwrap())
+            .body(body.to_string())
+            .unwrap()
+            .into()
+    };
+
+    let res = response(
+        "https://supply-xml.booking.com/hotels/ota/OTA_HotelRateAmountNotif",
+        r#"<OTA_HotelRateAmountNotifRS><Errors><Error Type="12" Code="392" ShortText="Invalid hotel code"/></Errors></OTA_HotelRateAmountNotifRS>"#,
+    );
+    let err = res.error_for_channel().await.unwrap_err();
+    assert_eq!(
+        err.channel_rejection(),
+        Some(ChannelRejection::PropertyNotMapped)
+    );
+    assert!(!err.is_transient());
+
+    let ok = r#"<OTA_HotelRateAmountNotifRS><Success/></OTA_HotelRateAmountNotifRS>"#;
+    let res = response(
+        "https://supply-xml.booking.com/hotels/ota/OTA_HotelRateAmountNotif",
+        ok,
+    );
+    assert_eq!(res.error_for_channel().await.unwrap(), ok.as_bytes());
+
+    // behind a proxy the EQC namespace tells
+    let res = response(
+        "https://channels.ramhotels.example/eqc/ar",
+        r#"<AvailRateUpdateRS xmlns="http://www.expediaconnect.com/EQC/AR/2011/06"><Error code="3202">Rate plan is not active</Error></AvailRateUpdateRS>"#,
+    );
+    let err = res.error_for_channel().await.unwrap_err();
+    assert_eq!(err.code().as_deref(), Some("3202"));
+}
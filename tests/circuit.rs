@@ -0,0 +1,89 @@
+#![cfg(feature = "retry")]
+
+use std::{
+    sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
+};
+
+use error_pile::{CircuitBreaker, CircuitState, ErrPile, PileKind};
+
+#[tokio::test]
+async fn circuit_opens_after_consecutive_failures() {
+    let breaker = CircuitBreaker::new(3, Duration::from_millis(50));
+    let calls = AtomicU32::new(0);
+    let failing = || async {
+        calls.fetch_add(1, Ordering::SeqCst);
+        Err::<(), _>(ErrPile::timeout("graph", Duration::from_secs(5)))
+    };
+
+    for _ in 0..3 {
+        assert!(
+            breaker
+                .call("graph", failing())
+                .await
+                .unwrap_err()
+                .is_timeout()
+        );
+    }
+    assert_eq!(breaker.state("graph"), CircuitState::Open);
+    assert_eq!(breaker.state("pms"), CircuitState::Closed);
+
+    // fails fast without calling graph
+    let err = breaker.call("graph", failing()).await.unwrap_err();
+    assert!(err.is_not_ready());
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    // other keys are not affected
+    assert_eq!(breaker.call("pms", async { Ok(1) }).await.unwrap(), 1);
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert_eq!(breaker.state("graph"), CircuitState::HalfOpen);
+
+    // the probe fails, open again
+    assert!(
+        breaker
+            .call("graph", failing())
+            .await
+            .unwrap_err()
+            .is_timeout()
+    );
+    assert_eq!(breaker.state("graph"), CircuitState::Open);
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert_eq!(breaker.call("graph", async { Ok(2) }).await.unwrap(), 2);
+    assert_eq!(breaker.state("graph"), CircuitState::Closed);
+}
+
+#[tokio::test]
+async fn only_counted_errors_trip_the_circuit() {
+    let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+    for _ in 0..5 {
+        let _ = breaker
+            .call("rooms", async {
+                Err::<(), _>(ErrPile::not_found("room", 4))
+            })
+            .await;
+    }
+    assert_eq!(breaker.state("rooms"), CircuitState::Closed);
+
+    let breaker = CircuitBreaker::new(2, Duration::from_secs(60)).trips_on(PileKind::Auth);
+    for _ in 0..2 {
+        let _ = breaker
+            .call("rooms", async { Err::<(), _>(ErrPile::Auth) })
+            .await;
+    }
+    assert_eq!(breaker.state("rooms"), CircuitState::Open);
+}
+
+#[tokio::test]
+async fn a_dropped_probe_lets_the_next_call_probe() {
+    let breaker = CircuitBreaker::new(1, Duration::ZERO);
+    let _ = breaker
+        .call("graph", async { Err::<(), _>(ErrPile::NotReady) })
+        .await;
+
+    // the probe never finishes
+    let probe = breaker.call("graph", std::future::pending::<Result<(), ErrPile>>());
+    let _ = tokio::time::timeout(Duration::from_millis(10), probe).await;
+
+    assert_eq!(breaker.call("graph", async { Ok(()) }).await.ok(), Some(()));
+}
@@ -0,0 +1,101 @@
+// helpers shared by the test binaries, each uses only some of them
+#![allow(dead_code)]
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// a json response with `status`, as if it came from the wire
+pub fn response(status: u16, body: &str) -> reqwest::Response {
+    http::Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(body.to_string())
+        .unwrap()
+        .into()
+}
+
+/// `response` for the blocking client
+#[cfg(feature = "blocking")]
+pub fn blocking_response(status: u16, body: &str) -> reqwest::blocking::Response {
+    http::Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(body.to_string())
+        .unwrap()
+        .into()
+}
+
+/// a request received by `serve`
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    /// the request line and the headers
+    pub head: String,
+    pub body: String,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.head.lines().find_map(|l| {
+            let (key, value) = l.split_once(':')?;
+            key.eq_ignore_ascii_case(name).then(|| value.trim())
+        })
+    }
+}
+
+/// Serves every connection on a local port with `handler`, which returns
+/// the raw response (see `reply`). The whole request is read first, the
+/// headers and the body may come in separate reads
+pub async fn serve(mut handler: impl FnMut(Request) -> String + Send + 'static) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut req = Vec::new();
+            let mut buf = vec![0; 8192];
+            let (head, body) = loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                req.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&req);
+                if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                    let len: usize = head
+                        .lines()
+                        .find_map(|l| {
+                            l.to_lowercase()
+                                .strip_prefix("content-length: ")
+                                .map(|v| v.parse().unwrap())
+                        })
+                        .unwrap_or(0);
+                    if body.len() >= len || n == 0 {
+                        break (head.to_string(), body.to_string());
+                    }
+                }
+                if n == 0 {
+                    break (text.to_string(), String::new());
+                }
+            };
+
+            let mut line = head.split_whitespace();
+            let method = line.next().unwrap_or_default().to_string();
+            let path = line.next().unwrap_or_default().to_string();
+            let res = handler(Request {
+                method,
+                path,
+                head,
+                body,
+            });
+            socket.write_all(res.as_bytes()).await.unwrap();
+        }
+    });
+
+    base
+}
+
+/// the raw response `serve` writes back, closing the connection
+pub fn reply(status: u16, content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {status} X\r\ncontent-type: {content_type}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
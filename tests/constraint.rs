@@ -0,0 +1,108 @@
+#![cfg(feature = "store-sqlite")]
+
+use error_pile::{ConstraintViolation, ErrPile, PileKind, register_constraint_kind};
+use sqlx::{SqlitePool, sqlite::SqlitePoolOptions};
+
+async fn pool() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+
+    sqlx::raw_sql(
+        "CREATE TABLE rooms (id INTEGER PRIMARY KEY);
+         CREATE TABLE reservations (
+             id INTEGER PRIMARY KEY,
+             room INTEGER NOT NULL REFERENCES rooms(id),
+             night TEXT NOT NULL,
+             nights INTEGER NOT NULL CONSTRAINT ck_reservation_nights CHECK (nights > 0),
+             UNIQUE (room, night)
+         );
+         INSERT INTO rooms (id) VALUES (412);
+         INSERT INTO reservations (room, night, nights) VALUES (412, '2025-07-01', 2);",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    pool
+}
+
+async fn insert(pool: &SqlitePool, room: i64, night: &str, nights: i64) -> ErrPile {
+    sqlx::query("INSERT INTO reservations (room, night, nights) VALUES (?, ?, ?)")
+        .bind(room)
+        .bind(night)
+        .bind(nights)
+        .execute(pool)
+        .await
+        .map(drop)
+        .map_err(ErrPile::from)
+        .unwrap_err()
+}
+
+#[tokio::test]
+async fn violations_are_not_transient() {
+    let pool = pool().await;
+
+    let unique = insert(&pool, 412, "2025-07-01", 1).await;
+    assert!(unique.is_unique_violation());
+    assert!(!unique.is_transient());
+    assert_eq!(
+        unique.constraint(),
+        Some("reservations.room, reservations.night")
+    );
+
+    let foreign_key = insert(&pool, 999, "2025-07-02", 1).await;
+    assert_eq!(
+        foreign_key.constraint_violation(),
+        Some(ConstraintViolation::ForeignKey)
+    );
+    assert!(!foreign_key.is_transient());
+    // SQLite doesn't name the foreign key, nothing to look up
+    assert_eq!(foreign_key.constraint(), None);
+    assert_eq!(foreign_key.kind(), PileKind::Database);
+}
+
+#[tokio::test]
+async fn database_errors_tell_their_driver() {
+    let pool = pool().await;
+    let err = insert(&pool, 412, "2025-07-01", 1).await;
+    assert_eq!(err.db_system(), Some("sqlite"));
+    let err = err.with_correlation_id("correlation_id", "req-1");
+    assert_eq!(err.db_system(), Some("sqlite"));
+
+    assert_eq!(ErrPile::from(sqlx::Error::RowNotFound).db_system(), None);
+}
+
+#[tokio::test]
+async fn registered_constraints_map_to_their_kind() {
+    register_constraint_kind("ck_reservation_nights", PileKind::Validation);
+    let pool = pool().await;
+
+    let check = insert(&pool, 412, "2025-07-03", 0).await;
+    assert_eq!(
+        check.constraint_violation(),
+        Some(ConstraintViolation::Check)
+    );
+    assert_eq!(check.constraint(), Some("ck_reservation_nights"));
+    assert_eq!(check.kind(), PileKind::Validation);
+    assert_eq!(check.status_code(), 422);
+}
+
+#[tokio::test]
+async fn unique_violations_become_conflicts() {
+    register_constraint_kind("reservations.room, reservations.night", PileKind::Conflict);
+    let pool = pool().await;
+
+    let err = insert(&pool, 412, "2025-07-01", 1).await;
+    assert!(err.is_conflict());
+    assert_eq!(err.status_code(), 409);
+}
+
+#[test]
+fn other_errors_have_no_constraint() {
+    let err = ErrPile::from(sqlx::Error::RowNotFound);
+    assert_eq!(err.constraint(), None);
+    assert!(!err.is_unique_violation());
+}
@@ -0,0 +1,65 @@
+use error_pile::{RequestBuilderPileExt, curl_command};
+
+#[test]
+fn curl_command_redacts_credentials() {
+    let request = reqwest::Client::new()
+        .post("https://api.example/rooms?sig=abc&floor=2")
+        .bearer_auth("super-secret")
+        .header("cookie", "session=1")
+        .header("x-hotel", "ram")
+        .body(r#"{"name":"it's"}"#)
+        .build()
+        .unwrap();
+
+    let cmd = curl_command(&request);
+    assert!(!cmd.contains("super-secret"));
+    assert!(!cmd.contains("session=1"));
+    assert!(!cmd.contains("abc"));
+    assert!(cmd.starts_with("curl -X POST 'https://api.example/rooms?sig="));
+    assert!(cmd.contains("-H 'authorization: <redacted>'"));
+    assert!(cmd.contains("-H 'x-hotel: ram'"));
+    assert!(cmd.ends_with(r#"--data-raw '{"name":"it'\''s"}'"#));
+}
+
+#[test]
+fn curl_command_redacts_the_body() {
+    let client = reqwest::Client::new();
+
+    let form = client
+        .post("https://login.example/token")
+        .body("grant_type=password&username=desk&password=hunter2&client_secret=s3cr3t")
+        .build()
+        .unwrap();
+    let cmd = curl_command(&form);
+    assert!(!cmd.contains("hunter2"));
+    assert!(!cmd.contains("s3cr3t"));
+    assert!(cmd.contains("grant_type=password&username=desk&password=<redacted>"));
+
+    let json = client
+        .post("https://pay.example/charges")
+        .body(r#"{"card":"4111 1111 1111 1111","client_secret":"cs_1","amount":120}"#)
+        .build()
+        .unwrap();
+    let cmd = curl_command(&json);
+    assert!(!cmd.contains("4111 1111 1111 1111"));
+    assert!(!cmd.contains("cs_1"));
+    assert!(cmd.contains(r#""amount":120"#));
+}
+
+// capturing is global, so this lives in its own test binary
+#[tokio::test]
+async fn failed_requests_carry_curl() {
+    error_pile::set_capture_curl(true);
+
+    let err = reqwest::Client::new()
+        .get("http://127.0.0.1:1/rooms")
+        .header("x-api-key", "k")
+        .send_pile()
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err.curl(),
+        Some("curl -X GET 'http://127.0.0.1:1/rooms' -H 'x-api-key: <redacted>'")
+    );
+}
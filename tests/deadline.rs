@@ -0,0 +1,67 @@
+#![cfg(feature = "retry")]
+
+use std::{
+    sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
+};
+
+use error_pile::{Backoff, Deadline, ErrPile, Jitter, PileFutureExt, retry};
+
+#[tokio::test]
+async fn retries_stop_at_the_deadline() {
+    let calls = AtomicU32::new(0);
+    let backoff = Backoff::new()
+        .base_delay(Duration::from_secs(60))
+        .jitter(Jitter::None);
+
+    let started = std::time::Instant::now();
+    let err = Deadline::after(Duration::from_secs(30))
+        .scope(retry(&backoff, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>(ErrPile::NotReady)
+        }))
+        .await
+        .unwrap_err();
+
+    assert!(err.is_timeout());
+    assert_eq!(err.to_string(), "The operation `retry` timed out after 30s");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert!(started.elapsed() < Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn timeouts_are_shortened_by_the_deadline() {
+    let err = Deadline::after(Duration::from_millis(20))
+        .scope(
+            async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(())
+            }
+            .with_timeout(Duration::from_secs(10), "fetch folio"),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "The operation `fetch folio` timed out after 20ms"
+    );
+}
+
+#[tokio::test]
+async fn nested_scopes_keep_the_earliest_deadline() {
+    assert_eq!(Deadline::current(), None);
+
+    let outer = Deadline::after(Duration::from_secs(5));
+    let inner = outer
+        .scope(async {
+            let current = Deadline::current();
+            let nested = Deadline::after(Duration::from_secs(60))
+                .scope(async { Deadline::current() })
+                .await;
+            (current, nested)
+        })
+        .await;
+    assert_eq!(inner, (Some(outer), Some(outer)));
+    assert_eq!(Deadline::current(), None);
+    assert!(!outer.is_expired());
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "derive")]
+
+use error_pile::{AppError, ErrPile, IntoPile, PileKind};
+
+#[derive(Debug, thiserror::Error, IntoPile)]
+#[pile(kind = "internal")]
+enum BookingError {
+    #[error("room {0} does not exist")]
+    #[pile(kind = "not_found", code = "room_missing")]
+    RoomMissing(String),
+
+    #[error("the room is already booked")]
+    #[pile(kind = "conflict", code = "overbooked", status = 423)]
+    Overbooked,
+
+    #[error("the channel manager is busy")]
+    #[pile(kind = "upstream", transient)]
+    ChannelBusy { channel: String },
+
+    #[error("the rate plan is broken")]
+    BrokenRatePlan,
+
+    #[error(transparent)]
+    #[pile(transparent)]
+    Pms(ErrPile),
+}
+
+#[test]
+fn variants_map_to_their_kind_code_and_status() {
+    let err = ErrPile::from(BookingError::RoomMissing("214".into()));
+    assert_eq!(err.kind(), PileKind::NotFound);
+    assert!(err.is_not_found());
+    assert_eq!(err.code().as_deref(), Some("room_missing"));
+    assert_eq!(err.status_code(), 404);
+    assert_eq!(err.to_string(), "room 214 does not exist");
+    assert_eq!(err.user_message(), "room 214 does not exist");
+
+    let err = ErrPile::from(BookingError::Overbooked);
+    assert!(err.is_conflict());
+    assert_eq!(err.status_code(), 423);
+
+    let err = ErrPile::from(BookingError::ChannelBusy {
+        channel: "booking.com".into(),
+    });
+    assert!(err.is_transient());
+
+    let err = ErrPile::from(BookingError::BrokenRatePlan);
+    assert_eq!(err.kind(), PileKind::Internal);
+    assert_eq!(err.status_code(), 500);
+    assert!(!err.is_transient());
+}
+
+#[test]
+fn transparent_variants_are_handed_over() {
+    let err = ErrPile::from(BookingError::Pms(ErrPile::not_found("folio", "88")));
+    assert!(matches!(err, ErrPile::NotFound(ref not_found) if not_found.resource == "folio"));
+}
+
+#[test]
+fn the_enum_is_kept() {
+    let err = ErrPile::from(BookingError::Overbooked);
+    let ErrPile::App(app) = &err else {
+        panic!("expected an application error, got {err:?}");
+    };
+    let booking: &BookingError = app.downcast_ref().unwrap();
+    assert!(booking.is_overbooked());
+    assert!(!booking.is_room_missing());
+    assert!(
+        AppError::new(BookingError::Overbooked, PileKind::Conflict)
+            .code
+            .is_none()
+    );
+}
+
+fn book(room: &str) -> error_pile::PileResult<()> {
+    Err(BookingError::RoomMissing(room.into()))?
+}
+
+#[test]
+fn question_mark_converts() {
+    assert!(book("101").unwrap_err().is_not_found());
+}
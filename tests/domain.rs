@@ -0,0 +1,30 @@
+use std::error::Error;
+
+use error_pile::{ErrPile, PileKind, PileResult, StorageError, TransportError};
+
+fn read_folio(path: &str) -> PileResult<String> {
+    Ok(std::fs::read_to_string(path)?)
+}
+
+#[test]
+fn question_mark_goes_through_the_domain() {
+    let err = read_folio("/nonexistent/folio.json").unwrap_err();
+    assert!(
+        matches!(err, ErrPile::Storage(ref storage) if matches!(**storage, StorageError::IO(_)))
+    );
+    assert_eq!(err.kind(), PileKind::Io);
+
+    let err: ErrPile = url::Url::parse("not a url").unwrap_err().into();
+    assert!(
+        matches!(err, ErrPile::Transport(ref transport) if matches!(**transport, TransportError::Url(_)))
+    );
+    assert_eq!(err.kind(), PileKind::Parse);
+}
+
+#[test]
+fn domain_wrappers_are_transparent() {
+    let err: ErrPile = std::io::Error::other("disk full").into();
+    assert_eq!(err.to_string(), "IO Err: disk full");
+    // the io error is the direct source, the domain enum adds no level
+    assert_eq!(err.source().unwrap().to_string(), "disk full");
+}
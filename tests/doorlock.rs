@@ -0,0 +1,159 @@
+#![cfg(feature = "doorlock")]
+
+use std::time::Duration;
+
+use error_pile::{DoorLockError, DoorLockFailure, ErrPile, LockVendor, PileKind};
+use reqwest::{
+    StatusCode,
+    header::{HeaderMap, HeaderValue, RETRY_AFTER},
+};
+
+fn parse(vendor: LockVendor, status: u16, body: &str) -> ErrPile {
+    DoorLockError::parse(
+        vendor,
+        StatusCode::from_u16(status).unwrap(),
+        &HeaderMap::new(),
+        body.as_bytes(),
+    )
+    .into()
+}
+
+#[test]
+fn offline_locks_are_transient() {
+    let err = parse(
+        LockVendor::Visionline,
+        409,
+        r#"{"code": "DoorOffline", "message": "Door is offline", "doorId": "1204"}"#,
+    );
+
+    assert_eq!(err.variant_name(), "DoorLock");
+    assert_eq!(err.door_lock_failure(), Some(DoorLockFailure::LockOffline));
+    assert_eq!(err.kind(), PileKind::Network);
+    assert_eq!(err.code().as_deref(), Some("DoorOffline"));
+    assert!(err.is_transient());
+    assert_eq!(
+        err.to_string(),
+        "The door lock system (visionline) refused the request (lock_offline) for `1204` \
+         DoorOffline: Door is offline"
+    );
+}
+
+#[test]
+fn rejected_credentials_need_a_new_key() {
+    let err = parse(
+        LockVendor::Salto,
+        400,
+        r#"{"ErrorCode": 1021, "Message": "The mobile key was revoked"}"#,
+    );
+
+    assert_eq!(
+        err.door_lock_failure(),
+        Some(DoorLockFailure::CredentialRejected)
+    );
+    assert_eq!(err.kind(), PileKind::Permission);
+    assert_eq!(err.status_code(), 403);
+    assert_eq!(err.code().as_deref(), Some("1021"));
+    assert!(!err.is_transient());
+    assert!(DoorLockFailure::CredentialRejected.blocks_key_issuance());
+}
+
+#[test]
+fn audit_trails_dont_block_key_issuance() {
+    let mut headers = HeaderMap::new();
+    headers.insert(RETRY_AFTER, HeaderValue::from_static("30"));
+    let err = ErrPile::from(DoorLockError::parse(
+        LockVendor::Salto,
+        StatusCode::SERVICE_UNAVAILABLE,
+        &headers,
+        br#"{"error": {"code": "audit_trail", "message": "Audit trail is being synchronised"}}"#,
+    ));
+
+    let failure = err.door_lock_failure().unwrap();
+    assert_eq!(failure, DoorLockFailure::AuditTrailUnavailable);
+    assert!(!failure.blocks_key_issuance());
+    assert!(err.is_transient());
+    assert_eq!(err.retry_after(), Some(Duration::from_secs(30)));
+}
+
+#[test]
+fn the_status_decides_without_texts() {
+    let err = parse(LockVendor::Visionline, 401, r#"{"message": "Bad token"}"#);
+    assert_eq!(err.door_lock_failure(), Some(DoorLockFailure::Auth));
+    assert_eq!(err.kind(), PileKind::Config);
+
+    let err = parse(LockVendor::Salto, 502, "<html>Bad Gateway</html>");
+    assert_eq!(err.door_lock_failure(), Some(DoorLockFailure::Unavailable));
+    assert!(err.is_transient());
+
+    let err = parse(LockVendor::Salto, 400, r#"{"message": "Room unknown"}"#);
+    assert_eq!(err.door_lock_failure(), Some(DoorLockFailure::Rejected));
+    assert!(!err.is_transient());
+}
+
+#[test]
+fn the_login_and_offline_locks_come_first() {
+    let err = parse(
+        LockVendor::Visionline,
+        401,
+        r#"{"message": "Invalid credentials"}"#,
+    );
+    assert_eq!(err.door_lock_failure(), Some(DoorLockFailure::Auth));
+
+    let err = parse(
+        LockVendor::Salto,
+        409,
+        r#"{"message": "Lock offline, audit trail unavailable"}"#,
+    );
+    let failure = err.door_lock_failure().unwrap();
+    assert_eq!(failure, DoorLockFailure::LockOffline);
+    assert!(failure.blocks_key_issuance());
+}
+
+#[test]
+fn encoder_refusals_tell_the_front_desk() {
+    use error_pile::KeyEncoderReason;
+
+    let encode = |status: u16, body: &str| {
+        let err = DoorLockError::parse(
+            LockVendor::Salto,
+            StatusCode::from_u16(status).unwrap(),
+            &HeaderMap::new(),
+            body.as_bytes(),
+        );
+        ErrPile::key_encoder(err)
+    };
+
+    let err = encode(
+        409,
+        r#"{"ErrorCode": "12", "Message": "No card in encoder"}"#,
+    );
+    assert_eq!(
+        err.key_encoder_reason(),
+        Some(KeyEncoderReason::CardReadFailure)
+    );
+    assert!(err.is_transient());
+    assert!(
+        std::error::Error::source(&err)
+            .unwrap()
+            .to_string()
+            .contains("No card in encoder")
+    );
+
+    let err = encode(
+        503,
+        r#"{"ErrorCode": "7", "Message": "Encoder is offline"}"#,
+    );
+    assert_eq!(
+        err.key_encoder_reason(),
+        Some(KeyEncoderReason::EncoderOffline)
+    );
+
+    let err = encode(
+        409,
+        r#"{"ErrorCode": "31", "Message": "Key rejected for room 1204"}"#,
+    );
+    assert_eq!(
+        err.key_encoder_reason(),
+        Some(KeyEncoderReason::RoomNotAuthorized)
+    );
+}
@@ -0,0 +1,34 @@
+#![cfg(feature = "stream")]
+
+use error_pile::{DownloadFailure, ErrPile, download_to};
+
+fn response(body: &'static str) -> reqwest::Response {
+    http::Response::builder()
+        .status(200)
+        .body(body)
+        .unwrap()
+        .into()
+}
+
+#[tokio::test]
+async fn download_verifies_checksum() {
+    let mut out = Vec::new();
+    let sha = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+    let written = download_to(response("hello"), &mut out, Some(sha))
+        .await
+        .unwrap();
+    assert_eq!(written, 5);
+    assert_eq!(out, b"hello");
+
+    let err = download_to(response("hellO"), &mut Vec::new(), Some(sha))
+        .await
+        .unwrap_err();
+    assert!(err.is_transient());
+    let ErrPile::Download(download) = &err else {
+        panic!("expected a download error");
+    };
+    assert!(matches!(
+        download.failure,
+        DownloadFailure::ChecksumMismatch { .. }
+    ));
+}
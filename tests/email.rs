@@ -0,0 +1,54 @@
+#![cfg(feature = "lettre")]
+
+use std::time::Duration;
+
+use error_pile::{EmailSink, ErrPile, ErrorReport, ErrorSink, Severity};
+use lettre::transport::stub::AsyncStubTransport;
+
+fn sink() -> EmailSink<AsyncStubTransport> {
+    EmailSink::new(
+        AsyncStubTransport::new_ok(),
+        "agent@hotel.example".parse().unwrap(),
+        ["ops@hotel.example".parse().unwrap()],
+    )
+}
+
+#[tokio::test]
+async fn only_critical_errors_are_mailed() {
+    let sink = sink();
+
+    sink.send(&ErrorReport::new(&ErrPile::NotReady))
+        .await
+        .unwrap();
+    assert!(sink.transport().messages().await.is_empty());
+
+    let err = ErrPile::config_missing("GRAPH_CLIENT_SECRET");
+    assert_eq!(err.severity(), Severity::Critical);
+    sink.send(&ErrorReport::new(&err)).await.unwrap();
+
+    let messages = sink.transport().messages().await;
+    assert_eq!(messages.len(), 1);
+    let (envelope, email) = &messages[0];
+    assert_eq!(envelope.to()[0].to_string(), "ops@hotel.example");
+    assert!(email.contains("Subject: [critical] config: Invalid configuration for"));
+    assert!(email.contains("Fingerprint: "));
+}
+
+#[tokio::test]
+async fn repeated_fingerprints_are_throttled() {
+    let sink = sink()
+        .min_severity(Severity::Warning)
+        .throttle(Duration::from_secs(60));
+
+    let page = ErrPile::Page {
+        index: 2,
+        source: Box::new(ErrPile::InUse),
+    };
+    for _ in 0..3 {
+        sink.send(&ErrorReport::new(&page)).await.unwrap();
+    }
+
+    let messages = sink.transport().messages().await;
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].1.contains("Caused by:"));
+}
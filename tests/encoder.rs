@@ -0,0 +1,86 @@
+use std::error::Error;
+
+use error_pile::{ErrPile, KeyEncoderFault, KeyEncoderReason, PileKind};
+
+/// what a wrapper of a lock vendor SDK looks like
+#[derive(Debug, thiserror::Error)]
+#[error("vendor status {status:#04x}")]
+struct VendorError {
+    status: u8,
+    station: &'static str,
+}
+
+impl KeyEncoderFault for VendorError {
+    fn reason(&self) -> KeyEncoderReason {
+        match self.status {
+            0x10 => KeyEncoderReason::EncoderOffline,
+            0x21 => KeyEncoderReason::CardReadFailure,
+            0x40 => KeyEncoderReason::RoomNotAuthorized,
+            _ => KeyEncoderReason::InvalidTrackData,
+        }
+    }
+
+    fn encoder(&self) -> Option<String> {
+        Some(self.station.to_string())
+    }
+}
+
+#[test]
+fn vendor_errors_keep_their_source() {
+    let err = ErrPile::key_encoder(VendorError {
+        status: 0x21,
+        station: "FD-2",
+    });
+
+    assert_eq!(
+        err.key_encoder_reason(),
+        Some(KeyEncoderReason::CardReadFailure)
+    );
+    assert_eq!(
+        err.to_string(),
+        "The key card could not be encoded on FD-2: card_read_failure"
+    );
+    assert_eq!(err.source().unwrap().to_string(), "vendor status 0x21");
+    assert_eq!(err.variant_name(), "KeyEncoder");
+    assert_eq!(err.code().as_deref(), Some("card_read_failure"));
+}
+
+#[test]
+fn reinsert_card_is_retried() {
+    let err = ErrPile::from(KeyEncoderReason::CardReadFailure);
+
+    assert!(err.is_transient());
+    assert_eq!(err.kind(), PileKind::Io);
+    assert_eq!(
+        err.user_message(),
+        "The key card could not be read, please re-insert the card"
+    );
+}
+
+#[test]
+fn offline_encoder_needs_maintenance() {
+    let err = ErrPile::key_encoder(VendorError {
+        status: 0x10,
+        station: "FD-1",
+    });
+
+    assert!(!err.is_transient());
+    assert!(KeyEncoderReason::EncoderOffline.needs_maintenance());
+    assert!(!KeyEncoderReason::CardReadFailure.needs_maintenance());
+    assert_eq!(
+        err.user_message(),
+        "The key encoder is not responding, please call maintenance"
+    );
+}
+
+#[test]
+fn unauthorized_room() {
+    let err = ErrPile::from(KeyEncoderReason::RoomNotAuthorized);
+
+    assert_eq!(err.kind(), PileKind::Permission);
+    assert_eq!(err.status_code(), 403);
+    assert_eq!(
+        err.to_string(),
+        "The key card could not be encoded: room_not_authorized"
+    );
+}
@@ -0,0 +1,35 @@
+// the decoders are global, so this lives in its own test binary
+
+use error_pile::{ErrPile, ReqwestPileResExt};
+
+mod common;
+
+use common::response;
+
+fn door_lock_decoder(
+    _status: &reqwest::StatusCode,
+    _headers: &reqwest::header::HeaderMap,
+    body: &[u8],
+) -> Option<ErrPile> {
+    let json: serde_json::Value = serde_json::from_slice(body).ok()?;
+    let fault = json.get("lockFault")?;
+    Some(ErrPile::custom(format!(
+        "door lock {}: {}",
+        fault["code"].as_str()?,
+        fault["text"].as_str()?
+    )))
+}
+
+#[tokio::test]
+async fn registered_decoders_run_first() {
+    error_pile::register_error_decoder(door_lock_decoder);
+
+    let body =
+        r#"{"lockFault":{"code":"E12","text":"card expired"},"error":{"code":"x","message":"y"}}"#;
+    let err = response(400, body).to_pile_empty().await.unwrap_err();
+    assert!(matches!(&err, ErrPile::Custom(msg) if msg == "door lock E12: card expired"));
+
+    // other bodies still use the built-in decoding
+    let err = response(404, "nope").to_pile_empty().await.unwrap_err();
+    assert!(matches!(err, ErrPile::Http(_)));
+}
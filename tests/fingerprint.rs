@@ -0,0 +1,36 @@
+use error_pile::{ErrPile, normalize_message};
+
+#[test]
+fn volatile_parts_are_normalized() {
+    assert_eq!(
+        normalize_message(
+            "Reservation 4711 not found (request 0f8fad5b-d9cb-469f-a165-70867728950e)"
+        ),
+        "Reservation <n> not found (request <id>)"
+    );
+    assert_eq!(
+        normalize_message("blob 9f86d081884c7d659a2feaa0 is locked"),
+        "blob <id> is locked"
+    );
+    assert_eq!(normalize_message("room is taken"), "room is taken");
+}
+
+#[test]
+fn near_identical_errors_share_a_fingerprint() {
+    let first = ErrPile::custom("Graph call 12 failed for 0f8fad5b-d9cb-469f-a165-70867728950e");
+    let second = ErrPile::custom("Graph call 13 failed for 7c9e6679-7425-40de-944b-e07fc1f90ae7");
+    assert_eq!(first.fingerprint(), second.fingerprint());
+    assert_eq!(first.fingerprint().len(), 16);
+
+    // same message, different kind
+    assert_ne!(
+        ErrPile::not_found("room", 1).fingerprint(),
+        ErrPile::custom("The room `1` could not be found").fingerprint()
+    );
+
+    let page = |index| ErrPile::Page {
+        index,
+        source: Box::new(ErrPile::NotReady),
+    };
+    assert_eq!(page(1).fingerprint(), page(7).fingerprint());
+}
@@ -0,0 +1,136 @@
+#![cfg(feature = "fiscal")]
+
+use std::time::Duration;
+
+use error_pile::{ErrPile, FiscalError, FiscalFailure, PileKind};
+use reqwest::{
+    StatusCode,
+    header::{HeaderMap, HeaderValue, RETRY_AFTER},
+};
+
+fn parse(status: u16, body: &str) -> FiscalError {
+    FiscalError::parse(
+        StatusCode::from_u16(status).unwrap(),
+        &HeaderMap::new(),
+        body.as_bytes(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn schema_failures_are_resent() {
+    let err = parse(
+        400,
+        r#"{"errors":[{"code":"E-104","message":"Document does not validate against the XSD: element TaxTotal missing"}]}"#,
+    );
+
+    assert_eq!(err.failure, FiscalFailure::SchemaValidation);
+    assert!(err.retry_allowed);
+    assert!(!err.must_void());
+    assert!(!err.is_transient());
+
+    let err = ErrPile::from(err);
+    assert_eq!(err.kind(), PileKind::Validation);
+    assert_eq!(err.fiscal_retry_allowed(), Some(true));
+    assert_eq!(err.code().as_deref(), Some("E-104"));
+}
+
+#[test]
+fn sequence_conflicts_are_voided() {
+    let err = parse(
+        409,
+        r#"{"error":{"code":"DUP","message":"Invoice RHM-2025-000412 is already registered"}}"#,
+    );
+
+    assert_eq!(err.failure, FiscalFailure::SequenceConflict);
+    assert!(err.must_void());
+    assert_eq!(
+        err.to_string(),
+        "The tax authority refused the invoice (sequence_conflict) DUP: Invoice RHM-2025-000412 is already registered"
+    );
+
+    let err = parse(
+        400,
+        r#"{"code": "out_of_sequence", "message": "Invoice number out of sequence"}"#,
+    );
+    assert_eq!(err.failure, FiscalFailure::SequenceConflict);
+    assert_eq!(err.code.as_deref(), Some("out_of_sequence"));
+}
+
+#[test]
+fn sequence_words_alone_are_not_voided() {
+    let err = parse(
+        400,
+        r#"{"code":"E-17","message":"Schema validation failed: element InvoiceSequence missing"}"#,
+    );
+    assert_eq!(err.failure, FiscalFailure::SchemaValidation);
+    assert!(!err.must_void());
+
+    let err = parse(
+        409,
+        r#"{"message":"Invoice RHM-2025-000412 already exists"}"#,
+    );
+    assert_eq!(err.failure, FiscalFailure::Unknown);
+    assert!(err.needs_review());
+    assert!(!err.must_void());
+    assert!(!err.retry_allowed);
+}
+
+#[test]
+fn service_windows_are_waited_out() {
+    let mut headers = HeaderMap::new();
+    headers.insert(RETRY_AFTER, HeaderValue::from_static("900"));
+    let err = FiscalError::parse(
+        StatusCode::SERVICE_UNAVAILABLE,
+        &headers,
+        br#"{"message":"The service is not available"}"#,
+    )
+    .unwrap();
+
+    assert_eq!(err.failure, FiscalFailure::ServiceWindow);
+    assert!(err.retry_allowed);
+
+    let err = ErrPile::from(err);
+    assert!(err.is_transient());
+    assert_eq!(err.retry_after(), Some(Duration::from_secs(900)));
+    assert_eq!(err.kind(), PileKind::NotReady);
+
+    let err = parse(200, r#"{"message":"Scheduled maintenance until 02:00"}"#);
+    assert_eq!(err.failure, FiscalFailure::ServiceWindow);
+}
+
+#[test]
+fn rule_rejections_are_voided() {
+    let err = parse(
+        422,
+        r#"{"code":"VAT-7","message":"Buyer tax id is not valid"}"#,
+    );
+
+    assert_eq!(err.failure, FiscalFailure::Rejected);
+    assert!(err.must_void());
+    assert!(!parse(400, r#"{"code":"X-1","message":"Refused"}"#).must_void());
+    assert_eq!(
+        parse(502, r#"{"message":"Bad gateway"}"#).failure,
+        FiscalFailure::Unavailable
+    );
+}
+
+#[test]
+fn bodies_without_errors_are_not_fiscal() {
+    assert!(FiscalError::parse(StatusCode::BAD_REQUEST, &HeaderMap::new(), b"{}").is_none());
+    assert!(FiscalError::parse(StatusCode::BAD_GATEWAY, &HeaderMap::new(), b"").is_none());
+}
+
+#[cfg(feature = "xml")]
+#[test]
+fn soap_faults() {
+    let err = parse(
+        500,
+        r#"<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/"><soap:Body><soap:Fault>
+            <faultcode>s005</faultcode><faultstring>Invalid document schema</faultstring>
+        </soap:Fault></soap:Body></soap:Envelope>"#,
+    );
+
+    assert_eq!(err.failure, FiscalFailure::SchemaValidation);
+    assert_eq!(err.code.as_deref(), Some("s005"));
+}
@@ -0,0 +1,2 @@
+NotFound = Wir konnten { $resource } nicht finden
+    .front-desk = Kein { $resource } mit der Nummer { $id }
@@ -0,0 +1,6 @@
+NotFound = We could not find this { $resource }
+    .front-desk = No { $resource } with id { $id }
+RateLimited = Please try again in { $retry_after ->
+        [one] a second
+       *[other] { $retry_after } seconds
+    }
@@ -0,0 +1 @@
+NotFound = Nous n'avons pas trouvé { $resource }
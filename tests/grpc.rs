@@ -0,0 +1,49 @@
+#![cfg(feature = "tonic")]
+
+use std::time::Duration;
+
+use error_pile::{ErrPile, PileKind};
+use tonic::{Code, Status};
+
+#[test]
+fn kinds_map_to_grpc_codes() {
+    let cases = [
+        (ErrPile::Auth, Code::Unauthenticated),
+        (ErrPile::Permission, Code::PermissionDenied),
+        (ErrPile::InUse, Code::Aborted),
+        (ErrPile::NotReady, Code::Unavailable),
+        (ErrPile::not_found("reservation", 12), Code::NotFound),
+        (
+            ErrPile::timeout("sync", Duration::from_secs(1)),
+            Code::DeadlineExceeded,
+        ),
+        (ErrPile::custom("boom"), Code::Internal),
+    ];
+    for (err, code) in cases {
+        assert_eq!(Status::from(err).code(), code);
+    }
+}
+
+#[test]
+fn errors_round_trip_through_the_details() {
+    let status = Status::from(ErrPile::rate_limited(Some(Duration::from_secs(3)), None));
+    assert_eq!(status.code(), Code::ResourceExhausted);
+    assert!(status.message().starts_with("Too many requests"));
+
+    let err = ErrPile::from(status);
+    assert!(err.is_rate_limited());
+    assert!(err.is_transient());
+    assert_eq!(err.retry_after(), Some(Duration::from_secs(3)));
+}
+
+#[test]
+fn bare_statuses_are_classified_by_code() {
+    let err = ErrPile::from(Status::unavailable("lock bridge restarting"));
+    assert_eq!(err.kind(), PileKind::NotReady);
+    assert!(err.is_transient());
+    assert_eq!(err.to_string(), "lock bridge restarting");
+
+    let err = ErrPile::from(Status::invalid_argument("bad room"));
+    assert_eq!(err.kind(), PileKind::Validation);
+    assert!(!err.is_transient());
+}
@@ -0,0 +1,23 @@
+use error_pile::{ErrPile, MicrosoftError, ReqwestPileResExt};
+
+mod common;
+
+use common::response;
+
+// the mapping is global, so this lives in its own test binary
+#[tokio::test]
+async fn auth_statuses_are_mapped() {
+    error_pile::set_map_auth_status(true);
+
+    let err = response(401, "{}").to_pile_empty().await.unwrap_err();
+    assert!(matches!(err, ErrPile::Auth));
+    assert_eq!(err.status_code(), 401);
+
+    let err = response(403, "").to_pile_empty().await.unwrap_err();
+    assert!(matches!(err, ErrPile::Permission));
+
+    // a structured body is more specific than the status
+    let body = r#"{"error":{"code":"InvalidApiKey","message":"bad key"}}"#;
+    let err = response(401, body).to_pile_empty().await.unwrap_err();
+    assert!(matches!(err, ErrPile::Microsoft(MicrosoftError::AZ(_))));
+}
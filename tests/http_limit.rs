@@ -0,0 +1,24 @@
+use error_pile::{ErrPile, ReqwestPileResExt};
+
+// the limit is global, so this lives in its own test binary
+#[tokio::test]
+async fn error_body_is_bounded() {
+    error_pile::set_max_error_body(16);
+
+    let res: reqwest::Response = http::Response::builder()
+        .status(502)
+        .header("content-type", "text/html")
+        .body("<html>".repeat(1000))
+        .unwrap()
+        .into();
+
+    let ErrPile::Http(http) = res.to_pile_empty().await.unwrap_err() else {
+        panic!("expected Http variant");
+    };
+
+    assert!(http.body_truncated);
+    assert_eq!(
+        http.body_snippet,
+        "<html><html><htm …[truncated at 16 bytes]"
+    );
+}
@@ -0,0 +1,163 @@
+#![cfg(feature = "i18n")]
+
+use std::{path::Path, time::Duration};
+
+use error_pile::{Audience, ErrPile, LocalizedMessages, PileKind, set_localized_messages};
+
+fn messages() -> LocalizedMessages {
+    LocalizedMessages::load_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/i18n"))
+        .unwrap()
+}
+
+fn not_found() -> ErrPile {
+    ErrPile::not_found("reservation", "R-1042")
+}
+
+fn rate_limited(secs: u64) -> ErrPile {
+    ErrPile::rate_limited(Some(Duration::from_secs(secs)), None)
+}
+
+#[test]
+fn messages_are_in_the_language() {
+    let messages = messages();
+
+    assert_eq!(
+        messages
+            .render(&not_found(), "de", Audience::Guest)
+            .as_deref(),
+        Some("Wir konnten reservation nicht finden")
+    );
+    assert_eq!(
+        messages
+            .render(&not_found(), "de", Audience::FrontDesk)
+            .as_deref(),
+        Some("Kein reservation mit der Nummer R-1042")
+    );
+    assert_eq!(
+        messages
+            .render(&not_found(), "fr-CH", Audience::Guest)
+            .as_deref(),
+        Some("Nous n'avons pas trouvé reservation")
+    );
+    assert_eq!(
+        messages.render(&not_found(), "de", Audience::Engineer),
+        None
+    );
+}
+
+#[test]
+fn closest_language_then_english() {
+    let messages = messages();
+
+    // another region of the language
+    assert_eq!(
+        messages
+            .render(&not_found(), "de-AT", Audience::Guest)
+            .as_deref(),
+        Some("Wir konnten reservation nicht finden")
+    );
+    assert_eq!(
+        messages
+            .render(&not_found(), "fr", Audience::Guest)
+            .as_deref(),
+        Some("Nous n'avons pas trouvé reservation")
+    );
+    // no translation for the language or for the message
+    assert_eq!(
+        messages
+            .render(&not_found(), "it", Audience::Guest)
+            .as_deref(),
+        Some("We could not find this reservation")
+    );
+    assert_eq!(
+        messages
+            .render(&rate_limited(30), "de", Audience::Guest)
+            .as_deref(),
+        Some("Please try again in 30 seconds")
+    );
+    assert_eq!(messages.render(&ErrPile::Auth, "de", Audience::Guest), None);
+}
+
+#[test]
+fn numbers_select_plurals() {
+    let messages = messages();
+    assert_eq!(
+        messages
+            .render(&rate_limited(1), "en", Audience::Guest)
+            .as_deref(),
+        Some("Please try again in a second")
+    );
+}
+
+#[test]
+fn only_counts_are_numbers() {
+    let mut messages = LocalizedMessages::new();
+    messages.add("en", "Custom = Failed: { $message }").unwrap();
+    assert_eq!(
+        messages
+            .render(&ErrPile::custom("0042"), "en", Audience::Guest)
+            .as_deref(),
+        Some("Failed: 0042")
+    );
+    assert_eq!(
+        messages
+            .render(&ErrPile::custom("1e3"), "en", Audience::Guest)
+            .as_deref(),
+        Some("Failed: 1e3")
+    );
+}
+
+#[test]
+fn messages_that_dont_format_fall_back() {
+    let mut messages = messages();
+    messages
+        .add("de", "NotFound = { $resource } { $room } fehlt")
+        .unwrap();
+    assert_eq!(
+        messages
+            .render(&not_found(), "de", Audience::Guest)
+            .as_deref(),
+        Some("We could not find this reservation")
+    );
+
+    let mut messages = LocalizedMessages::new();
+    messages
+        .add("en", "NotFound = { $room } is missing")
+        .unwrap();
+    assert_eq!(messages.render(&not_found(), "en", Audience::Guest), None);
+}
+
+#[test]
+fn invalid_resources_are_config_errors() {
+    let mut messages = LocalizedMessages::new();
+    let err = messages.add("de", "NotFound = { $resource").unwrap_err();
+    assert_eq!(err.kind(), PileKind::Config);
+
+    let err = messages.add("not a language", "Auth = x").unwrap_err();
+    assert_eq!(err.kind(), PileKind::Config);
+}
+
+// the translations in use are global, the steps share one test
+#[test]
+fn translations_in_use() {
+    let builtin = not_found().user_message();
+    assert_eq!(not_found().user_message_in("de"), builtin);
+
+    set_localized_messages(Some(messages()));
+    assert_eq!(
+        not_found().user_message_in("de-CH"),
+        "Wir konnten reservation nicht finden"
+    );
+    assert_eq!(
+        not_found().message_for_in(Audience::FrontDesk, "de"),
+        "Kein reservation mit der Nummer R-1042"
+    );
+    // without a translation, the built-in message
+    assert_eq!(
+        ErrPile::Auth.user_message_in("de"),
+        ErrPile::Auth.user_message()
+    );
+
+    set_localized_messages(None);
+    assert_eq!(not_found().user_message_in("de"), builtin);
+}
@@ -0,0 +1,67 @@
+#![cfg(feature = "backon")]
+
+use std::time::Duration;
+
+use error_pile::ErrPile;
+
+#[test]
+fn backoff_builds_backon_delays() {
+    use backon::BackoffBuilder;
+    use error_pile::{Backoff, Jitter};
+
+    let delays: Vec<_> = Backoff::new()
+        .base_delay(Duration::from_millis(10))
+        .jitter(Jitter::None)
+        .max_attempts(4)
+        .build()
+        .collect();
+    assert_eq!(
+        delays,
+        [10, 20, 40].map(Duration::from_millis).to_vec(),
+        "the first call isn't a retry"
+    );
+}
+
+#[tokio::test]
+async fn backon_retries_transient_errors_after_the_server_wait() {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use backon::Retryable;
+    use error_pile::{Backoff, backon_adjust};
+
+    let calls = AtomicU32::new(0);
+    let fetch = || async {
+        match calls.fetch_add(1, Ordering::SeqCst) {
+            0 => Err(ErrPile::rate_limited(Some(Duration::from_millis(5)), None)),
+            1 => Err(ErrPile::NotReady),
+            _ => Ok(12),
+        }
+    };
+    let rooms = fetch
+        .retry(Backoff::new().base_delay(Duration::from_millis(1)))
+        .when(ErrPile::is_transient)
+        .adjust(backon_adjust)
+        .await
+        .unwrap();
+    assert_eq!(rooms, 12);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    let throttled = ErrPile::rate_limited(Some(Duration::from_secs(3)), None);
+    assert_eq!(
+        backon_adjust(&throttled, Some(Duration::from_millis(1))),
+        Some(Duration::from_secs(3))
+    );
+    assert_eq!(backon_adjust(&throttled, None), None);
+
+    // longer than the backoff waits for
+    let closed = ErrPile::rate_limited(Some(Duration::from_secs(600)), None);
+    assert_eq!(backon_adjust(&closed, Some(Duration::from_millis(1))), None);
+    let mut adjust = Backoff::new()
+        .max_retry_after(Duration::from_secs(1))
+        .backon_adjust();
+    assert_eq!(adjust(&throttled, Some(Duration::from_millis(1))), None);
+    assert_eq!(
+        adjust(&ErrPile::NotReady, Some(Duration::from_millis(1))),
+        Some(Duration::from_millis(1))
+    );
+}
@@ -0,0 +1,84 @@
+#![cfg(feature = "join")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use error_pile::{ErrPile, PileResult, try_join_all_piles, try_join_all_piles_bounded};
+
+async fn sync_room(room: u32) -> PileResult<u32> {
+    tokio::task::yield_now().await;
+    match room {
+        102 => Err(ErrPile::NotReady),
+        104 => Err(ErrPile::not_found("room", room)),
+        _ => Ok(room),
+    }
+}
+
+fn labeled(rooms: &[u32]) -> Vec<(String, impl Future<Output = PileResult<u32>>)> {
+    rooms
+        .iter()
+        .map(|room| (format!("room {room}"), sync_room(*room)))
+        .collect()
+}
+
+#[tokio::test]
+async fn values_keep_their_order() {
+    let rooms = try_join_all_piles(labeled(&[101, 103, 105])).await.unwrap();
+    assert_eq!(rooms, [101, 103, 105]);
+}
+
+#[tokio::test]
+async fn every_failure_is_labeled() {
+    let err = try_join_all_piles(labeled(&[101, 102, 103, 104]))
+        .await
+        .unwrap_err();
+
+    let multi = err.multi().unwrap();
+    let failures: Vec<_> = multi
+        .iter()
+        .map(|item| (item.index, item.label.as_deref().unwrap()))
+        .collect();
+    assert_eq!(failures, [(1, "room 102"), (3, "room 104")]);
+    assert!(!err.is_transient());
+
+    let err = try_join_all_piles(labeled(&[101, 102])).await.unwrap_err();
+    assert!(err.is_transient());
+}
+
+#[tokio::test]
+async fn cancellation_is_returned_as_is() {
+    let tasks = (0..4).map(|n| {
+        (format!("task {n}"), async move {
+            match n {
+                1 => Err(ErrPile::cancelled()),
+                _ => Err(ErrPile::NotReady),
+            }
+        })
+    });
+
+    let err = try_join_all_piles::<_, _, _, ()>(tasks).await.unwrap_err();
+    assert!(err.is_cancelled());
+    assert!(err.multi().is_none());
+}
+
+#[tokio::test]
+async fn concurrency_is_bounded() {
+    let running = AtomicUsize::new(0);
+    let peak = AtomicUsize::new(0);
+
+    let tasks = (0..10).map(|n| {
+        let (running, peak) = (&running, &peak);
+        (format!("task {n}"), async move {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            for _ in 0..3 {
+                tokio::task::yield_now().await;
+            }
+            running.fetch_sub(1, Ordering::SeqCst);
+            Ok(n)
+        })
+    });
+
+    let values = try_join_all_piles_bounded(tasks, 3).await.unwrap();
+    assert_eq!(values, (0..10).collect::<Vec<_>>());
+    assert_eq!(peak.load(Ordering::SeqCst), 3);
+}
@@ -0,0 +1,89 @@
+use error_pile::{ErrPile, PileKind};
+use serde_json::json;
+
+#[test]
+fn kinds_follow_the_variant() {
+    assert_eq!(ErrPile::Auth.kind(), PileKind::Auth);
+    assert_eq!(ErrPile::not_found("room", 12).kind(), PileKind::NotFound);
+    assert_eq!(ErrPile::custom("boom").kind(), PileKind::Internal);
+    assert_eq!(PileKind::RateLimited.to_string(), "rate_limited");
+
+    let page = ErrPile::Page {
+        index: 2,
+        source: Box::new(ErrPile::NotReady),
+    };
+    assert_eq!(page.kind(), PileKind::NotReady);
+}
+
+#[test]
+fn code_is_read_from_the_payload() {
+    let err = ErrPile::from(json!({"error": {"code": "itemNotFound", "message": "gone"}}));
+    assert_eq!(err.kind(), PileKind::Upstream);
+    assert_eq!(err.code().as_deref(), Some("itemNotFound"));
+    assert_eq!(err.upstream_status(), None);
+    assert_eq!(ErrPile::Auth.code(), None);
+}
+
+#[test]
+fn chain_starts_with_the_error() {
+    let err = ErrPile::Page {
+        index: 0,
+        source: Box::new(ErrPile::InUse),
+    };
+
+    let chain: Vec<String> = err.chain().map(|e| e.to_string()).collect();
+    assert_eq!(
+        chain,
+        [
+            "Failed to fetch page 0 of the listing",
+            "This action can't be performed as it is being currently used elsewhere"
+        ]
+    );
+}
+
+#[test]
+fn user_messages_hide_internal_details() {
+    assert_eq!(
+        ErrPile::not_found("reservation", 7).user_message(),
+        "The reservation `7` could not be found"
+    );
+    assert_eq!(
+        ErrPile::custom("password=hunter2 rejected").user_message(),
+        "Something went wrong, please contact support if the problem persists"
+    );
+
+    let page = ErrPile::Page {
+        index: 0,
+        source: Box::new(ErrPile::NotReady),
+    };
+    assert_eq!(page.user_message(), ErrPile::NotReady.to_string());
+}
+
+#[test]
+fn every_kind_is_listed_once() {
+    let names: std::collections::BTreeSet<_> = PileKind::iter().map(|k| k.as_str()).collect();
+    assert_eq!(names.len(), PileKind::ALL.len());
+
+    for kind in PileKind::iter() {
+        assert_eq!(serde_json::to_value(kind).unwrap(), kind.as_str());
+    }
+}
+
+#[test]
+fn variant_names() {
+    assert_eq!(ErrPile::Auth.variant_name(), "Auth");
+    assert_eq!(ErrPile::not_found("room", 12).variant_name(), "NotFound");
+    assert_eq!(
+        ErrPile::custom_static("feature disabled").variant_name(),
+        "Custom"
+    );
+
+    let io: ErrPile = std::io::Error::other("disk full").into();
+    assert_eq!(io.variant_name(), "Storage");
+
+    let page = ErrPile::Page {
+        index: 2,
+        source: Box::new(ErrPile::NotReady),
+    };
+    assert_eq!(page.variant_name(), "Page");
+}
@@ -0,0 +1,184 @@
+#![cfg(feature = "tower")]
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU32, Ordering},
+    },
+    time::Duration,
+};
+
+use error_pile::{Backoff, ErrPile, PileRetry};
+use tower::{BoxError, ServiceBuilder, ServiceExt, service_fn};
+
+fn fast() -> PileRetry {
+    PileRetry::new(
+        Backoff::new()
+            .base_delay(Duration::from_millis(1))
+            .max_delay(Duration::from_millis(5)),
+    )
+}
+
+#[tokio::test]
+async fn transient_errors_are_retried() {
+    let calls = Arc::new(AtomicU32::new(0));
+    let service = service_fn({
+        let calls = calls.clone();
+        move |room: u32| {
+            let call = calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                match call {
+                    0 => Err(ErrPile::rate_limited(Some(Duration::from_millis(2)), None)),
+                    1 => Err(ErrPile::NotReady),
+                    _ => Ok(room * 2),
+                }
+            }
+        }
+    });
+
+    let service = ServiceBuilder::new().layer(fast().layer()).service(service);
+    assert_eq!(service.oneshot(6).await.unwrap(), 12);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn permanent_and_foreign_errors_are_returned() {
+    let calls = Arc::new(AtomicU32::new(0));
+    let service = service_fn({
+        let calls = calls.clone();
+        move |_: ()| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async { Err::<(), BoxError>(ErrPile::Auth.into()) }
+        }
+    });
+    let service = ServiceBuilder::new().layer(fast().layer()).service(service);
+    let err = service.oneshot(()).await.unwrap_err();
+    assert!(err.downcast_ref::<ErrPile>().unwrap().is_encrypted());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let calls = Arc::new(AtomicU32::new(0));
+    let service = service_fn({
+        let calls = calls.clone();
+        move |_: ()| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async { Err::<(), BoxError>("connection reset".into()) }
+        }
+    });
+    let service = ServiceBuilder::new().layer(fast().layer()).service(service);
+    service.oneshot(()).await.unwrap_err();
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn service_errors_become_responses_with_a_correlation_id() {
+    use error_pile::PileErrorLayer;
+
+    let service = ServiceBuilder::new()
+        .layer(PileErrorLayer)
+        .service(service_fn(|req: http::Request<String>| async move {
+            let id = req.headers()["x-correlation-id"]
+                .to_str()
+                .unwrap()
+                .to_string();
+            match req.uri().path() {
+                "/rooms" => Ok(http::Response::new(id)),
+                _ => Err(ErrPile::custom("db password=hunter2 refused")),
+            }
+        }));
+
+    let req = http::Request::builder()
+        .uri("/folios")
+        .header("x-correlation-id", "req-7")
+        .body(String::new())
+        .unwrap();
+    let res = service.clone().oneshot(req).await.unwrap();
+    assert_eq!(res.status(), 500);
+    assert_eq!(res.headers()["x-correlation-id"], "req-7");
+    let body: serde_json::Value = serde_json::from_str(res.body()).unwrap();
+    assert_eq!(body["code"], "internal");
+    assert_eq!(body["correlation_id"], "req-7");
+    assert!(!res.body().contains("hunter2"));
+
+    // a new id is handed to the handler and echoed
+    let req = http::Request::builder()
+        .uri("/rooms")
+        .body(String::new())
+        .unwrap();
+    let res = service.oneshot(req).await.unwrap();
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["x-correlation-id"], res.body().as_str());
+    assert_eq!(res.body().len(), 36);
+}
+
+#[derive(Debug)]
+struct Handshake(std::io::Error);
+
+impl std::fmt::Display for Handshake {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("tls handshake failed")
+    }
+}
+
+impl std::error::Error for Handshake {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+#[test]
+fn foreign_errors_keep_their_sources() {
+    let err: BoxError = Box::new(Handshake(std::io::Error::other("socket closed")));
+    let err = ErrPile::from(err);
+    assert!(matches!(err, ErrPile::Other(_)));
+    assert_eq!(err.to_string(), "tls handshake failed");
+    let source = std::error::Error::source(&err).unwrap();
+    assert_eq!(source.to_string(), "socket closed");
+}
+
+#[test]
+fn the_correlation_id_comes_first() {
+    let err = ErrPile::custom("folio locked")
+        .with_correlation_id("correlation_id", "req-1")
+        .with_correlation_id("correlation_id", "req-2");
+    assert_eq!(err.correlation_ids(), [("correlation_id", "req-2".into())]);
+    assert_eq!(err.to_string(), "folio locked");
+}
+
+// the installed queue is global, only this test reports
+#[cfg(feature = "report")]
+#[tokio::test]
+async fn reported_errors_carry_the_correlation_id() {
+    use error_pile::{ErrorQueue, ErrorReport, ErrorSink, PileErrorLayer, PileResult};
+
+    #[derive(Clone, Default)]
+    struct Collect(Arc<std::sync::Mutex<Vec<ErrorReport>>>);
+
+    #[async_trait::async_trait]
+    impl ErrorSink for Collect {
+        async fn send(&self, report: &ErrorReport) -> PileResult {
+            self.0.lock().unwrap().push(report.clone());
+            Ok(())
+        }
+    }
+
+    let reports = Collect::default();
+    let queue = ErrorQueue::new(4).sink(reports.clone()).install();
+    let service = ServiceBuilder::new()
+        .layer(PileErrorLayer)
+        .service(service_fn(|_: http::Request<String>| async {
+            Err::<http::Response<String>, _>(ErrPile::custom("night audit failed"))
+        }));
+    let req = http::Request::builder()
+        .header("x-correlation-id", "req-9")
+        .body(String::new())
+        .unwrap();
+    service.oneshot(req).await.unwrap();
+    queue.shutdown().await;
+
+    let reports = reports.0.lock().unwrap();
+    assert_eq!(reports[0].message, "night audit failed");
+    assert_eq!(
+        reports[0].correlation_ids,
+        [("correlation_id".to_string(), "req-9".to_string())]
+    );
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "log")]
+
+// the logger is global, so this lives in its own test binary
+
+use std::sync::Mutex;
+
+use error_pile::{ErrPile, PileResult, log_err};
+use log::{Level, Log, Metadata, Record};
+
+static RECORDS: Mutex<Vec<(Level, String, String)>> = Mutex::new(Vec::new());
+
+struct Recorder;
+
+impl Log for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        RECORDS.lock().unwrap().push((
+            record.level(),
+            record.target().to_string(),
+            record.args().to_string(),
+        ));
+    }
+
+    fn flush(&self) {}
+}
+
+fn check_in(taken: bool) -> PileResult {
+    if taken {
+        let err = ErrPile::Page {
+            index: 3,
+            source: Box::new(ErrPile::InUse),
+        };
+        return err.log(Level::Warn).into();
+    }
+    Ok(())
+}
+
+#[test]
+fn errors_are_logged_with_their_chain() {
+    log::set_logger(&Recorder).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+
+    assert!(check_in(false).is_ok());
+    assert!(check_in(true).is_err());
+    let err = log_err!(ErrPile::NotReady);
+    assert!(err.is_not_ready());
+    let _ = log_err!(Level::Info, ErrPile::Auth);
+
+    let records = RECORDS.lock().unwrap();
+    assert_eq!(
+        records[0],
+        (
+            Level::Warn,
+            "error_pile::logging".to_string(),
+            "Failed to fetch page 3 of the listing: This action can't be performed as it is being currently used elsewhere".to_string()
+        )
+    );
+    assert_eq!(records[1].0, Level::Error);
+    assert_eq!(records[1].1, "logging");
+    assert_eq!(
+        records[1].2,
+        "The resource is not ready yet, please try again later"
+    );
+    assert_eq!(records[2].0, Level::Info);
+}
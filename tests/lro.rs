@@ -0,0 +1,67 @@
+#![cfg(feature = "lro")]
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use error_pile::{ErrPile, LroPoller, MicrosoftError};
+
+mod common;
+
+/// `/ok` runs for two polls, `/failed` fails, `/stuck` never finishes
+async fn operation_server() -> String {
+    let polls = Arc::new(AtomicUsize::new(0));
+
+    common::serve(move |req| {
+        let body = match req.path.as_str() {
+            "/ok" if polls.fetch_add(1, Ordering::SeqCst) < 2 => r#"{"status":"running"}"#,
+            "/ok" => r#"{"status":"succeeded","analyzeResult":{"pages":3}}"#,
+            "/failed" => {
+                r#"{"status":"failed","error":{"code":"InvalidContent","message":"corrupt pdf"}}"#
+            }
+            _ => r#"{"status":"notStarted"}"#,
+        };
+        common::reply(200, "application/json", body)
+    })
+    .await
+}
+
+fn accepted(location: String) -> reqwest::Response {
+    http::Response::builder()
+        .status(202)
+        .header("operation-location", location)
+        .body(String::new())
+        .unwrap()
+        .into()
+}
+
+#[tokio::test]
+async fn operations_are_polled_to_completion() {
+    let base = operation_server().await;
+    let client = reqwest::Client::new();
+    let poller = LroPoller::new().interval(Duration::from_millis(10));
+
+    let done: serde_json::Value = poller
+        .poll(&client, accepted(format!("{base}/ok")))
+        .await
+        .unwrap();
+    assert_eq!(done["analyzeResult"]["pages"], 3);
+
+    let err = poller
+        .poll::<serde_json::Value>(&client, accepted(format!("{base}/failed")))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ErrPile::Microsoft(MicrosoftError::AZ(_))));
+
+    let err = poller
+        .clone()
+        .deadline(Duration::from_millis(50))
+        .poll::<serde_json::Value>(&client, accepted(format!("{base}/stuck")))
+        .await
+        .unwrap_err();
+    assert!(err.is_timeout());
+}
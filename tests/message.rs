@@ -0,0 +1,77 @@
+use std::{borrow::Cow, time::Duration};
+
+use error_pile::{ErrPile, RemoteError};
+
+#[test]
+fn fixed_messages_are_borrowed() {
+    for err in [
+        ErrPile::Auth,
+        ErrPile::Permission,
+        ErrPile::InUse,
+        ErrPile::NotReady,
+        ErrPile::Floor,
+        ErrPile::Ceil,
+        ErrPile::FrameTooLarge,
+        ErrPile::custom("room type not mapped"),
+        ErrPile::custom_static("feature disabled"),
+    ] {
+        let message = err.source_str();
+        assert!(matches!(message, Cow::Borrowed(_)), "{err:?}");
+        assert_eq!(message, err.to_string());
+    }
+
+    let remote: ErrPile = RemoteError::new(&ErrPile::custom("rate plan closed")).into();
+    assert!(matches!(
+        remote.source_str(),
+        Cow::Borrowed("rate plan closed")
+    ));
+}
+
+#[test]
+fn sources_are_preferred() {
+    let err: ErrPile = std::io::Error::other("disk full").into();
+    assert_eq!(err.source_str(), "disk full");
+
+    let err = ErrPile::timeout("night audit", Duration::from_secs(30));
+    assert_eq!(
+        err.source_str(),
+        "The operation `night audit` timed out after 30s"
+    );
+}
+
+#[test]
+fn optional_parts_of_the_messages() {
+    let err = ErrPile::conflict_with_version("reservation", "7");
+    assert_eq!(
+        err.to_string(),
+        "The reservation was modified by someone else (current version: 7)"
+    );
+    let err = ErrPile::conflict("reservation");
+    assert_eq!(
+        err.to_string(),
+        "The reservation was modified by someone else"
+    );
+
+    assert_eq!(
+        ErrPile::cancelled_because("shutdown").to_string(),
+        "The operation was cancelled: shutdown"
+    );
+    assert_eq!(
+        ErrPile::cancelled().to_string(),
+        "The operation was cancelled"
+    );
+}
+
+const ROOM_TYPE_NOT_MAPPED: ErrPile = ErrPile::custom_static("room type not mapped");
+
+#[test]
+fn static_messages_are_not_copied() {
+    let ErrPile::Custom(message) = ROOM_TYPE_NOT_MAPPED else {
+        unreachable!();
+    };
+    assert!(matches!(message, Cow::Borrowed("room type not mapped")));
+
+    let owned = ErrPile::custom(String::from("room type not mapped"));
+    assert!(matches!(owned, ErrPile::Custom(Cow::Owned(_))));
+    assert_eq!(owned.to_string(), ROOM_TYPE_NOT_MAPPED.to_string());
+}
@@ -0,0 +1,108 @@
+#![cfg(feature = "metrics")]
+
+// set_count_errors is global, so this lives in its own test binary
+
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicU64, Ordering},
+};
+
+use error_pile::ErrPile;
+use metrics::{
+    Counter, CounterFn, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit,
+};
+
+/// counters by their rendered key, `name{label=value,...}`
+#[derive(Default)]
+struct TestRecorder(Mutex<Vec<(String, Arc<AtomicU64>)>>);
+
+struct Count(Arc<AtomicU64>);
+
+impl CounterFn for Count {
+    fn increment(&self, value: u64) {
+        self.0.fetch_add(value, Ordering::Relaxed);
+    }
+
+    fn absolute(&self, value: u64) {
+        self.0.store(value, Ordering::Relaxed);
+    }
+}
+
+impl TestRecorder {
+    fn value(&self, key: &str) -> u64 {
+        let counters = self.0.lock().unwrap();
+        counters
+            .iter()
+            .filter(|(k, _)| k == key)
+            .map(|(_, c)| c.load(Ordering::Relaxed))
+            .sum()
+    }
+}
+
+impl Recorder for TestRecorder {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+        let labels: Vec<String> = key
+            .labels()
+            .map(|l| format!("{}={}", l.key(), l.value()))
+            .collect();
+        let name = format!("{}{{{}}}", key.name(), labels.join(","));
+
+        let count = Arc::new(AtomicU64::new(0));
+        self.0.lock().unwrap().push((name, count.clone()));
+        Counter::from_arc(Arc::new(Count(count)))
+    }
+
+    fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+        Gauge::noop()
+    }
+
+    fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+        Histogram::noop()
+    }
+}
+
+#[test]
+fn errors_are_counted_by_kind() {
+    let recorder = TestRecorder::default();
+
+    metrics::with_local_recorder(&recorder, || {
+        ErrPile::NotReady.count();
+        ErrPile::NotReady.count();
+        ErrPile::Auth.count();
+    });
+
+    assert_eq!(
+        recorder.value("errpile_total{kind=not_ready,code=none,transient=true}"),
+        2
+    );
+    assert_eq!(
+        recorder.value("errpile_total{kind=auth,code=none,transient=false}"),
+        1
+    );
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn reported_errors_are_counted_once_enabled() {
+    use error_pile::set_count_errors;
+
+    let recorder = TestRecorder::default();
+
+    metrics::with_local_recorder(&recorder, || {
+        ErrPile::InUse.emit();
+        set_count_errors(true);
+        ErrPile::InUse.emit();
+        set_count_errors(false);
+    });
+
+    assert_eq!(
+        recorder.value("errpile_total{kind=in_use,code=none,transient=false}"),
+        1
+    );
+}
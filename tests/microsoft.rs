@@ -0,0 +1,483 @@
+use error_pile::{ErrPile, GraphErrorCode, MSResponseError, MicrosoftError, TransportError};
+
+mod common;
+
+fn ms_error(body: &str) -> ErrPile {
+    let err: MSResponseError = serde_json::from_str(body).unwrap();
+    MicrosoftError::MS(Box::new(err)).into()
+}
+
+#[test]
+fn graph_codes_are_typed() {
+    let err = ms_error(r#"{"error":{"code":"ItemNotFound","message":"gone","innerError":{}}}"#);
+    assert_eq!(err.graph_code(), Some(GraphErrorCode::ItemNotFound));
+
+    let err = ms_error(r#"{"error":{"code":"roomOnFire","message":"?","innerError":{}}}"#);
+    let code = err.graph_code().unwrap();
+    assert_eq!(code, GraphErrorCode::Other("roomOnFire".into()));
+    assert_eq!(code.to_string(), "roomOnFire");
+
+    assert_eq!(
+        GraphErrorCode::NameAlreadyExists.to_string(),
+        "nameAlreadyExists"
+    );
+    assert_eq!(ErrPile::Auth.graph_code(), None);
+}
+
+#[tokio::test]
+async fn graph_throttling_is_transient() {
+    use error_pile::ReqwestPileResExt;
+
+    let res: reqwest::Response = http::Response::builder()
+        .status(503)
+        .header("content-type", "application/json")
+        .body(
+            r#"{"error":{"code":"serviceNotAvailable","message":"busy","innerError":{"retryAfterSeconds":7}}}"#,
+        )
+        .unwrap()
+        .into();
+
+    let err = res.to_pile_empty().await.unwrap_err();
+    assert!(err.is_rate_limited());
+    assert!(err.is_transient());
+    assert_eq!(err.retry_after(), Some(std::time::Duration::from_secs(7)));
+
+    let err = ms_error(
+        r#"{"error":{"code":"activityLimitReached","message":"slow down","innerError":{"retryAfter":"3"}}}"#,
+    );
+    assert!(err.is_transient());
+    assert_eq!(err.retry_after(), Some(std::time::Duration::from_secs(3)));
+}
+
+#[test]
+fn batch_items_are_converted() {
+    use error_pile::GraphBatchResponse;
+
+    let batch: GraphBatchResponse = serde_json::from_str(
+        r#"{"responses":[
+            {"id":"2","status":404,"headers":{"content-type":"application/json"},
+             "body":{"error":{"code":"itemNotFound","message":"no event","innerError":{}}}},
+            {"id":"1","status":200,"body":{"subject":"Checkout"}},
+            {"id":"3","status":429,"headers":{"Retry-After":"5"},"body":{}}
+        ]}"#,
+    )
+    .unwrap();
+
+    let results = batch.into_results::<serde_json::Value>();
+    assert_eq!(results.keys().collect::<Vec<_>>(), ["1", "2", "3"]);
+    assert_eq!(results["1"].as_ref().unwrap()["subject"], "Checkout");
+
+    let err = results["2"].as_ref().unwrap_err();
+    assert_eq!(err.graph_code(), Some(GraphErrorCode::ItemNotFound));
+
+    let err = results["3"].as_ref().unwrap_err();
+    assert_eq!(err.retry_after(), Some(std::time::Duration::from_secs(5)));
+}
+
+#[test]
+fn graph_diagnostics_are_displayed() {
+    let err = ms_error(
+        r#"{"error":{"code":"accessDenied","message":"no","innerError":{
+            "request-id":"r-1","client-request-id":"c-2","date":"2026-10-15T08:00:00"}}}"#,
+    );
+
+    let ErrPile::Microsoft(MicrosoftError::MS(ms)) = &err else {
+        panic!("expected MS variant");
+    };
+    let diagnostics = ms.error.diagnostics();
+    assert_eq!(diagnostics.request_id.as_deref(), Some("r-1"));
+    assert_eq!(diagnostics.client_request_id.as_deref(), Some("c-2"));
+
+    assert_eq!(
+        err.to_string(),
+        "Request responded with an error (request-id: r-1, client-request-id: c-2, date: 2026-10-15T08:00:00)"
+    );
+}
+
+/// serves canned Graph pages, checks every page is authorized
+async fn graph_server() -> String {
+    common::serve(|req| {
+        let host = format!("http://{}", req.header("host").unwrap());
+        let authorization = req.header("authorization");
+
+        let (status, body) = if req.path == "/foreign" {
+            // the token must not follow a link to another host
+            match authorization {
+                Some(_) => (500, String::new()),
+                None => (200, r#"{"value":[9]}"#.to_string()),
+            }
+        } else if authorization != Some("Bearer t") {
+            (401, String::new())
+        } else if req.path == "/events" {
+            (
+                200,
+                format!(r#"{{"value":[1,2],"@odata.nextLink":"{host}/page2"}}"#),
+            )
+        } else if req.path == "/page2" {
+            (
+                200,
+                format!(r#"{{"value":[3],"@odata.nextLink":"{host}/page3"}}"#),
+            )
+        } else if req.path == "/leak" {
+            let foreign = host.replace("127.0.0.1", "localhost");
+            (
+                200,
+                format!(r#"{{"value":[8],"@odata.nextLink":"{foreign}/foreign"}}"#),
+            )
+        } else if req.path == "/garbled" {
+            (
+                200,
+                r#"{"value":[5],"@odata.nextLink":"http://[::1/page2"}"#.to_string(),
+            )
+        } else if req.path == "/single" {
+            (200, r#"{"value":[7],"@odata.deltaLink":"x"}"#.to_string())
+        } else {
+            (
+                404,
+                r#"{"error":{"code":"itemNotFound","message":"gone"}}"#.to_string(),
+            )
+        };
+        common::reply(status, "application/json", &body)
+    })
+    .await
+}
+
+#[tokio::test]
+async fn pages_are_followed() {
+    use error_pile::MSResponse;
+
+    let base = graph_server().await;
+    let client = reqwest::Client::new();
+
+    let pages =
+        MSResponse::<Vec<u32>>::collect_all(client.get(format!("{base}/single")).bearer_auth("t"))
+            .await
+            .unwrap();
+    assert_eq!(pages.items, [7]);
+    assert_eq!(pages.delta_link.as_deref(), Some("x"));
+
+    let pages =
+        MSResponse::<Vec<u32>>::collect_all(client.get(format!("{base}/leak")).bearer_auth("t"))
+            .await
+            .unwrap();
+    assert_eq!(pages.items, [8, 9]);
+    assert_eq!(pages.delta_link, None);
+
+    let err =
+        MSResponse::<Vec<u32>>::collect_all(client.get(format!("{base}/events")).bearer_auth("t"))
+            .await
+            .unwrap_err();
+
+    let ErrPile::Page { index, source } = &err else {
+        panic!("expected Page variant, got {err:?}");
+    };
+    assert_eq!(*index, 2);
+    assert_eq!(source.graph_code(), Some(GraphErrorCode::ItemNotFound));
+
+    // the first page came, the link to the second one is garbage
+    let err =
+        MSResponse::<Vec<u32>>::collect_all(client.get(format!("{base}/garbled")).bearer_auth("t"))
+            .await
+            .unwrap_err();
+    let ErrPile::Page { index, source } = &err else {
+        panic!("expected Page variant, got {err:?}");
+    };
+    assert_eq!(*index, 1);
+    assert!(
+        matches!(&**source, ErrPile::Transport(t) if matches!(**t, TransportError::Url(_))),
+        "{source:?}"
+    );
+}
+
+#[test]
+fn graph_errors_chain() {
+    use std::error::Error;
+
+    let err = ms_error(
+        r#"{"error":{"code":"accessDenied","message":"Access is denied","innerError":{"code":"lockedMailbox"}}}"#,
+    );
+    assert_eq!(err.source_str(), "accessDenied - Access is denied");
+
+    let ms = err.source().unwrap();
+    assert_eq!(ms.source().unwrap().to_string(), "lockedMailbox");
+
+    // Graph omits innerError now and then
+    let err = ms_error(r#"{"error":{"code":"itemNotFound","message":"gone"}}"#);
+    assert!(err.source().unwrap().source().is_none());
+}
+
+#[test]
+fn analyze_operations_are_typed() {
+    use error_pile::AZOperation;
+
+    let poll = |body: &str| {
+        serde_json::from_str::<AZOperation<serde_json::Value>>(body)
+            .unwrap()
+            .into_result()
+    };
+
+    let err = poll(r#"{"status":"running","createdDateTime":"2026-10-15T08:00:00Z"}"#).unwrap_err();
+    assert!(err.is_not_ready());
+    assert!(err.is_transient());
+
+    let err = poll(
+        r#"{"status":"failed","error":{"code":"InvalidRequest","message":"bad pdf","innererror":{"code":"InvalidContent","message":"corrupt"}}}"#,
+    )
+    .unwrap_err();
+    assert!(matches!(err, ErrPile::Microsoft(MicrosoftError::AZ(_))));
+    // a Document Intelligence code, not a Graph one
+    assert_eq!(err.graph_code(), None);
+    assert_eq!(err.code().as_deref(), Some("InvalidRequest"));
+
+    let ok = poll(r#"{"status":"succeeded","analyzeResult":{"pages":[]}}"#).unwrap();
+    assert_eq!(ok["pages"], serde_json::json!([]));
+
+    let mut headers = reqwest::header::HeaderMap::new();
+    assert!(error_pile::operation_location(&headers).is_err());
+    headers.insert(
+        "operation-location",
+        "https://di.example/analyzeResults/1".parse().unwrap(),
+    );
+    assert_eq!(
+        error_pile::operation_location(&headers).unwrap().path(),
+        "/analyzeResults/1"
+    );
+}
+
+#[test]
+fn analyze_warnings_are_kept() {
+    use error_pile::{AZOperation, AZOutcome};
+
+    #[derive(serde::Deserialize)]
+    struct Layout {
+        pages: Vec<serde_json::Value>,
+    }
+
+    let op: AZOperation<AZOutcome<Layout>> = serde_json::from_str(
+        r#"{"status":"succeeded","analyzeResult":{"pages":[{}],
+            "warnings":[{"code":"LowConfidence","message":"page 1 is blurry","target":"/pages/0"}]}}"#,
+    )
+    .unwrap();
+
+    let outcome = op.into_result().unwrap();
+    assert_eq!(outcome.value.pages.len(), 1);
+    assert_eq!(outcome.warnings[0].code(), "LowConfidence");
+    assert_eq!(
+        outcome.warnings[0].to_string(),
+        "LowConfidence - page 1 is blurry (/pages/0)"
+    );
+}
+
+#[test]
+fn az_codes_are_typed() {
+    use error_pile::{AZError, AZErrorCode};
+
+    let err: AZError = serde_json::from_str(
+        r#"{"error":{"code":"InvalidRequest","message":"bad","innererror":{"code":"InvalidContentDimensions","message":"too small"}}}"#,
+    )
+    .unwrap();
+
+    assert_eq!(err.error.code_enum(), AZErrorCode::InvalidRequest);
+    assert_eq!(
+        err.error.innererror.as_ref().unwrap().code_enum(),
+        Some(AZErrorCode::InvalidContentDimensions)
+    );
+    assert_eq!(
+        AZErrorCode::from("somethingNew"),
+        AZErrorCode::Other("somethingNew".into())
+    );
+}
+
+#[test]
+fn az_errors_know_when_to_retry() {
+    use error_pile::AZError;
+
+    let az = |body: &str| ErrPile::from(Box::new(serde_json::from_str::<AZError>(body).unwrap()));
+
+    assert!(az(r#"{"error":{"code":"ServiceUnavailable","message":"busy"}}"#).is_transient());
+    assert!(!az(r#"{"error":{"code":"ModelNotFound","message":"no model"}}"#).is_transient());
+
+    // the inner cause is permanent, retrying won't fix the document
+    let err = az(
+        r#"{"error":{"code":"InternalServerError","message":"failed","innererror":{"code":"InvalidContent","message":"corrupt"}}}"#,
+    );
+    assert!(!err.is_transient());
+
+    let err = az(
+        r#"{"error":{"code":"SomethingNew","message":"?","details":[{"error":{"code":"Timeout","message":"slow"}}]}}"#,
+    );
+    assert!(err.is_transient());
+}
+
+fn graph_response(status: u16, code: &str, message: &str) -> reqwest::Response {
+    http::Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(format!(
+            r#"{{"error":{{"code":"{code}","message":"{message}"}}}}"#
+        ))
+        .unwrap()
+        .into()
+}
+
+#[tokio::test]
+async fn sharepoint_errors_are_recognised() {
+    use error_pile::ReqwestPileResExt;
+
+    let err = graph_response(
+        423,
+        "resourceLocked",
+        "The resource you are attempting to access is locked",
+    )
+    .to_pile_empty()
+    .await
+    .unwrap_err();
+    assert!(matches!(err, ErrPile::InUse));
+    assert!(err.is_sharepoint_locked());
+
+    let err = graph_response(
+        423,
+        "resourceLocked",
+        "The file Folio.pdf is checked out for editing by Ram",
+    )
+    .to_pile_empty()
+    .await
+    .unwrap_err();
+    assert!(err.is_sharepoint_checked_out());
+    assert!(err.is_sharepoint_locked());
+
+    let err = graph_response(
+        500,
+        "generalException",
+        "The attempted operation is prohibited because it exceeds the list view threshold.",
+    )
+    .to_pile_empty()
+    .await
+    .unwrap_err();
+    assert!(err.is_list_threshold());
+    assert!(!err.is_sharepoint_locked());
+}
+
+#[tokio::test]
+async fn drive_conflicts_suggest_resolution() {
+    use error_pile::{DriveConflict, DriveResolution, ReqwestPileResExt};
+
+    let err = graph_response(
+        409,
+        "nameAlreadyExists",
+        "The specified item name already exists",
+    )
+    .to_pile_empty()
+    .await
+    .unwrap_err();
+    let conflict = err.drive_conflict().unwrap();
+    assert_eq!(conflict, DriveConflict::NameAlreadyExists);
+    assert_eq!(conflict.suggested_resolution(), DriveResolution::Rename);
+    assert_eq!(
+        conflict.suggested_resolution().conflict_behavior(),
+        "rename"
+    );
+
+    let err = graph_response(507, "quotaLimitReached", "Insufficient Space Available")
+        .to_pile_empty()
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.drive_conflict().map(|c| c.suggested_resolution()),
+        Some(DriveResolution::Abort)
+    );
+
+    assert_eq!(ErrPile::Auth.drive_conflict(), None);
+}
+
+#[tokio::test]
+async fn send_mail_failures_are_categorised() {
+    use error_pile::{MailAction, MailFailure, ReqwestPileResExt};
+
+    let err = graph_response(
+        400,
+        "ErrorInvalidRecipients",
+        "At least one recipient isn't valid",
+    )
+    .to_pile_empty()
+    .await
+    .unwrap_err();
+    assert_eq!(err.mail_failure(), Some(MailFailure::RecipientNotResolved));
+    assert_eq!(err.mail_failure().unwrap().action(), MailAction::Drop);
+
+    let err = graph_response(403, "ErrorSendAsDenied", "The user account which was used to submit this request does not have the right to send mail on behalf of the specified sending account")
+        .to_pile_empty()
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.mail_failure().map(|f| f.action()),
+        Some(MailAction::Alert)
+    );
+
+    let err = ErrPile::rate_limited(None, None);
+    assert_eq!(err.mail_failure(), Some(MailFailure::Throttled));
+}
+
+#[tokio::test]
+async fn directory_replication_lag_is_transient() {
+    use error_pile::{ConsistencyHint, ReqwestPileResExt};
+
+    let err = graph_response(
+        404,
+        "Request_ResourceNotFound",
+        "Resource 'a1' does not exist or one of its queried reference-property objects are not present.",
+    )
+    .to_pile_empty()
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err.retry_with_consistency(),
+        Some(ConsistencyHint::ReplicationLag)
+    );
+    assert!(err.is_transient());
+
+    let err = graph_response(
+        400,
+        "Request_UnsupportedQuery",
+        "Unsupported Query. ConsistencyLevel:eventual header is required.",
+    )
+    .to_pile_empty()
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err.retry_with_consistency(),
+        Some(ConsistencyHint::ConsistencyLevelRequired)
+    );
+    assert!(!err.is_transient());
+}
+
+#[test]
+fn ms_response_accepts_graph_shapes() {
+    use error_pile::{MSResponse, PileResult};
+
+    #[derive(Debug, serde::Deserialize, PartialEq)]
+    struct Room {
+        id: String,
+    }
+
+    let parse = |body: &str| -> PileResult<Room> {
+        let res: MSResponse<Room> = serde_json::from_str(body)?;
+        res.into()
+    };
+
+    let room = Room { id: "101".into() };
+    assert_eq!(parse(r#"{"value":{"id":"101"}}"#).unwrap(), room);
+    assert_eq!(parse(r#"{"value":[{"id":"101"}]}"#).unwrap(), room);
+    assert_eq!(parse(r#"{"d":{"results":{"id":"101"}}}"#).unwrap(), room);
+    assert_eq!(parse(r#"{"d":{"id":"101"}}"#).unwrap(), room);
+    assert_eq!(parse(r#"{"@odata.context":"x","id":"101"}"#).unwrap(), room);
+
+    let err = parse(r#"{"error":{"code":"itemNotFound","message":"gone"}}"#).unwrap_err();
+    assert_eq!(err.graph_code(), Some(GraphErrorCode::ItemNotFound));
+
+    let err = serde_json::from_str::<MSResponse<Room>>(r#"{"rooms":[]}"#).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("response matched none of the shapes `value`")
+    );
+}
@@ -0,0 +1,108 @@
+#![cfg(feature = "middleware")]
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use error_pile::{ErrPile, PileMiddleware};
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
+
+mod common;
+
+/// the method and `Idempotency-Key` of a request the server received
+type Seen = Arc<Mutex<Vec<(String, Option<String>)>>>;
+
+/// answers with `responses` in order, the last one over and over
+async fn scripted_server(responses: &[&'static str]) -> (String, Seen) {
+    let seen = Seen::default();
+    let mut responses = responses.iter().copied().collect::<VecDeque<_>>();
+
+    let requests = seen.clone();
+    let base = common::serve(move |req| {
+        let key = req.header("idempotency-key").map(str::to_string);
+        requests.lock().unwrap().push((req.method, key));
+
+        let res = if responses.len() > 1 {
+            responses.pop_front().unwrap()
+        } else {
+            responses[0]
+        };
+        res.to_string()
+    })
+    .await;
+
+    (base, seen)
+}
+
+const UNAVAILABLE: &str =
+    "HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+const NOT_FOUND: &str = "HTTP/1.1 404 Not Found\r\ncontent-type: application/json\r\ncontent-length: 30\r\nconnection: close\r\n\r\n{\"error\":\"no such reservation\"}";
+const OK: &str = "HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok";
+
+fn client(middleware: PileMiddleware) -> ClientWithMiddleware {
+    ClientBuilder::new(reqwest::Client::new())
+        .with(middleware)
+        .build()
+}
+
+#[tokio::test]
+async fn transient_failures_are_retried() {
+    let (base, seen) = scripted_server(&[UNAVAILABLE, UNAVAILABLE, OK]).await;
+    let client = client(PileMiddleware::with_retries(3, Duration::from_millis(1)));
+
+    let res = client.get(format!("{base}/rates")).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "ok");
+    assert_eq!(seen.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn retries_give_up_with_the_last_error() {
+    let (base, seen) = scripted_server(&[UNAVAILABLE]).await;
+    let client = client(PileMiddleware::with_retries(2, Duration::from_millis(1)));
+
+    let err = ErrPile::from(client.get(&base).send().await.unwrap_err());
+    assert!(err.is_transient());
+    assert_eq!(seen.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn error_responses_become_errors() {
+    let (base, seen) = scripted_server(&[NOT_FOUND]).await;
+    let client = client(PileMiddleware::with_retries(3, Duration::from_millis(1)));
+
+    let err = ErrPile::from(client.get(&base).send().await.unwrap_err());
+    assert_eq!(err.upstream_status(), Some(404));
+    // not transient, sent once
+    assert_eq!(seen.lock().unwrap().len(), 1);
+
+    let err = ErrPile::from(reqwest_middleware::Error::middleware(
+        std::io::Error::other("proxy refused"),
+    ));
+    assert_eq!(
+        err.to_string(),
+        ErrPile::custom("proxy refused").to_string()
+    );
+}
+
+#[tokio::test]
+async fn posts_are_retried_only_with_an_idempotency_key() {
+    let (base, seen) = scripted_server(&[UNAVAILABLE, OK]).await;
+    let once = client(PileMiddleware::with_retries(3, Duration::from_millis(1)));
+    assert!(once.post(&base).send().await.is_err());
+    assert_eq!(*seen.lock().unwrap(), [("POST".to_string(), None)]);
+
+    let (base, seen) = scripted_server(&[UNAVAILABLE, OK]).await;
+    let client = client(
+        PileMiddleware::with_retries(3, Duration::from_millis(1))
+            .idempotency_keys(|| "booking-42".into()),
+    );
+    assert!(client.post(&base).send().await.is_ok());
+    // every attempt sends the same key
+    let key = Some("booking-42".to_string());
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [("POST".to_string(), key.clone()), ("POST".to_string(), key)]
+    );
+}
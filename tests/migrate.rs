@@ -0,0 +1,39 @@
+#![cfg(feature = "migrate")]
+
+use error_pile::{ErrPile, MigrationFailure};
+use sqlx::migrate::MigrateError;
+
+#[test]
+fn migration_failures_are_classified() {
+    let err = ErrPile::from(MigrateError::VersionMismatch(20261015));
+    assert_eq!(
+        err.migration_failure(),
+        Some(MigrationFailure::ChecksumMismatch)
+    );
+    assert!(err.is_dirty_migration());
+
+    let err = ErrPile::from(MigrateError::Dirty(20261015));
+    assert_eq!(err.migration_failure(), Some(MigrationFailure::Dirty));
+    assert!(err.is_dirty_migration());
+
+    let err = ErrPile::from(MigrateError::VersionTooNew(20261015, 20261001));
+    assert_eq!(
+        err.migration_failure(),
+        Some(MigrationFailure::VersionConflict)
+    );
+    assert!(!err.is_dirty_migration());
+
+    // an unreachable database is worth retrying, bad SQL is not
+    let err = ErrPile::from(MigrateError::Execute(sqlx::Error::PoolTimedOut));
+    assert_eq!(
+        err.migration_failure(),
+        Some(MigrationFailure::Connectivity)
+    );
+    assert!(err.is_transient());
+
+    let err = ErrPile::from(MigrateError::Execute(sqlx::Error::RowNotFound));
+    assert_eq!(err.migration_failure(), Some(MigrationFailure::Execution));
+    assert!(!err.is_transient());
+
+    assert_eq!(ErrPile::NotReady.migration_failure(), None);
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "test-util")]
+
+use std::time::Duration;
+
+use error_pile::{
+    ErrPile, MSResponseError, MicrosoftError, MockErrorResponse, PileKind, ReqwestPileResExt,
+    assert_pile,
+};
+
+async fn decode(res: MockErrorResponse) -> ErrPile {
+    reqwest::Response::from(res)
+        .error_for_pile()
+        .await
+        .unwrap_err()
+}
+
+#[tokio::test]
+async fn graph_throttling() {
+    let err = decode(MockErrorResponse::graph_throttled(Duration::from_secs(3))).await;
+    assert_eq!(err.kind(), PileKind::RateLimited);
+    assert_eq!(err.retry_after(), Some(Duration::from_secs(3)));
+}
+
+#[tokio::test]
+async fn graph_errors() {
+    let err = decode(MockErrorResponse::graph(
+        404,
+        "itemNotFound",
+        "The resource could not be found.",
+    ))
+    .await;
+    assert_eq!(err.code().as_deref(), Some("itemNotFound"));
+
+    let err = decode(MockErrorResponse::graph_unauthorized()).await;
+    assert_eq!(err.code().as_deref(), Some("InvalidAuthenticationToken"));
+
+    let err = decode(MockErrorResponse::graph_locked()).await;
+    assert_pile!(err, ErrPile::InUse);
+}
+
+#[tokio::test]
+async fn document_intelligence_errors() {
+    let err = decode(MockErrorResponse::document_intelligence_invalid_content()).await;
+    assert_pile!(
+        err,
+        ErrPile::Microsoft(MicrosoftError::AZ(az))
+            if az.error.code == "InvalidRequest" && !az.is_transient()
+    );
+}
+
+#[tokio::test]
+async fn problems_and_validation() {
+    let err = decode(MockErrorResponse::problem(
+        409,
+        "Room occupied",
+        "Room 101 is occupied",
+    ))
+    .await;
+    assert_pile!(err, ErrPile::Problem(problem) if problem.title.as_deref() == Some("Room occupied"));
+
+    let err = decode(MockErrorResponse::validation([
+        ("arrival", "must be before departure"),
+        ("arrival", "must not be in the past"),
+        ("adults", "at least one adult"),
+    ]))
+    .await;
+    let ErrPile::Validation(errors) = &err else {
+        panic!("expected validation errors, got {err:?}");
+    };
+    assert_eq!(errors.0.len(), 3);
+}
+
+#[test]
+fn bodies_parse_as_the_vendor_types() {
+    let res = MockErrorResponse::graph(503, "serviceNotAvailable", "Service unavailable");
+    let parsed: MSResponseError = serde_json::from_slice(&res.body_bytes()).unwrap();
+    assert_eq!(parsed.error.code, "serviceNotAvailable");
+
+    let res = res.into_http();
+    assert_eq!(res.status(), 503);
+    assert_eq!(res.headers()["content-type"], "application/json");
+    assert!(res.headers().contains_key("request-id"));
+}
@@ -0,0 +1,108 @@
+use std::{error::Error, time::Duration};
+
+use error_pile::{ErrPile, MultiPile, PileIteratorExt, PileKind};
+
+#[test]
+fn every_failure_is_kept() {
+    let mut failures = MultiPile::new();
+    assert!(failures.is_empty());
+
+    failures.push_labeled(2, "folio 1042", ErrPile::not_found("folio", 1042));
+    failures.push(7, ErrPile::custom("Invalid tax code"));
+    assert_eq!(failures.len(), 2);
+    assert_eq!(failures.items()[0].label.as_deref(), Some("folio 1042"));
+    assert_eq!(
+        failures.to_string(),
+        "2 items failed; #2 (folio 1042): The folio `1042` could not be found; \
+         #7: Invalid tax code"
+    );
+    assert!(failures.source().unwrap().to_string().contains("1042"));
+
+    let err = failures.into_result().unwrap_err();
+    assert_eq!(err.variant_name(), "Multi");
+    assert_eq!(err.multi().unwrap().len(), 2);
+    assert_eq!(err.kind(), PileKind::Internal);
+    assert_eq!(err.status_code(), 500);
+    assert!(!err.is_transient());
+}
+
+#[test]
+fn no_failures_is_ok() {
+    assert!(MultiPile::new().into_result().is_ok());
+}
+
+#[test]
+fn long_batches_are_counted() {
+    let failures: MultiPile = (0..8).map(|_| ErrPile::NotReady).collect();
+    assert_eq!(failures.items()[7].index, 7);
+    assert!(failures.to_string().starts_with("8 items failed; #0: "));
+    assert!(failures.to_string().ends_with("; and 3 more"));
+}
+
+#[test]
+fn shared_kinds_and_statuses() {
+    let failures: MultiPile = [
+        ErrPile::not_found("room", 101),
+        ErrPile::not_found("room", 102),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(failures.shared_kind(), Some(PileKind::NotFound));
+
+    let err = ErrPile::from(failures);
+    assert!(err.is_not_found());
+    assert_eq!(err.status_code(), 404);
+
+    let mut failures = MultiPile::new();
+    failures.push(0, ErrPile::not_found("room", 101));
+    failures.push(1, ErrPile::Permission);
+    assert_eq!(failures.shared_kind(), None);
+    assert_eq!(failures.status_code(), 400);
+}
+
+#[test]
+fn transient_when_every_failure_is() {
+    let mut failures = MultiPile::new();
+    failures.push(0, ErrPile::NotReady);
+    failures.push(
+        1,
+        ErrPile::rate_limited(Some(Duration::from_secs(30)), None),
+    );
+
+    let err = ErrPile::from(failures);
+    assert!(err.is_transient());
+    assert_eq!(err.retry_after(), Some(Duration::from_secs(30)));
+    assert_eq!(err.status_code(), 500);
+
+    let mut failures = MultiPile::new();
+    failures.push(0, ErrPile::NotReady);
+    failures.push(1, ErrPile::Auth);
+    assert!(!failures.is_transient());
+}
+
+#[test]
+fn results_are_partitioned() {
+    let rows = ["12.50", "n/a", "7", "", "3.20"];
+    let parse = |row: &str| {
+        row.parse::<f64>()
+            .map_err(|_| ErrPile::custom(format!("Invalid amount `{row}`")))
+    };
+
+    let (amounts, failures) = rows.iter().map(|row| parse(row)).collect_piles();
+    assert_eq!(amounts, [12.5, 7.0, 3.2]);
+    let indices: Vec<_> = failures.iter().map(|item| item.index).collect();
+    assert_eq!(indices, [1, 3]);
+
+    let err = rows
+        .iter()
+        .map(|row| parse(row))
+        .collect_piles_strict()
+        .unwrap_err();
+    assert_eq!(err.multi().unwrap().len(), 2);
+
+    let amounts = ["1", "2"]
+        .iter()
+        .map(|row| parse(row))
+        .collect_piles_strict();
+    assert_eq!(amounts.unwrap(), [1.0, 2.0]);
+}
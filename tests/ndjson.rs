@@ -0,0 +1,73 @@
+#![cfg(feature = "report")]
+
+use std::{fs, path::PathBuf};
+
+use error_pile::{ErrPile, ErrorReport, ErrorSink, NdjsonSink};
+use serde_json::Value;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("error-pile-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[tokio::test]
+async fn reports_are_appended_as_json_lines() {
+    let dir = temp_dir("ndjson-lines");
+    let sink = NdjsonSink::new(dir.join("errors.ndjson"));
+
+    let page = ErrPile::Page {
+        index: 2,
+        source: Box::new(ErrPile::NotReady),
+    };
+    sink.send(&ErrorReport::new(&page)).await.unwrap();
+    sink.send(&ErrorReport::new(&ErrPile::InUse)).await.unwrap();
+
+    let text = fs::read_to_string(sink.path()).unwrap();
+    let lines: Vec<Value> = text
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["kind"], "not_ready");
+    assert_eq!(lines[0]["severity"], "warning");
+    assert_eq!(lines[0]["chain"][0], ErrPile::NotReady.to_string());
+    assert!(lines[0]["correlation_ids"].is_object());
+    assert_eq!(lines[1]["kind"], "in_use");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn files_are_rotated_by_size_and_pruned() {
+    let dir = temp_dir("ndjson-rotate");
+    let report = ErrorReport::new(&ErrPile::NotReady);
+    let line = serde_json::to_vec(&report).unwrap().len() as u64 + 1;
+    // two lines per file
+    let sink = NdjsonSink::new(dir.join("errors.ndjson"))
+        .max_bytes(line * 2)
+        .keep(2);
+
+    for _ in 0..9 {
+        sink.write(&report).unwrap();
+        // rotated names have millisecond timestamps
+        std::thread::sleep(std::time::Duration::from_millis(2));
+    }
+
+    let mut names: Vec<String> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    assert_eq!(names.len(), 3, "{names:?}");
+    assert_eq!(names[0], "errors.ndjson");
+    assert!(names[1].starts_with("errors.ndjson.2"));
+
+    // the current file holds the last line, the rotated ones two each
+    for name in &names {
+        let lines = fs::read_to_string(dir.join(name)).unwrap().lines().count();
+        assert_eq!(lines, if name == "errors.ndjson" { 1 } else { 2 });
+    }
+
+    fs::remove_dir_all(dir).unwrap();
+}
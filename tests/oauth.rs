@@ -0,0 +1,70 @@
+use error_pile::{ErrPile, ReqwestPileResExt, TokenErrorKind};
+
+mod common;
+
+use common::response;
+
+#[tokio::test]
+async fn token_endpoint_errors_are_typed() {
+    let body = r#"{"error":"invalid_client","error_description":"AADSTS7000215: Invalid client secret provided.\r\nTrace ID: 1","error_codes":[7000215],"trace_id":"t","correlation_id":"c"}"#;
+    let err = response(401, body).to_pile_empty().await.unwrap_err();
+
+    assert_eq!(err.token_error_kind(), Some(TokenErrorKind::InvalidClient));
+    assert_eq!(
+        err.to_string(),
+        "Failed to acquire a token: invalid_client - AADSTS7000215: Invalid client secret provided."
+    );
+    let ErrPile::TokenAcquisition(token) = &err else {
+        panic!("expected TokenAcquisition variant");
+    };
+    assert_eq!(token.error_codes, [7000215]);
+    // the guest doesn't read about our client secret
+    let shown = err.user_message();
+    assert!(!shown.contains("AADSTS"), "{shown}");
+    assert!(!shown.contains("invalid_client"), "{shown}");
+
+    let err = response(
+        400,
+        r#"{"error":"consent_required","error_description":"AADSTS65001"}"#,
+    )
+    .to_pile_empty()
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err.token_error_kind().map(|k| k.remedy()),
+        Some("grant admin consent for the app registration")
+    );
+
+    // not every `error` string is an OAuth error
+    let err = response(404, r#"{"error":"Not Found"}"#)
+        .to_pile_empty()
+        .await
+        .unwrap_err();
+    assert_eq!(err.token_error_kind(), None);
+}
+
+#[tokio::test]
+async fn aadsts_codes_are_parsed() {
+    use error_pile::{AadstsCode, AadstsKind};
+
+    let body = r#"{"error":"invalid_client","error_description":"AADSTS7000222: The provided client secret keys for app '1' are expired."}"#;
+    let err = response(401, body).to_pile_empty().await.unwrap_err();
+    let code = err.aadsts_code().unwrap();
+    assert_eq!(code, AadstsCode(7000222));
+    assert_eq!(code.kind(), AadstsKind::ExpiredClientSecret);
+    assert_eq!(code.to_string(), "AADSTS7000222");
+
+    let err = response(
+        400,
+        "AADSTS53003: Access has been blocked by Conditional Access policies.",
+    )
+    .to_pile_empty()
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err.aadsts_code().map(|c| c.kind()),
+        Some(AadstsKind::ConditionalAccessBlocked)
+    );
+
+    assert_eq!(ErrPile::Auth.aadsts_code(), None);
+}
@@ -0,0 +1,156 @@
+#![cfg(feature = "ohip")]
+
+use std::time::Duration;
+
+use error_pile::{
+    ErrPile, OhipError, OhipFailure, OhipRetry, PileKind, RESORT_NOT_AVAILABLE_DELAY,
+    ReqwestPileResExt,
+};
+use serde_json::json;
+
+fn ohip_response(status: u16, body: serde_json::Value) -> reqwest::Response {
+    http::Response::builder()
+        .status(status)
+        .header("content-type", "application/problem+json")
+        .body(body.to_string())
+        .unwrap()
+        .into()
+}
+
+#[test]
+fn business_rule_violation() {
+    let err = OhipError::from_body(
+        Some(400),
+        &json!({
+            "title": "Bad Request",
+            "status": 400,
+            "o:errorDetails": [{
+                "title": "Reservation is already checked in",
+                "o:errorCode": "FOF00112",
+                "o:errorPath": "reservationId"
+            }]
+        }),
+    )
+    .unwrap();
+
+    assert_eq!(err.failure, OhipFailure::BusinessRule);
+    assert_eq!(err.code.as_deref(), Some("FOF00112"));
+    assert_eq!(err.errors[0].path.as_deref(), Some("reservationId"));
+    assert_eq!(err.retry_guidance(), OhipRetry::DoNotRetry);
+    assert_eq!(
+        err.to_string(),
+        "OPERA Cloud refused the request (FOF00112): Bad Request"
+    );
+    assert_eq!(ErrPile::from(err).kind(), PileKind::Conflict);
+}
+
+#[test]
+fn o_errors_array() {
+    let err = OhipError::from_body(
+        Some(422),
+        &json!({"o-errors": [{"o:errorCode": "RATE_CLOSED", "detail": "Rate BAR is closed"}]}),
+    )
+    .unwrap();
+
+    assert_eq!(err.failure, OhipFailure::BusinessRule);
+    assert_eq!(
+        err.to_string(),
+        "OPERA Cloud refused the request (RATE_CLOSED): Rate BAR is closed"
+    );
+}
+
+#[test]
+fn plain_problems_are_not_ohip() {
+    assert!(
+        OhipError::from_body(Some(400), &json!({"title": "Bad Request", "status": 400})).is_none()
+    );
+}
+
+#[test]
+fn status_classification() {
+    let failure = |status| {
+        OhipError::from_body(Some(status), &json!({"o:errorDetails": []}))
+            .unwrap()
+            .failure
+    };
+
+    assert_eq!(failure(400), OhipFailure::Validation);
+    assert_eq!(failure(401), OhipFailure::Auth);
+    assert_eq!(failure(403), OhipFailure::Forbidden);
+    assert_eq!(failure(404), OhipFailure::NotFound);
+    assert_eq!(failure(429), OhipFailure::RateLimited);
+    assert_eq!(failure(502), OhipFailure::Unavailable);
+}
+
+#[tokio::test]
+async fn resort_not_available_is_retried_later() {
+    let res = ohip_response(
+        400,
+        json!({
+            "title": "Resort RHM01 is not available",
+            "status": 400,
+            "o:errorCode": "FOF00086"
+        }),
+    );
+
+    let err = res.error_for_pile().await.unwrap_err();
+    assert_eq!(
+        err.ohip_retry(),
+        Some(OhipRetry::Retry(Some(RESORT_NOT_AVAILABLE_DELAY)))
+    );
+    assert!(err.is_transient());
+    assert!(err.is_not_ready());
+    assert_eq!(err.retry_after(), Some(RESORT_NOT_AVAILABLE_DELAY));
+    assert_eq!(err.status_code(), 503);
+}
+
+#[test]
+fn business_rules_mentioning_the_hotel_stay_rules() {
+    let err = OhipError::from_body(
+        Some(409),
+        &json!({
+            "title": "Rate code BAR is not active for hotel RHM01",
+            "o:errorCode": "FOF00412"
+        }),
+    )
+    .unwrap();
+    assert_eq!(err.failure, OhipFailure::BusinessRule);
+    assert_eq!(err.retry_guidance(), OhipRetry::DoNotRetry);
+
+    let err = OhipError::from_body(
+        Some(503),
+        &json!({"title": "Hotel RHM01 is not available", "o:errorCode": "GW-503"}),
+    )
+    .unwrap();
+    assert_eq!(err.failure, OhipFailure::ResortNotAvailable);
+}
+
+#[tokio::test]
+async fn expired_token_is_refreshed() {
+    let res = ohip_response(
+        401,
+        json!({"title": "Unauthorized", "status": 401, "o:errorCode": "OAUTH-401"}),
+    );
+
+    let err = res.error_for_pile().await.unwrap_err();
+    assert_eq!(err.ohip_retry(), Some(OhipRetry::RefreshToken));
+    assert!(!err.is_transient());
+    assert_eq!(err.variant_name(), "Ohip");
+}
+
+#[tokio::test]
+async fn throttling_follows_retry_after() {
+    let res: reqwest::Response = http::Response::builder()
+        .status(503)
+        .header("retry-after", "20")
+        .body(json!({"title": "Service Unavailable", "o:errorCode": "GW-503"}).to_string())
+        .unwrap()
+        .into();
+
+    let err = res.error_for_pile().await.unwrap_err();
+    assert_eq!(
+        err.ohip_retry(),
+        Some(OhipRetry::Retry(Some(Duration::from_secs(20))))
+    );
+    assert_eq!(err.code().as_deref(), Some("GW-503"));
+}
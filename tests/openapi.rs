@@ -0,0 +1,47 @@
+#![cfg(feature = "utoipa")]
+
+use error_pile::{ErrPile, RemoteError};
+use utoipa::{IntoResponses, OpenApi, openapi::RefOr};
+
+#[utoipa::path(get, path = "/rooms/{id}", responses((status = 200, body = String), ErrPile))]
+#[allow(dead_code)]
+async fn room() {}
+
+#[derive(OpenApi)]
+#[openapi(paths(room), components(schemas(RemoteError)))]
+struct Docs;
+
+#[test]
+fn error_responses_have_examples() {
+    let responses = ErrPile::responses();
+    assert_eq!(
+        responses.keys().collect::<Vec<_>>(),
+        ["401", "403", "409", "500", "503"]
+    );
+
+    let RefOr::T(not_ready) = &responses["503"] else {
+        panic!("inline response expected");
+    };
+    let RefOr::T(content) = &not_ready.content["application/json"] else {
+        panic!("inline content expected");
+    };
+    let example = content.example.as_ref().unwrap();
+    assert_eq!(example["kind"], "not_ready");
+    assert_eq!(example["transient"], true);
+}
+
+#[test]
+fn handlers_document_the_error_envelope() {
+    let doc = serde_json::to_value(Docs::openapi()).unwrap();
+
+    let responses = &doc["paths"]["/rooms/{id}"]["get"]["responses"];
+    assert!(responses.get("200").is_some());
+    assert_eq!(
+        responses["409"]["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/RemoteError"
+    );
+
+    let envelope = &doc["components"]["schemas"]["RemoteError"];
+    assert!(envelope["properties"].get("kind").is_some());
+    assert!(envelope["properties"].get("metadata").is_some());
+}
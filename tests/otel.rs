@@ -0,0 +1,75 @@
+#![cfg(feature = "otel")]
+
+use std::{borrow::Cow, time::SystemTime};
+
+use error_pile::ErrPile;
+use opentelemetry::{
+    KeyValue, Value,
+    trace::{Span, SpanContext, Status},
+};
+
+/// keeps what was recorded on it
+#[derive(Default)]
+struct TestSpan {
+    attributes: Vec<KeyValue>,
+    events: Vec<String>,
+    status: Status,
+}
+
+impl Span for TestSpan {
+    fn add_event_with_timestamp<T>(&mut self, name: T, _: SystemTime, _: Vec<KeyValue>)
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.events.push(name.into().into_owned());
+    }
+
+    fn span_context(&self) -> &SpanContext {
+        &SpanContext::NONE
+    }
+
+    fn is_recording(&self) -> bool {
+        true
+    }
+
+    fn set_attribute(&mut self, attribute: KeyValue) {
+        self.attributes.push(attribute);
+    }
+
+    fn set_status(&mut self, status: Status) {
+        self.status = status;
+    }
+
+    fn update_name<T>(&mut self, _: T)
+    where
+        T: Into<Cow<'static, str>>,
+    {
+    }
+
+    fn add_link(&mut self, _: SpanContext, _: Vec<KeyValue>) {}
+
+    fn end_with_timestamp(&mut self, _: SystemTime) {}
+}
+
+#[test]
+fn errors_mark_the_span_failed() {
+    let err = ErrPile::rate_limited(None, Some("graph".into()));
+    let mut span = TestSpan::default();
+    err.record_on_span(&mut span);
+
+    assert_eq!(span.events, ["exception"]);
+    assert_eq!(
+        span.status,
+        Status::error("Too many requests to graph, please try again later")
+    );
+
+    let attribute = |key: &str| {
+        span.attributes
+            .iter()
+            .find(|kv| kv.key.as_str() == key)
+            .map(|kv| kv.value.clone())
+    };
+    assert_eq!(attribute("error.type"), Some(Value::from("rate_limited")));
+    assert_eq!(attribute("http.response.status_code"), None);
+    assert_eq!(attribute("db.system"), None);
+}
@@ -0,0 +1,43 @@
+use std::error::Error;
+
+use error_pile::{ErrPile, IntoPileExt, PileKind};
+
+#[derive(Debug, thiserror::Error)]
+#[error("row {row} has no rate")]
+struct RateSheetError {
+    row: usize,
+    #[source]
+    source: std::num::ParseFloatError,
+}
+
+fn rate_sheet_error() -> RateSheetError {
+    RateSheetError {
+        row: 12,
+        source: "12,5".parse::<f64>().unwrap_err(),
+    }
+}
+
+#[test]
+fn errors_are_kept_with_their_type() {
+    let err = rate_sheet_error().pile();
+    let ErrPile::Other(other) = &err else {
+        panic!("expected an other error, got {err:?}");
+    };
+    assert_eq!(other.type_name, "other::RateSheetError");
+    assert_eq!(other.downcast_ref::<RateSheetError>().unwrap().row, 12);
+
+    assert_eq!(err.kind(), PileKind::Internal);
+    assert_eq!(err.to_string(), "row 12 has no rate");
+    assert_eq!(err.source().unwrap().to_string(), "invalid float literal");
+    assert!(
+        err.pretty()
+            .to_string()
+            .contains("type: other::RateSheetError")
+    );
+}
+
+#[test]
+fn piles_are_handed_back() {
+    let err = ErrPile::not_found("rate plan", "BAR").pile();
+    assert!(err.is_not_found());
+}
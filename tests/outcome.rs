@@ -0,0 +1,72 @@
+use error_pile::{ErrPile, OutcomeSummary, OutcomeThreshold, PileOutcome};
+
+fn sync_rooms() -> PileOutcome<u32> {
+    let mut outcome = PileOutcome::new();
+    for room in 101..=110 {
+        let result = match room {
+            104 => Err(ErrPile::NotReady),
+            _ => Ok(room),
+        };
+        outcome.push_labeled(format!("room {room}"), result);
+    }
+    outcome
+}
+
+#[test]
+fn summary_counts_the_items() {
+    let outcome = sync_rooms();
+    assert!(!outcome.is_complete());
+    assert_eq!(
+        outcome.summary(),
+        OutcomeSummary {
+            total: 10,
+            succeeded: 9,
+            failed: 1,
+        }
+    );
+    assert_eq!(outcome.failed[0].index, 3);
+
+    let outcome: PileOutcome<u32> = [Ok(1), Err(ErrPile::Auth), Ok(3)].into_iter().collect();
+    assert_eq!(outcome.succeeded, [1, 3]);
+    assert_eq!(outcome.failed[0].index, 1);
+}
+
+#[test]
+fn thresholds_decide_the_result() {
+    assert!(sync_rooms().into_result(OutcomeThreshold::Strict).is_err());
+    let outcome = sync_rooms()
+        .into_result(OutcomeThreshold::MaxFailures(1))
+        .unwrap();
+    assert_eq!(outcome.failed.len(), 1, "the failures are kept");
+    assert!(
+        sync_rooms()
+            .into_result(OutcomeThreshold::MaxFailureRatio(0.1))
+            .is_ok()
+    );
+
+    let err = sync_rooms()
+        .into_result(OutcomeThreshold::MaxFailureRatio(0.05))
+        .unwrap_err();
+    let multi = err.multi().unwrap();
+    assert_eq!(multi.items()[0].label.as_deref(), Some("room 104"));
+    assert!(err.is_transient());
+
+    assert!(
+        PileOutcome::<u32>::new()
+            .into_result(OutcomeThreshold::MaxFailureRatio(0.0))
+            .is_ok()
+    );
+}
+
+#[test]
+fn outcomes_round_trip_through_json() {
+    let json = serde_json::to_value(sync_rooms()).unwrap();
+    assert_eq!(json["summary"]["failed"], 1);
+    assert_eq!(json["failed"][0]["label"], "room 104");
+    assert_eq!(json["failed"][0]["error"]["kind"], "not_ready");
+
+    let outcome: PileOutcome<u32> = serde_json::from_value(json).unwrap();
+    assert_eq!(outcome.succeeded.len(), 9);
+    assert!(outcome.failed[0].error.is_not_ready());
+    assert!(outcome.failed[0].error.is_transient());
+}
@@ -0,0 +1,82 @@
+use error_pile::{ErrPile, PileKind, pile_catch, pile_catch_async};
+
+#[test]
+fn panics_become_errors_with_their_location() {
+    let err =
+        pile_catch(|| -> error_pile::PileResult<u32> { panic!("hook exploded") }).unwrap_err();
+    let ErrPile::Panic(panic) = &err else {
+        panic!("{err:?}");
+    };
+    assert_eq!(panic.message, "hook exploded");
+    assert!(
+        panic
+            .location
+            .as_deref()
+            .unwrap()
+            .starts_with("tests/panic.rs:")
+    );
+    assert_eq!(err.kind(), PileKind::Internal);
+    assert!(!err.user_message().contains("hook exploded"));
+
+    let room = 12;
+    let err =
+        pile_catch(|| -> error_pile::PileResult<()> { panic!("room {room} missing") }).unwrap_err();
+    assert!(err.to_string().ends_with(": room 12 missing"), "{err}");
+
+    assert_eq!(pile_catch(|| Ok(3)).unwrap(), 3);
+    assert!(
+        pile_catch(|| Err::<(), _>(ErrPile::Auth))
+            .unwrap_err()
+            .is_encrypted()
+    );
+}
+
+#[test]
+fn panics_caught_elsewhere_leave_no_location() {
+    let _ = pile_catch(|| Ok(()));
+    let _ = std::panic::catch_unwind(|| panic!("caught by someone else"));
+
+    let payload: Box<dyn std::any::Any + Send> = Box::new("payload of a joined thread");
+    let ErrPile::Panic(panic) = ErrPile::from_panic(&*payload) else {
+        unreachable!()
+    };
+    assert_eq!(panic.location, None);
+
+    // a panic caught inside the closure isn't the one reported
+    let err = pile_catch(|| -> error_pile::PileResult<()> {
+        let _ = std::panic::catch_unwind(|| panic!("inner"));
+        Ok(())
+    });
+    assert!(err.is_ok());
+    let payload: Box<dyn std::any::Any + Send> = Box::new("later");
+    assert!(matches!(
+        ErrPile::from_panic(&*payload),
+        ErrPile::Panic(ref panic) if panic.location.is_none()
+    ));
+}
+
+#[tokio::test]
+async fn panicking_futures_become_errors() {
+    let err = pile_catch_async(async {
+        tokio::task::yield_now().await;
+        if true {
+            panic!("report generator failed");
+        }
+        Ok(())
+    })
+    .await
+    .unwrap_err();
+    assert!(matches!(err, ErrPile::Panic(ref panic) if panic.message == "report generator failed"));
+    let ErrPile::Panic(panic) = &err else {
+        unreachable!()
+    };
+    assert!(
+        panic
+            .location
+            .as_deref()
+            .unwrap()
+            .starts_with("tests/panic.rs:")
+    );
+
+    assert_eq!(pile_catch_async(async { Ok(7) }).await.unwrap(), 7);
+}
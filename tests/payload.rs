@@ -0,0 +1,77 @@
+use std::{collections::BTreeSet, fs, path::PathBuf};
+
+use error_pile::{PileKind, parse_known_error_payload};
+use serde::Deserialize;
+
+/// an entry of `tests/fixtures/payloads/corpus.json`, the bodies are
+/// synthesized from the documentation of each service
+#[derive(Debug, Deserialize)]
+struct Case {
+    file: String,
+    /// `None` for the bodies that stay plain HTTP errors
+    kind: Option<PileKind>,
+    #[serde(default)]
+    code: Option<String>,
+    #[serde(default)]
+    transient: bool,
+    /// the feature the parser of the payload is behind
+    #[serde(default)]
+    feature: Option<String>,
+}
+
+fn corpus_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/payloads")
+}
+
+fn corpus() -> Vec<Case> {
+    let manifest = fs::read(corpus_dir().join("corpus.json")).unwrap();
+    serde_json::from_slice(&manifest).unwrap()
+}
+
+fn enabled(feature: Option<&str>) -> bool {
+    match feature {
+        None => true,
+        Some("xml") => cfg!(feature = "xml"),
+        Some("channel") => cfg!(feature = "channel"),
+        Some("ohip") => cfg!(feature = "ohip"),
+        Some("sms") => cfg!(feature = "sms"),
+        Some(other) => panic!("unknown feature {other} in corpus.json"),
+    }
+}
+
+#[test]
+fn documented_payloads_are_recognized() {
+    for case in corpus() {
+        if !enabled(case.feature.as_deref()) {
+            continue;
+        }
+
+        let body = fs::read(corpus_dir().join(&case.file)).unwrap();
+        let err = parse_known_error_payload(&body);
+
+        assert_eq!(err.as_ref().map(|e| e.kind()), case.kind, "{}", case.file);
+        let Some(err) = err else {
+            continue;
+        };
+        assert_eq!(err.code(), case.code, "{}", case.file);
+        assert_eq!(err.is_transient(), case.transient, "{}", case.file);
+    }
+}
+
+#[test]
+fn every_fixture_is_in_the_corpus() {
+    let listed: BTreeSet<_> = corpus().into_iter().map(|case| case.file).collect();
+    let on_disk: BTreeSet<_> = fs::read_dir(corpus_dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name != "corpus.json")
+        .collect();
+
+    assert_eq!(listed, on_disk);
+}
+
+#[test]
+fn empty_bodies_are_unknown() {
+    assert!(parse_known_error_payload(b"").is_none());
+    assert!(parse_known_error_payload(b"{}").is_none());
+}
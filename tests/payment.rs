@@ -0,0 +1,56 @@
+use error_pile::{DeclineCategory, ErrPile, PileKind};
+
+#[test]
+fn processor_codes_are_categorized() {
+    for (code, category) in [
+        ("51", DeclineCategory::InsufficientFunds),
+        ("05", DeclineCategory::DoNotHonor),
+        ("54", DeclineCategory::ExpiredCard),
+        ("59", DeclineCategory::FraudSuspected),
+        ("1A", DeclineCategory::ThreeDsRequired),
+        ("65", DeclineCategory::ThreeDsRequired),
+        ("N7", DeclineCategory::Other),
+    ] {
+        assert_eq!(
+            DeclineCategory::from_processor_code(code),
+            category,
+            "{code}"
+        );
+    }
+}
+
+#[test]
+fn declines_are_never_retried() {
+    let err = ErrPile::payment_declined("51");
+    assert!(!err.is_transient());
+    assert!(DeclineCategory::InsufficientFunds.is_soft());
+    assert!(!ErrPile::payment_declined("05").is_transient());
+    assert_eq!(err.kind(), PileKind::PaymentDeclined);
+    assert_eq!(err.status_code(), 402);
+    assert_eq!(err.code().as_deref(), Some("51"));
+    assert_eq!(
+        err.to_string(),
+        "The payment was declined: insufficient_funds (code 51)"
+    );
+}
+
+#[test]
+fn three_ds_needs_the_guest() {
+    let err = ErrPile::payment_declined("1A");
+    assert!(!err.is_transient());
+    assert_eq!(
+        err.decline_category(),
+        Some(DeclineCategory::ThreeDsRequired)
+    );
+    assert_eq!(
+        err.user_message(),
+        "The bank requires the cardholder to confirm the payment (3-D Secure)"
+    );
+
+    // what the front desk sees survives the wire
+    let received: ErrPile = serde_json::from_value(serde_json::to_value(&err).unwrap()).unwrap();
+    assert_eq!(received.user_message(), err.user_message());
+    assert!(!received.is_transient());
+    assert_eq!(received.kind(), PileKind::PaymentDeclined);
+    assert_eq!(received.status_code(), 402);
+}
@@ -0,0 +1,89 @@
+#![cfg(feature = "derive")]
+
+use error_pile::{ErrPile, PileKind, PileResult, pile_context};
+
+#[pile_context(room)]
+async fn load_room(room: &str, password: String) -> PileResult<u32> {
+    let _ = password;
+    if room == "214" {
+        return Ok(2);
+    }
+    Err(ErrPile::not_found("room", room))
+}
+
+#[pile_context(room, nights)]
+async fn reserve(room: &str, nights: u32) -> PileResult<u32> {
+    let beds = load_room(room, "hunter2".into()).await?;
+    Ok(beds * nights)
+}
+
+#[pile_context(database)]
+async fn connect(database: &str) -> PileResult {
+    let _ = database;
+    Err(ErrPile::custom("the database is unreachable"))
+}
+
+struct FrontDesk {
+    hotel: &'static str,
+}
+
+impl FrontDesk {
+    #[pile_context]
+    async fn check_in(&self) -> PileResult {
+        Err(ErrPile::custom(format!("{} is closed", self.hotel)))
+    }
+}
+
+#[tokio::test]
+async fn frames_are_recorded_innermost_first() {
+    assert_eq!(reserve("214", 3).await.unwrap(), 6);
+
+    let line = line!() + 1;
+    let err = reserve("101", 3).await.unwrap_err();
+    assert_eq!(err.kind(), PileKind::NotFound);
+    assert!(err.is_not_found());
+    assert_eq!(err.to_string(), "The room `101` could not be found");
+
+    let frames = err.context_frames();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].function, "pile_context::load_room");
+    assert_eq!(frames[0].args, [("room", "\"101\"".to_string())]);
+    assert_eq!(frames[1].function, "pile_context::reserve");
+    assert_eq!(
+        frames[1].args,
+        [("room", "\"101\"".to_string()), ("nights", "3".to_string())]
+    );
+    assert_eq!(frames[1].location.file(), file!());
+    assert_eq!(frames[1].location.line(), line);
+    assert!(
+        frames[0]
+            .to_string()
+            .starts_with("pile_context::load_room(room: \"101\") at ")
+    );
+
+    let pretty = err.pretty().to_string();
+    assert!(pretty.contains("\n\nContext:\n    pile_context::load_room(room: \"101\") at "));
+}
+
+#[tokio::test]
+async fn methods_keep_their_receiver() {
+    let desk = FrontDesk { hotel: "Ram Inn" };
+    let err = desk.check_in().await.unwrap_err();
+    assert_eq!(err.to_string(), "Ram Inn is closed");
+    assert_eq!(err.context_frames()[0].function, "pile_context::check_in");
+    assert!(err.context_frames()[0].args.is_empty());
+}
+
+#[tokio::test]
+async fn failed_calls_redact_their_arguments() {
+    let err = connect("postgres://pms:s3cret@db:5432/pms")
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.context_frames()[0].args,
+        [(
+            "database",
+            "\"postgres://pms:<redacted>@db:5432/pms\"".to_string()
+        )]
+    );
+}
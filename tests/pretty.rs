@@ -0,0 +1,83 @@
+use error_pile::{ErrPile, HttpError};
+use reqwest::{StatusCode, header::HeaderMap};
+
+#[test]
+fn chain_location_and_metadata_are_listed() {
+    let mut http = HttpError::new(
+        StatusCode::NOT_FOUND,
+        HeaderMap::new(),
+        br#"{"error": {"code": "itemNotFound", "message": "gone"}}"#,
+    );
+    http.method = Some(reqwest::Method::GET);
+    http.url = Some(
+        "https://graph.microsoft.com/v1.0/me?access_token=abc"
+            .parse()
+            .unwrap(),
+    );
+    let err = ErrPile::Page {
+        index: 2,
+        source: Box::new(ErrPile::Http(Box::new(http))),
+    };
+
+    assert_eq!(
+        err.pretty().to_string(),
+        "Error: Failed to fetch page 2 of the listing
+
+Caused by:
+    0: Request responded with an error (404 Not Found)
+    1: Not Found (404) GET https://graph.microsoft.com/v1.0/me?access_token=<redacted> itemNotFound: gone
+
+Location: GET https://graph.microsoft.com/v1.0/me?access_token=<redacted> (page 2)
+
+Metadata:
+    kind: upstream
+    severity: error
+    code: itemNotFound
+    http_status: 404"
+    );
+}
+
+#[test]
+fn single_cause_is_not_numbered() {
+    let err = ErrPile::Page {
+        index: 0,
+        source: Box::new(ErrPile::NotReady),
+    };
+    assert_eq!(
+        format!("{:?}", err.pretty()),
+        "Error: Failed to fetch page 0 of the listing
+
+Caused by:
+    The resource is not ready yet, please try again later
+
+Location: page 0
+
+Hint: this is usually temporary, retrying should help
+
+Metadata:
+    kind: not_ready
+    severity: warning
+    transient: true"
+    );
+}
+
+#[test]
+fn colors_are_opt_in() {
+    let err = ErrPile::Page {
+        index: 0,
+        source: Box::new(ErrPile::NotReady),
+    };
+    assert!(!err.pretty().to_string().contains('\x1b'));
+    // the tests don't run on a terminal
+    assert!(!err.pretty().auto_color().to_string().contains('\x1b'));
+
+    let colored = err.pretty().color(true).to_string();
+    assert!(colored.starts_with("\x1b[1;31mError:\x1b[0m \x1b[1;31mFailed to fetch page 0"));
+    assert!(
+        colored
+            .contains("\n    \x1b[2mThe resource is not ready yet, please try again later\x1b[0m")
+    );
+    assert!(
+        colored.contains("\x1b[33mHint: this is usually temporary, retrying should help\x1b[0m")
+    );
+}
@@ -0,0 +1,47 @@
+//! the problem settings are global, so they get their own binary
+use std::time::Duration;
+
+use error_pile::{ErrPile, set_problem_type_base};
+
+#[test]
+fn problem_details_describe_the_error_for_the_client() {
+    set_problem_type_base("https://errors.hotel.example/");
+
+    let problem = ErrPile::rate_limited(Some(Duration::from_secs(30)), None).to_problem_details();
+    assert_eq!(
+        problem.type_uri.as_deref(),
+        Some("https://errors.hotel.example/rate_limited")
+    );
+    assert_eq!(problem.title.as_deref(), Some("Too Many Requests"));
+    assert_eq!(problem.status, Some(429));
+    assert_eq!(problem.extension("code").unwrap(), "rate_limited");
+    assert_eq!(problem.extension("retry_after").unwrap(), 30);
+
+    let problem = ErrPile::custom("password=hunter2").to_problem_details();
+    assert_eq!(problem.status, Some(500));
+    assert!(!serde_json::to_string(&problem).unwrap().contains("hunter2"));
+    assert!(problem.extension("retry_after").is_none());
+}
+
+#[cfg(feature = "axum")]
+#[tokio::test]
+async fn axum_answers_with_problem_documents_when_enabled() {
+    use axum_core::response::IntoResponse;
+    use error_pile::{ProblemDetails, set_problem_responses};
+    use http_body_util::BodyExt;
+
+    set_problem_responses(true);
+    let err = ErrPile::not_found("reservation", 12);
+    let res = err.into_response();
+    assert_eq!(res.status(), 404);
+    assert_eq!(res.headers()["content-type"], ProblemDetails::CONTENT_TYPE);
+
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    let problem: ProblemDetails = serde_json::from_slice(&body).unwrap();
+    assert_eq!(problem.status, Some(404));
+    assert_eq!(
+        problem.detail.as_deref(),
+        Some("The reservation `12` could not be found")
+    );
+    assert_eq!(problem.extension("code").unwrap(), "not_found");
+}
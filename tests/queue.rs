@@ -0,0 +1,81 @@
+#![cfg(feature = "report")]
+
+// the installed queue is global, so this lives in its own test binary
+
+use std::sync::{Arc, Mutex};
+
+use error_pile::{ErrPile, ErrorQueue, ErrorReport, ErrorSink, Overflow, PileResult};
+
+#[derive(Clone, Default)]
+struct Collect(Arc<Mutex<Vec<String>>>);
+
+#[async_trait::async_trait]
+impl ErrorSink for Collect {
+    async fn send(&self, report: &ErrorReport) -> PileResult {
+        self.0.lock().unwrap().push(report.message.clone());
+        Ok(())
+    }
+}
+
+struct Broken;
+
+#[async_trait::async_trait]
+impl ErrorSink for Broken {
+    async fn send(&self, _: &ErrorReport) -> PileResult {
+        Err(ErrPile::NotReady)
+    }
+}
+
+fn room(n: u32) -> ErrPile {
+    ErrPile::custom(format!("room {n} failed"))
+}
+
+#[tokio::test]
+async fn reports_reach_every_sink() {
+    assert!(!room(0).report());
+
+    let first = Collect::default();
+    let second = Collect::default();
+    let queue = ErrorQueue::new(16)
+        .sink(Broken)
+        .sink(first.clone())
+        .sink(second.clone())
+        .install();
+
+    assert!(room(1).report());
+    assert!(room(2).report());
+    queue.shutdown().await;
+
+    assert_eq!(*first.0.lock().unwrap(), ["room 1 failed", "room 2 failed"]);
+    assert_eq!(
+        *second.0.lock().unwrap(),
+        ["room 1 failed", "room 2 failed"]
+    );
+
+    // shut down queues are uninstalled
+    assert!(!room(3).report());
+    assert_eq!(queue.dropped(), 0);
+}
+
+#[tokio::test]
+async fn overflow_policy_decides_what_is_dropped() {
+    for (overflow, kept) in [
+        (Overflow::DropNewest, ["room 1 failed", "room 2 failed"]),
+        (Overflow::DropOldest, ["room 3 failed", "room 4 failed"]),
+    ] {
+        let sink = Collect::default();
+        let queue = ErrorQueue::new(2)
+            .overflow(overflow)
+            .sink(sink.clone())
+            .start();
+
+        // the worker only runs once the test yields
+        for n in 1..=4 {
+            queue.push(ErrorReport::new(&room(n)));
+        }
+        queue.shutdown().await;
+
+        assert_eq!(*sink.0.lock().unwrap(), kept);
+        assert_eq!(queue.dropped(), 2);
+    }
+}
@@ -0,0 +1,22 @@
+// the pii patterns are global, so this lives in its own test binary
+
+use error_pile::{pii_patterns, redact, set_pii_patterns};
+use regex::Regex;
+
+#[test]
+fn configured_pii_patterns_are_masked() {
+    assert_eq!(redact("guest jane@example.com"), "guest jane@example.com");
+
+    set_pii_patterns(vec![
+        Regex::new(r"[\w.+-]+@[\w-]+\.[\w.]+").unwrap(),
+        Regex::new(r"\bP\d{7}\b").unwrap(),
+    ]);
+    assert_eq!(pii_patterns().len(), 2);
+    assert_eq!(
+        redact("guest jane@example.com, passport P1234567"),
+        "guest <redacted>, passport <redacted>"
+    );
+
+    set_pii_patterns(Vec::new());
+    assert_eq!(redact("guest jane@example.com"), "guest jane@example.com");
+}
@@ -0,0 +1,40 @@
+#![cfg(feature = "warp")]
+
+use error_pile::{ErrPile, recover_pile};
+use warp::Filter;
+
+#[tokio::test]
+async fn rejected_errors_get_the_standard_body() {
+    let route = warp::path!("reservations" / u32)
+        .and_then(|id: u32| async move {
+            Err::<String, _>(warp::Rejection::from(ErrPile::not_found("reservation", id)))
+        })
+        .recover(recover_pile);
+
+    let res = warp::test::request()
+        .path("/reservations/12")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 404);
+    assert_eq!(res.headers()["content-type"], "application/json");
+    let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+    assert_eq!(body, ErrPile::not_found("reservation", 12).response_body());
+}
+
+#[tokio::test]
+async fn unreadable_requests_are_validation_errors() {
+    let route = warp::path("folios")
+        .and(warp::header::<String>("x-property"))
+        .map(|property: String| property)
+        .recover(recover_pile);
+
+    let res = warp::test::request().path("/folios").reply(&route).await;
+    assert_eq!(res.status(), 422);
+    let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+    assert_eq!(body["code"], "validation");
+
+    // no route matched, warp answers it
+    let res = warp::test::request().path("/rooms").reply(&route).await;
+    assert_eq!(res.status(), 404);
+    assert!(res.body().is_empty());
+}
@@ -0,0 +1,106 @@
+#![cfg(feature = "report")]
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use error_pile::{
+    ErrPile, ErrorQueue, ErrorReport, ErrorReporter, ErrorSink, PileKind, PileResult,
+};
+
+#[derive(Clone, Default)]
+struct Collect(Arc<Mutex<Vec<ErrorReport>>>);
+
+#[async_trait::async_trait]
+impl ErrorSink for Collect {
+    async fn send(&self, report: &ErrorReport) -> PileResult {
+        self.0.lock().unwrap().push(report.clone());
+        Ok(())
+    }
+}
+
+fn sftp_down(attempt: u32) -> ErrPile {
+    ErrPile::custom(format!("SFTP connection to 10.0.0.{attempt} dropped"))
+}
+
+#[tokio::test]
+async fn duplicates_are_summarized() {
+    let reporter = ErrorReporter::new(Collect::default(), Duration::from_secs(60));
+
+    for attempt in 0..5 {
+        reporter.report(&sftp_down(attempt)).await.unwrap();
+    }
+    reporter.report(&ErrPile::NotReady).await.unwrap();
+
+    {
+        let sent = reporter.sink().0.lock().unwrap();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0].count, 1);
+        assert_eq!(sent[1].kind, PileKind::NotReady);
+    }
+
+    reporter.flush().await.unwrap();
+    let sent = reporter.sink().0.lock().unwrap();
+    assert_eq!(sent.len(), 3);
+    assert_eq!(sent[2].count, 4);
+    assert_eq!(sent[2].message, "SFTP connection to 10.0.0.4 dropped");
+    assert!(sent[2].to_string().contains("(seen 4 times since "));
+}
+
+#[tokio::test]
+async fn a_new_window_carries_the_suppressed_count() {
+    let reporter = ErrorReporter::new(Collect::default(), Duration::ZERO);
+
+    reporter.report(&sftp_down(1)).await.unwrap();
+    reporter.report(&sftp_down(2)).await.unwrap();
+    reporter.flush().await.unwrap();
+
+    let sent = reporter.sink().0.lock().unwrap();
+    assert_eq!(sent.len(), 2);
+    assert!(sent.iter().all(|r| r.count == 1));
+}
+
+#[tokio::test]
+async fn summaries_go_out_once_the_window_is_over() {
+    let reporter = ErrorReporter::new(Collect::default(), Duration::from_millis(50));
+
+    reporter.report(&sftp_down(1)).await.unwrap();
+    reporter.report(&sftp_down(2)).await.unwrap();
+    reporter.flush_expired().await.unwrap();
+    assert_eq!(reporter.sink().0.lock().unwrap().len(), 1);
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    reporter.flush_expired().await.unwrap();
+    let sent = reporter.sink().0.lock().unwrap();
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[1].count, 1);
+}
+
+#[tokio::test]
+async fn the_queue_flushes_the_reporters() {
+    // without a new report, the ticks send the summary
+    let sent = Collect::default();
+    let queue = ErrorQueue::new(8)
+        .sink(ErrorReporter::new(sent.clone(), Duration::from_millis(20)))
+        .flush_interval(Duration::from_millis(10))
+        .start();
+    queue.push(ErrorReport::new(&sftp_down(1)));
+    queue.push(ErrorReport::new(&sftp_down(2)));
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(sent.0.lock().unwrap().len(), 2);
+    queue.shutdown().await;
+
+    // and the shutdown sends what is left
+    let sent = Collect::default();
+    let queue = ErrorQueue::new(8)
+        .sink(ErrorReporter::new(sent.clone(), Duration::from_secs(3600)))
+        .start();
+    queue.push(ErrorReport::new(&sftp_down(1)));
+    queue.push(ErrorReport::new(&sftp_down(2)));
+    queue.push(ErrorReport::new(&sftp_down(3)));
+    queue.shutdown().await;
+    let sent = sent.0.lock().unwrap();
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[1].count, 2);
+}
@@ -0,0 +1,176 @@
+#![cfg(feature = "retry")]
+
+use std::{
+    sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
+};
+
+use error_pile::{Backoff, ErrPile, Jitter, PileKind, RetryPolicy, retry, retry_idempotent};
+
+fn fast() -> Backoff {
+    Backoff::new()
+        .base_delay(Duration::from_millis(1))
+        .max_delay(Duration::from_millis(5))
+}
+
+#[tokio::test]
+async fn transient_errors_are_retried() {
+    let calls = AtomicU32::new(0);
+    let rooms = retry(&fast(), || async {
+        match calls.fetch_add(1, Ordering::SeqCst) {
+            0 | 1 => Err(ErrPile::NotReady),
+            _ => Ok(12),
+        }
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(rooms, 12);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn permanent_errors_and_exhausted_attempts_are_returned() {
+    let calls = AtomicU32::new(0);
+    let err = retry(&fast(), || async {
+        calls.fetch_add(1, Ordering::SeqCst);
+        Err::<(), _>(ErrPile::Auth)
+    })
+    .await
+    .unwrap_err();
+    assert!(err.is_encrypted());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let calls = AtomicU32::new(0);
+    let err = retry(&fast().max_attempts(3), || async {
+        calls.fetch_add(1, Ordering::SeqCst);
+        Err::<(), _>(ErrPile::NotReady)
+    })
+    .await
+    .unwrap_err();
+    assert!(err.is_not_ready());
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn delays_grow_with_jitter_and_honor_retry_after() {
+    let backoff = Backoff::new()
+        .base_delay(Duration::from_millis(100))
+        .max_delay(Duration::from_secs(1));
+
+    for attempt in 0..6 {
+        let full = Duration::from_millis(100 * 2u64.pow(attempt)).min(Duration::from_secs(1));
+        let delay = backoff.delay(attempt, &ErrPile::NotReady);
+        assert!(delay >= full / 2 && delay <= full, "{attempt}: {delay:?}");
+    }
+
+    let throttled = ErrPile::rate_limited(Some(Duration::from_millis(700)), None);
+    assert_eq!(backoff.delay(0, &throttled), Duration::from_millis(700));
+}
+
+#[test]
+fn server_waits_replace_the_backoff_up_to_a_cap() {
+    let backoff = Backoff::new()
+        .max_delay(Duration::from_secs(1))
+        .max_retry_after(Duration::from_secs(90));
+
+    // longer than max_delay, the server knows best
+    let throttled = ErrPile::rate_limited(Some(Duration::from_secs(60)), None);
+    assert_eq!(
+        backoff.next_delay(0, Duration::ZERO, &throttled),
+        Some(Duration::from_secs(60))
+    );
+
+    // retrying before the server allows it only extends the ban
+    let banned = ErrPile::rate_limited(Some(Duration::from_secs(600)), None);
+    assert_eq!(backoff.next_delay(0, Duration::ZERO, &banned), None);
+
+    // wrappers report the wait of the error they wrap
+    let page = ErrPile::Page {
+        index: 1,
+        source: Box::new(throttled),
+    };
+    assert_eq!(page.retry_after(), Some(Duration::from_secs(60)));
+    assert_eq!(
+        backoff.next_delay(0, Duration::ZERO, &page),
+        Some(Duration::from_secs(60))
+    );
+}
+
+#[test]
+fn policy_gives_up_per_kind_attempts_and_elapsed_time() {
+    let backoff = Backoff::new()
+        .base_delay(Duration::from_millis(100))
+        .jitter(Jitter::None)
+        .retry_kind(PileKind::Conflict, true)
+        .retry_kind(PileKind::NotReady, false);
+
+    assert_eq!(backoff.next_delay(0, Duration::ZERO, &ErrPile::Auth), None);
+    assert_eq!(
+        backoff.next_delay(0, Duration::ZERO, &ErrPile::NotReady),
+        None
+    );
+    let conflict = ErrPile::conflict("reservation 12");
+    assert_eq!(
+        backoff.next_delay(0, Duration::ZERO, &conflict),
+        Some(Duration::from_millis(100))
+    );
+
+    let timeout = ErrPile::timeout("sync", Duration::from_secs(1));
+    assert_eq!(
+        backoff.next_delay(1, Duration::ZERO, &timeout),
+        Some(Duration::from_millis(200))
+    );
+    // the fourth attempt was the last one
+    assert_eq!(backoff.next_delay(3, Duration::ZERO, &timeout), None);
+
+    let backoff = backoff.max_elapsed(Duration::from_secs(1));
+    assert_eq!(
+        backoff.next_delay(2, Duration::from_millis(700), &timeout),
+        None
+    );
+}
+
+#[test]
+fn multiplier_and_full_jitter() {
+    let backoff = Backoff::new()
+        .base_delay(Duration::from_millis(10))
+        .multiplier(3.0)
+        .jitter(Jitter::None);
+    assert_eq!(
+        backoff.delay(2, &ErrPile::NotReady),
+        Duration::from_millis(90)
+    );
+
+    let backoff = backoff.jitter(Jitter::Full);
+    for _ in 0..20 {
+        assert!(backoff.delay(2, &ErrPile::NotReady) <= Duration::from_millis(90));
+    }
+}
+
+#[tokio::test]
+async fn idempotent_retries_reuse_the_key() {
+    let keys = std::sync::Mutex::new(Vec::new());
+    let issued = AtomicU32::new(0);
+    let payment = retry_idempotent(
+        &fast(),
+        || format!("pay-{}", issued.fetch_add(1, Ordering::SeqCst)),
+        |key| {
+            let mut keys = keys.lock().unwrap();
+            keys.push(key);
+            let attempt = keys.len();
+            async move {
+                match attempt {
+                    1 => Err(ErrPile::NotReady),
+                    _ => Ok("charged"),
+                }
+            }
+        },
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(payment, "charged");
+    assert_eq!(issued.load(Ordering::SeqCst), 1);
+    assert_eq!(*keys.lock().unwrap(), ["pay-0", "pay-0"]);
+}
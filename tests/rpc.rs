@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use error_pile::{ErrPile, JsonRpcError, PileKind};
+use serde_json::json;
+
+#[test]
+fn kinds_use_the_reserved_codes() {
+    let err = ErrPile::not_found("door", "214").to_json_rpc();
+    assert_eq!(err.code, -32001);
+    assert!(err.is_reserved());
+    assert_eq!(err.message, "The door `214` could not be found");
+    assert_eq!(err.data.as_ref().unwrap()["kind"], "not_found");
+
+    let err = JsonRpcError::from(ErrPile::custom("lock firmware crashed"));
+    assert_eq!(err.code, JsonRpcError::INTERNAL_ERROR);
+}
+
+#[test]
+fn errors_survive_the_round_trip() {
+    let sent = ErrPile::rate_limited(Some(Duration::from_secs(5)), None);
+    let body = serde_json::to_string(&sent.to_json_rpc()).unwrap();
+
+    let received: ErrPile = serde_json::from_str::<JsonRpcError>(&body).unwrap().into();
+    assert_eq!(received.kind(), PileKind::RateLimited);
+    assert!(received.is_transient());
+    assert_eq!(received.retry_after(), Some(Duration::from_secs(5)));
+}
+
+#[test]
+fn device_errors_keep_their_code_and_data() {
+    let err: JsonRpcError = serde_json::from_value(json!({
+        "code": 1042,
+        "message": "card not encoded",
+        "data": {"slot": 2},
+    }))
+    .unwrap();
+    assert!(!err.is_reserved());
+
+    let ErrPile::Remote(remote) = ErrPile::from(err) else {
+        panic!("expected a remote error");
+    };
+    assert_eq!(remote.kind, PileKind::Upstream);
+    assert_eq!(remote.code.as_deref(), Some("1042"));
+    assert_eq!(remote.metadata["data"], json!({"slot": 2}));
+
+    let err = ErrPile::from(JsonRpcError {
+        code: -32006,
+        message: "encoder timed out".into(),
+        data: None,
+    });
+    assert_eq!(err.kind(), PileKind::Timeout);
+    assert!(err.is_transient());
+}
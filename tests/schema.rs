@@ -0,0 +1,43 @@
+#![cfg(feature = "schemars")]
+
+use error_pile::{AZError, ErrPile, MSResponseError, RemoteError};
+use schemars::schema_for;
+use serde_json::json;
+
+#[test]
+fn the_wire_format_has_a_schema() {
+    let schema = schema_for!(RemoteError).to_value();
+    assert_eq!(schema["type"], "object");
+    for field in [
+        "kind",
+        "code",
+        "message",
+        "user_message",
+        "transient",
+        "chain",
+        "metadata",
+    ] {
+        assert!(schema["properties"].get(field).is_some(), "{field}");
+    }
+    assert_eq!(schema["required"], json!(["kind", "message"]));
+
+    // the kinds are listed in their serialized form
+    let kinds = serde_json::to_string(&schema["$defs"]["PileKind"]).unwrap();
+    assert!(kinds.contains("\"not_ready\""));
+
+    assert_eq!(schema_for!(ErrPile), schema_for!(RemoteError));
+}
+
+#[test]
+fn microsoft_errors_have_a_schema() {
+    let graph = schema_for!(MSResponseError).to_value();
+    let inner = &graph["$defs"]["MSResponseErrorInner"];
+    assert!(inner["properties"].get("innerError").is_some());
+
+    let az = schema_for!(AZError).to_value();
+    assert!(
+        az["$defs"]["AZErrorDetails"]["properties"]
+            .get("innererror")
+            .is_some()
+    );
+}
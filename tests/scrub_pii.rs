@@ -0,0 +1,56 @@
+// the scrubbing mode is global, so this lives in its own test binary
+
+use error_pile::{ErrPile, PiiScrubbing, redact, scrub_pii, set_pii_hash_key, set_pii_scrubbing};
+
+#[test]
+fn guest_data_is_scrubbed_when_turned_on() {
+    let text = "no room for guest: Jane Doe, jane.doe@example.com, phone: +44 20 7946 0958, passport P1234567";
+    assert_eq!(redact(text), text);
+
+    set_pii_scrubbing(PiiScrubbing::Remove);
+    assert_eq!(
+        redact(text),
+        "no room for guest: <pii>, <pii>, phone: <pii>, passport <pii>"
+    );
+    assert_eq!(
+        scrub_pii(r#"{"first_name": "Jane", "last_name": "Doe", "room": "101"}"#),
+        r#"{"first_name": "<pii>", "last_name": "<pii>", "room": "101"}"#
+    );
+    // nothing to scrub, nothing copied
+    assert!(matches!(
+        scrub_pii("room 101 is out of order"),
+        std::borrow::Cow::Borrowed(_)
+    ));
+
+    // no key, no hash to brute-force
+    set_pii_scrubbing(PiiScrubbing::Hash);
+    assert_eq!(redact("guest jane.doe@example.com"), "guest <pii>");
+
+    set_pii_hash_key("rotate-me");
+    assert_eq!(
+        redact("guest jane.doe@example.com"),
+        "guest <pii:ce4a9f3abafb50d6>"
+    );
+    let first = redact("guest jane.doe@example.com").into_owned();
+    let second = redact("again jane.doe@example.com").into_owned();
+    assert!(first.starts_with("guest <pii:"), "{first}");
+    assert_eq!(first["guest ".len()..], second["again ".len()..]);
+    assert_ne!(first, redact("guest john@example.com"));
+
+    // what the other services receive
+    set_pii_scrubbing(PiiScrubbing::Remove);
+    let err = ErrPile::custom("guest jane.doe@example.com has no profile");
+    let wire = serde_json::to_value(&err).unwrap();
+    assert_eq!(wire["message"], "guest <pii> has no profile");
+
+    #[cfg(feature = "report")]
+    {
+        let report = error_pile::ErrorReport::new(&err);
+        assert!(report.pii_scrubbed);
+        assert_eq!(report.message, "guest <pii> has no profile");
+        assert!(!error_pile::ErrorReport::new(&ErrPile::NotReady).pii_scrubbed);
+    }
+
+    set_pii_scrubbing(PiiScrubbing::Off);
+    assert_eq!(redact(text), text);
+}
@@ -0,0 +1,40 @@
+#![cfg(feature = "sentry")]
+
+use error_pile::{ErrPile, TokenError};
+use sentry_core::protocol::Level;
+
+#[test]
+fn events_carry_chain_tags_and_fingerprint() {
+    let err = ErrPile::Page {
+        index: 1,
+        source: Box::new(ErrPile::NotReady),
+    };
+    let event = err.to_sentry_event();
+
+    assert_eq!(event.level, Level::Warning);
+    assert_eq!(event.exception.len(), 2);
+    assert_eq!(
+        event.exception.last().unwrap().value.as_deref(),
+        Some("Failed to fetch page 1 of the listing")
+    );
+    assert_eq!(event.tags["kind"], "not_ready");
+    assert_eq!(event.tags["transient"], "true");
+    assert_eq!(*event.fingerprint, [err.fingerprint()]);
+}
+
+#[test]
+fn correlation_ids_become_tags() {
+    let err = ErrPile::TokenAcquisition(Box::new(TokenError {
+        error: "invalid_client".into(),
+        error_description: Some("AADSTS7000215: Invalid client secret provided.".into()),
+        error_codes: vec![7000215],
+        trace_id: Some("0f3d".into()),
+        correlation_id: Some("9a1c".into()),
+    }));
+    let event = err.to_sentry_event();
+
+    assert_eq!(event.tags["code"], "invalid_client");
+    assert_eq!(event.tags["trace_id"], "0f3d");
+    assert_eq!(event.tags["correlation_id"], "9a1c");
+    assert_eq!(*event.fingerprint, [err.fingerprint()]);
+}
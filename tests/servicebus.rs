@@ -0,0 +1,34 @@
+#![cfg(feature = "servicebus")]
+
+use error_pile::ErrPile;
+use fe2o3_amqp_types::{
+    definitions::{AmqpError, Error, LinkError},
+    primitives::Symbol,
+};
+
+#[test]
+fn service_bus_conditions_are_classified() {
+    let busy = Error::new(
+        fe2o3_amqp_types::definitions::ErrorCondition::Custom(Symbol::from(
+            "com.microsoft:server-busy",
+        )),
+        Some("namespace is throttled".to_string()),
+        None,
+    );
+    let err = ErrPile::service_bus("reservations", busy);
+    assert!(err.is_transient());
+    assert_eq!(
+        err.to_string(),
+        "Service Bus error on `reservations` (com.microsoft:server-busy)"
+    );
+
+    let err = ErrPile::from(Error::from(LinkError::DetachForced));
+    assert!(err.is_transient());
+
+    let err = ErrPile::from(Error::from(AmqpError::UnauthorizedAccess));
+    assert!(!err.is_transient());
+    assert_eq!(
+        err.to_string(),
+        "Service Bus error (amqp:unauthorized-access)"
+    );
+}
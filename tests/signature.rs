@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use error_pile::{
+    ErrPile, PileKind, WebhookVerificationFailure, check_webhook_timestamp, constant_time_eq,
+    signature_header, verify_signature,
+};
+use reqwest::header::{HeaderMap, HeaderValue};
+
+#[test]
+fn signatures_are_compared_whole() {
+    assert!(constant_time_eq(b"a1b2c3", b"a1b2c3"));
+    assert!(!constant_time_eq(b"a1b2c3", b"a1b2c4"));
+    assert!(!constant_time_eq(b"a1b2c3", b"a1b2c"));
+    assert!(constant_time_eq(b"", b""));
+
+    let err = verify_signature(b"a1b2c3", b"000000").unwrap_err();
+    assert_eq!(
+        err.webhook_verification(),
+        Some(&WebhookVerificationFailure::SignatureMismatch)
+    );
+    assert_eq!(err.status_code(), 401);
+    assert_eq!(err.kind(), PileKind::Auth);
+    assert!(!err.is_transient());
+}
+
+#[test]
+fn missing_signature_headers() {
+    let mut headers = HeaderMap::new();
+    headers.insert("stripe-signature", HeaderValue::from_static("t=1,v1=ab"));
+    assert_eq!(
+        signature_header(&headers, "stripe-signature").unwrap(),
+        "t=1,v1=ab"
+    );
+
+    headers.insert("x-adyen-hmac", HeaderValue::from_static(" "));
+    let err = signature_header(&headers, "x-adyen-hmac").unwrap_err();
+    assert_eq!(err.variant_name(), "WebhookVerification");
+    assert_eq!(
+        err.to_string(),
+        "The webhook could not be verified: the `x-adyen-hmac` header is missing"
+    );
+}
+
+#[test]
+fn replayed_webhooks_are_refused() {
+    let tolerance = Duration::from_secs(300);
+    let now = Utc::now().timestamp();
+
+    assert!(check_webhook_timestamp(now - 10, tolerance).is_ok());
+    for timestamp in [now - 3600, now + 3600, i64::MIN, i64::MAX] {
+        let err = check_webhook_timestamp(timestamp, tolerance).unwrap_err();
+        assert!(matches!(
+            err.webhook_verification(),
+            Some(WebhookVerificationFailure::TimestampOutsideTolerance { .. })
+        ));
+        assert!(!err.is_transient());
+    }
+}
+
+#[test]
+fn failures_convert_into_the_variant() {
+    let err = ErrPile::from(WebhookVerificationFailure::SignatureMismatch);
+    assert!(matches!(err, ErrPile::WebhookVerification(_)));
+}
+
+#[cfg(feature = "hmac")]
+#[test]
+fn hmac_sha256_signatures() {
+    use error_pile::verify_hmac_sha256;
+
+    let payload = b"The quick brown fox jumps over the lazy dog";
+    let hex = "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8";
+    let base64 = "97yD9DBThCSxMpjmqm+xQ+9NWaFJRhdZl0edvC0aPNg=";
+
+    assert!(verify_hmac_sha256(b"key", payload, hex).is_ok());
+    assert!(verify_hmac_sha256(b"key", payload, &format!("sha256={hex}")).is_ok());
+    assert!(verify_hmac_sha256(b"key", payload, base64).is_ok());
+
+    let err = verify_hmac_sha256(b"other key", payload, hex).unwrap_err();
+    assert_eq!(
+        err.webhook_verification(),
+        Some(&WebhookVerificationFailure::SignatureMismatch)
+    );
+    assert!(verify_hmac_sha256(b"key", payload, "not a signature").is_err());
+}
+
+#[cfg(feature = "hmac")]
+#[test]
+fn stripe_signs_the_timestamp_along() {
+    use error_pile::verify_stripe_signature;
+
+    let payload = br#"{"id":"evt_1"}"#;
+    let v1 = "c89214b5b5da833daed6f0b8c5bb6bd58cea9022bd80ccc78230f3942d632925";
+    let forever = Duration::from_secs(u64::MAX);
+
+    let header = format!("t=1700000000,v1=00ff,v1={v1}");
+    assert!(verify_stripe_signature(b"whsec_test", payload, &header, forever).is_ok());
+
+    let err = verify_stripe_signature(b"whsec_other", payload, &header, forever).unwrap_err();
+    assert_eq!(
+        err.webhook_verification(),
+        Some(&WebhookVerificationFailure::SignatureMismatch)
+    );
+
+    // the timestamp is part of what is signed
+    let header = format!("t=1700000001,v1={v1}");
+    assert!(verify_stripe_signature(b"whsec_test", payload, &header, forever).is_err());
+
+    let header = format!("t=1700000000,v1={v1}");
+    let err = verify_stripe_signature(b"whsec_test", payload, &header, Duration::from_secs(300))
+        .unwrap_err();
+    assert!(matches!(
+        err.webhook_verification(),
+        Some(WebhookVerificationFailure::TimestampOutsideTolerance { .. })
+    ));
+
+    // the raw payload HMAC doesn't take the Stripe header
+    assert!(error_pile::verify_hmac_sha256(b"whsec_test", payload, &header).is_err());
+}
@@ -0,0 +1,126 @@
+#![cfg(feature = "sms")]
+
+use std::time::Duration;
+
+use error_pile::{ErrPile, PileKind, ReqwestPileResExt, SmsError, SmsFailure};
+use serde_json::json;
+
+fn twilio_response(status: u16, code: u32, message: &str) -> reqwest::Response {
+    let body = json!({
+        "code": code,
+        "message": message,
+        "more_info": format!("https://www.twilio.com/docs/errors/{code}"),
+        "status": status,
+    });
+    http::Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .header("retry-after", "30")
+        .body(body.to_string())
+        .unwrap()
+        .into()
+}
+
+#[tokio::test]
+async fn opted_out_guests_are_not_retried() {
+    let res = twilio_response(400, 21610, "Attempt to send to unsubscribed recipient");
+
+    let err = res.error_for_pile().await.unwrap_err();
+    assert_eq!(err.variant_name(), "Sms");
+    assert!(err.sms_opted_out());
+    assert!(!err.is_transient());
+    assert_eq!(err.kind(), PileKind::Permission);
+    assert_eq!(err.code().as_deref(), Some("21610"));
+}
+
+#[tokio::test]
+async fn throttling_is_transient() {
+    let res = twilio_response(429, 20429, "Too Many Requests");
+
+    let err = res.error_for_pile().await.unwrap_err();
+    assert!(err.is_transient());
+    assert!(err.is_rate_limited());
+    assert!(!err.sms_opted_out());
+    assert_eq!(err.retry_after(), Some(Duration::from_secs(30)));
+    assert_eq!(err.status_code(), 429);
+}
+
+#[test]
+fn invalid_numbers() {
+    let err = SmsError::from_body(
+        Some(400),
+        &json!({
+            "code": 21211,
+            "message": "The 'To' number +4915 is not a valid phone number.",
+            "more_info": "https://www.twilio.com/docs/errors/21211",
+            "status": 400
+        }),
+    )
+    .unwrap();
+
+    assert_eq!(err.failure, SmsFailure::InvalidNumber);
+    assert!(!err.is_transient());
+    assert_eq!(ErrPile::from(err).kind(), PileKind::Validation);
+}
+
+#[test]
+fn other_codes_follow_the_status() {
+    assert_eq!(
+        SmsFailure::from_code(Some(30008), Some(503)),
+        SmsFailure::Unavailable
+    );
+    assert_eq!(
+        SmsFailure::from_code(Some(21606), Some(400)),
+        SmsFailure::Rejected
+    );
+    assert_eq!(SmsFailure::from_code(None, Some(401)), SmsFailure::Auth);
+}
+
+#[test]
+fn other_bodies_are_not_sms_errors() {
+    // a code without the `more_info` url
+    assert!(SmsError::from_body(Some(400), &json!({"code": 21211, "message": "x"})).is_none());
+    assert!(
+        SmsError::from_body(
+            Some(400),
+            &json!({"code": 1001, "message": "x", "status": 400})
+        )
+        .is_none()
+    );
+    assert!(
+        SmsError::from_body(
+            Some(400),
+            &json!({"code": 1001, "message": "x", "more_info": "see the docs"})
+        )
+        .is_none()
+    );
+    assert!(
+        SmsError::from_body(
+            Some(400),
+            &json!({"code": "E1", "message": "x", "status": 400})
+        )
+        .is_none()
+    );
+}
+
+#[tokio::test]
+async fn problem_documents_with_a_code_stay_problems() {
+    let res: reqwest::Response = http::Response::builder()
+        .status(409)
+        .header("content-type", "application/problem+json")
+        .body(
+            json!({
+                "title": "Rate closed",
+                "status": 409,
+                "code": 4107,
+                "message": "BAR is closed",
+                "more_info": "https://docs.ramhotels.example/errors/4107"
+            })
+            .to_string(),
+        )
+        .unwrap()
+        .into();
+
+    let err = res.error_for_pile().await.unwrap_err();
+    assert_eq!(err.variant_name(), "Problem");
+}
@@ -0,0 +1,45 @@
+use error_pile::{ErrPile, MicrosoftError, ReqwestPileResExt};
+
+// capturing is global, so this lives in its own test binary
+#[tokio::test]
+async fn error_responses_are_captured() {
+    error_pile::set_capture_snapshots(true);
+
+    let body = r#"{"error":{"code":"ServiceUnavailable","message":"try later"}}"#;
+    let res: reqwest::Response = http::Response::builder()
+        .status(503)
+        .header("content-type", "application/json")
+        .header("set-cookie", "session=abc")
+        .header("request-id", "42")
+        .header("retry-after", "12")
+        .body(body.to_string())
+        .unwrap()
+        .into();
+
+    let err = res.to_pile_empty().await.unwrap_err();
+    assert!(matches!(
+        err.peeled(),
+        ErrPile::Microsoft(MicrosoftError::AZ(_))
+    ));
+    assert_eq!(err.to_string(), err.peeled().to_string());
+
+    let snapshot = err.snapshot().expect("response was captured");
+    assert_eq!(snapshot.status, 503);
+    assert_eq!(snapshot.body, body);
+    assert!(
+        snapshot
+            .headers
+            .contains(&("set-cookie".into(), "<redacted>".into()))
+    );
+    assert!(
+        snapshot
+            .headers
+            .contains(&("request-id".into(), "42".into()))
+    );
+
+    // the AZ error keeps no headers, the snapshot has the wait
+    assert_eq!(err.retry_after(), Some(std::time::Duration::from_secs(12)));
+
+    let json = serde_json::to_value(snapshot).unwrap();
+    assert_eq!(json["status"], 503);
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "spawn")]
+
+use std::time::Duration;
+
+use error_pile::{ErrPile, PileKind, spawn_blocking_pile, spawn_pile};
+
+#[tokio::test]
+async fn panicking_tasks_are_named() {
+    let err = spawn_pile("night-audit-export", async {
+        tokio::task::yield_now().await;
+        if true {
+            panic!("folio 12 has no rate");
+        }
+        Ok(())
+    })
+    .await
+    .unwrap_err();
+
+    assert!(matches!(&err, ErrPile::Task(task) if task.name == "night-audit-export"));
+    assert_eq!(
+        err.to_string(),
+        "Task `night-audit-export` did not complete"
+    );
+    assert_eq!(err.kind(), PileKind::Internal);
+    let ErrPile::Task(task) = &err else {
+        unreachable!()
+    };
+    assert!(
+        matches!(&task.source, ErrPile::Panic(panic) if panic.message == "folio 12 has no rate")
+    );
+    let location = err.location().unwrap();
+    assert!(
+        location.starts_with("tests/spawn.rs:") && location.ends_with("(task night-audit-export)"),
+        "{location}"
+    );
+
+    let err = spawn_blocking_pile("pdf-render", || -> error_pile::PileResult<()> {
+        panic!("pdfium crashed")
+    })
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("pdf-render"));
+}
+
+#[tokio::test]
+async fn results_and_aborts() {
+    assert_eq!(spawn_pile("sum", async { Ok(12) }).await.unwrap(), 12);
+    assert!(
+        spawn_blocking_pile("auth", || Err::<(), _>(ErrPile::Auth))
+            .await
+            .unwrap_err()
+            .is_encrypted()
+    );
+
+    let task = spawn_pile("sync", async {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        Ok(())
+    });
+    assert_eq!(task.name(), "sync");
+    task.abort();
+    let err = task.await.unwrap_err();
+    assert_eq!(err.kind(), PileKind::Cancelled);
+}
@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use chrono::{TimeDelta, Utc};
+use error_pile::{ErrPile, ErrStats, PileKind};
+
+#[test]
+fn counts_by_kind_within_the_window() {
+    let stats = ErrStats::new(Duration::from_secs(3600));
+    let now = Utc::now();
+
+    stats.record_at(&ErrPile::NotReady, now - TimeDelta::hours(2));
+    stats.record_at(&ErrPile::NotReady, now - TimeDelta::minutes(30));
+    stats.record_at(&ErrPile::Auth, now - TimeDelta::minutes(20));
+    stats.record_at(&ErrPile::NotReady, now - TimeDelta::minutes(10));
+
+    let snapshot = stats.snapshot_at(now);
+    assert_eq!(snapshot.window_secs, 3600);
+    assert_eq!(snapshot.total, 3, "the occurrence of two hours ago is out");
+    assert_eq!(snapshot.by_kind[0].kind, PileKind::NotReady);
+    assert_eq!(snapshot.by_kind[0].stats.count, 2);
+    assert_eq!(
+        snapshot.by_kind[0].stats.first_seen,
+        now - TimeDelta::minutes(30)
+    );
+    assert_eq!(
+        snapshot.by_kind[0].stats.last_seen,
+        now - TimeDelta::minutes(10)
+    );
+    assert_eq!(snapshot.by_kind[1].kind, PileKind::Auth);
+    assert_eq!(snapshot.transient + snapshot.permanent, 3);
+    assert_eq!(snapshot.transient_ratio, snapshot.transient as f64 / 3.0);
+}
+
+#[test]
+fn old_occurrences_are_dropped_when_recording() {
+    let stats = ErrStats::new(Duration::from_secs(600));
+    let now = Utc::now();
+
+    stats.record_at(&ErrPile::Auth, now - TimeDelta::hours(3));
+    stats.record_at(&ErrPile::Auth, now - TimeDelta::minutes(2));
+    stats.record_at(&ErrPile::Auth, now - TimeDelta::minutes(1));
+
+    let snapshot = stats.snapshot_at(now);
+    assert_eq!(snapshot.total, 2);
+    assert_eq!(snapshot.first_seen, Some(now - TimeDelta::minutes(2)));
+    assert_eq!(snapshot.last_seen, Some(now - TimeDelta::minutes(1)));
+}
+
+#[test]
+fn empty_snapshot_serializes() {
+    let stats = ErrStats::new(Duration::from_secs(60));
+    let snapshot = stats.snapshot();
+    assert_eq!(snapshot.total, 0);
+    assert_eq!(snapshot.transient_ratio, 0.0);
+    assert!(snapshot.first_seen.is_none());
+
+    stats.record(&ErrPile::Auth);
+    let json = serde_json::to_value(stats.snapshot()).unwrap();
+    assert_eq!(json["total"], 1);
+    assert_eq!(json["by_kind"][0]["kind"], "auth");
+    assert_eq!(json["by_kind"][0]["count"], 1);
+
+    stats.clear();
+    assert_eq!(stats.snapshot().total, 0);
+}
+
+#[cfg(feature = "report")]
+#[tokio::test]
+async fn the_sink_records_what_it_forwards() {
+    use std::sync::Mutex;
+
+    use error_pile::{ErrorReport, ErrorSink, PileResult, StatsSink};
+
+    #[derive(Default)]
+    struct Collect(Mutex<Vec<ErrorReport>>);
+
+    #[async_trait::async_trait]
+    impl ErrorSink for Collect {
+        async fn send(&self, report: &ErrorReport) -> PileResult {
+            self.0.lock().unwrap().push(report.clone());
+            Ok(())
+        }
+    }
+
+    let sink = StatsSink::new(Collect::default(), Duration::from_secs(3600));
+    let mut summary = ErrorReport::new(&ErrPile::NotReady);
+    summary.count = 4;
+    // counted, not stored one by one
+    let mut flood = ErrorReport::new(&ErrPile::NotReady);
+    flood.count = 10_000_000;
+    sink.stats().record_report(&flood);
+    sink.stats().clear();
+    sink.send(&summary).await.unwrap();
+    sink.send(&ErrorReport::new(&ErrPile::Auth)).await.unwrap();
+
+    assert_eq!(sink.sink().0.lock().unwrap().len(), 2);
+    let snapshot = sink.stats().snapshot();
+    assert_eq!(snapshot.total, 5);
+    assert_eq!(snapshot.by_kind[0].kind, PileKind::NotReady);
+    assert_eq!(snapshot.by_kind[0].stats.count, 4);
+}
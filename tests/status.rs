@@ -0,0 +1,78 @@
+use error_pile::ErrPile;
+
+#[test]
+fn not_found_maps_to_404() {
+    let err = ErrPile::not_found("reservation", 42);
+    assert_eq!(err.to_string(), "The reservation `42` could not be found");
+    assert_eq!(err.status_code(), 404);
+    assert!(err.is_not_found());
+}
+
+#[test]
+fn rate_limited_is_transient() {
+    use std::time::Duration;
+
+    let err = ErrPile::rate_limited(Some(Duration::from_secs(3)), Some("graph".into()));
+    assert!(err.is_transient());
+    assert_eq!(err.status_code(), 429);
+    assert_eq!(err.retry_after(), Some(Duration::from_secs(3)));
+    assert_eq!(
+        err.to_string(),
+        "Too many requests to graph, please try again later"
+    );
+}
+
+#[test]
+fn conflict_maps_to_409() {
+    let err = ErrPile::conflict_with_version("reservation", "W/\"7\"");
+    assert!(err.is_conflict());
+    assert!(!err.is_transient());
+    assert_eq!(err.status_code(), 409);
+    assert_eq!(
+        err.to_string(),
+        "The reservation was modified by someone else (current version: W/\"7\")"
+    );
+}
+
+#[test]
+fn timeout_is_transient() {
+    use std::time::Duration;
+
+    let err = ErrPile::timeout("graph poll", Duration::from_secs(30));
+    assert!(err.is_timeout());
+    assert!(err.is_transient());
+    assert_eq!(err.status_code(), 504);
+    assert_eq!(
+        err.to_string(),
+        "The operation `graph poll` timed out after 30s"
+    );
+}
+
+#[test]
+fn unsupported_maps_to_501() {
+    let err = ErrPile::unsupported("folio export v2");
+    assert!(err.is_unsupported());
+    assert_eq!(err.status_code(), 501);
+    assert_eq!(err.to_string(), "`folio export v2` is not supported");
+}
+
+#[test]
+fn config_reports_key_and_file() {
+    let err = ErrPile::config_in_file("graph.tenant_id", "/etc/agent.toml", "value is missing");
+    assert!(err.is_config());
+    assert_eq!(
+        err.to_string(),
+        "Invalid configuration for `graph.tenant_id` in /etc/agent.toml: value is missing"
+    );
+}
+
+#[cfg(feature = "ssh")]
+#[test]
+fn ssh_key_keeps_path() {
+    let err = error_pile::load_ssh_key("/nonexistent/id_ed25519", None).unwrap_err();
+    assert_eq!(
+        err.ssh_key_issue(),
+        Some(error_pile::SshKeyIssue::Unreadable)
+    );
+    assert!(err.to_string().contains("/nonexistent/id_ed25519"));
+}
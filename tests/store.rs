@@ -0,0 +1,65 @@
+#![cfg(feature = "store-sqlite")]
+
+use chrono::{Duration, Utc};
+use error_pile::{ErrPile, ErrorReport, ErrorSink, SqliteErrorStore};
+use sqlx::sqlite::SqlitePoolOptions;
+
+async fn store() -> SqliteErrorStore {
+    // every connection to :memory: is its own database
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    let store = SqliteErrorStore::new(pool);
+    store.migrate().await.unwrap();
+    // running it again is fine
+    store.migrate().await.unwrap();
+    store
+}
+
+#[tokio::test]
+async fn reports_are_stored_and_queried() {
+    let store = store().await;
+
+    let page = ErrPile::Page {
+        index: 4,
+        source: Box::new(ErrPile::NotReady),
+    };
+    let id = store.insert(&ErrorReport::new(&page)).await.unwrap();
+    store
+        .send(&ErrorReport::new(&ErrPile::not_found("room", 12)))
+        .await
+        .unwrap();
+    store.send(&ErrorReport::new(&page)).await.unwrap();
+
+    let recent = store.recent(10).await.unwrap();
+    assert_eq!(recent.len(), 3);
+    assert_eq!(recent[1].kind, "not_found");
+
+    let stored = store.by_fingerprint(&page.fingerprint(), 10).await.unwrap();
+    assert_eq!(stored.len(), 2);
+    assert_eq!(stored[1].id, id);
+    assert_eq!(stored[1].message, "Failed to fetch page 4 of the listing");
+    assert_eq!(
+        stored[1].chain,
+        ["The resource is not ready yet, please try again later"]
+    );
+    assert!(stored[1].transient);
+    assert_eq!(stored[1].occurrences, 1);
+
+    let since = Utc::now() - Duration::hours(1);
+    assert_eq!(
+        store.counts_by_kind(since).await.unwrap(),
+        [("not_ready".to_string(), 2), ("not_found".to_string(), 1)]
+    );
+
+    assert_eq!(store.purge_before(since).await.unwrap(), 0);
+    assert_eq!(
+        store
+            .purge_before(Utc::now() + Duration::hours(1))
+            .await
+            .unwrap(),
+        3
+    );
+}
@@ -0,0 +1,38 @@
+#![cfg(feature = "test-util")]
+
+use error_pile::{AZError, ErrPile, MSResponseError, PileKind};
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn errors_survive_the_wire(err in any::<ErrPile>()) {
+        let json = serde_json::to_string(&err).unwrap();
+        let received: ErrPile = serde_json::from_str(&json).unwrap();
+
+        prop_assert_eq!(received.kind(), err.kind());
+        prop_assert_eq!(received.code(), err.code());
+        prop_assert_eq!(received.is_transient(), err.is_transient());
+        prop_assert_eq!(received.status_code(), err.status_code());
+    }
+
+    #[test]
+    fn graph_errors_round_trip(err in any::<MSResponseError>()) {
+        let value = serde_json::to_value(&err).unwrap();
+        let back: MSResponseError = serde_json::from_value(value.clone()).unwrap();
+        prop_assert_eq!(serde_json::to_value(&back).unwrap(), value);
+    }
+
+    #[test]
+    fn document_intelligence_errors_round_trip(err in any::<AZError>()) {
+        let value = serde_json::to_value(&err).unwrap();
+        let back: AZError = serde_json::from_value(value.clone()).unwrap();
+        prop_assert_eq!(serde_json::to_value(&back).unwrap(), value);
+        prop_assert_eq!(back.is_transient(), err.is_transient());
+    }
+
+    #[test]
+    fn kinds_serialize_as_snake_case(kind in any::<PileKind>()) {
+        let value = serde_json::to_value(kind).unwrap();
+        prop_assert_eq!(serde_json::from_value::<PileKind>(value).unwrap(), kind);
+    }
+}
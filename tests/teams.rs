@@ -0,0 +1,49 @@
+use error_pile::{ErrPile, post_teams_webhook};
+
+mod common;
+
+/// answers like a Teams incoming webhook, depending on the path
+async fn webhook_server() -> String {
+    common::serve(|req| {
+        let (status, body) = match req.path.as_str() {
+            "/ok" => (200, "1"),
+            "/busy" => (
+                200,
+                "Microsoft Teams endpoint returned HTTP error 429 with ContextId tcid=0",
+            ),
+            "/big" => (400, "Webhook message size exceeded"),
+            _ => (502, "Bad gateway"),
+        };
+        common::reply(status, "text/plain", body)
+    })
+    .await
+}
+
+#[tokio::test]
+async fn teams_webhook_errors_are_mapped() {
+    let base = webhook_server().await;
+    let client = reqwest::Client::new();
+    let card = serde_json::json!({"text": "Room 101 checked out"});
+
+    post_teams_webhook(&client, &format!("{base}/ok"), &card)
+        .await
+        .unwrap();
+
+    let err = post_teams_webhook(&client, &format!("{base}/busy"), &card)
+        .await
+        .unwrap_err();
+    assert!(err.is_rate_limited());
+    assert!(err.is_transient());
+
+    let err = post_teams_webhook(&client, &format!("{base}/big"), &card)
+        .await
+        .unwrap_err();
+    assert_eq!(err.field_errors().unwrap().0[0].code, "size_exceeded");
+    assert!(!err.is_transient());
+
+    let err = post_teams_webhook(&client, &format!("{base}/down"), &card)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ErrPile::Http(_)));
+    assert!(err.is_transient());
+}
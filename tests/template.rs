@@ -0,0 +1,78 @@
+#![cfg(any(feature = "tera", feature = "handlebars"))]
+
+use error_pile::{ErrPile, PileKind, PileResult, TemplateEngine};
+
+#[cfg(feature = "tera")]
+#[test]
+fn tera_syntax_errors_have_the_line() {
+    let mut tera = tera::Tera::default();
+    let err = ErrPile::from(
+        tera.add_raw_template("invoice.html", "<h1>Invoice</h1>\n<p>{{ guest.name }</p>")
+            .unwrap_err(),
+    );
+
+    assert_eq!(err.variant_name(), "Template");
+    assert_eq!(
+        err.template_location(),
+        Some((Some("invoice.html"), Some(2)))
+    );
+    assert_eq!(err.kind(), PileKind::Internal);
+    assert!(!err.is_transient());
+    assert!(
+        err.to_string().contains("`invoice.html` at line 2"),
+        "{err}"
+    );
+}
+
+#[cfg(feature = "tera")]
+#[test]
+fn tera_render_errors_have_the_template() {
+    fn render(tera: &tera::Tera) -> PileResult<String> {
+        Ok(tera.render("invoice.html", &tera::Context::new())?)
+    }
+
+    let mut tera = tera::Tera::default();
+    tera.add_raw_template("invoice.html", "Dear {{ guest.name }}")
+        .unwrap();
+
+    let err = render(&tera).unwrap_err();
+    let ErrPile::Template(template) = &err else {
+        panic!("{err:?}");
+    };
+    assert_eq!(template.engine, TemplateEngine::Tera);
+    assert_eq!(template.template.as_deref(), Some("invoice.html"));
+    assert!(template.message.contains("guest.name"), "{template}");
+    // the error of tera is kept as the source
+    assert!(err.chain().count() > 2);
+}
+
+#[cfg(feature = "handlebars")]
+#[test]
+fn handlebars_errors_have_the_template_and_line() {
+    let mut handlebars = handlebars::Handlebars::new();
+    let err = ErrPile::from(
+        handlebars
+            .register_template_string("confirmation", "Dear {{guest}},\n{{#if paid}}paid")
+            .unwrap_err(),
+    );
+    let ErrPile::Template(template) = &err else {
+        panic!("{err:?}");
+    };
+    assert_eq!(template.engine, TemplateEngine::Handlebars);
+    assert_eq!(template.template.as_deref(), Some("confirmation"));
+    assert!(template.line.is_some(), "{template}");
+
+    handlebars.set_strict_mode(true);
+    handlebars
+        .register_template_string("confirmation", "Dear {{guest}}")
+        .unwrap();
+    let err = ErrPile::from(
+        handlebars
+            .render("confirmation", &serde_json::json!({}))
+            .unwrap_err(),
+    );
+    assert_eq!(
+        err.template_location(),
+        Some((Some("confirmation"), Some(1)))
+    );
+}
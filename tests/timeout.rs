@@ -0,0 +1,34 @@
+#![cfg(feature = "retry")]
+
+use std::time::Duration;
+
+use error_pile::{ErrPile, PileFutureExt};
+
+#[tokio::test]
+async fn slow_futures_time_out_with_the_operation_name() {
+    let err = std::future::pending::<Result<(), ErrPile>>()
+        .with_timeout(Duration::from_millis(10), "fetch folio")
+        .await
+        .unwrap_err();
+
+    assert!(err.is_timeout());
+    assert!(err.is_transient());
+    let ErrPile::Timeout(timeout) = &err else {
+        panic!("expected a timeout");
+    };
+    assert_eq!(timeout.operation, "fetch folio");
+    assert_eq!(timeout.after, Duration::from_millis(10));
+}
+
+#[tokio::test]
+async fn results_within_the_limit_pass_through() {
+    let ok = async { Ok(3) }
+        .with_timeout(Duration::from_secs(1), "count rooms")
+        .await;
+    assert_eq!(ok.unwrap(), 3);
+
+    let err = async { Err::<(), _>(ErrPile::NotReady) }
+        .with_timeout(Duration::from_secs(1), String::from("sync"))
+        .await;
+    assert!(err.unwrap_err().is_not_ready());
+}
@@ -0,0 +1,90 @@
+#![cfg(feature = "tracing")]
+
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+
+use error_pile::{ErrPile, PileResult, PileResultExt};
+use tracing::{
+    Event, Metadata, Subscriber,
+    field::{Field, Visit},
+    span,
+};
+
+/// name and Debug value of each field of an event
+type EventFields = Vec<(String, String)>;
+
+/// keeps the fields of every event
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<EventFields>>>);
+
+struct Fields(EventFields);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0
+            .push((field.name().to_string(), format!("{value:?}")));
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+
+    fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields(Vec::new());
+        event.record(&mut fields);
+        self.0.lock().unwrap().push(fields.0);
+    }
+
+    fn enter(&self, _: &span::Id) {}
+
+    fn exit(&self, _: &span::Id) {}
+}
+
+#[test]
+fn traced_records_structured_fields() {
+    let recorder = Recorder::default();
+
+    tracing::subscriber::with_default(recorder.clone(), || {
+        let res: PileResult = Err(ErrPile::Page {
+            index: 1,
+            source: Box::new(ErrPile::NotReady),
+        });
+        assert!(res.traced().is_err());
+
+        let ok: PileResult<u8> = Ok(1);
+        assert_eq!(ok.traced().unwrap(), 1);
+    });
+
+    let events = recorder.0.lock().unwrap();
+    assert_eq!(events.len(), 1);
+
+    let field = |name: &str| {
+        events[0]
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    };
+    assert_eq!(field("kind"), Some("not_ready"));
+    assert_eq!(field("transient"), Some("true"));
+    assert_eq!(field("code"), None);
+    assert_eq!(
+        field("chain"),
+        Some("The resource is not ready yet, please try again later")
+    );
+    assert_eq!(
+        field("message"),
+        Some("Failed to fetch page 1 of the listing")
+    );
+}
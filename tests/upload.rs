@@ -0,0 +1,31 @@
+#![cfg(feature = "multipart")]
+
+use error_pile::{ErrPile, PileForm};
+
+mod common;
+
+#[tokio::test]
+async fn rejected_uploads_tell_what_was_sent() {
+    // reads the whole upload, then refuses it as too large
+    let base = common::serve(|_| common::reply(413, "text/plain", "")).await;
+    let form = PileForm::new().text("folio", "F-1001").bytes(
+        "scan",
+        Some("passport.pdf".into()),
+        vec![7u8; 100_000],
+    );
+
+    let err = form
+        .send(reqwest::Client::new().post(format!("{base}/documents")))
+        .await
+        .unwrap_err();
+    let ErrPile::Upload(upload) = &err else {
+        panic!("{err:?}");
+    };
+    // every byte went out, the upload was refused as a whole
+    assert_eq!(upload.field, None);
+    assert_eq!(upload.completed_fields, ["folio", "scan"]);
+    assert_eq!(upload.sent, 100_006);
+    assert_eq!(upload.total, 100_006);
+    assert_eq!(upload.source.upstream_status(), Some(413));
+    assert_eq!(err.upstream_status(), Some(413));
+}
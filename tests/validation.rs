@@ -0,0 +1,58 @@
+use error_pile::{ErrPile, FieldError, ValidationBuilder};
+
+#[test]
+fn validation_display_lists_fields() {
+    let err = ErrPile::validation([
+        FieldError::new("check_in", "required", "check-in date is required"),
+        FieldError::new("guests", "range", "at least one guest"),
+    ]);
+
+    assert_eq!(
+        err.to_string(),
+        "Validation failed: check_in: check-in date is required; guests: at least one guest"
+    );
+    assert_eq!(err.field_errors().unwrap().for_field("guests").count(), 1);
+}
+
+#[cfg(feature = "validator")]
+#[test]
+fn validator_errors_are_flattened() {
+    use validator::{ValidationError, ValidationErrors};
+
+    let mut errs = ValidationErrors::new();
+    errs.add("email", ValidationError::new("email"));
+    errs.add(
+        "nights",
+        ValidationError::new("range").with_message("must stay at least one night".into()),
+    );
+
+    let err = ErrPile::from(errs);
+    let fields = &err.field_errors().unwrap().0;
+    assert_eq!(fields[0], FieldError::new("email", "email", "email"));
+    assert_eq!(fields[1].message, "must stay at least one night");
+}
+
+#[test]
+fn builder_collects_every_field() {
+    let mut check = ValidationBuilder::new();
+    check
+        .require("check_in", false, "check-in date is required")
+        .require("nights", true, "must stay at least one night")
+        .push("email", "email");
+    assert!(!check.is_empty());
+
+    let err = check.finish().unwrap_err();
+    assert_eq!(err.status_code(), 422);
+    assert_eq!(
+        err.field_errors().unwrap().0,
+        [
+            FieldError::new("check_in", "invalid", "check-in date is required"),
+            FieldError::new("email", "email", "email"),
+        ]
+    );
+
+    let guests = 2;
+    let mut check = ValidationBuilder::new();
+    check.require("guests", guests > 0, "at least one guest");
+    assert!(check.finish().is_ok());
+}
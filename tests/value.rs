@@ -0,0 +1,223 @@
+use error_pile::{ExtractOptions, extract_all_errors, extract_error, value::SerdeValue};
+use serde_json::json;
+
+fn extract(value: serde_json::Value) -> String {
+    SerdeValue(value).extract_error_from_json()
+}
+
+#[test]
+fn extraction_follows_error_fields() {
+    assert_eq!(extract(json!({"error": "boom"})), "boom");
+    assert_eq!(extract(json!({"error": {"message": "nested"}})), "nested");
+    assert_eq!(
+        extract(json!({"error": {"code": 1, "message": ""}, "reason": "fallback"})),
+        "fallback"
+    );
+    assert_eq!(extract(json!("plain")), "plain");
+    assert_eq!(extract(json!(42)), "42");
+    assert_eq!(extract(json!({"status": 1})), "{\n  \"status\": 1\n}");
+}
+
+#[test]
+fn extraction_stops_at_depth_limit() {
+    let mut value = json!({"message": "too deep"});
+    for _ in 0..100 {
+        value = json!({ "error": value });
+    }
+
+    // deep payloads end up formatted instead of overflowing the stack
+    assert!(extract(value).starts_with('{'));
+}
+
+#[test]
+fn error_arrays_are_joined() {
+    assert_eq!(
+        extract(json!({"errors": [{"message": "name is required"}, {"message": "room is taken"}]})),
+        "name is required; room is taken"
+    );
+    assert_eq!(
+        extract(json!({"error": {"code": "x", "details": ["a", "b", "c", "d", "e", "f", "g"]}})),
+        "a; b; c; d; e (+2 more)"
+    );
+}
+
+#[test]
+fn structured_extraction_keeps_code_and_target() {
+    let body = json!({
+        "error": {
+            "code": "InvalidRequest",
+            "target": "checkIn",
+            "innererror": {"message": "ignored"},
+            "message": "check-in must be before check-out"
+        }
+    });
+
+    let extracted = SerdeValue(body.clone()).extract_structured();
+    assert_eq!(extracted.code.as_deref(), Some("InvalidRequest"));
+    assert_eq!(extracted.target.as_deref(), Some("checkIn"));
+    assert_eq!(extracted.message, "check-in must be before check-out");
+    assert_eq!(extracted.raw, body);
+
+    let extracted =
+        SerdeValue(json!({"errorCode": 4012, "reason": "card declined"})).extract_structured();
+    assert_eq!(extracted.code.as_deref(), Some("4012"));
+    assert_eq!(extracted.target, None);
+}
+
+#[test]
+fn values_can_be_queried_by_path() {
+    let body = SerdeValue(json!({
+        "error": {
+            "innererror": {"code": "ExpiredCard", "retryable": false},
+            "details": [{"code": "A"}, {"code": "B", "amount": 120}]
+        },
+        "matrix": [[1, 2], [3, 4]]
+    }));
+
+    assert_eq!(body.str_at("/error/innererror/code"), Some("ExpiredCard"));
+    assert_eq!(body.str_at("error.innererror.code"), Some("ExpiredCard"));
+    assert_eq!(body.str_at("error.details[1].code"), Some("B"));
+    assert_eq!(body.u64_at("error.details[1].amount"), Some(120));
+    assert_eq!(body.bool_at("error.innererror.retryable"), Some(false));
+    assert_eq!(body.i64_at("matrix[1][0]"), Some(3));
+    assert_eq!(body.get_at::<Vec<i64>>("matrix[0]"), Some(vec![1, 2]));
+    assert_eq!(body.at("error.details[5]"), None);
+    assert_eq!(body.at("error.missing.code"), None);
+}
+
+#[test]
+fn extraction_is_length_capped() {
+    let body = SerdeValue(json!({"message": "é".repeat(5000)}));
+
+    let message = body.extract_error_with_limit(11);
+    assert_eq!(message, format!("{}… (10000 bytes)", "é".repeat(5)));
+
+    let message = body.extract_error_from_json();
+    assert!(message.len() < error_pile::value::DEFAULT_MAX_EXTRACTED_LEN + 32);
+    assert!(message.ends_with("… (10000 bytes)"));
+}
+
+#[test]
+fn odata_errors_are_formatted() {
+    let body = SerdeValue(json!({
+        "error": {
+            "code": "ErrorItemNotFound",
+            "message": "The specified object was not found in the store.",
+            "innerError": {"request-id": "b1c2", "date": "2026-10-15T08:00:00"}
+        }
+    }));
+
+    assert_eq!(
+        body.extract_error_from_json(),
+        "ErrorItemNotFound: The specified object was not found in the store. (request-id: b1c2)"
+    );
+    assert_eq!(
+        body.extract_structured().code.as_deref(),
+        Some("ErrorItemNotFound")
+    );
+}
+
+#[test]
+fn validation_problem_details_are_flattened() {
+    let body = SerdeValue(json!({
+        "type": "https://tools.ietf.org/html/rfc9110#section-15.5.1",
+        "title": "One or more validation errors occurred.",
+        "status": 400,
+        "errors": {
+            "Email": ["The Email field is not a valid e-mail address."],
+            "Nights": ["Must be at least 1.", "Must be a number."]
+        }
+    }));
+
+    assert_eq!(
+        body.extract_error_from_json(),
+        "Email: The Email field is not a valid e-mail address.; Nights: Must be at least 1.; Nights: Must be a number."
+    );
+    assert_eq!(
+        body.validation_errors()
+            .unwrap()
+            .for_field("Nights")
+            .count(),
+        2
+    );
+}
+
+#[test]
+fn byte_payloads_are_decoded() {
+    let mut bom = vec![0xEF, 0xBB, 0xBF];
+    bom.extend_from_slice(br#"{"message":"from sftp"}"#);
+    assert_eq!(
+        SerdeValue::from_bytes(&bom).extract_error_from_json(),
+        "from sftp"
+    );
+
+    let mut utf16: Vec<u8> = vec![0xFF, 0xFE];
+    for unit in r#"{"error":"Zimmer belegt"}"#.encode_utf16() {
+        utf16.extend_from_slice(&unit.to_le_bytes());
+    }
+    assert_eq!(
+        SerdeValue::from_bytes(&utf16).extract_error_from_json(),
+        "Zimmer belegt"
+    );
+
+    // Latin-1 text that is not JSON at all
+    let latin1 = b"Fehler: Zimmer \xfcberbucht\r\n";
+    assert_eq!(
+        SerdeValue::from_bytes(latin1).extract_error_from_json(),
+        "Fehler: Zimmer überbucht"
+    );
+}
+
+#[test]
+fn extraction_runs_on_plain_values() {
+    let body = json!({"fault": {"faultcode": "Room.Locked", "faultstring": "room 12 is locked"}});
+
+    let opts = ExtractOptions::new().fields(["fault", "faultstring"]);
+    let extracted = extract_error(&body, &opts);
+    assert_eq!(extracted.message, "room 12 is locked");
+    assert_eq!(extracted.raw, body);
+
+    let odata =
+        json!({"error": {"code": "itemNotFound", "message": "The resource could not be found."}});
+    let extracted = extract_error(&odata, &ExtractOptions::new().max_len(12));
+    assert_eq!(extracted.code.as_deref(), Some("itemNotFound"));
+    assert_eq!(extracted.message, "The resource… (32 bytes)");
+
+    let deep = json!({"error": {"error": {"message": "too deep"}}});
+    let extracted = extract_error(&deep, &ExtractOptions::new().max_depth(2));
+    assert!(extracted.message.starts_with('{'));
+}
+
+#[test]
+fn collect_all_gathers_nested_messages() {
+    let body = json!({
+        "error": {
+            "code": "InvalidRequest",
+            "message": "Invalid request.",
+            "innererror": {"code": "InvalidContent", "message": "The file is corrupted or format is unsupported."},
+            "details": [
+                {"code": "InvalidPage", "message": "Page 3 could not be read.", "target": "pages"},
+                {"code": "InvalidPage", "message": "Page 3 could not be read.", "target": "pages"}
+            ]
+        }
+    });
+
+    assert_eq!(
+        SerdeValue(body.clone()).extract_all(),
+        [
+            "Invalid request.",
+            "The file is corrupted or format is unsupported.",
+            "Page 3 could not be read."
+        ]
+    );
+
+    // nothing past the depth limit
+    assert_eq!(
+        extract_all_errors(&body, &ExtractOptions::new().max_depth(1)),
+        ["Invalid request."]
+    );
+    assert_eq!(
+        extract_all_errors(&json!("plain"), &ExtractOptions::new()),
+        ["plain"]
+    );
+}
@@ -0,0 +1,22 @@
+use error_pile::value::{SerdeValue, prefer_error_fields, reset_error_fields, set_error_fields};
+use serde_json::json;
+
+// the field list is global, so this lives in its own test binary
+#[test]
+fn error_fields_are_configurable() {
+    let body = SerdeValue(json!({
+        "message": "Bad Request",
+        "developerMessage": "rate plan BAR is closed",
+        "fault": {"faultstring": "soap says no"}
+    }));
+    assert_eq!(body.extract_error_from_json(), "Bad Request");
+
+    prefer_error_fields(["developerMessage"]);
+    assert_eq!(body.extract_error_from_json(), "rate plan BAR is closed");
+
+    set_error_fields(["fault.faultstring"]);
+    assert_eq!(body.extract_error_from_json(), "soap says no");
+
+    reset_error_fields();
+    assert_eq!(body.extract_error_from_json(), "Bad Request");
+}
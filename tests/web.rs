@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+use error_pile::{ErrPile, ErrorEnvelope, FieldError};
+use serde_json::json;
+
+#[test]
+fn response_body_only_shows_what_the_client_may_see() {
+    assert_eq!(
+        ErrPile::not_found("reservation", 12).response_body(),
+        json!({
+            "status": 404,
+            "code": "not_found",
+            "user_message": "The reservation `12` could not be found",
+            "correlation_id": null,
+            "retry_after": null,
+            "field_errors": null,
+        })
+    );
+
+    let body = ErrPile::custom("connection string host=db password=hunter2").response_body();
+    assert_eq!(body["code"], "internal");
+    assert!(!body.to_string().contains("hunter2"));
+}
+
+#[test]
+fn envelopes_have_the_same_shape_both_ways() {
+    let err = ErrPile::validation([FieldError::new(
+        "arrival",
+        "required",
+        "arrival is required",
+    )]);
+    let envelope = err.envelope().with_correlation_id("req-42");
+    assert_eq!(envelope.status, 422);
+    assert_eq!(envelope.field_errors.as_ref().unwrap().0.len(), 1);
+
+    let json = envelope.to_json();
+    assert!(json.starts_with(r#"{"status":422,"code":"validation","#));
+    assert_eq!(
+        serde_json::from_str::<ErrorEnvelope>(&json).unwrap(),
+        envelope
+    );
+
+    // rounded up, coming back half a second early gets throttled again
+    let envelope = ErrPile::rate_limited(Some(Duration::from_millis(1500)), None).envelope();
+    assert_eq!(envelope.retry_after, Some(2));
+}
+
+#[test]
+fn retry_after_is_rounded_up_to_whole_seconds() {
+    let secs = |wait| ErrPile::rate_limited(Some(wait), None).retry_after_secs();
+    assert_eq!(secs(Duration::ZERO), Some(1));
+    assert_eq!(secs(Duration::from_millis(1)), Some(1));
+    assert_eq!(secs(Duration::from_secs(30)), Some(30));
+    assert_eq!(secs(Duration::from_millis(30_001)), Some(31));
+    assert_eq!(ErrPile::NotReady.retry_after_secs(), None);
+}
+
+#[cfg(feature = "axum")]
+#[tokio::test]
+async fn axum_responses_use_the_status_mapping() {
+    use axum_core::response::IntoResponse;
+    use http_body_util::BodyExt;
+
+    let res = ErrPile::Permission.into_response();
+    assert_eq!(res.status(), 403);
+    assert_eq!(res.headers()["content-type"], "application/json");
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body, ErrPile::Permission.response_body());
+
+    let res = ErrPile::rate_limited(Some(Duration::from_secs(30)), None).into_response();
+    assert_eq!(res.status(), 429);
+    assert_eq!(res.headers()["retry-after"], "30");
+}
+
+#[cfg(feature = "actix")]
+#[tokio::test]
+async fn actix_responses_match_axum() {
+    use actix_web::ResponseError;
+
+    let err = ErrPile::not_found("reservation", 12);
+    let res = err.error_response();
+    assert_eq!(res.status(), 404);
+    assert_eq!(
+        res.headers().get("content-type").unwrap(),
+        "application/json"
+    );
+    let body = actix_web::body::to_bytes(res.into_body()).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body, err.response_body());
+
+    let res = ErrPile::rate_limited(Some(Duration::from_secs(30)), None).error_response();
+    assert_eq!(res.status(), 429);
+    assert_eq!(res.headers().get("retry-after").unwrap(), "30");
+}
+
+#[cfg(feature = "rocket")]
+#[rocket::get("/reservations/<id>")]
+fn rocket_reservation(id: u32) -> Result<String, ErrPile> {
+    Err(ErrPile::not_found("reservation", id))
+}
+
+#[cfg(feature = "rocket")]
+#[tokio::test]
+async fn rocket_responses_match_axum() {
+    use rocket::local::asynchronous::Client;
+
+    let rocket = rocket::build().mount("/", rocket::routes![rocket_reservation]);
+    let client = Client::untracked(rocket).await.unwrap();
+
+    let res = client.get("/reservations/12").dispatch().await;
+    assert_eq!(res.status().code, 404);
+    assert_eq!(
+        res.headers().get_one("content-type"),
+        Some("application/json")
+    );
+    let body: serde_json::Value = serde_json::from_str(&res.into_string().await.unwrap()).unwrap();
+    assert_eq!(body, ErrPile::not_found("reservation", 12).response_body());
+}
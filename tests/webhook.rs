@@ -0,0 +1,66 @@
+#![cfg(feature = "report")]
+
+use error_pile::{ErrPile, ErrorReport, ErrorSink, WebhookSink};
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+mod common;
+
+/// answers every post with `1` and hands the json bodies over
+async fn chat_server() -> (String, mpsc::UnboundedReceiver<Value>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let base = common::serve(move |req| {
+        tx.send(serde_json::from_str(&req.body).unwrap()).unwrap();
+        common::reply(200, "text/plain", "1")
+    })
+    .await;
+
+    (base, rx)
+}
+
+#[tokio::test]
+async fn teams_cards_are_batched() {
+    let (base, mut bodies) = chat_server().await;
+    let sink = WebhookSink::teams(reqwest::Client::new(), format!("{base}/teams")).max_batch(2);
+
+    sink.send(&ErrorReport::new(&ErrPile::NotReady))
+        .await
+        .unwrap();
+    assert!(bodies.try_recv().is_err());
+    sink.send(&ErrorReport::new(&ErrPile::custom(
+        "db password is hunter2",
+    )))
+    .await
+    .unwrap();
+
+    let card = bodies.recv().await.unwrap();
+    let body = &card["attachments"][0]["content"]["body"];
+    assert_eq!(body.as_array().unwrap().len(), 4);
+    assert_eq!(
+        body[0]["text"],
+        "The resource is not ready yet, please try again later"
+    );
+    assert_eq!(body[1]["facts"][0]["value"], "not_ready");
+
+    // internal details stay out of the user message
+    let facts = body[3]["facts"].as_array().unwrap();
+    let user_message = facts.iter().find(|f| f["title"] == "User message").unwrap();
+    assert!(!user_message["value"].as_str().unwrap().contains("hunter2"));
+}
+
+#[tokio::test]
+async fn slack_messages_are_flushed() {
+    let (base, mut bodies) = chat_server().await;
+    let sink = WebhookSink::slack(reqwest::Client::new(), format!("{base}/slack"));
+
+    sink.send(&ErrorReport::new(&ErrPile::Auth)).await.unwrap();
+    sink.flush().await.unwrap();
+
+    let message = bodies.recv().await.unwrap();
+    assert_eq!(message["text"], "1 error(s) reported");
+    assert_eq!(message["blocks"][0]["fields"][0]["text"], "*Kind*\nauth");
+
+    // nothing left for the delayed flush
+    sink.flush().await.unwrap();
+    assert!(bodies.try_recv().is_err());
+}
@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use error_pile::{ErrPile, FieldError, FieldErrors, PileKind};
+use serde_json::json;
+
+#[test]
+fn errors_serialize_to_the_wire_format() {
+    let err = ErrPile::rate_limited(Some(Duration::from_secs(2)), None);
+    let value = serde_json::to_value(&err).unwrap();
+
+    assert_eq!(value["kind"], "rate_limited");
+    assert_eq!(value["code"], json!(null));
+    assert_eq!(value["message"], err.to_string());
+    assert_eq!(value["transient"], true);
+    assert_eq!(value["http_status"], json!(null));
+    assert_eq!(value["chain"], json!([]));
+    assert_eq!(value["metadata"]["retry_after_ms"], 2000);
+}
+
+#[test]
+fn field_errors_are_in_the_metadata() {
+    let err = ErrPile::Validation(FieldErrors(vec![FieldError::new(
+        "email",
+        "required",
+        "email is required",
+    )]));
+    let value = serde_json::to_value(&err).unwrap();
+
+    assert_eq!(value["kind"], "validation");
+    assert_eq!(value["user_message"], err.to_string());
+    assert_eq!(value["chain"], json!(["email: email is required"]));
+    assert_eq!(
+        value["metadata"]["field_errors"],
+        json!([{"field": "email", "code": "required", "message": "email is required"}])
+    );
+}
+
+#[test]
+fn serialized_messages_are_redacted() {
+    let err = ErrPile::custom("login failed with password=hunter2");
+    let text = serde_json::to_string(&err).unwrap();
+    assert!(!text.contains("hunter2"));
+}
+
+#[test]
+fn field_errors_and_forwarded_errors_are_redacted() {
+    let err = ErrPile::Validation(FieldErrors(vec![FieldError::new(
+        "card",
+        "invalid",
+        "4111 1111 1111 1111 was declined",
+    )]));
+    let text = serde_json::to_string(&err).unwrap();
+    assert!(!text.contains("4111 1111 1111 1111"));
+
+    let received: ErrPile = serde_json::from_value(json!({
+        "kind": "upstream",
+        "message": "login failed with password=hunter2",
+        "user_message": "Bearer abcdefghijklmnop was refused",
+        "chain": ["client_secret=s3cr3t"],
+        "metadata": {"field_errors": [{"field": "pwd", "message": "pwd=hunter2"}]}
+    }))
+    .unwrap();
+    let text = serde_json::to_string(&received).unwrap();
+    for secret in ["hunter2", "abcdefghijklmnop", "s3cr3t"] {
+        assert!(!text.contains(secret), "{secret} in {text}");
+    }
+}
+
+#[test]
+fn received_errors_keep_their_semantics() {
+    let sent = serde_json::to_string(&ErrPile::rate_limited(
+        Some(Duration::from_millis(1500)),
+        None,
+    ))
+    .unwrap();
+    let received: ErrPile = serde_json::from_str(&sent).unwrap();
+
+    assert!(matches!(received, ErrPile::Remote(_)));
+    assert_eq!(received.kind(), PileKind::RateLimited);
+    assert!(received.is_rate_limited());
+    assert!(received.is_transient());
+    assert_eq!(received.retry_after(), Some(Duration::from_millis(1500)));
+    assert_eq!(received.status_code(), 429);
+
+    // forwarded as received
+    assert_eq!(serde_json::to_string(&received).unwrap(), sent);
+}
+
+#[test]
+fn remote_auth_and_not_ready_are_recognized() {
+    let auth: ErrPile = serde_json::from_value(json!({
+        "kind": "auth",
+        "message": "token expired",
+        "metadata": {"request_id": "abc"}
+    }))
+    .unwrap();
+    assert!(auth.is_encrypted());
+    assert!(!auth.is_transient());
+    assert_eq!(auth.status_code(), 401);
+    assert_eq!(auth.to_string(), "token expired");
+    assert_eq!(
+        auth.correlation_ids(),
+        vec![("request_id", "abc".to_string())]
+    );
+
+    let not_ready: ErrPile = serde_json::from_value(json!({
+        "kind": "not_ready",
+        "message": "the PMS is still syncing",
+        "transient": true,
+        "code": "SYNCING"
+    }))
+    .unwrap();
+    assert!(not_ready.is_not_ready());
+    assert_eq!(not_ready.code().as_deref(), Some("SYNCING"));
+    assert_eq!(not_ready.status_code(), 503);
+}
@@ -0,0 +1,25 @@
+// the service name is global, so this lives in its own test binary
+
+use error_pile::{ErrPile, RemoteError, service_name, set_service_name};
+use serde_json::json;
+
+#[test]
+fn errors_carry_the_service_they_came_from() {
+    let value = serde_json::to_value(ErrPile::NotReady).unwrap();
+    assert!(value.get("origin").is_none());
+
+    set_service_name("pms-sync");
+    assert_eq!(service_name().as_deref(), Some("pms-sync"));
+    let value = serde_json::to_value(ErrPile::NotReady).unwrap();
+    assert_eq!(value["origin"], "pms-sync");
+
+    // a received error keeps the origin of the service that raised it
+    let received: ErrPile = serde_json::from_value(
+        json!({"kind": "in_use", "message": "room locked", "origin": "door-locks"}),
+    )
+    .unwrap();
+    assert_eq!(
+        RemoteError::new(&received).origin.as_deref(),
+        Some("door-locks")
+    );
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "xml")]
+
+use error_pile::{ErrPile, ReqwestPileResExt, XmlFault};
+
+#[test]
+fn soap_11_fault() {
+    let body = r#"<?xml version="1.0"?>
+<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">
+  <soap:Body>
+    <soap:Fault>
+      <faultcode>soap:Client</faultcode>
+      <faultstring>Invalid hotel code</faultstring>
+      <detail><ErrorCode>392</ErrorCode></detail>
+    </soap:Fault>
+  </soap:Body>
+</soap:Envelope>"#;
+
+    let fault = XmlFault::parse(body).unwrap();
+    assert!(fault.soap);
+    assert_eq!(fault.to_string(), "soap:Client - Invalid hotel code (392)");
+}
+
+#[test]
+fn soap_12_fault() {
+    let body = r#"<env:Envelope xmlns:env="http://www.w3.org/2003/05/soap-envelope">
+  <env:Body><env:Fault>
+    <env:Code><env:Value>env:Receiver</env:Value></env:Code>
+    <env:Reason><env:Text xml:lang="en">Rate plan &amp; inventory locked</env:Text></env:Reason>
+  </env:Fault></env:Body>
+</env:Envelope>"#;
+
+    let fault = XmlFault::parse(body).unwrap();
+    assert_eq!(fault.code.as_deref(), Some("env:Receiver"));
+    assert_eq!(
+        fault.message.as_deref(),
+        Some("Rate plan & inventory locked")
+    );
+}
+
+#[tokio::test]
+async fn xml_error_response() {
+    let res: reqwest::Response = http::Response::builder()
+        .status(400)
+        .header("content-type", "application/xml")
+        .body(
+            "<Error><Code>InvalidDates</Code><Message>Departure before arrival</Message></Error>"
+                .to_string(),
+        )
+        .unwrap()
+        .into();
+
+    let ErrPile::Xml(fault) = res.to_pile_empty().await.unwrap_err() else {
+        panic!("expected Xml variant");
+    };
+    assert!(!fault.soap);
+    assert_eq!(fault.code.as_deref(), Some("InvalidDates"));
+}
+
+#[test]
+fn ota_errors() {
+    let body = r#"<OTA_HotelRateAmountNotifRS xmlns="http://www.opentravel.org/OTA/2003/05" Version="1.000">
+  <Errors>
+    <Error Type="3" Code="402" ShortText="Invalid room type">Room type DBLX is not mapped</Error>
+    <Error Type="3" Code="448">Rate plan code BAR-FLEX does not exist</Error>
+  </Errors>
+</OTA_HotelRateAmountNotifRS>"#;
+
+    let fault = XmlFault::parse(body).unwrap();
+    assert!(!fault.soap);
+    assert_eq!(fault.root, "OTA_HotelRateAmountNotifRS");
+    assert_eq!(fault.code.as_deref(), Some("402"));
+    assert_eq!(fault.message.as_deref(), Some("Invalid room type"));
+    assert_eq!(
+        fault.detail.as_deref(),
+        Some("Room type DBLX is not mapped")
+    );
+}